@@ -4,6 +4,10 @@
 //! path from the pathsStorage tree. Each file's contents are the raw prototype
 //! record data from the record start through the end of the containing blob,
 //! preserving relative pointer resolution into out-of-line data.
+//!
+//! With the `models` and `json` features, [`AssetsBinVfs::with_json_view`]
+//! additionally exposes every prototype as a `<path>.json` file containing the
+//! decoded record, with string IDs and path IDs resolved.
 
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
@@ -13,21 +17,10 @@ use vfs::error::VfsErrorKind;
 use vfs::{FileSystem, VfsMetadata};
 
 use crate::models::assets_bin::{self, AssetsBinError, PrototypeDatabase};
-
-/// Known item sizes for each blob type (from RE).
-/// Index corresponds to blob index in the databases array.
-const ITEM_SIZES: [usize; 10] = [
-    0x78, // 0: MaterialPrototype
-    0x70, // 1: VisualPrototype
-    0x20, // 2: SkeletonExtenderPrototype
-    0x28, // 3: ModelPrototype
-    0x70, // 4: PointLightPrototype
-    0x10, // 5: EffectPrototype
-    0x18, // 6: VelocityFieldPrototype
-    0x10, // 7: EffectPresetPrototype
-    0x10, // 8: EffectMetadataPrototype
-    0x10, // 9: AtlasContourProto
-];
+#[cfg(all(feature = "models", feature = "json"))]
+use crate::models::assets_bin::{HashmapSection, StringsSection};
+#[cfg(all(feature = "models", feature = "json"))]
+use crate::models::prototype_json::{self, JsonContext};
 
 /// Pre-computed file location within the owned assets.bin data.
 #[derive(Debug, Clone)]
@@ -38,6 +31,39 @@ struct FileLocation {
     byte_end: usize,
 }
 
+/// Location of a decoded `.json` view of a prototype record.
+#[cfg(all(feature = "models", feature = "json"))]
+#[derive(Debug, Clone)]
+struct JsonLocation {
+    /// Resource path of the underlying prototype (without leading `/`).
+    path: String,
+    blob_index: usize,
+    item_size: usize,
+    record: FileLocation,
+}
+
+/// Byte ranges of the strings section within the owned data, so a
+/// [`StringsSection`] can be rebuilt without re-parsing the database.
+#[cfg(all(feature = "models", feature = "json"))]
+#[derive(Debug)]
+struct StringsLocation {
+    capacity: u32,
+    buckets: std::ops::Range<usize>,
+    values: std::ops::Range<usize>,
+    bucket_stride: usize,
+    value_stride: usize,
+    string_data: std::ops::Range<usize>,
+}
+
+/// State backing the decoded JSON view.
+#[cfg(all(feature = "models", feature = "json"))]
+#[derive(Debug)]
+struct JsonView {
+    files: HashMap<String, JsonLocation>,
+    paths: HashMap<u64, String>,
+    strings: StringsLocation,
+}
+
 /// A virtual filesystem backed by an assets.bin PrototypeDatabase.
 ///
 /// Owns the raw file data and exposes prototype records as virtual files.
@@ -47,6 +73,8 @@ pub struct AssetsBinVfs {
     data: Vec<u8>,
     files: HashMap<String, FileLocation>,
     dirs: HashMap<String, Vec<String>>,
+    #[cfg(all(feature = "models", feature = "json"))]
+    json: Option<JsonView>,
 }
 
 /// Compute the byte offset of a subslice within a parent slice.
@@ -91,17 +119,96 @@ impl AssetsBinVfs {
     /// the parsed database. Only the raw bytes and the index are retained.
     pub fn new(data: Vec<u8>) -> Result<Self, rootcause::Report<AssetsBinError>> {
         let db = assets_bin::parse_assets_bin(&data)?;
-        let (files, dirs) = Self::build_index(&db, &data);
-        Ok(Self { data, files, dirs })
+        let mut dir_children = HashMap::new();
+        let files = Self::build_index(&db, &data, &mut dir_children)
+            .into_iter()
+            .map(|(path, (loc, _, _))| (path, loc))
+            .collect();
+        Ok(Self {
+            data,
+            files,
+            dirs: Self::finish_dirs(dir_children),
+            #[cfg(all(feature = "models", feature = "json"))]
+            json: None,
+        })
     }
 
+    /// Build a VFS that additionally exposes a decoded `<path>.json` file next
+    /// to every prototype record.
+    ///
+    /// JSON files are rendered on demand when opened. Visual and model
+    /// prototypes are fully decoded; other prototype types carry their fixed
+    /// record bytes as hex.
+    #[cfg(all(feature = "models", feature = "json"))]
+    pub fn with_json_view(data: Vec<u8>) -> Result<Self, rootcause::Report<AssetsBinError>> {
+        let db = assets_bin::parse_assets_bin(&data)?;
+        let mut dir_children = HashMap::new();
+        let files = Self::build_index(&db, &data, &mut dir_children);
+
+        let mut json_files = HashMap::new();
+        for (path, (loc, blob_index, item_size)) in &files {
+            let json_path = format!("{path}.json");
+            register_path_in_dirs(&json_path, &mut dir_children);
+            json_files.insert(
+                json_path,
+                JsonLocation {
+                    path: path.trim_start_matches('/').to_string(),
+                    blob_index: *blob_index,
+                    item_size: *item_size,
+                    record: loc.clone(),
+                },
+            );
+        }
+
+        let map = &db.strings.offsets_map;
+        let range_of = |slice: &[u8]| {
+            let start = subslice_offset(&data, slice);
+            start..start + slice.len()
+        };
+        let strings = StringsLocation {
+            capacity: map.capacity,
+            buckets: range_of(map.buckets),
+            values: range_of(map.values),
+            bucket_stride: map.bucket_stride,
+            value_stride: map.value_stride,
+            string_data: range_of(db.strings.string_data),
+        };
+
+        let json = JsonView {
+            files: json_files,
+            paths: db.build_path_table(),
+            strings,
+        };
+
+        Ok(Self {
+            files: files
+                .into_iter()
+                .map(|(path, (loc, _, _))| (path, loc))
+                .collect(),
+            data,
+            dirs: Self::finish_dirs(dir_children),
+            json: Some(json),
+        })
+    }
+
+    fn finish_dirs(
+        dir_children: HashMap<String, BTreeSet<String>>,
+    ) -> HashMap<String, Vec<String>> {
+        dir_children
+            .into_iter()
+            .map(|(k, v)| (k, v.into_iter().collect()))
+            .collect()
+    }
+
+    /// Index every prototype record, returning its location, blob index and
+    /// record size keyed by `/`-prefixed path.
     fn build_index(
         db: &PrototypeDatabase<'_>,
         data: &[u8],
-    ) -> (HashMap<String, FileLocation>, HashMap<String, Vec<String>>) {
+        dir_children: &mut HashMap<String, BTreeSet<String>>,
+    ) -> HashMap<String, (FileLocation, usize, usize)> {
         let self_id_index = db.build_self_id_index();
         let mut files = HashMap::new();
-        let mut dir_children: HashMap<String, BTreeSet<String>> = HashMap::new();
 
         // Ensure root directory exists.
         dir_children.entry("/".to_string()).or_default();
@@ -114,16 +221,16 @@ impl AssetsBinVfs {
             let Ok(location) = db.decode_r2p_value(r2p_value) else {
                 continue;
             };
-            if location.blob_index >= ITEM_SIZES.len() {
+            let Ok(layout) = db.layout(location.blob_index) else {
                 continue;
-            }
+            };
 
             let raw_path = db.reconstruct_path(i, &self_id_index);
             if raw_path.is_empty() {
                 continue;
             }
 
-            let item_size = ITEM_SIZES[location.blob_index];
+            let item_size = layout.item_size;
             let blob = &db.databases[location.blob_index];
 
             let blob_start = subslice_offset(data, blob.data);
@@ -138,13 +245,17 @@ impl AssetsBinVfs {
             let full_path = format!("/{raw_path}");
             files.insert(
                 full_path.clone(),
-                FileLocation {
-                    byte_offset: record_offset,
-                    byte_end: blob_end,
-                },
+                (
+                    FileLocation {
+                        byte_offset: record_offset,
+                        byte_end: blob_end,
+                    },
+                    location.blob_index,
+                    item_size,
+                ),
             );
 
-            register_path_in_dirs(&full_path, &mut dir_children);
+            register_path_in_dirs(&full_path, dir_children);
         }
 
         // Register parent directories for path entries that have no prototype
@@ -158,20 +269,58 @@ impl AssetsBinVfs {
             let raw_path = db.reconstruct_path(i, &self_id_index);
             if !raw_path.is_empty() {
                 let full_path = format!("/{raw_path}");
-                register_path_in_dirs(&full_path, &mut dir_children);
+                register_path_in_dirs(&full_path, dir_children);
             }
         }
 
-        let dirs = dir_children
-            .into_iter()
-            .map(|(k, v)| (k, v.into_iter().collect()))
-            .collect();
+        files
+    }
+
+    /// Render the decoded JSON for a prototype record.
+    #[cfg(all(feature = "models", feature = "json"))]
+    fn render_json(&self, json: &JsonView, loc: &JsonLocation) -> vfs::VfsResult<Vec<u8>> {
+        let s = &json.strings;
+        let strings = StringsSection {
+            offsets_map: HashmapSection {
+                capacity: s.capacity,
+                buckets: &self.data[s.buckets.clone()],
+                values: &self.data[s.values.clone()],
+                bucket_stride: s.bucket_stride,
+                value_stride: s.value_stride,
+            },
+            string_data: &self.data[s.string_data.clone()],
+        };
+        let ctx = JsonContext {
+            strings: &strings,
+            paths: &json.paths,
+        };
+
+        let record_data = &self.data[loc.record.byte_offset..loc.record.byte_end];
+        let value = prototype_json::prototype_to_json(
+            &loc.path,
+            loc.blob_index,
+            record_data,
+            loc.item_size,
+            &ctx,
+        )
+        .map_err(|e| vfs::VfsError::from(VfsErrorKind::Other(e)))?;
+
+        serde_json::to_vec_pretty(&value).map_err(|e| VfsErrorKind::Other(e.to_string()).into())
+    }
 
-        (files, dirs)
+    /// Look up a JSON view entry by VFS key.
+    #[cfg(all(feature = "models", feature = "json"))]
+    fn json_entry(&self, key: &str) -> Option<(&JsonView, &JsonLocation)> {
+        let json = self.json.as_ref()?;
+        Some((json, json.files.get(key)?))
     }
 
-    /// Number of file entries in this VFS.
+    /// Number of file entries in this VFS (including JSON views, if enabled).
     pub fn file_count(&self) -> usize {
+        #[cfg(all(feature = "models", feature = "json"))]
+        if let Some(json) = &self.json {
+            return self.files.len() + json.files.len();
+        }
         self.files.len()
     }
 
@@ -180,13 +329,26 @@ impl AssetsBinVfs {
         self.dirs.len()
     }
 
-    /// Iterate over all file paths and their sizes (in bytes).
+    /// Iterate over all prototype record paths and their sizes (in bytes).
+    ///
+    /// JSON views are not included; see [`Self::json_files`].
     pub fn files(&self) -> impl Iterator<Item = (&str, usize)> {
         self.files
             .iter()
             .map(|(path, loc)| (path.as_str(), loc.byte_end - loc.byte_offset))
     }
 
+    /// Iterate over the paths of all decoded JSON views.
+    ///
+    /// Views have no size until they are rendered, which only happens when
+    /// one is opened or its metadata is requested.
+    #[cfg(all(feature = "models", feature = "json"))]
+    pub fn json_files(&self) -> impl Iterator<Item = &str> {
+        self.json
+            .iter()
+            .flat_map(|json| json.files.keys().map(|path| path.as_str()))
+    }
+
     /// Iterate over all directory paths.
     pub fn dirs(&self) -> impl Iterator<Item = &str> {
         self.dirs.keys().map(|k| k.as_str())
@@ -213,6 +375,10 @@ impl FileSystem for AssetsBinVfs {
 
     fn open_file(&self, path: &str) -> vfs::VfsResult<Box<dyn vfs::SeekAndRead + Send>> {
         let key = lookup_key(path);
+        #[cfg(all(feature = "models", feature = "json"))]
+        if let Some((json, loc)) = self.json_entry(key) {
+            return Ok(Box::new(Cursor::new(self.render_json(json, loc)?)));
+        }
         let loc = self
             .files
             .get(key)
@@ -231,6 +397,16 @@ impl FileSystem for AssetsBinVfs {
 
     fn metadata(&self, path: &str) -> vfs::VfsResult<VfsMetadata> {
         let key = lookup_key(path);
+        #[cfg(all(feature = "models", feature = "json"))]
+        if let Some((json, loc)) = self.json_entry(key) {
+            return Ok(VfsMetadata {
+                file_type: vfs::VfsFileType::File,
+                len: self.render_json(json, loc)?.len() as u64,
+                created: None,
+                modified: None,
+                accessed: None,
+            });
+        }
         if let Some(loc) = self.files.get(key) {
             Ok(VfsMetadata {
                 file_type: vfs::VfsFileType::File,
@@ -254,6 +430,10 @@ impl FileSystem for AssetsBinVfs {
 
     fn exists(&self, path: &str) -> vfs::VfsResult<bool> {
         let key = lookup_key(path);
+        #[cfg(all(feature = "models", feature = "json"))]
+        if self.json_entry(key).is_some() {
+            return Ok(true);
+        }
         Ok(self.files.contains_key(key) || self.dirs.contains_key(key))
     }
 
@@ -293,3 +473,119 @@ impl FileSystem for AssetsBinVfs {
         Err(VfsErrorKind::NotSupported.into())
     }
 }
+
+#[cfg(all(test, feature = "models", feature = "json"))]
+mod tests {
+    use std::io::Read;
+
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::models::assets_bin::PathEntry;
+    use crate::models::assets_bin_writer::PrototypeDatabaseWriter;
+    use crate::models::prototype_layout;
+
+    const MODEL_BLOB: usize = 3;
+
+    /// A ModelPrototype record for `visual_id` with one skeleton extender and
+    /// one dye carrying a single tint.
+    fn model_record(visual_id: u64, skel_ext_id: u64, tint_material_id: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 0x60];
+        data[..8].copy_from_slice(&visual_id.to_le_bytes());
+        data[8] = 1; // skelExtResIds count
+        data[11] = 1; // dyes count
+        data[0x10..0x18].copy_from_slice(&0x28i64.to_le_bytes());
+        data[0x20..0x28].copy_from_slice(&0x30i64.to_le_bytes());
+        data[0x28..0x30].copy_from_slice(&skel_ext_id.to_le_bytes());
+
+        // DyeEntry at +0x30: tint names at +0x50, tint materials at +0x58.
+        data[0x30..0x34].copy_from_slice(&0x10u32.to_le_bytes());
+        data[0x34..0x38].copy_from_slice(&0x11u32.to_le_bytes());
+        data[0x38..0x3C].copy_from_slice(&1i32.to_le_bytes());
+        data[0x40..0x48].copy_from_slice(&0x20i64.to_le_bytes());
+        data[0x48..0x50].copy_from_slice(&0x28i64.to_le_bytes());
+        data[0x50..0x54].copy_from_slice(&0x12u32.to_le_bytes());
+        data[0x58..0x60].copy_from_slice(&tint_material_id.to_le_bytes());
+        data
+    }
+
+    fn sample() -> Vec<u8> {
+        let prototypes: Vec<_> = prototype_layout::LAYOUTS
+            .iter()
+            .map(|l| (l.magic, 0))
+            .collect();
+        let mut writer = PrototypeDatabaseWriter::new(&prototypes);
+        writer.add_string(0x10, "steel");
+        writer.add_string(0x11, "paint");
+        writer.add_string(0x12, "red");
+        for (self_id, parent_id, name) in [
+            (1, 0, "content"),
+            (2, 1, "Hull.model"),
+            (3, 1, "Hull.visual"),
+            (4, 1, "Red.mfm"),
+        ] {
+            writer.set_path(PathEntry {
+                self_id,
+                parent_id,
+                name: name.to_string(),
+            });
+        }
+        writer
+            .insert_prototype(2, MODEL_BLOB, &model_record(3, 0xDEAD, 4))
+            .unwrap();
+        writer.to_bytes()
+    }
+
+    fn read_json(vfs: &AssetsBinVfs, path: &str) -> Value {
+        let mut data = Vec::new();
+        vfs.open_file(path).unwrap().read_to_end(&mut data).unwrap();
+        assert_eq!(vfs.metadata(path).unwrap().len, data.len() as u64);
+        serde_json::from_slice(&data).unwrap()
+    }
+
+    #[test]
+    fn json_view_decodes_written_records() {
+        let vfs = AssetsBinVfs::with_json_view(sample()).unwrap();
+
+        let model = read_json(&vfs, "/content/Hull.model.json");
+        assert_eq!(model["path"], "content/Hull.model");
+        assert_eq!(model["type"], "ModelPrototype");
+        assert_eq!(
+            model["record"],
+            json!({
+                "visual": "content/Hull.visual",
+                "misc_type": 0,
+                "skeleton_extenders": ["0x000000000000DEAD"],
+                "animations": [],
+                "dyes": [{
+                    "matter": "steel",
+                    "replaces": "paint",
+                    "tints": [{ "name": "red", "material": "content/Red.mfm" }],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn json_view_sits_next_to_raw_records() {
+        let data = sample();
+        let plain = AssetsBinVfs::new(data.clone()).unwrap();
+        let vfs = AssetsBinVfs::with_json_view(data).unwrap();
+
+        let mut records: Vec<_> = vfs.files().collect();
+        let mut plain_records: Vec<_> = plain.files().collect();
+        records.sort();
+        plain_records.sort();
+        assert_eq!(records, plain_records);
+
+        let mut views: Vec<_> = vfs.json_files().collect();
+        views.sort();
+        assert_eq!(views, ["/content/Hull.model.json"]);
+        assert_eq!(vfs.file_count(), plain.file_count() + 1);
+
+        let children: Vec<_> = vfs.read_dir("/content").unwrap().collect();
+        assert!(children.contains(&"Hull.model".to_string()));
+        assert!(children.contains(&"Hull.model.json".to_string()));
+        assert!(!vfs.exists("/content/Hull.visual.json").unwrap());
+    }
+}
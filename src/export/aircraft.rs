@@ -0,0 +1,163 @@
+//! Carrier and airsupport plane models.
+//!
+//! An `Aircraft` GameParam names the plane's `.model` and, through
+//! `bombName`, the Projectile it carries. The plane visual has no explicit
+//! markup for propellers or payload, so both are found by node name:
+//! propellers by names like `Propeller` or `prop_1`, and payload attachment
+//! points by `HP_` nodes naming the payload, like `HP_Bomb_1`. The payload
+//! model is placed once at every payload hardpoint.
+
+use crate::game_params::types::{PlaneCategory, Species};
+use crate::models::assets_bin::StringsSection;
+use crate::models::visual::VisualPrototype;
+
+/// What a plane carries, from the species of its Projectile param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PayloadKind {
+    Bomb,
+    Torpedo,
+    Rocket,
+}
+
+impl PayloadKind {
+    /// Payload kind for a Projectile species. Skip bombs count as bombs;
+    /// depth charges, mines and shells are not exported as payload.
+    pub fn from_species(species: Species) -> Option<Self> {
+        match species {
+            Species::Bomb | Species::Skip => Some(Self::Bomb),
+            Species::Torpedo => Some(Self::Torpedo),
+            Species::Rocket => Some(Self::Rocket),
+            _ => None,
+        }
+    }
+
+    /// glTF group name for payload of this kind.
+    pub fn group(self) -> &'static str {
+        match self {
+            Self::Bomb => "Bombs",
+            Self::Torpedo => "Torpedoes",
+            Self::Rocket => "Rockets",
+        }
+    }
+}
+
+/// Payload resolved from an aircraft's `bombName`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PayloadInfo {
+    /// Projectile param name.
+    pub projectile: String,
+    pub kind: PayloadKind,
+    /// Projectile `.model` path, if it has one.
+    pub model_path: Option<String>,
+}
+
+/// An aircraft found in GameParams.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AircraftInfo {
+    /// GameParam key, e.g. "PAUA102_F4U_Corsair".
+    pub param_name: String,
+    /// GameParam index, e.g. "PAUA102".
+    pub param_index: String,
+    pub display_name: Option<String>,
+    pub category: PlaneCategory,
+    /// Plane `.model` path.
+    pub model_path: String,
+    pub payload: Option<PayloadInfo>,
+}
+
+impl AircraftInfo {
+    /// Whether `name` is this aircraft's param index or a case-insensitive
+    /// substring of its param or display name.
+    pub fn matches(&self, name: &str) -> bool {
+        let needle = name.to_lowercase();
+        self.param_index == name
+            || [Some(&self.param_name), self.display_name.as_ref()]
+                .into_iter()
+                .flatten()
+                .any(|n| n.to_lowercase().contains(&needle))
+    }
+}
+
+/// A named node of a plane visual with its model-space transform.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AircraftNode {
+    pub name: String,
+    /// Column-major 4x4 transform in model space.
+    pub transform: [f32; 16],
+}
+
+/// Whether a plane visual node is a propeller.
+pub fn is_propeller_node(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.contains("propeller") || lower.starts_with("prop_") || lower.contains("rotor")
+}
+
+/// Whether a plane visual node is a payload attachment point: an `HP_`
+/// node naming a bomb, torpedo or rocket, e.g. `HP_Bomb_1` or
+/// `HP_Torpedo`. Other hardpoints (guns, lights, propellers) are not.
+pub fn is_payload_hardpoint(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.starts_with("hp_")
+        && ["bomb", "torp", "rocket"]
+            .iter()
+            .any(|payload| lower.contains(payload))
+}
+
+/// Propeller nodes and payload hardpoints of a plane visual, each in node
+/// order.
+pub fn classify_nodes(
+    visual: &VisualPrototype,
+    strings: &StringsSection<'_>,
+) -> (Vec<AircraftNode>, Vec<AircraftNode>) {
+    let mut propellers = Vec::new();
+    let mut hardpoints = Vec::new();
+    for (i, &name_id) in visual.nodes.name_ids.iter().enumerate() {
+        let Some(name) = strings.get_string_by_id(name_id) else {
+            continue;
+        };
+        let list = if is_propeller_node(name) {
+            &mut propellers
+        } else if is_payload_hardpoint(name) {
+            &mut hardpoints
+        } else {
+            continue;
+        };
+        list.push(AircraftNode {
+            name: name.to_string(),
+            transform: visual.node_world_transform(i as u16),
+        });
+    }
+    (propellers, hardpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_plane_nodes_and_payload() {
+        assert!(is_propeller_node("Propeller_L"));
+        assert!(is_propeller_node("prop_1"));
+        assert!(!is_propeller_node("PropertyNode"));
+        assert!(is_payload_hardpoint("HP_Bomb_1"));
+        assert!(is_payload_hardpoint("HP_Torpedo"));
+        assert!(is_payload_hardpoint("HP_Rocket_L2"));
+        assert!(!is_payload_hardpoint("HP_Propeller"));
+        assert!(!is_payload_hardpoint("HP_Gun_1"));
+        assert!(!is_payload_hardpoint("Wing_L"));
+
+        assert_eq!(
+            PayloadKind::from_species(Species::Skip),
+            Some(PayloadKind::Bomb)
+        );
+        assert_eq!(
+            PayloadKind::from_species(Species::Torpedo),
+            Some(PayloadKind::Torpedo)
+        );
+        assert_eq!(PayloadKind::from_species(Species::DepthCharge), None);
+    }
+}
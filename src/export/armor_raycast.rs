@@ -0,0 +1,393 @@
+//! Ray casts against a ship's combined armor.
+//!
+//! [`ArmorScene`] flattens hull and mount [`InteractiveArmorMesh`]es into one
+//! triangle list (mount transforms applied) under a bounding volume hierarchy,
+//! and [`ArmorScene::raycast`] returns every plate a ray passes through, in
+//! order. Coordinates are those of the meshes: right-handed, Z negated.
+
+use super::gltf_export::{ArmorTriangleInfo, InteractiveArmorMesh};
+use crate::game_params::penetration::{self, ImpactClassification};
+use crate::game_params::types::ShellInfo;
+
+/// Maximum triangles per BVH leaf.
+const LEAF_SIZE: usize = 4;
+
+/// Hits on the same mesh closer than this (world units) are one plate hit,
+/// e.g. a ray through the edge shared by two of the plate's triangles.
+const DUPLICATE_HIT_DISTANCE: f32 = 1e-4;
+
+/// One plate crossed by a ray.
+#[derive(Debug, Clone)]
+pub struct ArmorHit {
+    /// Distance from the ray origin, in world units.
+    pub distance: f32,
+    /// World-space impact point.
+    pub point: [f32; 3],
+    /// Unit plate normal, facing back against the ray.
+    pub normal: [f32; 3],
+    /// Angle between the ray and the plate normal in degrees
+    /// (0 = perpendicular impact, 90 = grazing).
+    pub impact_angle_deg: f32,
+    /// Plate thickness in millimeters (`info.thickness_mm`).
+    pub thickness_mm: f32,
+    /// Thickness along the ray: `thickness_mm / cos(impact angle)`.
+    /// Infinite for exactly grazing hits.
+    pub effective_thickness_mm: f32,
+    /// Index of the source mesh in the slice given to [`ArmorScene::new`].
+    pub mesh: usize,
+    /// Per-triangle metadata: material, zone, layers.
+    pub info: ArmorTriangleInfo,
+}
+
+impl ArmorHit {
+    /// Classify this hit for `shell` arriving at `velocity` m/s.
+    /// See [`penetration::classify_impact`].
+    pub fn classify(&self, shell: &ShellInfo, velocity: f32) -> ImpactClassification {
+        penetration::classify_impact(shell, self.thickness_mm, self.impact_angle_deg, velocity)
+    }
+}
+
+/// BVH node. Leaves have `count > 0` and cover `tris[first..first + count]`;
+/// inner nodes have their left child at `index + 1` and right child at `first`.
+#[derive(Debug, Clone)]
+struct BvhNode {
+    min: [f32; 3],
+    max: [f32; 3],
+    first: u32,
+    count: u32,
+}
+
+/// A triangle with its source mesh and triangle index.
+#[derive(Debug, Clone)]
+struct SceneTriangle {
+    vertices: [[f32; 3]; 3],
+    mesh: u32,
+    triangle: u32,
+}
+
+/// Combined armor of a ship, ready for ray casts.
+#[derive(Debug, Clone)]
+pub struct ArmorScene {
+    tris: Vec<SceneTriangle>,
+    nodes: Vec<BvhNode>,
+    /// Per-mesh triangle metadata, indexed by `SceneTriangle::{mesh, triangle}`.
+    infos: Vec<Vec<ArmorTriangleInfo>>,
+}
+
+impl ArmorScene {
+    /// Build the scene from armor meshes, e.g. from
+    /// [`ShipModelContext::interactive_armor_meshes`](super::ship::ShipModelContext::interactive_armor_meshes).
+    pub fn new(meshes: &[InteractiveArmorMesh]) -> Self {
+        let mut tris = Vec::new();
+        for (mi, mesh) in meshes.iter().enumerate() {
+            for (ti, idx) in mesh.indices.chunks_exact(3).enumerate() {
+                let vertices = [0, 1, 2].map(|i| {
+                    let p = mesh.positions[idx[i] as usize];
+                    match &mesh.transform {
+                        Some(m) => transform_point(m, p),
+                        None => p,
+                    }
+                });
+                tris.push(SceneTriangle {
+                    vertices,
+                    mesh: mi as u32,
+                    triangle: ti as u32,
+                });
+            }
+        }
+
+        let mut scene = Self {
+            tris,
+            nodes: Vec::new(),
+            infos: meshes.iter().map(|m| m.triangle_info.clone()).collect(),
+        };
+        if !scene.tris.is_empty() {
+            scene.build_node(0, scene.tris.len());
+        }
+        scene
+    }
+
+    /// Number of triangles in the scene.
+    pub fn triangle_count(&self) -> usize {
+        self.tris.len()
+    }
+
+    /// Every plate hit along the ray from `origin` in `direction`, nearest
+    /// first. Plates are hit from either side, so a ray through a hull
+    /// reports both the entry and exit plates. A ray through an edge or
+    /// vertex shared by triangles of one mesh reports a single hit.
+    pub fn raycast(&self, origin: [f32; 3], direction: [f32; 3]) -> Vec<ArmorHit> {
+        let dir = normalize(direction);
+        let mut hits = Vec::new();
+        if self.nodes.is_empty() || dir == [0.0; 3] {
+            return hits;
+        }
+        let inv_dir = dir.map(|d| 1.0 / d);
+
+        let mut stack = vec![0usize];
+        while let Some(ni) = stack.pop() {
+            let node = &self.nodes[ni];
+            if !ray_hits_box(origin, inv_dir, node.min, node.max) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(ni + 1);
+                stack.push(node.first as usize);
+                continue;
+            }
+            let range = node.first as usize..(node.first + node.count) as usize;
+            for tri in &self.tris[range] {
+                if let Some(hit) = self.hit_triangle(tri, origin, dir) {
+                    hits.push(hit);
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        let mut deduped: Vec<ArmorHit> = Vec::with_capacity(hits.len());
+        for hit in hits {
+            let duplicate = deduped
+                .iter()
+                .rev()
+                .take_while(|kept| hit.distance - kept.distance < DUPLICATE_HIT_DISTANCE)
+                .any(|kept| kept.mesh == hit.mesh);
+            if !duplicate {
+                deduped.push(hit);
+            }
+        }
+        deduped
+    }
+
+    /// Möller–Trumbore intersection, two-sided.
+    fn hit_triangle(
+        &self,
+        tri: &SceneTriangle,
+        origin: [f32; 3],
+        dir: [f32; 3],
+    ) -> Option<ArmorHit> {
+        const EPSILON: f32 = 1e-7;
+        let [a, b, c] = tri.vertices;
+        let e1 = sub(b, a);
+        let e2 = sub(c, a);
+        let p = cross(dir, e2);
+        let det = dot(e1, p);
+        if det.abs() < EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = sub(origin, a);
+        let u = dot(s, p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = cross(s, e1);
+        let v = dot(dir, q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = dot(e2, q) * inv_det;
+        if t <= EPSILON {
+            return None;
+        }
+
+        let mut normal = normalize(cross(e1, e2));
+        let cos = dot(normal, dir);
+        if cos > 0.0 {
+            normal = normal.map(|n| -n);
+        }
+        let cos = cos.abs().min(1.0);
+        let info = self.infos[tri.mesh as usize][tri.triangle as usize].clone();
+        Some(ArmorHit {
+            distance: t,
+            point: [0, 1, 2].map(|i| origin[i] + dir[i] * t),
+            normal,
+            impact_angle_deg: cos.acos().to_degrees(),
+            thickness_mm: info.thickness_mm,
+            effective_thickness_mm: info.thickness_mm / cos,
+            mesh: tri.mesh as usize,
+            info,
+        })
+    }
+
+    /// Build the subtree for `tris[start..end]`, splitting at the centroid
+    /// median of the longest axis. Returns the node index.
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let (min, max) = bounds(
+            self.tris[start..end]
+                .iter()
+                .flat_map(|t| t.vertices.iter().copied()),
+        );
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            first: start as u32,
+            count: (end - start) as u32,
+        });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        let (cmin, cmax) = bounds(self.tris[start..end].iter().map(centroid));
+        let extent = sub(cmax, cmin);
+        let axis = if extent[0] >= extent[1] && extent[0] >= extent[2] {
+            0
+        } else if extent[1] >= extent[2] {
+            1
+        } else {
+            2
+        };
+        let mid = (start + end) / 2;
+        self.tris[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            centroid(a)[axis].total_cmp(&centroid(b)[axis])
+        });
+
+        self.build_node(start, mid);
+        let right = self.build_node(mid, end);
+        let node = &mut self.nodes[index];
+        node.first = right as u32;
+        node.count = 0;
+        index
+    }
+}
+
+fn centroid(tri: &SceneTriangle) -> [f32; 3] {
+    let [a, b, c] = tri.vertices;
+    [0, 1, 2].map(|i| (a[i] + b[i] + c[i]) / 3.0)
+}
+
+fn bounds(points: impl Iterator<Item = [f32; 3]>) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for p in points {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (min, max)
+}
+
+/// Slab test for a ray starting at `origin` (t >= 0).
+fn ray_hits_box(origin: [f32; 3], inv_dir: [f32; 3], min: [f32; 3], max: [f32; 3]) -> bool {
+    let mut t_near = 0.0f32;
+    let mut t_far = f32::INFINITY;
+    for i in 0..3 {
+        let t1 = (min[i] - origin[i]) * inv_dir[i];
+        let t2 = (max[i] - origin[i]) * inv_dir[i];
+        // NaN (origin on a slab plane of a zero direction) keeps the bound.
+        t_near = t_near.max(t1.min(t2));
+        t_far = t_far.min(t1.max(t2));
+    }
+    t_near <= t_far
+}
+
+/// p' = M * p (column-major 4x4, affine).
+fn transform_point(m: &[f32; 16], [x, y, z]: [f32; 3]) -> [f32; 3] {
+    [
+        m[0] * x + m[4] * y + m[8] * z + m[12],
+        m[1] * x + m[5] * y + m[9] * z + m[13],
+        m[2] * x + m[6] * y + m[10] * z + m[14],
+    ]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    if len > 0.0 {
+        v.map(|c| c / len)
+    } else {
+        [0.0; 3]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 square plate at `z`, facing +Z, split into two triangles.
+    fn plate(z: f32, thickness_mm: f32, transform: Option<[f32; 16]>) -> InteractiveArmorMesh {
+        let positions = vec![
+            [-1.0, -1.0, z],
+            [1.0, -1.0, z],
+            [1.0, 1.0, z],
+            [-1.0, -1.0, z],
+            [1.0, 1.0, z],
+            [-1.0, 1.0, z],
+        ];
+        let info = |triangle_index| ArmorTriangleInfo {
+            model_index: 1,
+            triangle_index,
+            material_id: 0,
+            material_name: "Cit_Belt".to_string(),
+            zone: "Citadel".to_string(),
+            thickness_mm,
+            layers: vec![thickness_mm],
+            color: [1.0; 4],
+            hidden: false,
+        };
+        InteractiveArmorMesh {
+            name: format!("plate_{z}"),
+            normals: vec![[0.0, 0.0, 1.0]; 6],
+            indices: (0..6).collect(),
+            colors: vec![[1.0; 4]; 6],
+            triangle_info: vec![info(0), info(1)],
+            positions,
+            transform,
+        }
+    }
+
+    #[test]
+    fn hits_are_ordered_with_effective_thickness() {
+        // Second plate is authored at z = 0 and moved to z = -5 by its transform.
+        let mut translate = [0.0; 16];
+        for i in [0, 5, 10, 15] {
+            translate[i] = 1.0;
+        }
+        translate[14] = -5.0;
+        let meshes = [plate(0.0, 100.0, Some(translate)), plate(0.0, 50.0, None)];
+        let scene = ArmorScene::new(&meshes);
+        assert_eq!(scene.triangle_count(), 4);
+
+        // 60° off the plate normal: effective thickness doubles. The ray leaves
+        // the plates' footprint before reaching the lower one.
+        let angle = 60.0f32.to_radians();
+        let hits = scene.raycast(
+            [-1.5, 0.0, 1.0],
+            [angle.sin() * 0.1, 0.0, -angle.cos() * 0.1],
+        );
+        assert_eq!(hits.len(), 1, "{hits:?}");
+        assert_eq!(hits[0].mesh, 1);
+        assert!((hits[0].impact_angle_deg - 60.0).abs() < 1e-3);
+        assert!((hits[0].effective_thickness_mm - 100.0).abs() < 1e-2);
+        assert_eq!(hits[0].normal, [0.0, 0.0, 1.0]);
+
+        let hits = scene.raycast([0.0, 0.5, 1.0], [0.0, 0.0, -1.0]);
+        let plates: Vec<(usize, f32)> = hits.iter().map(|h| (h.mesh, h.distance)).collect();
+        assert_eq!(plates, [(1, 1.0), (0, 6.0)]);
+        assert_eq!(hits[1].info.zone, "Citadel");
+        assert_eq!(hits[1].effective_thickness_mm, 100.0);
+
+        // Through the diagonal both triangles of each plate share: one hit per plate.
+        let hits = scene.raycast([0.0, 0.0, 1.0], [0.0, 0.0, -1.0]);
+        let plates: Vec<usize> = hits.iter().map(|h| h.mesh).collect();
+        assert_eq!(plates, [1, 0]);
+
+        assert!(scene.raycast([0.0, 0.0, 1.0], [0.0, 0.0, 1.0]).is_empty());
+    }
+}
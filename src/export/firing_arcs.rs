@@ -0,0 +1,330 @@
+//! Turret firing arcs and the ship-level "guns bearing" table.
+//!
+//! A mount can fire at ship bearings inside its traverse limits
+//! (`horizSector`) but outside its dead zones (`deadZone`), both given in
+//! degrees relative to the mount's rest direction. The rest direction comes
+//! from the mount's hardpoint transform.
+//!
+//! Bearings are measured in model space from the bow (+Z) toward starboard
+//! (+X), in degrees within `[0, 360)`. Positive mount yaw is taken to turn
+//! the same way.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::game_params::types::MountSpecies;
+use crate::models::visual::BoundingBox;
+
+use super::gltf_export::ArmorSubModel;
+
+/// Largest angle covered by one fan triangle, in degrees.
+const FAN_STEP_DEG: f32 = 5.0;
+
+/// Firing limits of one mount, placed on the ship.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MountArc {
+    /// Hardpoint name, e.g. "HP_JGM_1".
+    pub hp_name: String,
+    pub species: Option<MountSpecies>,
+    /// Mount position in model space.
+    pub position: [f32; 3],
+    /// Ship bearing the mount faces at rest.
+    pub rest_bearing_deg: f32,
+    /// Traverse limits relative to the rest direction; `None` = all round.
+    pub horiz_sector: Option<[f32; 2]>,
+    /// Yaw ranges relative to the rest direction where firing is blocked.
+    pub dead_zones: Vec<[f32; 2]>,
+}
+
+impl MountArc {
+    /// Place a mount from its hardpoint transform (column-major, model
+    /// space); the rest direction is the transform's +Z axis.
+    pub fn new(
+        hp_name: String,
+        species: Option<MountSpecies>,
+        hardpoint: &[f32; 16],
+        horiz_sector: Option<[f32; 2]>,
+        dead_zones: Vec<[f32; 2]>,
+    ) -> Self {
+        Self {
+            hp_name,
+            species,
+            position: [hardpoint[12], hardpoint[13], hardpoint[14]],
+            rest_bearing_deg: normalize_bearing(hardpoint[8].atan2(hardpoint[10]).to_degrees()),
+            horiz_sector,
+            dead_zones,
+        }
+    }
+
+    /// Whether the mount can fire at ship bearing `bearing_deg`.
+    pub fn can_fire_at(&self, bearing_deg: f32) -> bool {
+        // Yaw relative to rest, tried in each turn since sectors may extend
+        // past ±180°.
+        let yaw = normalize_bearing(bearing_deg - self.rest_bearing_deg);
+        let [min, max] = self.horiz_sector.unwrap_or([-180.0, 180.0]);
+        [yaw - 360.0, yaw, yaw + 360.0].into_iter().any(|yaw| {
+            yaw >= min
+                && yaw <= max
+                && !self
+                    .dead_zones
+                    .iter()
+                    .any(|&[dz_min, dz_max]| yaw > dz_min && yaw < dz_max)
+        })
+    }
+
+    /// Firing arcs as ship-bearing intervals `[start, end]`, clockwise from
+    /// `start`. `end` may exceed 360 for arcs crossing the bow.
+    pub fn arcs(&self) -> Vec<[f32; 2]> {
+        let [min, max] = self.horiz_sector.unwrap_or([-180.0, 180.0]);
+        let mut dead: Vec<[f32; 2]> = self
+            .dead_zones
+            .iter()
+            .filter(|[a, b]| b > a)
+            .copied()
+            .collect();
+        dead.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+        // Sector minus dead zones, in yaw relative to rest.
+        let mut arcs = Vec::new();
+        let mut start = min;
+        for [dz_min, dz_max] in dead {
+            if dz_min > start {
+                arcs.push([start, dz_min.min(max)]);
+            }
+            start = start.max(dz_max);
+            if start >= max {
+                break;
+            }
+        }
+        if start < max {
+            arcs.push([start, max]);
+        }
+
+        arcs.into_iter()
+            .filter(|[a, b]| b > a)
+            .map(|[a, b]| {
+                let start = normalize_bearing(self.rest_bearing_deg + a);
+                [start, start + (b - a).min(360.0)]
+            })
+            .collect()
+    }
+}
+
+/// Number of mounts able to fire at one bearing, per mount group
+/// (see [`MountSpecies::display_group`]).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BearingCount {
+    pub bearing_deg: f32,
+    pub by_group: BTreeMap<String, usize>,
+}
+
+/// Mounts bearing at every `step_deg` degrees around the ship.
+pub fn bearing_table(mounts: &[MountArc], step_deg: f32) -> Vec<BearingCount> {
+    if step_deg <= 0.0 {
+        return Vec::new();
+    }
+    let steps = (360.0 / step_deg).ceil() as usize;
+    (0..steps)
+        .map(|i| {
+            let bearing_deg = i as f32 * step_deg;
+            let mut by_group = BTreeMap::new();
+            for mount in mounts {
+                if mount.can_fire_at(bearing_deg) {
+                    *by_group
+                        .entry(group_name(mount.species).to_string())
+                        .or_insert(0) += 1;
+                }
+            }
+            BearingCount {
+                bearing_deg,
+                by_group,
+            }
+        })
+        .collect()
+}
+
+/// One flat translucent fan per mount at the mount's height, `radius`
+/// model units long, for the ship GLB. Positions are already right-handed
+/// (Z negated).
+pub fn arc_fan_meshes(mounts: &[MountArc], radius: f32) -> Vec<ArmorSubModel> {
+    mounts
+        .iter()
+        .filter_map(|mount| {
+            let [cx, cy, cz] = mount.position;
+            let [r, g, b] = group_color(mount.species);
+            let mut positions = Vec::new();
+            for [start, end] in mount.arcs() {
+                let segments = ((end - start) / FAN_STEP_DEG).ceil().max(1.0) as usize;
+                let step = (end - start) / segments as f32;
+                for s in 0..segments {
+                    let edge = |deg: f32| {
+                        let (sin, cos) = deg.to_radians().sin_cos();
+                        [cx + sin * radius, cy, -(cz + cos * radius)]
+                    };
+                    let a = start + step * s as f32;
+                    positions.extend([[cx, cy, -cz], edge(a + step), edge(a)]);
+                }
+            }
+            if positions.is_empty() {
+                return None;
+            }
+            let count = positions.len();
+            Some(ArmorSubModel {
+                name: format!("Arc_{}", mount.hp_name),
+                normals: vec![[0.0, 1.0, 0.0]; count],
+                indices: (0..count as u32).collect(),
+                colors: vec![[r, g, b, 0.25]; count],
+                positions,
+                transform: None,
+            })
+        })
+        .collect()
+}
+
+/// Top-down SVG of the firing arcs over the hull outline, bow up.
+///
+/// `hull` is the hull's model-space bounding box; arcs are drawn `radius`
+/// model units long.
+pub fn firing_arcs_svg(mounts: &[MountArc], hull: &BoundingBox, radius: f32) -> String {
+    const MARGIN: f32 = 10.0;
+    let [min_x, _, min_z] = hull.min;
+    let [max_x, _, max_z] = hull.max;
+    let extent_x = max_x.abs().max(min_x.abs()) + radius;
+    let (top, bottom) = (max_z + radius, min_z - radius);
+    let width = 2.0 * extent_x + 2.0 * MARGIN;
+    let height = top - bottom + 2.0 * MARGIN;
+    // Model (x, z) to SVG (x, y): starboard right, bow up.
+    let to_svg = |x: f32, z: f32| (x + extent_x + MARGIN, top - z + MARGIN);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width:.1} {height:.1}" width="{:.0}" height="{:.0}">"#,
+        width * 4.0,
+        height * 4.0
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    // Hull outline: the bounding box with a pointed bow.
+    let beam = (max_x - min_x) / 2.0;
+    let mid_x = (max_x + min_x) / 2.0;
+    let bow_start = max_z - beam * 2.0;
+    let outline = [
+        (mid_x, max_z),
+        (max_x, bow_start),
+        (max_x, min_z),
+        (min_x, min_z),
+        (min_x, bow_start),
+    ];
+    let points: Vec<String> = outline
+        .iter()
+        .map(|&(x, z)| {
+            let (sx, sy) = to_svg(x, z);
+            format!("{sx:.2},{sy:.2}")
+        })
+        .collect();
+    let _ = writeln!(
+        svg,
+        r##"<polygon points="{}" fill="#d8d8d8" stroke="#404040" stroke-width="0.5"/>"##,
+        points.join(" ")
+    );
+
+    for mount in mounts {
+        let [cx, _, cz] = mount.position;
+        let (sx, sy) = to_svg(cx, cz);
+        let [r, g, b] = group_color(mount.species);
+        let fill = format!(
+            "#{:02x}{:02x}{:02x}",
+            (r * 255.0) as u8,
+            (g * 255.0) as u8,
+            (b * 255.0) as u8
+        );
+        let _ = writeln!(svg, r#"<g><title>{}</title>"#, mount.hp_name);
+        for [start, end] in mount.arcs() {
+            let point = |deg: f32| {
+                let (sin, cos) = deg.to_radians().sin_cos();
+                to_svg(cx + sin * radius, cz + cos * radius)
+            };
+            let ((x0, y0), (x1, y1)) = (point(start), point(end));
+            let large_arc = u8::from(end - start > 180.0);
+            if end - start >= 359.99 {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{sx:.2}" cy="{sy:.2}" r="{radius:.2}" fill="{fill}" fill-opacity="0.2" stroke="{fill}" stroke-width="0.3"/>"#
+                );
+            } else {
+                // Clockwise in SVG (sweep 1) matches clockwise bearings.
+                let _ = writeln!(
+                    svg,
+                    r#"<path d="M {sx:.2} {sy:.2} L {x0:.2} {y0:.2} A {radius:.2} {radius:.2} 0 {large_arc} 1 {x1:.2} {y1:.2} Z" fill="{fill}" fill-opacity="0.2" stroke="{fill}" stroke-width="0.3"/>"#
+                );
+            }
+        }
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{sx:.2}" cy="{sy:.2}" r="1" fill="{fill}"/></g>"#
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Wrap a bearing into `[0, 360)`.
+fn normalize_bearing(deg: f32) -> f32 {
+    let deg = deg.rem_euclid(360.0);
+    if deg >= 360.0 { 0.0 } else { deg }
+}
+
+fn group_name(species: Option<MountSpecies>) -> &'static str {
+    species.map_or("Other", |s| s.display_group())
+}
+
+fn group_color(species: Option<MountSpecies>) -> [f32; 3] {
+    match species {
+        Some(MountSpecies::Main) => [0.85, 0.20, 0.15],
+        Some(MountSpecies::Secondary) => [0.95, 0.60, 0.10],
+        Some(MountSpecies::Torpedo) => [0.15, 0.45, 0.90],
+        Some(MountSpecies::AAircraft) => [0.30, 0.70, 0.30],
+        _ => [0.50, 0.50, 0.50],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arcs_exclude_dead_zones_and_follow_rest_direction() {
+        // Rear turret: rest direction rotated 180° (facing the stern).
+        let mut hardpoint = [0.0; 16];
+        hardpoint[0] = -1.0;
+        hardpoint[5] = 1.0;
+        hardpoint[10] = -1.0;
+        hardpoint[15] = 1.0;
+        hardpoint[14] = -50.0;
+        let mount = MountArc::new(
+            "HP_JGM_3".to_string(),
+            Some(MountSpecies::Main),
+            &hardpoint,
+            Some([-150.0, 150.0]),
+            vec![[-20.0, -10.0]],
+        );
+        assert_eq!(mount.rest_bearing_deg, 180.0);
+        assert_eq!(mount.arcs(), [[30.0, 160.0], [170.0, 330.0]]);
+
+        assert!(mount.can_fire_at(180.0));
+        assert!(mount.can_fire_at(90.0));
+        assert!(!mount.can_fire_at(0.0), "blocked toward the bow");
+        assert!(!mount.can_fire_at(165.0), "inside the dead zone");
+
+        let table = bearing_table(&[mount], 90.0);
+        let counts: Vec<usize> = table
+            .iter()
+            .map(|row| row.by_group.get("Main Battery").copied().unwrap_or(0))
+            .collect();
+        assert_eq!(counts, [0, 1, 1, 1]);
+    }
+}
@@ -3,6 +3,7 @@
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{Read, Write};
+use std::sync::Arc;
 
 use gltf_json as json;
 use json::validation::Checked::Valid;
@@ -11,15 +12,17 @@ use rootcause::Report;
 use thiserror::Error;
 
 use crate::game_params::types::ArmorMap;
+use crate::models::animation::{self, Animation};
 use crate::models::assets_bin::PrototypeDatabase;
-use crate::models::geometry::MergedGeometry;
+use crate::models::geometry::{MergedGeometry, VerticesPrototype};
 use crate::models::merged_models::{MergedModels, SpaceInstances};
 use crate::models::speedtree::SpeedTreeMesh;
 use crate::models::terrain::Terrain;
 use crate::models::vertex_format::{self, AttributeSemantic, VertexFormat};
-use crate::models::visual::VisualPrototype;
+use crate::models::visual::{BoundingBox, VisualNodes, VisualPrototype};
 
 use super::texture;
+use super::texture_cache::TextureCache;
 
 #[derive(Debug, Error)]
 pub enum ExportError {
@@ -46,6 +49,8 @@ pub enum ExportError {
         format_stride: usize,
         geo_stride: usize,
     },
+    #[error("vertex format error: {0}")]
+    VertexFormat(String),
     #[error("glTF serialization error: {0}")]
     Serialize(String),
     #[error("I/O error: {0}")]
@@ -53,16 +58,20 @@ pub enum ExportError {
 }
 
 /// Decoded primitive data ready for glTF export.
-struct DecodedPrimitive {
-    positions: Vec<[f32; 3]>,
-    normals: Vec<[f32; 3]>,
-    uvs: Vec<[f32; 2]>,
-    indices: Vec<u32>,
-    material_name: String,
+pub(super) struct DecodedPrimitive {
+    pub(super) positions: Vec<[f32; 3]>,
+    pub(super) normals: Vec<[f32; 3]>,
+    pub(super) uvs: Vec<[f32; 2]>,
+    pub(super) indices: Vec<u32>,
+    pub(super) material_name: String,
     /// MFM stem for texture lookup (e.g. "JSB039_Yamato_1945_Hull").
-    mfm_stem: Option<String>,
+    pub(super) mfm_stem: Option<String>,
     /// Full VFS path to the .mfm file (e.g. "content/location/.../textures/LBC001.mfm").
     mfm_full_path: Option<String>,
+    /// Per-vertex skeleton node indices (`JOINTS_0`). Empty unless skinning was requested.
+    joints: Vec<[u16; 4]>,
+    /// Per-vertex bone weights (`WEIGHTS_0`), same length as `joints`.
+    weights: Vec<[f32; 4]>,
 }
 
 /// Export a visual + geometry pair to a GLB binary and write it.
@@ -71,6 +80,14 @@ struct DecodedPrimitive {
 /// Primitives whose MFM stem matches a key will have the texture applied as
 /// `baseColorTexture` on their material. Camo variants are exposed via
 /// `KHR_materials_variants` so users can switch in Blender.
+///
+/// With `skinned`, the visual's node hierarchy is exported as a glTF skin and
+/// every vertex is bound to its skeleton nodes, so parts can be posed.
+///
+/// With `all_lods`, `lod` and every lower-detail LOD after it are exported:
+/// `lod` is the primary mesh and the others are chained through `MSFT_lod`
+/// (see [`add_lod_chain`]).
+#[allow(clippy::too_many_arguments)]
 pub fn export_glb(
     visual: &VisualPrototype,
     geometry: &MergedGeometry,
@@ -78,6 +95,8 @@ pub fn export_glb(
     lod: usize,
     texture_set: &TextureSet,
     damaged: bool,
+    skinned: bool,
+    all_lods: bool,
     writer: &mut impl Write,
 ) -> Result<(), Report<ExportError>> {
     if visual.lods.is_empty() {
@@ -90,23 +109,7 @@ pub fn export_glb(
         )));
     }
 
-    let lod_entry = &visual.lods[lod];
-
-    // Collect render sets for this LOD by matching LOD render_set_names to RS name_ids.
-    let self_id_index = db.build_self_id_index();
-    let primitives = collect_primitives(
-        visual,
-        geometry,
-        Some(db),
-        Some(&self_id_index),
-        lod_entry,
-        damaged,
-        None,
-    )?;
-
-    if primitives.is_empty() {
-        eprintln!("Warning: no primitives found for LOD {lod}");
-    }
+    let lods = lod_range(lod, visual.lods.len(), all_lods);
 
     // Build glTF document.
     let mut root = json::Root {
@@ -120,14 +123,38 @@ pub fn export_glb(
 
     // Accumulate all binary data into a single buffer.
     let mut bin_data: Vec<u8> = Vec::new();
-    let mut gltf_primitives = Vec::new();
     let mut mat_cache = MaterialCache::new();
+    let self_id_index = db.build_self_id_index();
 
-    for prim in &primitives {
-        let gltf_prim =
-            add_primitive_to_root(&mut root, &mut bin_data, prim, texture_set, &mut mat_cache)?;
-        gltf_primitives.push(gltf_prim);
-    }
+    // One mesh per exported LOD that has primitives, primary first.
+    let lod_meshes = add_lod_meshes(&mut root, lods, None, |root, lod| {
+        // Collect render sets for this LOD by matching LOD render_set_names to RS name_ids.
+        let primitives = collect_primitives(
+            visual,
+            geometry,
+            Some(db),
+            Some(&self_id_index),
+            &visual.lods[lod],
+            damaged,
+            None,
+            skinned,
+        )?;
+        primitives
+            .iter()
+            .map(|prim| {
+                add_primitive_to_root(root, &mut bin_data, prim, texture_set, &mut mat_cache)
+            })
+            .collect()
+    })?;
+
+    let (skin, joint_roots) = if skinned {
+        let node_names = skeleton_node_names(&visual.nodes, Some(db));
+        let (skin, joint_roots) =
+            add_skeleton_to_root(&mut root, &mut bin_data, &visual.nodes, &node_names);
+        (Some(skin), Some(joint_roots))
+    } else {
+        (None, None)
+    };
 
     // Pad binary data to 4-byte alignment.
     while !bin_data.len().is_multiple_of(4) {
@@ -149,21 +176,28 @@ pub fn export_glb(
         }
     }
 
-    // Create mesh with all primitives.
-    let mesh = root.push(json::Mesh {
-        primitives: gltf_primitives,
-        weights: None,
-        name: None,
-        extensions: Default::default(),
-        extras: Default::default(),
-    });
-
-    // Create a simple node hierarchy.
-    // For now, use a single root node with the mesh attached.
-    let root_node = root.push(json::Node {
-        mesh: Some(mesh),
-        ..Default::default()
-    });
+    // Create a node per LOD mesh. The primary LOD node is the scene root,
+    // with the skeleton beneath it when skinned; lower LODs share the skin.
+    // Without any mesh the root node only holds the skeleton.
+    let mut lod_nodes = Vec::new();
+    for (i, &(lod, mesh)) in lod_meshes.iter().enumerate() {
+        let node = root.push(json::Node {
+            mesh: Some(mesh),
+            name: root.meshes[mesh.value()].name.clone(),
+            skin,
+            children: if i == 0 { joint_roots.clone() } else { None },
+            ..Default::default()
+        });
+        lod_nodes.push((node, visual.lods[lod].extent));
+    }
+    let root_node = match lod_nodes.first() {
+        Some(&(node, _)) => node,
+        None => root.push(json::Node {
+            children: joint_roots,
+            ..Default::default()
+        }),
+    };
+    add_lod_chain(&mut root, &lod_nodes, &visual.bounding_box)?;
 
     let scene = root.push(json::Scene {
         nodes: vec![root_node],
@@ -173,7 +207,7 @@ pub fn export_glb(
     });
     root.scene = Some(scene);
 
-    // Add KHR_materials_variants root extension if we have camo schemes.
+    // Add KHR_materials_variants root extension if we have camo or dye variants.
     add_variants_extension(&mut root, texture_set);
 
     // Serialize and write GLB.
@@ -262,6 +296,11 @@ pub struct MapMesh {
     pub alpha_blend: bool,
     /// Alpha cutoff for mask mode (e.g. `Some(0.5)` for leaf transparency).
     pub alpha_cutoff: Option<f32>,
+    /// Per-vertex skeleton node indices (`JOINTS_0`) of an animated model's
+    /// mesh. Empty for static meshes.
+    pub joints: Vec<[u16; 4]>,
+    /// Per-vertex bone weights (`WEIGHTS_0`), same length as `joints`.
+    pub weights: Vec<[f32; 4]>,
 }
 
 /// A positioned model instance in the map.
@@ -270,6 +309,19 @@ pub struct MapModelInstance {
     pub mesh_range: std::ops::Range<usize>,
     /// Column-major 4×4 world transform (right-handed, Z negated).
     pub transform: [f32; 16],
+    /// Index into [`MapScene::animated_models`] if the model is animated.
+    /// Each instance gets its own copy of the skeleton, so its meshes are
+    /// skinned and its clips play independently.
+    pub animated_model: Option<usize>,
+}
+
+/// Skeleton and animation clips of an animated map model, e.g. a radar or
+/// a flag.
+pub struct AnimatedMapModel {
+    pub nodes: VisualNodes,
+    /// Node names in node index order, matched by the clips' channels.
+    pub node_names: Vec<String>,
+    pub animations: Vec<Animation>,
 }
 
 /// Complete decoded map scene, format-agnostic.
@@ -281,8 +333,12 @@ pub struct MapScene {
     pub model_meshes: Vec<MapMesh>,
     /// Positioned model instances referencing `model_meshes` by range.
     pub model_instances: Vec<MapModelInstance>,
+    /// Animated models referenced by [`MapModelInstance::animated_model`].
+    /// Only the glTF exporter plays their clips; other formats export the
+    /// rest pose.
+    pub animated_models: Vec<AnimatedMapModel>,
     /// Shared albedo textures (PNG bytes). Meshes reference these by index.
-    pub textures: Vec<Vec<u8>>,
+    pub textures: Vec<PngBytes>,
     /// Terrain mesh, if generated.
     pub terrain: Option<MapMesh>,
     /// Water plane mesh, if generated.
@@ -308,10 +364,13 @@ struct MapMaterialKey {
 /// Material cache mapping unique material parameters to their glTF index.
 type MapMaterialCache = HashMap<MapMaterialKey, json::Index<json::Material>>;
 
+/// Encoded PNG bytes, shared with the [`TextureCache`] that decoded them.
+pub type PngBytes = Arc<Vec<u8>>;
+
 /// A single SpeedTree species with its mesh and optional albedo texture.
 pub struct VegetationSpecies {
     pub mesh: SpeedTreeMesh,
-    pub albedo_png: Option<Vec<u8>>,
+    pub albedo_png: Option<PngBytes>,
 }
 
 /// Vegetation data: species meshes + positioned instances.
@@ -337,7 +396,15 @@ pub struct BuildMapSceneParams<'a> {
     pub vfs: Option<&'a vfs::VfsPath>,
     pub env: &'a MapEnvironment<'a>,
     pub bounds: SpaceBounds,
+    /// Load model textures and the terrain lightmap from `vfs`.
+    pub textures: bool,
     pub max_texture_size: Option<u32>,
+    /// Decodes the model and terrain textures.
+    pub texture_cache: &'a TextureCache,
+    /// Skin models that have animation clips (radar spins, flags, cranes)
+    /// and load the clips, as the ship exporter's `skinned` option does.
+    /// Requires `db` and `vfs`.
+    pub skinned: bool,
     pub vegetation: Option<&'a VegetationData>,
     pub vegetation_density: f32,
 }
@@ -352,16 +419,19 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
         vfs,
         env,
         ref bounds,
+        textures: load_textures,
         max_texture_size,
+        texture_cache,
+        skinned,
         vegetation,
         vegetation_density,
     } = *params;
     let self_id_index = db.map(|db| db.build_self_id_index());
 
     // Shared texture storage: each unique texture is stored once.
-    let mut textures: Vec<Vec<u8>> = Vec::new();
-    // Cache: MFM full path → Option<texture index>
-    let mut texture_cache: HashMap<String, Option<usize>> = HashMap::new();
+    let mut textures: Vec<Arc<Vec<u8>>> = Vec::new();
+    // MFM full path → Option<texture index>
+    let mut texture_indices: HashMap<String, Option<usize>> = HashMap::new();
 
     // Build path_id → model index map for instance lookups.
     let path_to_model: HashMap<u64, usize> = merged
@@ -375,6 +445,9 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
     // model_mesh_ranges[i] = range in model_meshes for model index i.
     let mut model_meshes: Vec<MapMesh> = Vec::new();
     let mut model_mesh_ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    // model_animations[i] = index into animated_models for model index i.
+    let mut animated_models: Vec<AnimatedMapModel> = Vec::new();
+    let mut model_animations: Vec<Option<usize>> = vec![None; merged.models.len()];
 
     for (model_idx, record) in merged.models.iter().enumerate() {
         let vp = &record.visual_proto;
@@ -385,6 +458,21 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
             continue;
         }
 
+        let animations = match (skinned, db, self_id_index.as_ref(), vfs) {
+            (true, Some(db), Some(index), Some(vfs)) => {
+                let paths: Vec<String> = record
+                    .model_proto
+                    .animations
+                    .iter()
+                    .filter_map(|anim| index.get(&anim.visual_resource_id))
+                    .map(|&idx| db.reconstruct_path(idx, index))
+                    .collect();
+                load_animations(vfs, &paths)
+            }
+            _ => Vec::new(),
+        };
+        let animated = !animations.is_empty();
+
         let lod_entry = &vp.lods[lod];
         let primitives = match collect_primitives(
             vp,
@@ -394,6 +482,7 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
             lod_entry,
             false,
             None,
+            animated,
         ) {
             Ok(p) => p,
             Err(e) => {
@@ -405,19 +494,19 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
 
         for prim in primitives {
             // Load texture on demand via full MFM path (deduplicated).
-            let albedo_texture = if let Some(vfs) = vfs
+            let albedo_texture = if load_textures
+                && let Some(vfs) = vfs
                 && let Some(mfm_path) = &prim.mfm_full_path
             {
-                *texture_cache.entry(mfm_path.clone()).or_insert_with(|| {
-                    texture::load_base_albedo_bytes(vfs, mfm_path)
-                        .and_then(|dds_bytes| {
-                            texture::dds_to_png_resized(&dds_bytes, max_texture_size).ok()
-                        })
-                        .map(|png_bytes| {
-                            let idx = textures.len();
-                            textures.push(png_bytes);
-                            idx
-                        })
+                *texture_indices.entry(mfm_path.clone()).or_insert_with(|| {
+                    let dds_path = texture::find_base_albedo_path(vfs, mfm_path)?;
+                    let png_bytes = texture_cache
+                        .png(vfs, &dds_path, max_texture_size)
+                        .ok()
+                        .flatten()?;
+                    let idx = textures.len();
+                    textures.push(png_bytes);
+                    Some(idx)
                 })
             } else {
                 None
@@ -433,9 +522,19 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
                 base_color: [1.0, 1.0, 1.0, 1.0],
                 alpha_blend: false,
                 alpha_cutoff: None,
+                joints: prim.joints,
+                weights: prim.weights,
             });
         }
 
+        if animated && model_meshes.len() > range_start {
+            model_animations[model_idx] = Some(animated_models.len());
+            animated_models.push(AnimatedMapModel {
+                nodes: vp.nodes.clone(),
+                node_names: skeleton_node_names(&vp.nodes, db),
+                animations,
+            });
+        }
         model_mesh_ranges.push(range_start..model_meshes.len());
     }
 
@@ -455,6 +554,7 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
             model_instances.push(MapModelInstance {
                 mesh_range: range.clone(),
                 transform: inst.transform.0,
+                animated_model: model_animations[model_idx],
             });
         }
     } else {
@@ -463,12 +563,12 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
             if range.is_empty() {
                 continue;
             }
-            let _ = model_idx;
             model_instances.push(MapModelInstance {
                 mesh_range: range.clone(),
                 transform: [
                     1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
                 ],
+                animated_model: model_animations[model_idx],
             });
         }
     }
@@ -478,33 +578,20 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
         let mut mesh = generate_terrain_mesh(cfg);
 
         // Try to load the lightmap shadow DDS as terrain albedo.
-        if let Some(vfs) = vfs
+        if load_textures
+            && let Some(vfs) = vfs
             && let Some(lm_path) = &cfg.lightmap_path
         {
-            let dds_bytes: Option<Vec<u8>> = (|| {
-                let mut buf = Vec::new();
-                vfs.join(lm_path)
-                    .ok()?
-                    .open_file()
-                    .ok()?
-                    .read_to_end(&mut buf)
-                    .ok()?;
-                if buf.is_empty() { None } else { Some(buf) }
-            })();
-            match dds_bytes {
-                Some(dds_bytes) => {
-                    match texture::dds_to_png_resized(&dds_bytes, max_texture_size) {
-                        Ok(png_bytes) => {
-                            let idx = textures.len();
-                            textures.push(png_bytes);
-                            mesh.albedo_texture = Some(idx);
-                            mesh.base_color = [1.0, 1.0, 1.0, 1.0];
-                            eprintln!("  Terrain lightmap loaded: {lm_path}");
-                        }
-                        Err(e) => eprintln!("  Warning: failed to decode terrain lightmap: {e}"),
-                    }
+            match texture_cache.png(vfs, lm_path, max_texture_size) {
+                Ok(Some(png_bytes)) => {
+                    let idx = textures.len();
+                    textures.push(png_bytes);
+                    mesh.albedo_texture = Some(idx);
+                    mesh.base_color = [1.0, 1.0, 1.0, 1.0];
+                    eprintln!("  Terrain lightmap loaded: {lm_path}");
                 }
-                None => eprintln!("  Warning: terrain lightmap not found: {lm_path}"),
+                Ok(None) => eprintln!("  Warning: terrain lightmap not found: {lm_path}"),
+                Err(e) => eprintln!("  Warning: failed to decode terrain lightmap: {e}"),
             }
         }
 
@@ -542,6 +629,8 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
                 base_color: [1.0, 1.0, 1.0, 1.0],
                 alpha_blend: false,
                 alpha_cutoff: Some(0.5),
+                joints: Vec::new(),
+                weights: Vec::new(),
             });
             species_mesh_ranges.push(Some(mesh_idx));
         }
@@ -593,7 +682,7 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
         );
     }
 
-    let tex_tried = texture_cache.len();
+    let tex_tried = texture_indices.len();
     let tex_loaded = textures.len();
     eprintln!(
         "Map scene: {} model meshes, {} instances, {tex_loaded}/{tex_tried} textures loaded",
@@ -606,10 +695,14 @@ pub fn build_map_scene(params: &BuildMapSceneParams<'_>) -> Result<MapScene, Rep
     if water.is_some() {
         eprintln!("  Water plane generated");
     }
+    if !animated_models.is_empty() {
+        eprintln!("  {} animated models", animated_models.len());
+    }
 
     Ok(MapScene {
         model_meshes,
         model_instances,
+        animated_models,
         textures,
         terrain,
         water,
@@ -765,6 +858,8 @@ fn generate_terrain_mesh(cfg: &TerrainConfig<'_>) -> MapMesh {
         base_color: [0.3, 0.35, 0.25, 1.0],
         alpha_blend: false,
         alpha_cutoff: None,
+        joints: Vec::new(),
+        weights: Vec::new(),
     }
 }
 
@@ -796,6 +891,8 @@ fn generate_water_mesh(cfg: &WaterConfig<'_>) -> MapMesh {
         base_color: [0.1, 0.3, 0.5, 0.85],
         alpha_blend: true,
         alpha_cutoff: None,
+        joints: Vec::new(),
+        weights: Vec::new(),
     }
 }
 
@@ -804,6 +901,10 @@ fn generate_water_mesh(cfg: &WaterConfig<'_>) -> MapMesh {
 // ---------------------------------------------------------------------------
 
 /// Serialize a [`MapScene`] to GLB format.
+///
+/// Instances of [`MapScene::animated_models`] are skinned to their own
+/// skeleton, and each of their clips becomes a glTF animation named
+/// `Instance_{i} {clip}`.
 pub fn export_map_scene_glb(
     scene: &MapScene,
     writer: &mut impl Write,
@@ -908,6 +1009,45 @@ pub fn export_map_scene_glb(
             continue;
         }
 
+        // Animated instances get their own skeleton beneath the instance
+        // node, with every part skinned to it and the clips targeting it.
+        if let Some(animated) = inst
+            .animated_model
+            .and_then(|idx| scene.animated_models.get(idx))
+        {
+            let (skin, joint_roots) = add_skeleton_to_root(
+                &mut root,
+                &mut bin_data,
+                &animated.nodes,
+                &animated.node_names,
+            );
+            for anim in &animated.animations {
+                let clip_name = format!("Instance_{i} {}", anim.identifier);
+                add_animation_to_root(&mut root, &mut bin_data, anim, skin, clip_name);
+            }
+            let mut children: Vec<json::Index<json::Node>> = instance_meshes
+                .iter()
+                .enumerate()
+                .map(|(j, &mesh)| {
+                    root.push(json::Node {
+                        mesh: Some(mesh),
+                        skin: Some(skin),
+                        name: Some(format!("Instance_{i}_part_{j}")),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+            children.extend(joint_roots);
+            let parent = root.push(json::Node {
+                children: Some(children),
+                name: Some(format!("Instance_{i}")),
+                matrix: Some(inst.transform),
+                ..Default::default()
+            });
+            scene_nodes.push(parent);
+            continue;
+        }
+
         // If the model has a single mesh, create one node with the transform.
         // If multiple, create a parent node with children.
         if instance_meshes.len() == 1 {
@@ -1066,6 +1206,91 @@ pub fn export_map_scene_glb(
     Ok(())
 }
 
+/// Append `JOINTS_0`/`WEIGHTS_0` accessors for skinned vertices to
+/// `attributes`. Does nothing when `joints` is empty.
+fn push_skin_attributes(
+    root: &mut json::Root,
+    bin_data: &mut Vec<u8>,
+    joints: &[[u16; 4]],
+    weights: &[[f32; 4]],
+    attributes: &mut BTreeMap<
+        json::validation::Checked<json::mesh::Semantic>,
+        json::Index<json::Accessor>,
+    >,
+) {
+    if joints.is_empty() {
+        return;
+    }
+    let byte_offset = bin_data.len();
+    for j in joints {
+        for &v in j {
+            bin_data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    pad_to_4(bin_data);
+    let joints_bv = root.push(json::buffer::View {
+        buffer: json::Index::new(0),
+        byte_length: USize64::from(bin_data.len() - byte_offset),
+        byte_offset: Some(USize64::from(byte_offset)),
+        byte_stride: None,
+        target: Some(Valid(json::buffer::Target::ArrayBuffer)),
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    let joints_acc = root.push(json::Accessor {
+        buffer_view: Some(joints_bv),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(joints.len()),
+        component_type: Valid(json::accessor::GenericComponentType(
+            json::accessor::ComponentType::U16,
+        )),
+        type_: Valid(json::accessor::Type::Vec4),
+        min: None,
+        max: None,
+        name: None,
+        normalized: false,
+        sparse: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    attributes.insert(Valid(json::mesh::Semantic::Joints(0)), joints_acc);
+
+    let byte_offset = bin_data.len();
+    for w in weights {
+        for &v in w {
+            bin_data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    let weights_bv = root.push(json::buffer::View {
+        buffer: json::Index::new(0),
+        byte_length: USize64::from(bin_data.len() - byte_offset),
+        byte_offset: Some(USize64::from(byte_offset)),
+        byte_stride: None,
+        target: Some(Valid(json::buffer::Target::ArrayBuffer)),
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    let weights_acc = root.push(json::Accessor {
+        buffer_view: Some(weights_bv),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(weights.len()),
+        component_type: Valid(json::accessor::GenericComponentType(
+            json::accessor::ComponentType::F32,
+        )),
+        type_: Valid(json::accessor::Type::Vec4),
+        min: None,
+        max: None,
+        name: None,
+        normalized: false,
+        sparse: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    attributes.insert(Valid(json::mesh::Semantic::Weights(0)), weights_acc);
+}
+
 /// Build a single glTF primitive from a [`MapMesh`].
 fn build_map_mesh_primitive(
     root: &mut json::Root,
@@ -1252,6 +1477,7 @@ fn build_map_mesh_primitive(
     if let Some(uv) = uv_accessor {
         attributes.insert(Valid(json::mesh::Semantic::TexCoords(0)), uv);
     }
+    push_skin_attributes(root, bin_data, &mesh.joints, &mesh.weights, &mut attributes);
 
     // Material: deduplicate by (texture index, base color, alpha blend, alpha cutoff).
     // Encode base_color as [u32; 4] for HashMap key (f32 isn't Hash).
@@ -1392,6 +1618,7 @@ pub fn export_merged_models_glb(
             lod_entry,
             false,
             None,
+            false,
         ) {
             Ok(p) => p,
             Err(e) => {
@@ -1605,7 +1832,13 @@ pub fn export_geometry_raw(
         };
 
         // Parse vertex format.
-        let format = vertex_format::parse_vertex_format(&vert_proto.format_name);
+        let format = match checked_vertex_format(vert_proto) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("Warning: primitive {i}: {e:?}, skipping");
+                continue;
+            }
+        };
         let stride = vert_proto.stride_in_bytes as usize;
 
         // Extract vertex slice.
@@ -1666,6 +1899,8 @@ pub fn export_geometry_raw(
             material_name: format!("Primitive_{i}"),
             mfm_stem: None,
             mfm_full_path: None,
+            joints: Vec::new(),
+            weights: Vec::new(),
         };
 
         let empty_textures = TextureSet::empty();
@@ -1763,7 +1998,11 @@ const DAMAGED_EXCLUDE: &[&str] = &["_patch_", "_hide"];
 ///
 /// When `damaged` is false, crack and hide geometry is excluded (intact hull).
 /// When `damaged` is true, patch and hide geometry is excluded (destroyed look).
-fn collect_primitives(
+///
+/// With `skinned`, each primitive also carries `JOINTS_0`/`WEIGHTS_0` data
+/// indexing the visual's skeleton nodes (see [`add_skeleton_to_root`]).
+#[allow(clippy::too_many_arguments)]
+pub(super) fn collect_primitives(
     visual: &VisualPrototype,
     geometry: &MergedGeometry,
     db: Option<&PrototypeDatabase<'_>>,
@@ -1771,6 +2010,7 @@ fn collect_primitives(
     lod: &crate::models::visual::Lod,
     damaged: bool,
     barrel_pitch: Option<&BarrelPitch>,
+    skinned: bool,
 ) -> Result<Vec<DecodedPrimitive>, Report<ExportError>> {
     let mut result = Vec::new();
     let exclude = if damaged {
@@ -1850,19 +2090,9 @@ fn collect_primitives(
             .map_err(|e| Report::new(ExportError::IndexDecode(format!("{e:?}"))))?;
 
         // Parse vertex format.
-        let format = vertex_format::parse_vertex_format(&vert_proto.format_name);
+        let format = checked_vertex_format(vert_proto)?;
         let stride = vert_proto.stride_in_bytes as usize;
 
-        if format.stride != stride {
-            // The parsed format stride doesn't match the geometry's stride.
-            // This can happen for formats we don't fully parse. Use the
-            // geometry stride and just extract what we can.
-            eprintln!(
-                "Warning: format \"{}\" parsed stride {} != geometry stride {}; using geometry stride",
-                vert_proto.format_name, format.stride, stride
-            );
-        }
-
         // Extract vertex slice.
         let vert_offset = vert_mapping.items_offset as usize;
         let vert_count = vert_mapping.items_count as usize;
@@ -1931,6 +2161,12 @@ fn collect_primitives(
             );
         }
 
+        let (joints, weights) = if skinned {
+            bind_vertices_to_skeleton(visual, rs, &verts)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
         // Material name for this render set.
         let material_name = db
             .and_then(|db| db.strings.get_string_by_id(rs.material_name_id))
@@ -1954,24 +2190,447 @@ fn collect_primitives(
             (None, None)
         };
 
-        result.push(DecodedPrimitive {
-            positions: verts.positions,
-            normals: verts.normals,
-            uvs: verts.uvs,
-            indices,
-            material_name,
-            mfm_stem,
-            mfm_full_path,
-        });
+        result.push(DecodedPrimitive {
+            positions: verts.positions,
+            normals: verts.normals,
+            uvs: verts.uvs,
+            indices,
+            material_name,
+            mfm_stem,
+            mfm_full_path,
+            joints,
+            weights,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Compute `JOINTS_0`/`WEIGHTS_0` for a render set's vertices.
+///
+/// Joint values are skeleton node indices, matching the joint order written by
+/// [`add_skeleton_to_root`]. Skinned render sets map their per-vertex blend
+/// bone indices through the render set's blend bone list (`node_name_ids`).
+/// Rigid render sets bind every vertex to their single attachment node with
+/// full weight. Unresolvable bones fall back to the root node.
+fn bind_vertices_to_skeleton(
+    visual: &VisualPrototype,
+    rs: &crate::models::visual::RenderSet,
+    verts: &UnpackedVertices,
+) -> (Vec<[u16; 4]>, Vec<[f32; 4]>) {
+    let blend_nodes: Vec<u16> = rs
+        .node_name_ids
+        .iter()
+        .map(|&name_id| visual.find_node_index_by_name_id(name_id).unwrap_or(0))
+        .collect();
+    let count = verts.positions.len();
+
+    if !rs.skinned || verts.bone_indices.len() != count {
+        let node = blend_nodes.first().copied().unwrap_or(0);
+        return (
+            vec![[node, 0, 0, 0]; count],
+            vec![[1.0, 0.0, 0.0, 0.0]; count],
+        );
+    }
+
+    let mut joints = Vec::with_capacity(count);
+    let mut weights = Vec::with_capacity(count);
+    for (indices, w) in verts.bone_indices.iter().zip(&verts.bone_weights) {
+        let mut j = [0u16; 4];
+        let mut wt = [0.0f32; 4];
+        for k in 0..3 {
+            // Zero-weight influences keep joint 0 so unused slots stay valid.
+            if w[k] > 0.0 {
+                j[k] = blend_nodes.get(indices[k] as usize).copied().unwrap_or(0);
+                wt[k] = w[k];
+            }
+        }
+        let sum: f32 = wt.iter().sum();
+        if sum > 0.0 {
+            wt.iter_mut().for_each(|x| *x /= sum);
+        } else {
+            wt[0] = 1.0;
+        }
+        joints.push(j);
+        weights.push(wt);
+    }
+    (joints, weights)
+}
+
+/// Names of a skeleton's nodes in node index order, as used for its joints.
+/// Nodes whose name can't be resolved are called `node_{index}`.
+fn skeleton_node_names(nodes: &VisualNodes, db: Option<&PrototypeDatabase<'_>>) -> Vec<String> {
+    (0..nodes.matrices.len())
+        .map(|i| {
+            nodes
+                .name_ids
+                .get(i)
+                .and_then(|&id| db?.strings.get_string_by_id(id))
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("node_{i}"))
+        })
+        .collect()
+}
+
+/// Add a node hierarchy to the glTF root as joint nodes plus a skin.
+///
+/// Every skeleton node becomes a joint named from `node_names` (see
+/// [`skeleton_node_names`]), in node index order, with its local matrix
+/// converted to right-handed space. Inverse bind matrices are taken
+/// from the rest pose, so the exported mesh is unchanged until a joint is
+/// posed. Returns the skin and the root joint nodes, which the caller should
+/// parent under the skinned mesh's node.
+fn add_skeleton_to_root(
+    root: &mut json::Root,
+    bin_data: &mut Vec<u8>,
+    nodes: &VisualNodes,
+    node_names: &[String],
+) -> (json::Index<json::Skin>, Vec<json::Index<json::Node>>) {
+    let node_count = nodes.matrices.len();
+
+    let joints: Vec<json::Index<json::Node>> = (0..node_count)
+        .map(|i| {
+            root.push(json::Node {
+                name: Some(node_names[i].clone()),
+                matrix: Some(negate_z_transform(nodes.matrices[i].0)),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut roots = Vec::new();
+    let mut children: Vec<Vec<json::Index<json::Node>>> = vec![Vec::new(); node_count];
+    for (i, &joint) in joints.iter().enumerate() {
+        let parent = nodes.parent_ids.get(i).copied().unwrap_or(0xFFFF) as usize;
+        if parent < node_count && parent != i {
+            children[parent].push(joint);
+        } else {
+            roots.push(joint);
+        }
+    }
+    for (joint, kids) in joints.iter().zip(children) {
+        if !kids.is_empty() {
+            root.nodes[joint.value()].children = Some(kids);
+        }
+    }
+
+    // Inverse bind matrices: inverse of each joint's rest-pose world transform.
+    let byte_offset = bin_data.len();
+    for i in 0..node_count {
+        let world = negate_z_transform(nodes.world_transform(i as u16));
+        for v in invert_affine(&world) {
+            bin_data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    let byte_length = bin_data.len() - byte_offset;
+
+    let ibm_bv = root.push(json::buffer::View {
+        buffer: json::Index::new(0),
+        byte_length: USize64::from(byte_length),
+        byte_offset: Some(USize64::from(byte_offset)),
+        byte_stride: None,
+        target: None,
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    let ibm_acc = root.push(json::Accessor {
+        buffer_view: Some(ibm_bv),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(node_count),
+        component_type: Valid(json::accessor::GenericComponentType(
+            json::accessor::ComponentType::F32,
+        )),
+        type_: Valid(json::accessor::Type::Mat4),
+        min: None,
+        max: None,
+        name: None,
+        normalized: false,
+        sparse: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    let skin = root.push(json::Skin {
+        inverse_bind_matrices: Some(ibm_acc),
+        joints,
+        skeleton: roots.first().copied(),
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    (skin, roots)
+}
+
+/// Add an animation clip targeting a skin's joints to the glTF root.
+///
+/// Channels are matched to joints by node name; channels for nodes missing
+/// from the skeleton are skipped. Key times are converted from frames to
+/// seconds and keys from left- to right-handed space. Returns `None` if no
+/// channel matched.
+fn add_animation_to_root(
+    root: &mut json::Root,
+    bin_data: &mut Vec<u8>,
+    anim: &Animation,
+    skin: json::Index<json::Skin>,
+    name: String,
+) -> Option<json::Index<json::Animation>> {
+    let joints_by_name: HashMap<String, json::Index<json::Node>> = root.skins[skin.value()]
+        .joints
+        .iter()
+        .filter_map(|&j| root.nodes[j.value()].name.clone().map(|n| (n, j)))
+        .collect();
+
+    let mut samplers = Vec::new();
+    let mut channels = Vec::new();
+
+    for channel in &anim.channels {
+        let Some(&node) = joints_by_name.get(&channel.node_name) else {
+            continue;
+        };
+
+        // Negate Z translation; conjugating a rotation by diag(1,1,-1)
+        // negates the quaternion's X and Y components.
+        let tracks: [(
+            json::animation::Property,
+            Vec<f32>,
+            Vec<f32>,
+            json::accessor::Type,
+        ); 3] = [
+            (
+                json::animation::Property::Translation,
+                channel.position_keys.iter().map(|k| k.0).collect(),
+                channel
+                    .position_keys
+                    .iter()
+                    .flat_map(|(_, [x, y, z])| [*x, *y, -*z])
+                    .collect(),
+                json::accessor::Type::Vec3,
+            ),
+            (
+                json::animation::Property::Rotation,
+                channel.rotation_keys.iter().map(|k| k.0).collect(),
+                channel
+                    .rotation_keys
+                    .iter()
+                    .flat_map(|(_, [x, y, z, w])| [-*x, -*y, *z, *w])
+                    .collect(),
+                json::accessor::Type::Vec4,
+            ),
+            (
+                json::animation::Property::Scale,
+                channel.scale_keys.iter().map(|k| k.0).collect(),
+                channel.scale_keys.iter().flat_map(|(_, v)| *v).collect(),
+                json::accessor::Type::Vec3,
+            ),
+        ];
+
+        for (property, frames, values, type_) in tracks {
+            if frames.is_empty() {
+                continue;
+            }
+            let times: Vec<f32> = frames.iter().map(|f| f / animation::FRAME_RATE).collect();
+            let min = times.iter().copied().fold(f32::MAX, f32::min);
+            let max = times.iter().copied().fold(f32::MIN, f32::max);
+
+            let input = push_f32_accessor(
+                root,
+                bin_data,
+                &times,
+                times.len(),
+                json::accessor::Type::Scalar,
+                Some((vec![min], vec![max])),
+            );
+            let output = push_f32_accessor(root, bin_data, &values, times.len(), type_, None);
+
+            let sampler = json::Index::new(samplers.len() as u32);
+            samplers.push(json::animation::Sampler {
+                input,
+                interpolation: Valid(json::animation::Interpolation::Linear),
+                output,
+                extensions: Default::default(),
+                extras: Default::default(),
+            });
+            channels.push(json::animation::Channel {
+                sampler,
+                target: json::animation::Target {
+                    node,
+                    path: Valid(property),
+                    extensions: Default::default(),
+                    extras: Default::default(),
+                },
+                extensions: Default::default(),
+                extras: Default::default(),
+            });
+        }
+    }
+
+    if channels.is_empty() {
+        return None;
+    }
+
+    Some(root.push(json::Animation {
+        channels,
+        samplers,
+        name: Some(name),
+        extensions: Default::default(),
+        extras: Default::default(),
+    }))
+}
+
+/// Read and parse animation resources, skipping (with a warning) any that
+/// are missing or fail to parse.
+pub(super) fn load_animations(vfs: &vfs::VfsPath, paths: &[String]) -> Vec<Animation> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let mut buf = Vec::new();
+            let read = vfs
+                .join(path)
+                .and_then(|p| p.open_file())
+                .map(|mut f| f.read_to_end(&mut buf));
+            if !matches!(read, Ok(Ok(_))) {
+                eprintln!("Warning: could not read animation '{path}'");
+                return None;
+            }
+            match animation::parse_animation(&buf) {
+                Ok(anim) => Some(anim),
+                Err(e) => {
+                    eprintln!("Warning: skipping animation '{path}': {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Append `values` as a tightly packed f32 accessor (no buffer view target).
+fn push_f32_accessor(
+    root: &mut json::Root,
+    bin_data: &mut Vec<u8>,
+    values: &[f32],
+    count: usize,
+    type_: json::accessor::Type,
+    min_max: Option<(Vec<f32>, Vec<f32>)>,
+) -> json::Index<json::Accessor> {
+    let byte_offset = bin_data.len();
+    for v in values {
+        bin_data.extend_from_slice(&v.to_le_bytes());
+    }
+    let byte_length = bin_data.len() - byte_offset;
+
+    let bv = root.push(json::buffer::View {
+        buffer: json::Index::new(0),
+        byte_length: USize64::from(byte_length),
+        byte_offset: Some(USize64::from(byte_offset)),
+        byte_stride: None,
+        target: None,
+        name: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    let (min, max) = match min_max {
+        Some((min, max)) => (Some(json::Value::from(min)), Some(json::Value::from(max))),
+        None => (None, None),
+    };
+    root.push(json::Accessor {
+        buffer_view: Some(bv),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(count),
+        component_type: Valid(json::accessor::GenericComponentType(
+            json::accessor::ComponentType::F32,
+        )),
+        type_: Valid(type_),
+        min,
+        max,
+        name: None,
+        normalized: false,
+        sparse: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+    })
+}
+
+/// Invert a column-major affine 4x4 transform.
+fn invert_affine(m: &[f32; 16]) -> [f32; 16] {
+    // 3x3 part: a = [[m0, m4, m8], [m1, m5, m9], [m2, m6, m10]]
+    let (a00, a01, a02) = (m[0], m[4], m[8]);
+    let (a10, a11, a12) = (m[1], m[5], m[9]);
+    let (a20, a21, a22) = (m[2], m[6], m[10]);
+
+    let c00 = a11 * a22 - a12 * a21;
+    let c01 = a12 * a20 - a10 * a22;
+    let c02 = a10 * a21 - a11 * a20;
+    let det = a00 * c00 + a01 * c01 + a02 * c02;
+    if det.abs() < f32::EPSILON {
+        let mut identity = [0.0; 16];
+        identity[0] = 1.0;
+        identity[5] = 1.0;
+        identity[10] = 1.0;
+        identity[15] = 1.0;
+        return identity;
     }
+    let inv_det = 1.0 / det;
+
+    // Inverse 3x3 (row-major r[row][col]) = adjugate / det.
+    let r = [
+        [
+            c00 * inv_det,
+            (a02 * a21 - a01 * a22) * inv_det,
+            (a01 * a12 - a02 * a11) * inv_det,
+        ],
+        [
+            c01 * inv_det,
+            (a00 * a22 - a02 * a20) * inv_det,
+            (a02 * a10 - a00 * a12) * inv_det,
+        ],
+        [
+            c02 * inv_det,
+            (a01 * a20 - a00 * a21) * inv_det,
+            (a00 * a11 - a01 * a10) * inv_det,
+        ],
+    ];
 
-    Ok(result)
+    let t = [m[12], m[13], m[14]];
+    let mut out = [0.0f32; 16];
+    for row in 0..3 {
+        for col in 0..3 {
+            out[col * 4 + row] = r[row][col];
+        }
+        out[12 + row] = -(r[row][0] * t[0] + r[row][1] * t[1] + r[row][2] * t[2]);
+    }
+    out[15] = 1.0;
+    out
 }
 
 struct UnpackedVertices {
     positions: Vec<[f32; 3]>,
     normals: Vec<[f32; 3]>,
     uvs: Vec<[f32; 2]>,
+    /// Blend bone indices (`iii`), empty if the format has no bone data.
+    bone_indices: Vec<[u8; 3]>,
+    /// Bone weights (`ww`), same length as `bone_indices`.
+    bone_weights: Vec<[f32; 3]>,
+}
+
+/// Parse a vertex buffer's format, rejecting unknown codes and formats whose
+/// computed stride disagrees with the buffer's stored stride.
+fn checked_vertex_format(
+    proto: &VerticesPrototype<'_>,
+) -> Result<VertexFormat, Report<ExportError>> {
+    let format = vertex_format::parse_vertex_format(&proto.format_name)
+        .map_err(|e| Report::new(ExportError::VertexFormat(format!("{e:?}"))))?;
+    let stride = proto.stride_in_bytes as usize;
+    if format.stride != stride {
+        return Err(Report::new(ExportError::StrideMismatch {
+            format_stride: format.stride,
+            geo_stride: stride,
+        }));
+    }
+    Ok(format)
 }
 
 /// Unpack vertex data into separate position, normal, and UV arrays.
@@ -1994,6 +2653,18 @@ fn unpack_vertices(data: &[u8], stride: usize, format: &VertexFormat) -> Unpacke
         .attributes
         .iter()
         .find(|a| a.semantic == AttributeSemantic::TexCoord0);
+    let bone_idx_attr = format
+        .attributes
+        .iter()
+        .find(|a| a.semantic == AttributeSemantic::BoneIndices);
+    let bone_weight_attr = format
+        .attributes
+        .iter()
+        .find(|a| a.semantic == AttributeSemantic::BoneWeights);
+    let (mut bone_indices, mut bone_weights) = match (bone_idx_attr, bone_weight_attr) {
+        (Some(_), Some(_)) => (Vec::with_capacity(count), Vec::with_capacity(count)),
+        _ => (Vec::new(), Vec::new()),
+    };
 
     for i in 0..count {
         let base = i * stride;
@@ -2023,12 +2694,24 @@ fn unpack_vertices(data: &[u8], stride: usize, format: &VertexFormat) -> Unpacke
             let packed = u32::from_le_bytes(data[off..off + 4].try_into().unwrap());
             uvs.push(vertex_format::unpack_uv(packed));
         }
+
+        // Bone indices + weights: 4 bytes each (`iiiww`)
+        if let (Some(idx_attr), Some(weight_attr)) = (bone_idx_attr, bone_weight_attr) {
+            let off = base + idx_attr.offset;
+            let packed = u32::from_le_bytes(data[off..off + 4].try_into().unwrap());
+            bone_indices.push(vertex_format::unpack_bone_indices(packed));
+            let off = base + weight_attr.offset;
+            let packed = u32::from_le_bytes(data[off..off + 4].try_into().unwrap());
+            bone_weights.push(vertex_format::unpack_bone_weights(packed));
+        }
     }
 
     UnpackedVertices {
         positions,
         normals,
         uvs,
+        bone_indices,
+        bone_weights,
     }
 }
 
@@ -2100,13 +2783,24 @@ fn apply_barrel_pitch(
 /// All texture data for a ship export: base albedo + camouflage variants.
 pub struct TextureSet {
     /// Base albedo PNGs keyed by MFM stem — the default ship appearance.
-    pub base: HashMap<String, Vec<u8>>,
+    pub base: HashMap<String, PngBytes>,
     /// Camouflage variant PNGs: scheme name → (MFM stem → PNG bytes).
     /// Only stems that have a texture for this scheme are included.
-    pub camo_schemes: Vec<(String, HashMap<String, Vec<u8>>)>,
+    pub camo_schemes: Vec<(String, HashMap<String, PngBytes>)>,
     /// UV scale/offset for tiled camo schemes. Key = `(scheme_index, mfm_stem)`.
     /// Only present for tiled camos; non-tiled camos use default UVs.
     pub tiled_uv_transforms: HashMap<(usize, String), [f32; 4]>,
+    /// Dye tint variants, exported after the camo schemes.
+    pub dye_variants: Vec<DyeVariant>,
+}
+
+/// A cosmetic dye tint exported as a material variant.
+pub struct DyeVariant {
+    /// Variant name shown in viewers.
+    pub name: String,
+    /// Tint albedo PNGs keyed by the dyed material's name. Primitives match
+    /// on either their render set material name or their MFM stem.
+    pub materials: HashMap<String, PngBytes>,
 }
 
 impl TextureSet {
@@ -2115,15 +2809,22 @@ impl TextureSet {
             base: HashMap::new(),
             camo_schemes: Vec::new(),
             tiled_uv_transforms: HashMap::new(),
+            dye_variants: Vec::new(),
         }
     }
+
+    /// Total number of material variants (camo schemes, then dye tints).
+    fn variant_count(&self) -> usize {
+        self.camo_schemes.len() + self.dye_variants.len()
+    }
 }
 
 /// Cached material info for a given MFM stem / material name.
 struct CachedMaterial {
     /// Default material index (base albedo or untextured).
     default_mat: json::Index<json::Material>,
-    /// Variant material indices, one per camo scheme (same order as TextureSet::camo_schemes).
+    /// Variant material indices, one per camo scheme followed by one per dye
+    /// variant (same order as `TextureSet::camo_schemes` and `dye_variants`).
     variant_mats: Vec<Option<json::Index<json::Material>>>,
 }
 
@@ -2205,6 +2906,7 @@ fn create_textured_material(
             tex_coord: Some(0),
             extras: Default::default(),
         }),
+        ..Default::default()
     });
 
     let texture_info = json::texture::Info {
@@ -2423,6 +3125,9 @@ fn add_primitive_to_root(
         attributes.insert(Valid(json::mesh::Semantic::TexCoords(0)), uv);
     }
 
+    // --- Skinning (JOINTS_0 / WEIGHTS_0) ---
+    push_skin_attributes(root, bin_data, &prim.joints, &prim.weights, &mut attributes);
+
     // Determine cache key: prefer MFM stem, fall back to material name.
     let cache_key = prim
         .mfm_stem
@@ -2449,7 +3154,7 @@ fn add_primitive_to_root(
         };
 
         // Create variant materials for each camo scheme.
-        let variant_mats: Vec<Option<json::Index<json::Material>>> = texture_set
+        let mut variant_mats: Vec<Option<json::Index<json::Material>>> = texture_set
             .camo_schemes
             .iter()
             .enumerate()
@@ -2472,6 +3177,25 @@ fn add_primitive_to_root(
             })
             .collect();
 
+        // Then for each dye tint that targets this material.
+        for dye in &texture_set.dye_variants {
+            let png_bytes = dye.materials.get(&prim.material_name).or_else(|| {
+                prim.mfm_stem
+                    .as_ref()
+                    .and_then(|stem| dye.materials.get(stem))
+            });
+            variant_mats.push(png_bytes.map(|png_bytes| {
+                create_textured_material(
+                    root,
+                    bin_data,
+                    png_bytes,
+                    &format!("{} [{}]", prim.material_name, dye.name),
+                    Some(format!("{}_{}", cache_key, dye.name)),
+                    None,
+                )
+            }));
+        }
+
         mat_cache.materials.insert(
             cache_key.clone(),
             CachedMaterial {
@@ -2484,11 +3208,11 @@ fn add_primitive_to_root(
     let cached = &mat_cache.materials[&cache_key];
 
     // Build KHR_materials_variants mappings for this primitive.
-    let prim_variants_ext = if !texture_set.camo_schemes.is_empty() {
+    let prim_variants_ext = if texture_set.variant_count() > 0 {
         let mut mappings = Vec::new();
         for (variant_idx, variant_mat) in cached.variant_mats.iter().enumerate() {
-            // Use the variant material if this stem has a camo texture for this scheme,
-            // otherwise fall back to the default material.
+            // Use the variant material if this stem has a camo or dye texture for
+            // this variant, otherwise fall back to the default material.
             let mat_index = variant_mat.unwrap_or(cached.default_mat);
             mappings.push(json::extensions::mesh::Mapping {
                 material: mat_index.value() as u32,
@@ -2508,16 +3232,20 @@ fn add_primitive_to_root(
         targets: None,
         extensions: Some(json::extensions::mesh::Primitive {
             khr_materials_variants: prim_variants_ext,
+            ..Default::default()
         }),
         extras: Default::default(),
     })
 }
 
 /// Add an armor mesh primitive (positions + normals, untextured) to the glTF root.
+///
+/// `material_prefix` distinguishes armor (`"armor"`) from collision (`"collision"`) materials.
 fn add_armor_primitive_to_root(
     root: &mut json::Root,
     bin_data: &mut Vec<u8>,
     armor: &ArmorSubModel,
+    material_prefix: &str,
 ) -> Result<json::mesh::Primitive, Report<ExportError>> {
     let mut attributes = BTreeMap::new();
 
@@ -2678,9 +3406,9 @@ fn add_armor_primitive_to_root(
         extras: Default::default(),
     });
 
-    // Untextured semi-transparent material for armor visualization.
+    // Untextured semi-transparent material for armor/collision visualization.
     let material = root.push(json::Material {
-        name: Some(format!("armor_{}", armor.name)),
+        name: Some(format!("{material_prefix}_{}", armor.name)),
         alpha_mode: Valid(json::material::AlphaMode::Blend),
         pbr_metallic_roughness: json::material::PbrMetallicRoughness {
             base_color_factor: json::material::PbrBaseColorFactor([1.0, 1.0, 1.0, 1.0]),
@@ -2705,26 +3433,25 @@ fn add_armor_primitive_to_root(
 
 /// Add `KHR_materials_variants` root extension and `extensionsUsed` entry.
 ///
-/// Creates variant definitions at the glTF root so that each camo scheme name
-/// appears as a selectable variant in viewers like Blender.
+/// Creates variant definitions at the glTF root so that each camo scheme and
+/// dye tint appears as a selectable variant in viewers like Blender.
 fn add_variants_extension(root: &mut json::Root, texture_set: &TextureSet) {
-    if texture_set.camo_schemes.is_empty() {
+    if texture_set.variant_count() == 0 {
         return;
     }
 
     let variants: Vec<json::extensions::scene::khr_materials_variants::Variant> = texture_set
         .camo_schemes
         .iter()
-        .map(
-            |(name, _)| json::extensions::scene::khr_materials_variants::Variant {
-                name: name.clone(),
-            },
-        )
+        .map(|(name, _)| name)
+        .chain(texture_set.dye_variants.iter().map(|dye| &dye.name))
+        .map(|name| json::extensions::scene::khr_materials_variants::Variant { name: name.clone() })
         .collect();
 
     let ext = json::extensions::root::KhrMaterialsVariants { variants };
     root.extensions = Some(json::extensions::root::Root {
         khr_materials_variants: Some(ext),
+        ..Default::default()
     });
 
     root.extensions_used
@@ -2736,6 +3463,131 @@ fn add_variants_extension(root: &mut json::Root, texture_set: &TextureSet) {
     }
 }
 
+/// Vertical field of view assumed when converting LOD extents to screen coverage.
+const LOD_REFERENCE_FOV_DEG: f32 = 60.0;
+
+/// LODs to export: `lod` alone, or with `all_lods` `lod` followed by every
+/// lower-detail LOD.
+fn lod_range(lod: usize, lod_count: usize, all_lods: bool) -> std::ops::Range<usize> {
+    if all_lods {
+        lod..lod_count
+    } else {
+        lod..lod + 1
+    }
+}
+
+/// Screen coverage below which an LOD should give way to the next one.
+///
+/// `extent` is the camera distance up to which the LOD is drawn. The result
+/// is the bounding box diagonal's share of the view height at that distance,
+/// for a [`LOD_REFERENCE_FOV_DEG`] vertical field of view. An extent of zero
+/// means the LOD is never switched out.
+fn lod_screen_coverage(extent: f32, bounding_box: &BoundingBox) -> f32 {
+    if extent <= 0.0 {
+        return 0.0;
+    }
+    let diagonal = (0..3)
+        .map(|k| (bounding_box.max[k] - bounding_box.min[k]).powi(2))
+        .sum::<f32>()
+        .sqrt();
+    let view_height = 2.0 * extent * (LOD_REFERENCE_FOV_DEG.to_radians() / 2.0).tan();
+    (diagonal / view_height).clamp(0.0, 1.0)
+}
+
+/// Add one mesh per LOD in `lods`, primary first, with the primitives
+/// `add_primitives` adds for that LOD. LODs without primitives are skipped
+/// with a warning, so every returned `(lod, mesh)` pair has primitives. The
+/// first mesh is named `name`; later ones get an `LOD{n}` suffix.
+fn add_lod_meshes(
+    root: &mut json::Root,
+    lods: std::ops::Range<usize>,
+    name: Option<&str>,
+    mut add_primitives: impl FnMut(
+        &mut json::Root,
+        usize,
+    ) -> Result<Vec<json::mesh::Primitive>, Report<ExportError>>,
+) -> Result<Vec<(usize, json::Index<json::Mesh>)>, Report<ExportError>> {
+    let mut meshes = Vec::new();
+    for lod in lods {
+        let primitives = add_primitives(root, lod)?;
+        if primitives.is_empty() {
+            match name {
+                Some(name) => {
+                    eprintln!("Warning: sub-model '{name}' has no primitives for LOD {lod}")
+                }
+                None => eprintln!("Warning: no primitives found for LOD {lod}"),
+            }
+            continue;
+        }
+
+        let mesh_name = match name {
+            _ if meshes.is_empty() => name.map(str::to_string),
+            Some(name) => Some(format!("{name} LOD{lod}")),
+            None => Some(format!("LOD{lod}")),
+        };
+        let mesh = root.push(json::Mesh {
+            primitives,
+            weights: None,
+            name: mesh_name,
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        meshes.push((lod, mesh));
+    }
+    Ok(meshes)
+}
+
+/// Chain lower-detail LOD nodes to the primary node (the first of `lods`)
+/// with the `MSFT_lod` extension, and store per-LOD `MSFT_screencoverage`
+/// thresholds derived from each node's LOD extent in the primary node's
+/// extras.
+///
+/// Does nothing for a single node.
+fn add_lod_chain(
+    root: &mut json::Root,
+    lods: &[(json::Index<json::Node>, f32)],
+    bounding_box: &BoundingBox,
+) -> Result<(), Report<ExportError>> {
+    if lods.len() < 2 {
+        return Ok(());
+    }
+
+    let ids = lods[1..]
+        .iter()
+        .map(|(n, _)| json::Value::from(n.value()))
+        .collect();
+    let mut msft_lod = json::Value::Object(Default::default());
+    msft_lod["ids"] = json::Value::Array(ids);
+
+    let coverages: Vec<f32> = lods
+        .iter()
+        .map(|&(_, extent)| lod_screen_coverage(extent, bounding_box))
+        .collect();
+    let mut extras = json::Value::Object(Default::default());
+    extras["MSFT_screencoverage"] = json::Value::from(coverages);
+    let extras = json::serialize::to_string(&extras)
+        .ok()
+        .and_then(|s| json::extras::RawValue::from_string(s).ok())
+        .ok_or_else(|| {
+            Report::new(ExportError::Serialize(
+                "MSFT_screencoverage extras".to_string(),
+            ))
+        })?;
+
+    let primary = &mut root.nodes[lods[0].0.value()];
+    primary
+        .extensions
+        .get_or_insert_with(Default::default)
+        .others
+        .insert("MSFT_lod".to_string(), msft_lod);
+    primary.extras = Some(extras);
+
+    if !root.extensions_used.iter().any(|e| e == "MSFT_lod") {
+        root.extensions_used.push("MSFT_lod".to_string());
+    }
+    Ok(())
+}
+
 fn pad_to_4(data: &mut Vec<u8>) {
     while !data.len().is_multiple_of(4) {
         data.push(0);
@@ -2927,7 +3779,7 @@ impl InteractiveArmorMesh {
     }
 }
 
-/// An armor mesh ready for glTF export (triangle soup, no textures).
+/// An armor or collision mesh ready for glTF export (triangle soup, no textures).
 pub struct ArmorSubModel {
     pub name: String,
     pub positions: Vec<[f32; 3]>,
@@ -2985,6 +3837,168 @@ impl ArmorSubModel {
     }
 }
 
+impl ArmorSubModel {
+    /// Build an uncolored `ArmorSubModel` from a decoded collision model.
+    ///
+    /// Collision hulls carry no thickness data, so `colors` is left empty and
+    /// the mesh uses the material's flat base color.
+    pub fn from_collision_model(collision: &crate::models::geometry::CollisionModel) -> Self {
+        let vert_count = collision.triangles.len() * 3;
+        let mut positions = Vec::with_capacity(vert_count);
+        let mut normals = Vec::with_capacity(vert_count);
+        let mut indices = Vec::with_capacity(vert_count);
+
+        for (ti, tri) in collision.triangles.iter().enumerate() {
+            for v in 0..3 {
+                positions.push(tri.vertices[v]);
+                normals.push(tri.normals[v]);
+                indices.push((ti * 3 + v) as u32);
+            }
+        }
+
+        Self {
+            name: collision.name.clone(),
+            positions,
+            normals,
+            indices,
+            colors: Vec::new(),
+            transform: None,
+        }
+    }
+}
+
+/// Colors cycled through for the splash boxes of successive hit locations.
+const SPLASH_BOX_PALETTE: [[f32; 3]; 8] = [
+    [0.90, 0.25, 0.20],
+    [0.20, 0.55, 0.90],
+    [0.30, 0.80, 0.30],
+    [0.95, 0.75, 0.15],
+    [0.65, 0.35, 0.85],
+    [0.15, 0.80, 0.80],
+    [0.95, 0.50, 0.70],
+    [0.60, 0.45, 0.25],
+];
+
+/// A `.splash` box rendered as a translucent cuboid, tagged with the hit
+/// location that lists it.
+#[derive(Debug, Clone)]
+pub struct SplashBoxModel {
+    /// Splash box name (e.g. "CM_SB_bow_1").
+    pub name: String,
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    /// Name of the owning hit location (e.g. "Cit"), if any lists this box.
+    pub hit_location: Option<String>,
+    /// HP pool of the owning hit location.
+    pub max_hp: Option<f32>,
+    /// Fraction of damage to the owning hit location that can be healed.
+    pub regenerated_hp_part: Option<f32>,
+    /// RGBA color; alpha sets the translucency.
+    pub color: [f32; 4],
+}
+
+impl SplashBoxModel {
+    /// Pair each splash box with the hit location that lists it. Boxes of
+    /// the same hit location share a color; unowned boxes are gray.
+    pub fn from_splash_boxes(
+        boxes: &[crate::models::geometry::SplashBox],
+        hit_locations: Option<&HashMap<String, crate::game_params::types::HitLocation>>,
+    ) -> Vec<Self> {
+        // Sorted so colors are stable between exports.
+        let mut owners: Vec<_> = hit_locations.into_iter().flatten().collect();
+        owners.sort_by(|a, b| a.0.cmp(b.0));
+
+        boxes
+            .iter()
+            .map(|b| {
+                let owner = owners
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (_, hl))| hl.splash_boxes().contains(&b.name));
+                let color = match owner {
+                    Some((i, _)) => {
+                        let [r, g, b] = SPLASH_BOX_PALETTE[i % SPLASH_BOX_PALETTE.len()];
+                        [r, g, b, 0.3]
+                    }
+                    None => [0.5, 0.5, 0.5, 0.15],
+                };
+                Self {
+                    name: b.name.clone(),
+                    min: b.min,
+                    max: b.max,
+                    hit_location: owner.map(|(_, (name, _))| (*name).clone()),
+                    max_hp: owner.map(|(_, (_, hl))| hl.max_hp()),
+                    regenerated_hp_part: owner.map(|(_, (_, hl))| hl.regenerated_hp_part()),
+                    color,
+                }
+            })
+            .collect()
+    }
+
+    /// The box as a 24-vertex flat-shaded cuboid, Z negated like the armor
+    /// meshes.
+    fn to_armor_sub_model(&self) -> ArmorSubModel {
+        // Negating Z swaps the Z bounds. The box is built in the negated
+        // space, so its faces still wind counter-clockwise from outside.
+        let lo = [self.min[0], self.min[1], -self.max[2]];
+        let hi = [self.max[0], self.max[1], -self.min[2]];
+        let mut positions = Vec::with_capacity(24);
+        let mut normals = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            for (side, value) in [(-1.0, lo[axis]), (1.0, hi[axis])] {
+                let base = positions.len() as u32;
+                for (cu, cv) in [
+                    (lo[u], lo[v]),
+                    (hi[u], lo[v]),
+                    (hi[u], hi[v]),
+                    (lo[u], hi[v]),
+                ] {
+                    let mut p = [0.0; 3];
+                    p[axis] = value;
+                    p[u] = cu;
+                    p[v] = cv;
+                    positions.push(p);
+                    let mut n = [0.0; 3];
+                    n[axis] = side;
+                    normals.push(n);
+                }
+                // Counter-clockwise seen from outside.
+                if side > 0.0 {
+                    indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+                } else {
+                    indices.extend([base, base + 2, base + 1, base, base + 3, base + 2]);
+                }
+            }
+        }
+        ArmorSubModel {
+            name: self.name.clone(),
+            colors: vec![self.color; positions.len()],
+            positions,
+            normals,
+            indices,
+            transform: None,
+        }
+    }
+
+    /// Node extras: splash box, zone name, HP and regeneration fraction.
+    fn extras(&self) -> json::Value {
+        let mut extras = json::Value::Object(Default::default());
+        extras["splash_box"] = json::Value::from(self.name.as_str());
+        if let Some(zone) = &self.hit_location {
+            extras["zone"] = json::Value::from(zone.as_str());
+        }
+        if let Some(max_hp) = self.max_hp {
+            extras["max_hp"] = json::Value::from(max_hp);
+        }
+        if let Some(part) = self.regenerated_hp_part {
+            extras["regenerated_hp_part"] = json::Value::from(part);
+        }
+        extras
+    }
+}
+
 /// A hull visual mesh for interactive viewers (positions, normals, indices + render set name).
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -3102,7 +4116,7 @@ pub fn collect_hull_meshes(
             .decode()
             .map_err(|e| Report::new(ExportError::IndexDecode(format!("{e:?}"))))?;
 
-        let format = vertex_format::parse_vertex_format(&vert_proto.format_name);
+        let format = checked_vertex_format(vert_proto)?;
         let stride = vert_proto.stride_in_bytes as usize;
 
         let vert_offset = vert_mapping.items_offset as usize;
@@ -3789,6 +4803,25 @@ pub struct SubModel<'a> {
     pub group: &'static str,
     /// If set, apply a pitch rotation to vertices weighted to barrel bones.
     pub barrel_pitch: Option<BarrelPitch>,
+    /// Export the visual's skeleton as a glTF skin so the sub-model can be posed.
+    pub skinned: bool,
+    /// Animation clips targeting the skeleton's nodes. Only exported when
+    /// `skinned` is set, since the clips animate the joint nodes.
+    pub animations: &'a [Animation],
+    /// Named points to tag in the exported node tree.
+    pub markers: &'a [NodeMarker],
+}
+
+/// A named point of a sub-model, e.g. a plane's propeller hub, tagged with
+/// `{"role": role}` in node extras. When the sub-model is skinned the joint
+/// of the same name is tagged, so the marker follows the pose; otherwise
+/// the marker is an empty child node of the sub-model.
+#[derive(Debug, Clone)]
+pub struct NodeMarker {
+    pub name: String,
+    /// Column-major 4x4 transform in the sub-model's space.
+    pub transform: [f32; 16],
+    pub role: &'static str,
 }
 
 /// Configuration for per-vertex barrel pitch rotation.
@@ -3807,13 +4840,23 @@ pub struct BarrelPitch {
 /// Each sub-model becomes a separate selectable object in Blender.
 /// `texture_set` contains base albedo + camo variant PNGs for material textures.
 /// `armor_models` are added as additional untextured semi-transparent meshes.
+/// `collision_models` and `firing_arc_models` are added the same way under
+/// separate "Collision" and "FiringArcs" groups, and `splash_boxes` as
+/// translucent boxes under "SplashBoxes".
+/// With `all_lods`, each sub-model's lower LODs are chained to it through
+/// `MSFT_lod`, as in [`export_glb`].
+#[allow(clippy::too_many_arguments)]
 pub fn export_ship_glb(
     sub_models: &[SubModel<'_>],
     armor_models: &[ArmorSubModel],
+    collision_models: &[ArmorSubModel],
+    firing_arc_models: &[ArmorSubModel],
+    splash_boxes: &[SplashBoxModel],
     db: &PrototypeDatabase<'_>,
     lod: usize,
     texture_set: &TextureSet,
     damaged: bool,
+    all_lods: bool,
     writer: &mut impl Write,
 ) -> Result<(), Report<ExportError>> {
     let mut root = json::Root {
@@ -3845,80 +4888,169 @@ pub fn export_ship_glb(
             continue;
         }
 
-        let lod_entry = &sub.visual.lods[lod];
-        let primitives = collect_primitives(
-            sub.visual,
-            sub.geometry,
-            Some(db),
-            Some(&self_id_index),
-            lod_entry,
-            damaged,
-            sub.barrel_pitch.as_ref(),
-        )?;
-
-        if primitives.is_empty() {
-            eprintln!(
-                "Warning: sub-model '{}' has no primitives for LOD {lod}",
-                sub.name
-            );
+        // One mesh per exported LOD, primary first.
+        let lods = lod_range(lod, sub.visual.lods.len(), all_lods);
+        let lod_meshes = add_lod_meshes(&mut root, lods, Some(&sub.name), |root, lod| {
+            let primitives = collect_primitives(
+                sub.visual,
+                sub.geometry,
+                Some(db),
+                Some(&self_id_index),
+                &sub.visual.lods[lod],
+                damaged,
+                sub.barrel_pitch.as_ref(),
+                sub.skinned,
+            )?;
+            primitives
+                .iter()
+                .map(|prim| {
+                    add_primitive_to_root(root, &mut bin_data, prim, texture_set, &mut mat_cache)
+                })
+                .collect()
+        })?;
+        if lod_meshes.is_empty() {
             continue;
         }
 
-        let mut gltf_primitives = Vec::new();
-        for prim in &primitives {
-            let gltf_prim =
-                add_primitive_to_root(&mut root, &mut bin_data, prim, texture_set, &mut mat_cache)?;
-            gltf_primitives.push(gltf_prim);
+        // Skinned sub-models carry their skeleton beneath the mesh node, so
+        // the node transform still places the whole part.
+        let (skin, joint_roots) = if sub.skinned {
+            let node_names = skeleton_node_names(&sub.visual.nodes, Some(db));
+            let (skin, joint_roots) =
+                add_skeleton_to_root(&mut root, &mut bin_data, &sub.visual.nodes, &node_names);
+            (Some(skin), Some(joint_roots))
+        } else {
+            (None, None)
+        };
+
+        if let Some(skin) = skin {
+            for anim in sub.animations {
+                let clip_name = format!("{} {}", sub.name, anim.identifier);
+                add_animation_to_root(&mut root, &mut bin_data, anim, skin, clip_name);
+            }
         }
 
-        // Create a mesh named after the sub-model.
-        let mesh = root.push(json::Mesh {
-            primitives: gltf_primitives,
-            weights: None,
-            name: Some(sub.name.clone()),
-            extensions: Default::default(),
-            extras: Default::default(),
-        });
+        let mut children = joint_roots.unwrap_or_default();
+        for marker in sub.markers {
+            let mut extras = json::Value::Object(Default::default());
+            extras["role"] = json::Value::from(marker.role);
+            let extras = json::serialize::to_string(&extras)
+                .ok()
+                .and_then(|s| json::extras::RawValue::from_string(s).ok())
+                .ok_or_else(|| {
+                    Report::new(ExportError::Serialize(format!(
+                        "marker extras for {}",
+                        marker.name
+                    )))
+                })?;
+            let joint =
+                skin.and_then(|skin| {
+                    root.skins[skin.value()].joints.iter().copied().find(|j| {
+                        root.nodes[j.value()].name.as_deref() == Some(marker.name.as_str())
+                    })
+                });
+            match joint {
+                Some(joint) => root.nodes[joint.value()].extras = Some(extras),
+                None => children.push(root.push(json::Node {
+                    name: Some(marker.name.clone()),
+                    matrix: Some(negate_z_transform(marker.transform)),
+                    extras: Some(extras),
+                    ..Default::default()
+                })),
+            }
+        }
+        let children = (!children.is_empty()).then_some(children);
 
-        // Create a node named after the sub-model, referencing the mesh.
-        let node = root.push(json::Node {
-            mesh: Some(mesh),
-            name: Some(sub.name.clone()),
-            matrix: sub.transform.map(negate_z_transform),
-            ..Default::default()
-        });
+        // Create a node named after each LOD mesh. Lower LODs stand in for the
+        // primary node, so they share its transform and skin.
+        let mut lod_nodes = Vec::new();
+        for (i, (lod, mesh)) in lod_meshes.into_iter().enumerate() {
+            let node = root.push(json::Node {
+                mesh: Some(mesh),
+                name: root.meshes[mesh.value()].name.clone(),
+                matrix: sub.transform.map(negate_z_transform),
+                skin,
+                children: if i == 0 { children.clone() } else { None },
+                ..Default::default()
+            });
+            lod_nodes.push((node, sub.visual.lods[lod].extent));
+        }
+        add_lod_chain(&mut root, &lod_nodes, &sub.visual.bounding_box)?;
 
-        grouped_nodes.entry(sub.group).or_default().push(node);
+        grouped_nodes
+            .entry(sub.group)
+            .or_default()
+            .push(lod_nodes[0].0);
     }
 
-    // Add armor meshes grouped under "Armor".
-    let mut armor_nodes: Vec<json::Index<json::Node>> = Vec::new();
-    for armor in armor_models {
-        if armor.positions.is_empty() {
-            continue;
-        }
+    // Add armor, collision and firing arc meshes grouped under "Armor",
+    // "Collision" and "FiringArcs".
+    for (group_name, material_prefix, meshes) in [
+        ("Armor", "armor", armor_models),
+        ("Collision", "collision", collision_models),
+        ("FiringArcs", "arc", firing_arc_models),
+    ] {
+        let mut nodes: Vec<json::Index<json::Node>> = Vec::new();
+        for armor in meshes {
+            if armor.positions.is_empty() {
+                continue;
+            }
+
+            let gltf_prim =
+                add_armor_primitive_to_root(&mut root, &mut bin_data, armor, material_prefix)?;
+
+            let mesh = root.push(json::Mesh {
+                primitives: vec![gltf_prim],
+                weights: None,
+                name: Some(armor.name.clone()),
+                extensions: Default::default(),
+                extras: Default::default(),
+            });
+
+            let node = root.push(json::Node {
+                mesh: Some(mesh),
+                name: Some(armor.name.clone()),
+                matrix: armor.transform.map(negate_z_transform),
+                ..Default::default()
+            });
 
-        let gltf_prim = add_armor_primitive_to_root(&mut root, &mut bin_data, armor)?;
+            nodes.push(node);
+        }
+        if !nodes.is_empty() {
+            grouped_nodes.insert(group_name, nodes);
+        }
+    }
 
+    // Splash boxes, tagged with their hit location in node extras.
+    let mut splash_nodes: Vec<json::Index<json::Node>> = Vec::new();
+    for splash in splash_boxes {
+        let cuboid = splash.to_armor_sub_model();
+        let gltf_prim = add_armor_primitive_to_root(&mut root, &mut bin_data, &cuboid, "splash")?;
         let mesh = root.push(json::Mesh {
             primitives: vec![gltf_prim],
             weights: None,
-            name: Some(armor.name.clone()),
+            name: Some(splash.name.clone()),
             extensions: Default::default(),
             extras: Default::default(),
         });
-
-        let node = root.push(json::Node {
+        let extras = json::serialize::to_string(&splash.extras())
+            .ok()
+            .and_then(|s| json::extras::RawValue::from_string(s).ok())
+            .ok_or_else(|| {
+                Report::new(ExportError::Serialize(format!(
+                    "splash box extras for {}",
+                    splash.name
+                )))
+            })?;
+        splash_nodes.push(root.push(json::Node {
             mesh: Some(mesh),
-            name: Some(armor.name.clone()),
-            matrix: armor.transform.map(negate_z_transform),
+            name: Some(splash.name.clone()),
+            extras: Some(extras),
             ..Default::default()
-        });
-
-        armor_nodes.push(node);
+        }));
     }
-    if !armor_nodes.is_empty() {
-        grouped_nodes.insert("Armor", armor_nodes);
+    if !splash_nodes.is_empty() {
+        grouped_nodes.insert("SplashBoxes", splash_nodes);
     }
 
     // Build scene hierarchy: one parent node per group.
@@ -3960,7 +5092,7 @@ pub fn export_ship_glb(
     });
     root.scene = Some(scene);
 
-    // Add KHR_materials_variants root extension if we have camo schemes.
+    // Add KHR_materials_variants root extension if we have camo or dye variants.
     add_variants_extension(&mut root, texture_set);
 
     // Serialize and write GLB.
@@ -3986,3 +5118,312 @@ pub fn export_ship_glb(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::assets_bin::parse_assets_bin;
+    use crate::models::assets_bin_writer::PrototypeDatabaseWriter;
+    use crate::models::geometry::parse_geometry;
+    use crate::models::geometry_writer::GeometryWriter;
+    use crate::models::visual::{Lod, RenderSet, VisualNodes};
+
+    fn lod(extent: f32, render_set_names: Vec<u32>) -> Lod {
+        Lod {
+            extent,
+            casts_shadow: false,
+            render_set_names,
+        }
+    }
+
+    /// The node carrying `MSFT_lod`, with its `ids` and screen coverages.
+    fn lod_chain(glb: &[u8]) -> (json::Root, usize, usize) {
+        let glb = gltf::binary::Glb::from_slice(glb).unwrap();
+        let root = json::Root::from_slice(&glb.json).unwrap();
+        let node = root.nodes.iter().find_map(|n| {
+            n.extensions
+                .as_ref()?
+                .others
+                .get("MSFT_lod")
+                .map(|e| (n, e))
+        });
+        let (node, msft_lod) = node.expect("a node with MSFT_lod");
+        let ids = msft_lod["ids"].as_array().unwrap().len();
+        let extras: json::Value =
+            json::deserialize::from_str(node.extras.as_ref().unwrap().get()).unwrap();
+        let coverages = extras["MSFT_screencoverage"].as_array().unwrap().len();
+        (root, ids, coverages)
+    }
+
+    #[test]
+    fn empty_lods_are_skipped_in_both_exporters() {
+        let mut vertices = Vec::new();
+        for pos in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for c in pos {
+                vertices.extend_from_slice(&c.to_le_bytes());
+            }
+            vertices.extend_from_slice(&[127, 127, 255, 0, 0, 0, 0, 0]);
+        }
+        let mut writer = GeometryWriter::new();
+        writer
+            .add_mesh(0x10, 0x20, "set3/xyznuv", &vertices, &[0, 1, 2])
+            .unwrap();
+        let geometry_bytes = writer.to_bytes().unwrap();
+        let geometry = parse_geometry(&geometry_bytes).unwrap();
+        let db_bytes = PrototypeDatabaseWriter::new(&[]).to_bytes();
+        let db = parse_assets_bin(&db_bytes).unwrap();
+
+        // LOD 1 has no render sets.
+        let visual = VisualPrototype {
+            nodes: VisualNodes {
+                name_map_name_ids: Vec::new(),
+                name_map_node_ids: Vec::new(),
+                name_ids: Vec::new(),
+                matrices: Vec::new(),
+                parent_ids: Vec::new(),
+            },
+            merged_geometry_path_id: 0,
+            underwater_model: false,
+            abovewater_model: true,
+            bounding_box: BoundingBox {
+                min: [0.0; 3],
+                max: [1.0, 1.0, 0.0],
+            },
+            render_sets: vec![RenderSet {
+                name_id: 1,
+                material_name_id: 2,
+                vertices_mapping_id: 0x10,
+                indices_mapping_id: 0x20,
+                material_mfm_path_id: 0,
+                skinned: false,
+                node_name_ids: Vec::new(),
+            }],
+            lods: vec![
+                lod(10.0, vec![1]),
+                lod(20.0, Vec::new()),
+                lod(40.0, vec![1]),
+            ],
+        };
+
+        let mut glb = Vec::new();
+        export_glb(
+            &visual,
+            &geometry,
+            &db,
+            0,
+            &TextureSet::empty(),
+            false,
+            false,
+            true,
+            &mut glb,
+        )
+        .unwrap();
+        let (root, ids, coverages) = lod_chain(&glb);
+        assert_eq!(root.meshes.len(), 2);
+        assert!(root.meshes.iter().all(|m| !m.primitives.is_empty()));
+        assert_eq!(root.meshes[1].name.as_deref(), Some("LOD2"));
+        assert_eq!((ids, coverages), (1, 2));
+
+        let sub_model = SubModel {
+            name: "Hull".to_string(),
+            visual: &visual,
+            geometry: &geometry,
+            transform: None,
+            group: "Hull",
+            barrel_pitch: None,
+            skinned: false,
+            animations: &[],
+            markers: &[NodeMarker {
+                name: "Propeller_1".to_string(),
+                transform: [
+                    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 1.0,
+                ],
+                role: "propeller",
+            }],
+        };
+        let mut glb = Vec::new();
+        export_ship_glb(
+            &[sub_model],
+            &[],
+            &[],
+            &[],
+            &[],
+            &db,
+            0,
+            &TextureSet::empty(),
+            false,
+            true,
+            &mut glb,
+        )
+        .unwrap();
+        let (root, ids, coverages) = lod_chain(&glb);
+        assert_eq!(root.meshes.len(), 2);
+        assert_eq!(root.meshes[1].name.as_deref(), Some("Hull LOD2"));
+        assert_eq!((ids, coverages), (1, 2));
+
+        // Without a skin, the marker is a tagged child of the primary node.
+        let marker = root
+            .nodes
+            .iter()
+            .position(|n| n.name.as_deref() == Some("Propeller_1"))
+            .unwrap();
+        let hull = root
+            .nodes
+            .iter()
+            .find(|n| n.mesh.is_some() && n.name.as_deref() == Some("Hull"))
+            .unwrap();
+        assert_eq!(hull.children.as_ref().unwrap()[0].value(), marker);
+        assert_eq!(
+            root.nodes[marker].matrix.unwrap()[14],
+            -2.0,
+            "marker Z is negated like the mesh"
+        );
+        assert_eq!(
+            root.nodes[marker].extras.as_ref().unwrap().get(),
+            r#"{"role":"propeller"}"#
+        );
+    }
+
+    #[test]
+    fn animated_map_instances_get_their_own_skin() {
+        use crate::models::animation::AnimationChannel;
+        use crate::models::visual::Matrix4x4;
+
+        let mut identity = [0.0; 16];
+        for i in [0, 5, 10, 15] {
+            identity[i] = 1.0;
+        }
+        let radar = MapMesh {
+            name: "Radar".to_string(),
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            uvs: Vec::new(),
+            indices: vec![0, 1, 2],
+            albedo_texture: None,
+            base_color: [1.0; 4],
+            alpha_blend: false,
+            alpha_cutoff: None,
+            joints: vec![[0, 0, 0, 0]; 3],
+            weights: vec![[1.0, 0.0, 0.0, 0.0]; 3],
+        };
+        let instance = |x: f32| {
+            let mut transform = identity;
+            transform[12] = x;
+            MapModelInstance {
+                mesh_range: 0..1,
+                transform,
+                animated_model: Some(0),
+            }
+        };
+        let scene = MapScene {
+            model_meshes: vec![radar],
+            model_instances: vec![instance(0.0), instance(10.0)],
+            animated_models: vec![AnimatedMapModel {
+                nodes: VisualNodes {
+                    name_map_name_ids: Vec::new(),
+                    name_map_node_ids: Vec::new(),
+                    name_ids: Vec::new(),
+                    matrices: vec![Matrix4x4(identity)],
+                    parent_ids: vec![0xFFFF],
+                },
+                node_names: vec!["Radar_Rot".to_string()],
+                animations: vec![Animation {
+                    total_time: 30.0,
+                    identifier: "spin".to_string(),
+                    internal_identifier: String::new(),
+                    channels: vec![AnimationChannel {
+                        node_name: "Radar_Rot".to_string(),
+                        scale_keys: Vec::new(),
+                        position_keys: Vec::new(),
+                        rotation_keys: vec![
+                            (0.0, [0.0, 0.0, 0.0, 1.0]),
+                            (30.0, [0.0, 1.0, 0.0, 0.0]),
+                        ],
+                    }],
+                }],
+            }],
+            textures: Vec::new(),
+            terrain: None,
+            water: None,
+            bounds: SpaceBounds {
+                min_x: 0.0,
+                max_x: 100.0,
+                min_z: 0.0,
+                max_z: 100.0,
+            },
+            vegetation_instances: Vec::new(),
+        };
+
+        let mut glb = Vec::new();
+        export_map_scene_glb(&scene, &mut glb).unwrap();
+        let glb = gltf::binary::Glb::from_slice(&glb).unwrap();
+        let root = json::Root::from_slice(&glb.json).unwrap();
+
+        // Both instances share the skinned mesh but not the skeleton.
+        assert_eq!(root.meshes.len(), 1);
+        assert_eq!(root.skins.len(), 2);
+        let names: Vec<_> = root.animations.iter().map(|a| a.name.as_deref()).collect();
+        assert_eq!(names, [Some("Instance_0 spin"), Some("Instance_1 spin")]);
+
+        for (i, skin) in root.skins.iter().enumerate() {
+            let instance = root
+                .nodes
+                .iter()
+                .find(|n| n.name.as_deref() == Some(format!("Instance_{i}").as_str()))
+                .unwrap();
+            let children = instance.children.as_ref().unwrap();
+            let part = &root.nodes[children[0].value()];
+            assert_eq!(part.skin.map(|s| s.value()), Some(i));
+            assert!(part.matrix.is_none());
+            assert_eq!(children[1], skin.joints[0]);
+            assert_eq!(root.animations[i].channels[0].target.node, skin.joints[0]);
+        }
+    }
+
+    #[test]
+    fn splash_box_sits_beside_its_armor() {
+        use crate::models::geometry::{ArmorModel, ArmorTriangle};
+
+        // An armor plate at model-space z = 1.5, inside the box's z = 1..2.
+        let armor = ArmorModel {
+            name: "CM_PA_united".to_string(),
+            triangles: vec![ArmorTriangle {
+                vertices: [[0.0, 0.0, 1.5], [1.0, 0.0, 1.5], [0.0, 1.0, 1.5]],
+                normals: [[0.0, 0.0, 1.0]; 3],
+                material_id: 0,
+                layer_index: 1,
+            }],
+            stored: None,
+        };
+        let armor = ArmorSubModel::from_armor_model(&armor, None, None);
+        let splash = SplashBoxModel {
+            name: "CM_SB_cit_1".to_string(),
+            min: [-1.0, -1.0, 1.0],
+            max: [2.0, 2.0, 2.0],
+            hit_location: None,
+            max_hp: None,
+            regenerated_hp_part: None,
+            color: [0.5; 4],
+        };
+        let cuboid = splash.to_armor_sub_model();
+
+        let (min, max) = bounding_coords(&cuboid.positions);
+        assert_eq!((min[2], max[2]), (-2.0, -1.0));
+        let armor_z = armor.positions[0][2];
+        assert!(min[2] < armor_z && armor_z < max[2], "{armor_z}");
+
+        // Every face winds counter-clockwise around its outward normal.
+        for tri in cuboid.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| cuboid.positions[tri[i] as usize]);
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let n = cuboid.normals[tri[0] as usize];
+            assert!(cross.iter().zip(n).map(|(c, n)| c * n).sum::<f32>() > 0.0);
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Import glTF/GLB meshes back into game geometry.
+//!
+//! This is the inverse of [`export_glb`](super::gltf_export::export_glb): each
+//! triangle primitive becomes one render set whose vertices are packed into a
+//! `set3/...` vertex format and added to a [`GeometryWriter`]. Coordinates are
+//! converted back to BigWorld's left-handed space by negating Z, matching the
+//! exporter, so an exported model re-imports with the same winding.
+//!
+//! Render sets are named after their mesh (`{mesh}_{primitive}` when a mesh has
+//! several primitives) and use the primitive's material name. Name IDs are the
+//! MurmurHash3 of the string, as in the assets.bin strings section; the
+//! strings are returned so they can be registered with
+//! [`PrototypeDatabaseWriter::add_string`](crate::models::assets_bin_writer::PrototypeDatabaseWriter::add_string).
+//!
+//! Vertex positions are kept in mesh space; node transforms are not baked in.
+
+use gltf::mesh::Mode;
+use rootcause::Report;
+use thiserror::Error;
+
+use crate::models::geometry_writer::GeometryWriter;
+use crate::models::prototype_layout::murmur3_32;
+use crate::models::vertex_format::{self, AttributeSemantic, VertexFormat};
+use crate::models::visual::{BoundingBox, Lod, RenderSet};
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("glTF parse error: {0}")]
+    Parse(String),
+    #[error("primitive {primitive} has unsupported mode {mode:?} (only triangle lists)")]
+    UnsupportedMode { primitive: String, mode: Mode },
+    #[error("primitive {primitive} is missing {attribute}")]
+    MissingAttribute {
+        primitive: String,
+        attribute: &'static str,
+    },
+    #[error("primitive {primitive} references more than 255 bones")]
+    TooManyBones { primitive: String },
+    #[error("geometry error: {0}")]
+    Geometry(String),
+}
+
+/// Result of [`import_glb`]: geometry buffers plus the visual layout that
+/// references them.
+#[derive(Debug)]
+pub struct ImportedModel {
+    pub geometry: GeometryWriter,
+    /// One render set per imported primitive. `material_mfm_path_id` is 0 and
+    /// must be filled in by the caller.
+    pub render_sets: Vec<RenderSet>,
+    /// A single LOD listing every render set. Its `extent` is left at 0 for
+    /// the caller to set.
+    pub lod: Lod,
+    pub bounding_box: BoundingBox,
+    /// `(name_id, text)` for every name ID used by the render sets.
+    pub strings: Vec<(u32, String)>,
+}
+
+/// Per-vertex attributes of one primitive, already in BigWorld space.
+#[derive(Default)]
+struct ImportedVertices {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    uvs2: Vec<[f32; 2]>,
+    /// `(tangent, binormal)` per vertex, empty if the primitive has no tangents.
+    tangents: Vec<([f32; 3], [f32; 3])>,
+    bone_indices: Vec<[u8; 3]>,
+    bone_weights: Vec<[f32; 3]>,
+}
+
+/// Pick the vertex format for a primitive from the attributes it carries.
+fn select_vertex_format(vertices: &ImportedVertices) -> &'static str {
+    let skinned = !vertices.bone_indices.is_empty();
+    let tangents = !vertices.tangents.is_empty();
+    let uv2 = !vertices.uvs2.is_empty();
+    match (skinned, tangents, uv2) {
+        (true, true, _) => "set3/xyznuviiiwwtb",
+        (true, false, _) => "set3/xyznuviiiww",
+        (false, true, true) => "set3/xyznuv2tb",
+        (false, true, false) => "set3/xyznuvtb",
+        (false, false, true) => "set3/xyznuv2",
+        (false, false, false) => "set3/xyznuv",
+    }
+}
+
+/// Pack vertices into raw bytes laid out as `format`.
+fn pack_vertices(vertices: &ImportedVertices, format: &VertexFormat) -> Vec<u8> {
+    let count = vertices.positions.len();
+    let mut out = vec![0u8; count * format.stride];
+    for i in 0..count {
+        let base = i * format.stride;
+        for attr in &format.attributes {
+            let off = base + attr.offset;
+            let packed = match attr.semantic {
+                AttributeSemantic::Position => {
+                    for (k, c) in vertices.positions[i].iter().enumerate() {
+                        out[off + k * 4..off + k * 4 + 4].copy_from_slice(&c.to_le_bytes());
+                    }
+                    continue;
+                }
+                AttributeSemantic::Normal => vertex_format::pack_normal(vertices.normals[i]),
+                AttributeSemantic::TexCoord0 => vertex_format::pack_uv(vertices.uvs[i]),
+                AttributeSemantic::TexCoord1 => vertex_format::pack_uv(vertices.uvs2[i]),
+                AttributeSemantic::Tangent => vertex_format::pack_normal(vertices.tangents[i].0),
+                AttributeSemantic::Binormal => vertex_format::pack_normal(vertices.tangents[i].1),
+                AttributeSemantic::BoneIndices => {
+                    vertex_format::pack_bone_indices(vertices.bone_indices[i])
+                }
+                AttributeSemantic::BoneWeights => {
+                    vertex_format::pack_bone_weights(vertices.bone_weights[i])
+                }
+                AttributeSemantic::Extra => 0,
+            };
+            out[off..off + 4].copy_from_slice(&packed.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Reduce glTF's 4 joint influences to the 3 the game stores, strongest
+/// first, with weights renormalized to sum to 1.
+fn top_three_influences(joints: [u16; 4], weights: [f32; 4]) -> ([u16; 3], [f32; 3]) {
+    let mut influences: Vec<(u16, f32)> = joints.into_iter().zip(weights).collect();
+    influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let total: f32 = influences[..3].iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        return ([joints[0]; 3], [1.0, 0.0, 0.0]);
+    }
+    let mut out_joints = [influences[0].0; 3];
+    let mut out_weights = [0.0; 3];
+    for (k, &(joint, weight)) in influences[..3].iter().enumerate() {
+        if weight > 0.0 {
+            out_joints[k] = joint;
+            out_weights[k] = weight / total;
+        }
+    }
+    (out_joints, out_weights)
+}
+
+/// Import a GLB file into game geometry and a matching render-set layout.
+pub fn import_glb(data: &[u8]) -> Result<ImportedModel, Report<ImportError>> {
+    let gltf =
+        gltf::Gltf::from_slice(data).map_err(|e| Report::new(ImportError::Parse(e.to_string())))?;
+    let blob = gltf.blob.as_deref();
+
+    let mut geometry = GeometryWriter::new();
+    let mut render_sets = Vec::new();
+    let mut strings: Vec<(u32, String)> = Vec::new();
+    let mut bbox_min = [f32::MAX; 3];
+    let mut bbox_max = [f32::MIN; 3];
+
+    let mut intern = |text: &str| -> u32 {
+        let id = murmur3_32(text.as_bytes(), 0);
+        if !strings.iter().any(|(existing, _)| *existing == id) {
+            strings.push((id, text.to_string()));
+        }
+        id
+    };
+
+    for mesh in gltf.meshes() {
+        let mesh_name = mesh
+            .name()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("mesh{}", mesh.index()));
+        let primitive_count = mesh.primitives().len();
+
+        // The node instancing this mesh decides its skin, and names the
+        // bone a rigid mesh is attached to.
+        let owner = gltf
+            .nodes()
+            .find(|n| n.mesh().map(|m| m.index()) == Some(mesh.index()));
+        let skin = owner.as_ref().and_then(|n| n.skin());
+
+        for primitive in mesh.primitives() {
+            let name = if primitive_count == 1 {
+                mesh_name.clone()
+            } else {
+                format!("{mesh_name}_{}", primitive.index())
+            };
+            if primitive.mode() != Mode::Triangles {
+                return Err(Report::new(ImportError::UnsupportedMode {
+                    primitive: name,
+                    mode: primitive.mode(),
+                }));
+            }
+            let missing = |attribute| {
+                Report::new(ImportError::MissingAttribute {
+                    primitive: name.clone(),
+                    attribute,
+                })
+            };
+
+            let reader = primitive.reader(|buffer| match buffer.source() {
+                gltf::buffer::Source::Bin => blob,
+                gltf::buffer::Source::Uri(_) => None,
+            });
+
+            // Negate Z: converts right-handed (glTF) back to left-handed (BigWorld).
+            let mut vertices = ImportedVertices {
+                positions: reader
+                    .read_positions()
+                    .ok_or_else(|| missing("POSITION"))?
+                    .map(|[x, y, z]| [x, y, -z])
+                    .collect(),
+                normals: reader
+                    .read_normals()
+                    .ok_or_else(|| missing("NORMAL"))?
+                    .map(|[x, y, z]| [x, y, -z])
+                    .collect(),
+                ..Default::default()
+            };
+            let count = vertices.positions.len();
+            vertices.uvs = match reader.read_tex_coords(0) {
+                Some(uvs) => uvs.into_f32().collect(),
+                None => vec![[0.0, 0.0]; count],
+            };
+            if let Some(tangents) = reader.read_tangents() {
+                vertices.tangents = tangents
+                    .zip(&vertices.normals)
+                    .map(|([x, y, z, w], n)| {
+                        let t = [x, y, -z];
+                        // Flipping handedness also flips the bitangent sign.
+                        let b = cross(*n, t).map(|c| -c * w);
+                        (t, b)
+                    })
+                    .collect();
+            }
+            if let Some(uvs2) = reader.read_tex_coords(1) {
+                vertices.uvs2 = uvs2.into_f32().collect();
+            }
+
+            let mut node_name_ids = Vec::new();
+            let mut skinned = false;
+            if let (Some(skin), Some(joints), Some(weights)) =
+                (&skin, reader.read_joints(0), reader.read_weights(0))
+            {
+                skinned = true;
+                let skin_joints: Vec<gltf::Node<'_>> = skin.joints().collect();
+                // Blend bone list: skin joint index -> position in node_name_ids.
+                let mut blend_bones: Vec<u16> = Vec::new();
+                for (joints, weights) in joints.into_u16().zip(weights.into_f32()) {
+                    let (joints, weights) = top_three_influences(joints, weights);
+                    let mut local = [0u8; 3];
+                    for (k, joint) in joints.into_iter().enumerate() {
+                        let slot = match blend_bones.iter().position(|&j| j == joint) {
+                            Some(slot) => slot,
+                            None => {
+                                blend_bones.push(joint);
+                                blend_bones.len() - 1
+                            }
+                        };
+                        local[k] = u8::try_from(slot).map_err(|_| {
+                            Report::new(ImportError::TooManyBones {
+                                primitive: name.clone(),
+                            })
+                        })?;
+                    }
+                    vertices.bone_indices.push(local);
+                    vertices.bone_weights.push(weights);
+                }
+                for joint in blend_bones {
+                    let node = skin_joints.get(joint as usize);
+                    let node_name = node
+                        .and_then(|n| n.name().map(str::to_string))
+                        .unwrap_or_else(|| format!("joint{joint}"));
+                    node_name_ids.push(intern(&node_name));
+                }
+            } else if let Some(node_name) = owner.as_ref().and_then(|n| n.name()) {
+                node_name_ids.push(intern(node_name));
+            }
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..count as u32).collect(),
+            };
+
+            for p in &vertices.positions {
+                for k in 0..3 {
+                    bbox_min[k] = bbox_min[k].min(p[k]);
+                    bbox_max[k] = bbox_max[k].max(p[k]);
+                }
+            }
+
+            let format_name = select_vertex_format(&vertices);
+            let format = vertex_format::parse_vertex_format(format_name)
+                .map_err(|e| Report::new(ImportError::Geometry(format!("{e:?}"))))?;
+            let packed = pack_vertices(&vertices, &format);
+            let mapping_id = render_sets.len() as u32;
+            geometry
+                .add_mesh(mapping_id, mapping_id, format_name, &packed, &indices)
+                .map_err(|e| Report::new(ImportError::Geometry(e.to_string())))?;
+
+            let material_name = primitive.material().name().unwrap_or("default").to_string();
+            render_sets.push(RenderSet {
+                name_id: intern(&name),
+                material_name_id: intern(&material_name),
+                vertices_mapping_id: mapping_id,
+                indices_mapping_id: mapping_id,
+                material_mfm_path_id: 0,
+                skinned,
+                node_name_ids,
+            });
+        }
+    }
+
+    if render_sets.is_empty() {
+        bbox_min = [0.0; 3];
+        bbox_max = [0.0; 3];
+    }
+    let lod = Lod {
+        extent: 0.0,
+        casts_shadow: true,
+        render_set_names: render_sets.iter().map(|rs| rs.name_id).collect(),
+    };
+
+    Ok(ImportedModel {
+        geometry,
+        render_sets,
+        lod,
+        bounding_box: BoundingBox {
+            min: bbox_min,
+            max: bbox_max,
+        },
+        strings,
+    })
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::models::geometry::parse_geometry;
+
+    /// A GLB with one triangle named "Hull" using material "hull_mat".
+    fn triangle_glb() -> Vec<u8> {
+        let mut bin = Vec::new();
+        for v in [
+            [0.0f32, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 1.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ] {
+            for c in v {
+                bin.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "buffers": [{"byteLength": 72}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 36}
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                 "min": [0, 0, 1], "max": [1, 1, 1]},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3"}
+            ],
+            "materials": [{"name": "hull_mat"}],
+            "meshes": [{"name": "Hull", "primitives": [
+                {"attributes": {"POSITION": 0, "NORMAL": 1}, "material": 0}
+            ]}],
+            "nodes": [{"name": "Hull", "mesh": 0}]
+        }"#;
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(json.as_bytes().to_vec()),
+            bin: Some(Cow::Owned(bin)),
+        };
+        glb.to_vec().unwrap()
+    }
+
+    /// [`triangle_glb`] with two UV sets and no tangents.
+    fn two_uv_triangle_glb() -> Vec<u8> {
+        let mut bin = Vec::new();
+        for v in [
+            [0.0f32, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 1.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ] {
+            for c in v {
+                bin.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        for uv in [[0.0f32, 0.0], [1.0, 0.0], [0.0, 1.0]] {
+            for c in uv {
+                bin.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        for uv in [[0.25f32, 0.5], [0.5, 0.5], [0.25, 0.75]] {
+            for c in uv {
+                bin.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "buffers": [{"byteLength": 120}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 72, "byteLength": 24},
+                {"buffer": 0, "byteOffset": 96, "byteLength": 24}
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                 "min": [0, 0, 1], "max": [1, 1, 1]},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3"},
+                {"bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2"},
+                {"bufferView": 3, "componentType": 5126, "count": 3, "type": "VEC2"}
+            ],
+            "materials": [{"name": "hull_mat"}],
+            "meshes": [{"name": "Hull", "primitives": [
+                {"attributes": {"POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2, "TEXCOORD_1": 3},
+                 "material": 0}
+            ]}],
+            "nodes": [{"name": "Hull", "mesh": 0}]
+        }"#;
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(json.as_bytes().to_vec()),
+            bin: Some(Cow::Owned(bin)),
+        };
+        glb.to_vec().unwrap()
+    }
+
+    #[test]
+    fn imports_triangle_into_geometry() {
+        let imported = import_glb(&triangle_glb()).unwrap();
+        assert_eq!(imported.render_sets.len(), 1);
+        let rs = &imported.render_sets[0];
+        let name_of = |id| {
+            imported
+                .strings
+                .iter()
+                .find(|(sid, _)| *sid == id)
+                .map(|(_, s)| s.as_str())
+        };
+        assert_eq!(name_of(rs.name_id), Some("Hull"));
+        assert_eq!(name_of(rs.material_name_id), Some("hull_mat"));
+        assert_eq!(imported.lod.render_set_names, vec![rs.name_id]);
+        assert_eq!(imported.bounding_box.min[2], -1.0);
+
+        let bytes = imported.geometry.to_bytes().unwrap();
+        let geom = parse_geometry(&bytes).unwrap();
+        assert_eq!(geom.merged_vertices[0].format_name, "set3/xyznuv");
+        let vertices = geom.merged_vertices[0].data.decode().unwrap();
+        let z = f32::from_le_bytes(vertices[8..12].try_into().unwrap());
+        assert_eq!(z, -1.0);
+        let normal = u32::from_le_bytes(vertices[12..16].try_into().unwrap());
+        assert_eq!(vertex_format::unpack_normal(normal), [0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn imports_second_uv_set_without_tangents() {
+        let imported = import_glb(&two_uv_triangle_glb()).unwrap();
+        let bytes = imported.geometry.to_bytes().unwrap();
+        let geom = parse_geometry(&bytes).unwrap();
+        assert_eq!(geom.merged_vertices[0].format_name, "set3/xyznuv2");
+
+        let format = vertex_format::parse_vertex_format("set3/xyznuv2").unwrap();
+        let vertices = geom.merged_vertices[0].data.decode().unwrap();
+        let uv1 = format
+            .attributes
+            .iter()
+            .find(|a| a.semantic == AttributeSemantic::TexCoord1)
+            .unwrap();
+        let at = format.stride + uv1.offset;
+        let packed = u32::from_le_bytes(vertices[at..at + 4].try_into().unwrap());
+        assert_eq!(vertex_format::unpack_uv(packed), [0.5, 0.5]);
+    }
+}
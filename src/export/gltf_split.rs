@@ -0,0 +1,221 @@
+//! Convert GLB output to `.gltf` + `.bin` with images as external PNG files.
+//!
+//! Exports embed every texture in the GLB, so a batch of ships stores the
+//! same AA gun or camouflage texture once per ship. [`write_gltf_split`]
+//! moves embedded images into a shared directory named by content hash;
+//! ships exported into the same tree then reference one copy of each
+//! texture.
+
+use std::path::{Component, Path, PathBuf};
+
+use gltf_json as json;
+use json::validation::USize64;
+use rootcause::Report;
+
+use super::gltf_export::ExportError;
+use super::texture_cache::write_file_atomic;
+
+/// Write `glb` as `gltf_path` (JSON), a `.bin` next to it, and its images as
+/// PNG files in `textures_dir`.
+///
+/// Images are named by the CRC32 and length of their bytes, and existing
+/// files are not rewritten, so `textures_dir` can be shared between
+/// exports, including concurrent ones: new images are written to a
+/// temporary file and renamed into place. Image URIs are relative to the `.gltf` file.
+pub fn write_gltf_split(
+    glb: &[u8],
+    gltf_path: &Path,
+    textures_dir: &Path,
+) -> Result<(), Report<ExportError>> {
+    let io_err = |e: std::io::Error| Report::new(ExportError::Io(e.to_string()));
+
+    let glb = gltf::binary::Glb::from_slice(glb)
+        .map_err(|e| Report::new(ExportError::Serialize(e.to_string())))?;
+    let mut root = json::Root::from_slice(&glb.json)
+        .map_err(|e| Report::new(ExportError::Serialize(e.to_string())))?;
+    let bin = glb.bin.as_deref().unwrap_or_default();
+
+    let gltf_dir = gltf_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(gltf_dir).map_err(io_err)?;
+    std::fs::create_dir_all(textures_dir).map_err(io_err)?;
+    let textures_uri = relative_uri(gltf_dir, textures_dir).map_err(io_err)?;
+
+    // Move embedded images out to the shared directory.
+    let mut image_views = vec![false; root.buffer_views.len()];
+    for image in &mut root.images {
+        let Some(view_index) = image.buffer_view.take() else {
+            continue;
+        };
+        let bytes = view_bytes(&root.buffer_views[view_index.value()], bin)?;
+        image_views[view_index.value()] = true;
+
+        let mut crc = flate2::Crc::new();
+        crc.update(bytes);
+        let file_name = format!("{:08x}{:08x}.png", crc.sum(), bytes.len());
+        let path = textures_dir.join(&file_name);
+        if !path.exists() {
+            write_file_atomic(&path, bytes).map_err(io_err)?;
+        }
+        image.mime_type = None;
+        image.uri = Some(format!("{textures_uri}{file_name}"));
+    }
+
+    // Rebuild the binary buffer without the image views.
+    let mut new_bin = Vec::new();
+    let mut remap = vec![None; root.buffer_views.len()];
+    let mut kept_views = Vec::new();
+    for (i, mut view) in std::mem::take(&mut root.buffer_views)
+        .into_iter()
+        .enumerate()
+    {
+        if image_views[i] {
+            continue;
+        }
+        let bytes = view_bytes(&view, bin)?;
+        while new_bin.len() % 4 != 0 {
+            new_bin.push(0);
+        }
+        view.byte_offset = Some(USize64::from(new_bin.len()));
+        new_bin.extend_from_slice(bytes);
+        remap[i] = Some(json::Index::new(kept_views.len() as u32));
+        kept_views.push(view);
+    }
+    root.buffer_views = kept_views;
+
+    let remap_index =
+        |index: &mut json::Index<json::buffer::View>| -> Result<(), Report<ExportError>> {
+            *index = remap[index.value()].ok_or_else(|| {
+                Report::new(ExportError::Serialize(
+                    "accessor references an image buffer view".to_string(),
+                ))
+            })?;
+            Ok(())
+        };
+    for accessor in &mut root.accessors {
+        if let Some(view) = &mut accessor.buffer_view {
+            remap_index(view)?;
+        }
+        if let Some(sparse) = &mut accessor.sparse {
+            remap_index(&mut sparse.indices.buffer_view)?;
+            remap_index(&mut sparse.values.buffer_view)?;
+        }
+    }
+
+    if let Some(buffer) = root.buffers.first_mut() {
+        let stem = gltf_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "model".to_string());
+        let bin_name = format!("{stem}.bin");
+        std::fs::write(gltf_dir.join(&bin_name), &new_bin).map_err(io_err)?;
+        buffer.byte_length = USize64::from(new_bin.len());
+        buffer.uri = Some(bin_name);
+    }
+
+    let json_string = json::serialize::to_string_pretty(&root)
+        .map_err(|e| Report::new(ExportError::Serialize(e.to_string())))?;
+    std::fs::write(gltf_path, json_string).map_err(io_err)?;
+
+    Ok(())
+}
+
+/// Bytes of `view` within the GLB binary chunk.
+fn view_bytes<'a>(
+    view: &json::buffer::View,
+    bin: &'a [u8],
+) -> Result<&'a [u8], Report<ExportError>> {
+    let start = view.byte_offset.map_or(0, |o| o.0 as usize);
+    let end = start + view.byte_length.0 as usize;
+    bin.get(start..end).ok_or_else(|| {
+        Report::new(ExportError::BufferIndexOutOfRange {
+            index: end,
+            count: bin.len(),
+        })
+    })
+}
+
+/// URI prefix (ending in `/`, or empty) leading from directory `from` to
+/// directory `to`. Both must exist.
+fn relative_uri(from: &Path, to: &Path) -> std::io::Result<String> {
+    let from = from.canonicalize()?;
+    let to = to.canonicalize()?;
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut rel = PathBuf::new();
+    for _ in common..from.len() {
+        rel.push("..");
+    }
+    for c in &to[common..] {
+        rel.push(c);
+    }
+    let mut uri: String = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    if !uri.is_empty() {
+        uri.push('/');
+    }
+    Ok(uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn images_move_to_shared_directory() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "buffers": [{"byteLength": 20}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 8, "byteLength": 12}
+            ],
+            "accessors": [
+                {"bufferView": 1, "componentType": 5126, "count": 1, "type": "VEC3"}
+            ],
+            "images": [{"bufferView": 0, "mimeType": "image/png"}]
+        }"#;
+        let mut bin = b"\x89PNGtest".to_vec();
+        for v in [1.0f32, 2.0, 3.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        let mut glb = Vec::new();
+        gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Borrowed(json.as_bytes()),
+            bin: Some(Cow::Owned(bin.clone())),
+        }
+        .to_writer(&mut glb)
+        .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("gltf_split_test_{}", std::process::id()));
+        let textures = dir.join("textures");
+        write_gltf_split(&glb, &dir.join("a/ship_a.gltf"), &textures).unwrap();
+        write_gltf_split(&glb, &dir.join("b/ship_b.gltf"), &textures).unwrap();
+
+        assert_eq!(std::fs::read_dir(&textures).unwrap().count(), 1);
+        let root =
+            json::Root::from_slice(&std::fs::read(dir.join("b/ship_b.gltf")).unwrap()).unwrap();
+        let uri = root.images[0].uri.as_deref().unwrap();
+        assert!(uri.starts_with("../textures/") && uri.ends_with(".png"));
+        assert_eq!(std::fs::read(dir.join("b").join(uri)).unwrap(), &bin[..8]);
+        assert_eq!(root.buffer_views.len(), 1);
+        assert_eq!(root.accessors[0].buffer_view.unwrap().value(), 0);
+        assert_eq!(root.buffers[0].uri.as_deref(), Some("ship_b.bin"));
+        assert_eq!(std::fs::read(dir.join("b/ship_b.bin")).unwrap(), &bin[8..]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,742 @@
+//! Export decoded meshes to Wavefront OBJ, binary STL and PLY.
+//!
+//! None of these formats have a node hierarchy, so models are first collected
+//! into a [`MeshScene`]: the same decoded primitives the GLB exporters use,
+//! plus the per-instance transforms that get baked into world space on write.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use rootcause::Report;
+
+use crate::models::assets_bin::PrototypeDatabase;
+use crate::models::geometry::MergedGeometry;
+use crate::models::visual::VisualPrototype;
+
+use super::gltf_export::{
+    ArmorSubModel, DecodedPrimitive, ExportError, MapMesh, MapScene, PngBytes, SubModel,
+    TextureSet, collect_primitives, negate_z_transform,
+};
+
+/// Mesh file formats supported besides GLB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    /// Wavefront OBJ with an MTL material library and PNG textures alongside.
+    Obj,
+    /// Binary STL (positions and facet normals only).
+    Stl,
+    /// Binary little-endian PLY with per-vertex normals and colors.
+    Ply,
+}
+
+impl MeshFormat {
+    /// File extension for this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            MeshFormat::Obj => "obj",
+            MeshFormat::Stl => "stl",
+            MeshFormat::Ply => "ply",
+        }
+    }
+}
+
+/// A single mesh in local space (right-handed: Z negated, as in the GLB exports).
+pub struct SceneMesh {
+    /// Human-readable name (sub-model, armor zone, map render set, ...).
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    /// Vertex normals (same length as positions, or empty).
+    pub normals: Vec<[f32; 3]>,
+    /// Vertex UVs in glTF convention (origin top-left), or empty.
+    pub uvs: Vec<[f32; 2]>,
+    /// Per-vertex RGBA colors (armor thickness), or empty.
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+    /// Material name, shared by every mesh using the same material.
+    pub material: String,
+    /// Index into [`MeshScene::textures`] for the albedo texture, if any.
+    pub texture: Option<usize>,
+    /// Base color used when no texture or vertex colors are present. RGBA linear.
+    pub base_color: [f32; 4],
+}
+
+/// A placement of a [`SceneMesh`] in the scene.
+pub struct MeshInstance {
+    /// Index into [`MeshScene::meshes`].
+    pub mesh: usize,
+    /// Column-major 4×4 world transform (right-handed). `None` = identity.
+    pub transform: Option<[f32; 16]>,
+}
+
+/// Meshes and their placements, ready to be written as a flat triangle list.
+///
+/// Meshes are kept in local space and referenced by instances so that map
+/// models placed many times are only decoded once.
+#[derive(Default)]
+pub struct MeshScene {
+    pub meshes: Vec<SceneMesh>,
+    pub instances: Vec<MeshInstance>,
+    /// Albedo textures (PNG bytes). Meshes reference these by index.
+    pub textures: Vec<PngBytes>,
+}
+
+impl MeshScene {
+    /// Collect one LOD of a visual, as exported by
+    /// [`export_glb`](super::gltf_export::export_glb).
+    pub fn from_model(
+        visual: &VisualPrototype,
+        geometry: &MergedGeometry,
+        db: &PrototypeDatabase<'_>,
+        lod: usize,
+        texture_set: &TextureSet,
+        damaged: bool,
+    ) -> Result<Self, Report<ExportError>> {
+        if visual.lods.is_empty() {
+            return Err(Report::new(ExportError::LodOutOfRange(lod, 0)));
+        }
+        if lod >= visual.lods.len() {
+            return Err(Report::new(ExportError::LodOutOfRange(
+                lod,
+                visual.lods.len() - 1,
+            )));
+        }
+
+        let self_id_index = db.build_self_id_index();
+        let primitives = collect_primitives(
+            visual,
+            geometry,
+            Some(db),
+            Some(&self_id_index),
+            &visual.lods[lod],
+            damaged,
+            None,
+            false,
+        )?;
+        if primitives.is_empty() {
+            eprintln!("Warning: no primitives found for LOD {lod}");
+        }
+
+        let mut scene = Self::default();
+        let mut texture_ids = HashMap::new();
+        for prim in primitives {
+            let name = prim.material_name.clone();
+            scene.push_primitive(name, prim, None, texture_set, &mut texture_ids);
+        }
+        Ok(scene)
+    }
+
+    /// Collect a multi-part ship, as exported by
+    /// [`export_ship_glb`](super::gltf_export::export_ship_glb).
+    ///
+    /// Only the base textures of `texture_set` are used; these formats have no
+    /// equivalent of material variants.
+    pub fn from_ship(
+        sub_models: &[SubModel<'_>],
+        armor_models: &[ArmorSubModel],
+        collision_models: &[ArmorSubModel],
+        db: &PrototypeDatabase<'_>,
+        lod: usize,
+        texture_set: &TextureSet,
+        damaged: bool,
+    ) -> Result<Self, Report<ExportError>> {
+        let mut scene = Self::default();
+        let mut texture_ids = HashMap::new();
+        let self_id_index = db.build_self_id_index();
+
+        for sub in sub_models {
+            if sub.visual.lods.is_empty() || lod >= sub.visual.lods.len() {
+                eprintln!(
+                    "Warning: sub-model '{}' has {} LODs, skipping (requested LOD {})",
+                    sub.name,
+                    sub.visual.lods.len(),
+                    lod
+                );
+                continue;
+            }
+
+            let primitives = collect_primitives(
+                sub.visual,
+                sub.geometry,
+                Some(db),
+                Some(&self_id_index),
+                &sub.visual.lods[lod],
+                damaged,
+                sub.barrel_pitch.as_ref(),
+                false,
+            )?;
+            for prim in primitives {
+                scene.push_primitive(
+                    sub.name.clone(),
+                    prim,
+                    sub.transform.map(negate_z_transform),
+                    texture_set,
+                    &mut texture_ids,
+                );
+            }
+        }
+
+        for (material_prefix, meshes) in [("armor", armor_models), ("collision", collision_models)]
+        {
+            for armor in meshes {
+                if armor.positions.is_empty() {
+                    continue;
+                }
+                scene.meshes.push(SceneMesh {
+                    name: armor.name.clone(),
+                    positions: armor.positions.clone(),
+                    normals: armor.normals.clone(),
+                    uvs: Vec::new(),
+                    colors: armor.colors.clone(),
+                    indices: armor.indices.clone(),
+                    material: format!("{material_prefix}_{}", armor.name),
+                    texture: None,
+                    base_color: [1.0, 1.0, 1.0, 1.0],
+                });
+                scene.instances.push(MeshInstance {
+                    mesh: scene.meshes.len() - 1,
+                    transform: armor.transform.map(negate_z_transform),
+                });
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// Collect a decoded map scene: instanced models, vegetation, terrain and water.
+    pub fn from_map(map: &MapScene) -> Self {
+        let mut scene = Self {
+            meshes: map
+                .model_meshes
+                .iter()
+                .map(SceneMesh::from_map_mesh)
+                .collect(),
+            instances: Vec::new(),
+            textures: map.textures.clone(),
+        };
+
+        for inst in &map.model_instances {
+            for mesh in inst.mesh_range.clone() {
+                scene.instances.push(MeshInstance {
+                    mesh,
+                    transform: Some(inst.transform),
+                });
+            }
+        }
+        for (mesh, positions) in &map.vegetation_instances {
+            for &[x, y, z] in positions {
+                let mut transform = IDENTITY;
+                transform[12..15].copy_from_slice(&[x, y, z]);
+                scene.instances.push(MeshInstance {
+                    mesh: *mesh,
+                    transform: Some(transform),
+                });
+            }
+        }
+        for mesh in [&map.terrain, &map.water].into_iter().flatten() {
+            scene.meshes.push(SceneMesh::from_map_mesh(mesh));
+            scene.instances.push(MeshInstance {
+                mesh: scene.meshes.len() - 1,
+                transform: None,
+            });
+        }
+
+        scene
+    }
+
+    /// Total number of triangles across all instances.
+    pub fn triangle_count(&self) -> usize {
+        self.instances
+            .iter()
+            .map(|inst| self.meshes[inst.mesh].indices.len() / 3)
+            .sum()
+    }
+
+    /// Add a decoded primitive as a new mesh with a single instance.
+    fn push_primitive(
+        &mut self,
+        name: String,
+        prim: DecodedPrimitive,
+        transform: Option<[f32; 16]>,
+        texture_set: &TextureSet,
+        texture_ids: &mut HashMap<String, usize>,
+    ) {
+        // Same keying as the GLB material cache: MFM stem, else material name.
+        let texture = prim.mfm_stem.as_ref().and_then(|stem| {
+            let png = texture_set.base.get(stem)?;
+            Some(*texture_ids.entry(stem.clone()).or_insert_with(|| {
+                self.textures.push(png.clone());
+                self.textures.len() - 1
+            }))
+        });
+        let material = prim
+            .mfm_stem
+            .clone()
+            .unwrap_or_else(|| prim.material_name.clone());
+
+        self.meshes.push(SceneMesh {
+            name,
+            positions: prim.positions,
+            normals: prim.normals,
+            uvs: prim.uvs,
+            colors: Vec::new(),
+            indices: prim.indices,
+            material,
+            texture,
+            base_color: [1.0, 1.0, 1.0, 1.0],
+        });
+        self.instances.push(MeshInstance {
+            mesh: self.meshes.len() - 1,
+            transform,
+        });
+    }
+
+    /// Iterate instances with their world-space positions and normals.
+    pub(super) fn world_instances(
+        &self,
+    ) -> impl Iterator<Item = (&SceneMesh, Vec<[f32; 3]>, Vec<[f32; 3]>)> + '_ {
+        self.instances.iter().map(|inst| {
+            let mesh = &self.meshes[inst.mesh];
+            match &inst.transform {
+                Some(m) => (
+                    mesh,
+                    mesh.positions
+                        .iter()
+                        .map(|&p| transform_point(m, p))
+                        .collect(),
+                    mesh.normals
+                        .iter()
+                        .map(|&n| transform_normal(m, n))
+                        .collect(),
+                ),
+                None => (mesh, mesh.positions.clone(), mesh.normals.clone()),
+            }
+        })
+    }
+}
+
+impl SceneMesh {
+    fn from_map_mesh(mesh: &MapMesh) -> Self {
+        let material = match mesh.albedo_texture {
+            Some(tex) => format!("map_texture_{tex}"),
+            None => {
+                let [r, g, b, a] = mesh.base_color.map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8);
+                format!("map_color_{r:02x}{g:02x}{b:02x}{a:02x}")
+            }
+        };
+        Self {
+            name: mesh.name.clone(),
+            positions: mesh.positions.clone(),
+            normals: mesh.normals.clone(),
+            uvs: mesh.uvs.clone(),
+            colors: Vec::new(),
+            indices: mesh.indices.clone(),
+            material,
+            texture: mesh.albedo_texture,
+            base_color: mesh.base_color,
+        }
+    }
+}
+
+const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, //
+    0.0, 1.0, 0.0, 0.0, //
+    0.0, 0.0, 1.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0,
+];
+
+/// p' = M * p (column-major 4x4, affine).
+fn transform_point(m: &[f32; 16], [x, y, z]: [f32; 3]) -> [f32; 3] {
+    [
+        m[0] * x + m[4] * y + m[8] * z + m[12],
+        m[1] * x + m[5] * y + m[9] * z + m[13],
+        m[2] * x + m[6] * y + m[10] * z + m[14],
+    ]
+}
+
+/// n' = normalize(R * n). Transforms are rotation + uniform scale, so the
+/// upper 3x3 is used directly instead of the inverse transpose.
+fn transform_normal(m: &[f32; 16], [x, y, z]: [f32; 3]) -> [f32; 3] {
+    normalize([
+        m[0] * x + m[4] * y + m[8] * z,
+        m[1] * x + m[5] * y + m[9] * z,
+        m[2] * x + m[6] * y + m[10] * z,
+    ])
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let len = (x * x + y * y + z * z).sqrt();
+    if len > 0.0 {
+        [x / len, y / len, z / len]
+    } else {
+        [0.0, 0.0, 0.0]
+    }
+}
+
+fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    normalize([
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ])
+}
+
+/// OBJ/MTL names can't contain whitespace.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+fn io_error(e: std::io::Error) -> Report<ExportError> {
+    Report::new(ExportError::Io(e.to_string()))
+}
+
+/// Write `scene` to `path` in the given format.
+///
+/// OBJ also writes its `.mtl` library and textures next to `path`; see [`write_obj`].
+pub fn write_mesh_file(
+    scene: &MeshScene,
+    format: MeshFormat,
+    path: &Path,
+) -> Result<(), Report<ExportError>> {
+    if format == MeshFormat::Obj {
+        return write_obj(scene, path);
+    }
+
+    let file = std::fs::File::create(path).map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        MeshFormat::Stl => write_stl(scene, &mut writer)?,
+        MeshFormat::Ply => write_ply(scene, &mut writer)?,
+        MeshFormat::Obj => unreachable!(),
+    }
+    writer.flush().map_err(io_error)
+}
+
+/// Write `scene` as a Wavefront OBJ file at `path`.
+///
+/// The material library is written to `<stem>.mtl` and each texture to
+/// `<stem>_<index>.png` in the same directory. UVs are flipped to OBJ's
+/// bottom-left origin. Armor vertex colors use the common `v x y z r g b`
+/// extension understood by Blender and MeshLab.
+pub fn write_obj(scene: &MeshScene, path: &Path) -> Result<(), Report<ExportError>> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "output".to_string());
+    let mtl_name = format!("{stem}.mtl");
+    let texture_name = |tex: usize| format!("{stem}_{tex}.png");
+
+    for (i, png) in scene.textures.iter().enumerate() {
+        std::fs::write(dir.join(texture_name(i)), png.as_slice()).map_err(io_error)?;
+    }
+
+    // One MTL entry per distinct material name, first mesh wins.
+    let mut materials: BTreeMap<String, &SceneMesh> = BTreeMap::new();
+    for mesh in &scene.meshes {
+        materials
+            .entry(sanitize_name(&mesh.material))
+            .or_insert(mesh);
+    }
+    let mut mtl = BufWriter::new(std::fs::File::create(dir.join(&mtl_name)).map_err(io_error)?);
+    for (name, mesh) in &materials {
+        let [r, g, b, a] = mesh.base_color;
+        writeln!(mtl, "newmtl {name}").map_err(io_error)?;
+        writeln!(mtl, "Kd {r} {g} {b}").map_err(io_error)?;
+        writeln!(mtl, "d {a}").map_err(io_error)?;
+        if let Some(tex) = mesh.texture {
+            writeln!(mtl, "map_Kd {}", texture_name(tex)).map_err(io_error)?;
+        }
+        writeln!(mtl).map_err(io_error)?;
+    }
+    mtl.flush().map_err(io_error)?;
+
+    let mut obj = BufWriter::new(std::fs::File::create(path).map_err(io_error)?);
+    writeln!(obj, "# wowsunpack").map_err(io_error)?;
+    writeln!(obj, "mtllib {mtl_name}").map_err(io_error)?;
+
+    // OBJ indices are 1-based and global across the file.
+    let (mut v_base, mut vt_base, mut vn_base) = (1u64, 1u64, 1u64);
+    for (i, (mesh, positions, normals)) in scene.world_instances().enumerate() {
+        let has_uvs = mesh.uvs.len() == positions.len();
+        let has_normals = normals.len() == positions.len();
+        let has_colors = mesh.colors.len() == positions.len();
+
+        writeln!(obj, "o {}_{i}", sanitize_name(&mesh.name)).map_err(io_error)?;
+        for (v, [x, y, z]) in positions.iter().enumerate() {
+            if has_colors {
+                let [r, g, b, _] = mesh.colors[v];
+                writeln!(obj, "v {x} {y} {z} {r} {g} {b}").map_err(io_error)?;
+            } else {
+                writeln!(obj, "v {x} {y} {z}").map_err(io_error)?;
+            }
+        }
+        if has_uvs {
+            for [u, v] in &mesh.uvs {
+                writeln!(obj, "vt {u} {}", 1.0 - v).map_err(io_error)?;
+            }
+        }
+        if has_normals {
+            for [x, y, z] in &normals {
+                writeln!(obj, "vn {x} {y} {z}").map_err(io_error)?;
+            }
+        }
+
+        writeln!(obj, "usemtl {}", sanitize_name(&mesh.material)).map_err(io_error)?;
+        for tri in mesh.indices.chunks_exact(3) {
+            write!(obj, "f").map_err(io_error)?;
+            for &idx in tri {
+                let idx = idx as u64;
+                let v = v_base + idx;
+                match (has_uvs, has_normals) {
+                    (true, true) => write!(obj, " {v}/{}/{}", vt_base + idx, vn_base + idx),
+                    (true, false) => write!(obj, " {v}/{}", vt_base + idx),
+                    (false, true) => write!(obj, " {v}//{}", vn_base + idx),
+                    (false, false) => write!(obj, " {v}"),
+                }
+                .map_err(io_error)?;
+            }
+            writeln!(obj).map_err(io_error)?;
+        }
+
+        v_base += positions.len() as u64;
+        if has_uvs {
+            vt_base += positions.len() as u64;
+        }
+        if has_normals {
+            vn_base += positions.len() as u64;
+        }
+    }
+
+    obj.flush().map_err(io_error)
+}
+
+/// Write `scene` as a binary STL.
+///
+/// STL stores an unindexed triangle soup with one normal per facet, computed
+/// from the world-space winding. Materials, UVs and colors are dropped.
+pub fn write_stl(scene: &MeshScene, writer: &mut impl Write) -> Result<(), Report<ExportError>> {
+    let tri_count = u32::try_from(scene.triangle_count()).map_err(|_| {
+        Report::new(ExportError::Io(format!(
+            "{} triangles exceed the STL limit",
+            scene.triangle_count()
+        )))
+    })?;
+
+    // The 80-byte header must not start with "solid" (reserved for ASCII STL).
+    let mut header = [0u8; 80];
+    let tag = b"wowsunpack binary STL";
+    header[..tag.len()].copy_from_slice(tag);
+    writer.write_all(&header).map_err(io_error)?;
+    writer
+        .write_all(&tri_count.to_le_bytes())
+        .map_err(io_error)?;
+
+    for (mesh, positions, _) in scene.world_instances() {
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[tri[i] as usize]);
+            for v in [face_normal(a, b, c), a, b, c] {
+                for f in v {
+                    writer.write_all(&f.to_le_bytes()).map_err(io_error)?;
+                }
+            }
+            // Attribute byte count (unused).
+            writer.write_all(&[0, 0]).map_err(io_error)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write `scene` as a binary little-endian PLY.
+///
+/// Every vertex carries a normal and an RGBA color: the armor thickness color
+/// where present, otherwise the mesh's base color.
+pub fn write_ply(scene: &MeshScene, writer: &mut impl Write) -> Result<(), Report<ExportError>> {
+    let vertex_count: usize = scene
+        .instances
+        .iter()
+        .map(|inst| scene.meshes[inst.mesh].positions.len())
+        .sum();
+
+    let header = format!(
+        "ply\n\
+         format binary_little_endian 1.0\n\
+         comment wowsunpack\n\
+         element vertex {vertex_count}\n\
+         property float x\n\
+         property float y\n\
+         property float z\n\
+         property float nx\n\
+         property float ny\n\
+         property float nz\n\
+         property uchar red\n\
+         property uchar green\n\
+         property uchar blue\n\
+         property uchar alpha\n\
+         element face {}\n\
+         property list uchar uint vertex_indices\n\
+         end_header\n",
+        scene.triangle_count()
+    );
+    writer.write_all(header.as_bytes()).map_err(io_error)?;
+
+    for (mesh, positions, normals) in scene.world_instances() {
+        for (v, pos) in positions.iter().enumerate() {
+            let normal = normals.get(v).copied().unwrap_or([0.0; 3]);
+            for f in pos.iter().chain(&normal) {
+                writer.write_all(&f.to_le_bytes()).map_err(io_error)?;
+            }
+            let color = mesh.colors.get(v).unwrap_or(&mesh.base_color);
+            let rgba = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            writer.write_all(&rgba).map_err(io_error)?;
+        }
+    }
+
+    // Face indices are global across instances and must fit PLY's uint.
+    let index_overflow = || {
+        Report::new(ExportError::Io(format!(
+            "{vertex_count} vertices exceed the PLY index limit"
+        )))
+    };
+    let mut base = 0u32;
+    for inst in &scene.instances {
+        let mesh = &scene.meshes[inst.mesh];
+        for tri in mesh.indices.chunks_exact(3) {
+            writer.write_all(&[3]).map_err(io_error)?;
+            for &idx in tri {
+                let idx = base.checked_add(idx).ok_or_else(index_overflow)?;
+                writer.write_all(&idx.to_le_bytes()).map_err(io_error)?;
+            }
+        }
+        base = u32::try_from(mesh.positions.len())
+            .ok()
+            .and_then(|len| base.checked_add(len))
+            .ok_or_else(index_overflow)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn stl_and_ply_bake_instance_transforms() {
+        let mut transform = IDENTITY;
+        transform[12..15].copy_from_slice(&[10.0, 0.0, 0.0]);
+        let scene = MeshScene {
+            meshes: vec![SceneMesh {
+                name: "Hull".to_string(),
+                positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                normals: Vec::new(),
+                uvs: Vec::new(),
+                colors: vec![[1.0, 0.0, 0.0, 1.0]; 3],
+                indices: vec![0, 1, 2],
+                material: "hull".to_string(),
+                texture: None,
+                base_color: [1.0; 4],
+            }],
+            instances: vec![
+                MeshInstance {
+                    mesh: 0,
+                    transform: None,
+                },
+                MeshInstance {
+                    mesh: 0,
+                    transform: Some(transform),
+                },
+            ],
+            textures: Vec::new(),
+        };
+
+        let mut stl = Vec::new();
+        write_stl(&scene, &mut stl).unwrap();
+        assert_eq!(stl.len(), 84 + 2 * 50);
+        assert_eq!(u32::from_le_bytes(stl[80..84].try_into().unwrap()), 2);
+        let facet = |i: usize, f: usize| {
+            let off = 84 + i * 50 + f * 4;
+            f32::from_le_bytes(stl[off..off + 4].try_into().unwrap())
+        };
+        assert_eq!([facet(0, 0), facet(0, 1), facet(0, 2)], [0.0, 0.0, 1.0]);
+        // Second instance's first vertex is translated by +10 X.
+        assert_eq!(facet(1, 3), 10.0);
+
+        let mut ply = Vec::new();
+        write_ply(&scene, &mut ply).unwrap();
+        let header_end = b"end_header\n";
+        let body = ply
+            .windows(header_end.len())
+            .position(|w| w == header_end)
+            .unwrap()
+            + header_end.len();
+        let header = std::str::from_utf8(&ply[..body]).unwrap();
+        assert!(header.contains("element vertex 6\n"));
+        assert!(header.contains("element face 2\n"));
+        // 6 vertices of 6 floats + 4 color bytes, then 2 faces of 1 + 3 * 4 bytes.
+        assert_eq!(ply.len() - body, 6 * 28 + 2 * 13);
+        assert_eq!(&ply[body + 24..body + 28], &[255, 0, 0, 255]);
+        // The second instance's face indices are offset past the first's vertices.
+        let face = body + 6 * 28 + 13;
+        assert_eq!(ply[face], 3);
+        assert_eq!(
+            u32::from_le_bytes(ply[face + 1..face + 5].try_into().unwrap()),
+            3
+        );
+    }
+
+    #[test]
+    fn obj_indices_are_global_and_one_based() {
+        let mesh = |name: &str, texture| SceneMesh {
+            name: name.to_string(),
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 0.25]],
+            colors: Vec::new(),
+            indices: vec![0, 1, 2],
+            material: format!("{name} mat"),
+            texture,
+            base_color: [0.5, 0.5, 0.5, 1.0],
+        };
+        let scene = MeshScene {
+            meshes: vec![mesh("Hull", Some(0)), mesh("Deck", None)],
+            instances: vec![
+                MeshInstance {
+                    mesh: 0,
+                    transform: None,
+                },
+                MeshInstance {
+                    mesh: 1,
+                    transform: None,
+                },
+            ],
+            textures: vec![Arc::new(b"\x89PNG".to_vec())],
+        };
+
+        let dir = std::env::temp_dir().join(format!("mesh_formats_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_obj(&scene, &dir.join("ship.obj")).unwrap();
+        let obj = std::fs::read_to_string(dir.join("ship.obj")).unwrap();
+        let mtl = std::fs::read_to_string(dir.join("ship.mtl")).unwrap();
+        let texture = std::fs::read(dir.join("ship_0.png")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(obj.contains("mtllib ship.mtl\n"));
+        assert!(obj.contains("usemtl Hull_mat\n"));
+        // UVs are flipped to a bottom-left origin.
+        assert!(obj.contains("vt 0 0.75\n"));
+        let faces: Vec<&str> = obj.lines().filter(|l| l.starts_with("f ")).collect();
+        assert_eq!(faces, ["f 1/1/1 2/2/2 3/3/3", "f 4/4/4 5/5/5 6/6/6"]);
+
+        assert!(mtl.contains("newmtl Hull_mat\nKd 0.5 0.5 0.5\nd 1\nmap_Kd ship_0.png\n"));
+        assert!(mtl.contains("newmtl Deck_mat\n"));
+        assert_eq!(texture, b"\x89PNG");
+    }
+}
@@ -1,13 +1,33 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 
+#[cfg(feature = "models")]
+pub mod aircraft;
+#[cfg(feature = "models")]
+pub mod armor_raycast;
 #[cfg(feature = "models")]
 pub mod camouflage;
 #[cfg(feature = "models")]
+pub mod firing_arcs;
+#[cfg(feature = "models")]
 pub mod gltf_export;
 #[cfg(feature = "models")]
+pub mod gltf_import;
+#[cfg(feature = "models")]
+pub mod gltf_split;
+#[cfg(feature = "models")]
+pub mod mesh_formats;
+#[cfg(feature = "models")]
+pub mod profile;
+#[cfg(feature = "models")]
 pub mod ship;
 #[cfg(feature = "models")]
 pub mod texture;
+#[cfg(feature = "models")]
+pub mod texture_cache;
+#[cfg(feature = "models")]
+pub mod usd_export;
+#[cfg(feature = "models")]
+pub mod zone_report;
 
 /// When true, export functions emit verbose diagnostic output to stderr
 /// (e.g. per-vertex UV decoding details).
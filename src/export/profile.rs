@@ -0,0 +1,363 @@
+//! 2D side-profile and top-down plan images of a ship, rendered on the CPU.
+//!
+//! [`render_profile`] projects a [`MeshScene`] orthographically and
+//! rasterizes it with a depth buffer. The result can be written as PNG, or as
+//! SVG with every horizontal run of same-colored pixels merged into one path
+//! per color. In the armor style the visual model is drawn as a flat
+//! backdrop and the armor plates on top of it, colored with
+//! [`thickness_to_color`](super::gltf_export::thickness_to_color). The SVG
+//! includes the [`armor_color_legend`] below the image.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use image_dds::image::ExtendedColorType;
+use image_dds::image::ImageEncoder;
+use image_dds::image::codecs::png::PngEncoder;
+use rootcause::Report;
+
+use super::gltf_export::{ArmorLegendEntry, armor_color_legend};
+use super::mesh_formats::MeshScene;
+use super::texture::TextureError;
+
+/// Fill color of the visual model in [`ProfileStyle::Silhouette`].
+const SILHOUETTE_COLOR: [u8; 4] = [96, 104, 112, 255];
+/// Fill color of the visual model behind the plates in [`ProfileStyle::Armor`].
+const BACKDROP_COLOR: [u8; 4] = [220, 220, 220, 255];
+/// Empty border around the ship, in pixels.
+const MARGIN_PX: f32 = 8.0;
+/// Largest image width or height, in pixels.
+pub const MAX_SIZE_PX: u32 = 16_384;
+/// Height of one legend row in the SVG, in pixels.
+const LEGEND_ROW_PX: u32 = 20;
+/// Width of one legend entry in the SVG, in pixels.
+const LEGEND_ENTRY_PX: u32 = 120;
+
+/// Direction the ship is viewed from. The bow points right in both views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileView {
+    /// From the model's +X side, Y up.
+    Side,
+    /// From above, +X down.
+    Top,
+}
+
+/// What is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileStyle {
+    /// Hull and mounts as one flat silhouette.
+    Silhouette,
+    /// Armor plates colored by thickness over a light silhouette; the nearest
+    /// plate to the viewer is shown.
+    Armor,
+}
+
+/// A rendered profile: opaque RGBA pixels on a transparent background.
+#[derive(Debug, Clone)]
+pub struct ProfileImage {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major, top row first.
+    pub pixels: Vec<[u8; 4]>,
+    /// Thickness color legend; empty for silhouettes.
+    pub legend: Vec<ArmorLegendEntry>,
+}
+
+impl ProfileImage {
+    /// Encode the image as PNG. The legend is not included.
+    pub fn to_png(&self) -> Result<Vec<u8>, Report<TextureError>> {
+        let mut png_buf = Vec::new();
+        PngEncoder::new(&mut png_buf)
+            .write_image(
+                self.pixels.as_flattened(),
+                self.width,
+                self.height,
+                ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| Report::new(TextureError::PngEncode(e.to_string())))?;
+        Ok(png_buf)
+    }
+
+    /// Vectorize the image as SVG, with the legend (if any) below it.
+    pub fn to_svg(&self) -> String {
+        // One path per color, one `h`/`v` rectangle per horizontal run.
+        let mut paths: BTreeMap<[u8; 4], String> = BTreeMap::new();
+        for (y, row) in self.pixels.chunks_exact(self.width as usize).enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let color = row[x];
+                let start = x;
+                while x < row.len() && row[x] == color {
+                    x += 1;
+                }
+                if color[3] == 0 {
+                    continue;
+                }
+                let len = x - start;
+                let _ = write!(
+                    paths.entry(color).or_default(),
+                    "M{start} {y}h{len}v1h-{len}z"
+                );
+            }
+        }
+
+        let per_row = (self.width / LEGEND_ENTRY_PX).max(1) as usize;
+        let legend_rows = self.legend.len().div_ceil(per_row) as u32;
+        let legend_height = if legend_rows > 0 {
+            legend_rows * LEGEND_ROW_PX + LEGEND_ROW_PX / 2
+        } else {
+            0
+        };
+        let total_height = self.height + legend_height;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {total_height}" width="{w}" height="{total_height}" shape-rendering="crispEdges">"#,
+            w = self.width
+        );
+        for ([r, g, b, _], d) in &paths {
+            let _ = writeln!(svg, r##"<path fill="#{r:02x}{g:02x}{b:02x}" d="{d}"/>"##);
+        }
+
+        for (i, entry) in self.legend.iter().enumerate() {
+            let x = (i % per_row) as u32 * LEGEND_ENTRY_PX + MARGIN_PX as u32;
+            let y = self.height + (i / per_row) as u32 * LEGEND_ROW_PX + LEGEND_ROW_PX / 4;
+            let [r, g, b, _] = to_rgba8(entry.color);
+            let _ = writeln!(
+                svg,
+                r##"<rect x="{x}" y="{y}" width="14" height="14" fill="#{r:02x}{g:02x}{b:02x}" stroke="#404040" stroke-width="0.5"/><text x="{}" y="{}" font-family="sans-serif" font-size="12">{:.0}–{:.0} mm</text>"##,
+                x + 20,
+                y + 12,
+                entry.min_mm,
+                entry.max_mm
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Render `scene` (as collected by
+/// [`MeshScene::from_ship`]) `width_px` pixels wide, clamped to
+/// `1..=`[`MAX_SIZE_PX`]; the height follows the ship's proportions, scaled
+/// down to fit within [`MAX_SIZE_PX`].
+///
+/// Meshes with vertex colors are taken to be armor, all others the visual
+/// model.
+pub fn render_profile(
+    scene: &MeshScene,
+    view: ProfileView,
+    style: ProfileStyle,
+    width_px: u32,
+) -> ProfileImage {
+    // Projected triangles per layer: (u right, v down, depth toward viewer
+    // smaller) and fill color.
+    let mut visual = Vec::new();
+    let mut armor = Vec::new();
+    for (mesh, positions, _) in scene.world_instances() {
+        let is_armor = !mesh.colors.is_empty();
+        if is_armor && style == ProfileStyle::Silhouette {
+            continue;
+        }
+        for idx in mesh.indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|i| project(view, positions[idx[i] as usize]));
+            if is_armor {
+                armor.push((vertices, to_rgba8(mesh.colors[idx[0] as usize])));
+            } else {
+                let color = match style {
+                    ProfileStyle::Silhouette => SILHOUETTE_COLOR,
+                    ProfileStyle::Armor => BACKDROP_COLOR,
+                };
+                visual.push((vertices, color));
+            }
+        }
+    }
+
+    let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+    for (vertices, _) in visual.iter().chain(&armor) {
+        for v in vertices {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(v[axis]);
+                max[axis] = max[axis].max(v[axis]);
+            }
+        }
+    }
+    if min[0] > max[0] {
+        (min, max) = ([0.0; 2], [0.0; 2]);
+    }
+
+    let width_px = width_px.clamp(1, MAX_SIZE_PX);
+    let extent = [(max[0] - min[0]).max(f32::EPSILON), max[1] - min[1]];
+    // Shrink to fit if the height would exceed the limit.
+    let scale = ((width_px as f32 - 2.0 * MARGIN_PX).max(1.0) / extent[0])
+        .min((MAX_SIZE_PX as f32 - 2.0 * MARGIN_PX) / extent[1].max(f32::EPSILON));
+    let height_px = (extent[1] * scale + 2.0 * MARGIN_PX).ceil().max(1.0) as u32;
+    let to_pixel = |[u, v, depth]: [f32; 3]| {
+        [
+            (u - min[0]) * scale + MARGIN_PX,
+            (v - min[1]) * scale + MARGIN_PX,
+            depth,
+        ]
+    };
+
+    let mut raster = Raster::new(width_px, height_px);
+    for (vertices, color) in visual {
+        raster.fill_triangle(vertices.map(to_pixel), color);
+    }
+    if style == ProfileStyle::Armor {
+        // Plates lie inside the hull skin; draw them over the backdrop.
+        raster.depth.fill(f32::INFINITY);
+        for (vertices, color) in armor {
+            raster.fill_triangle(vertices.map(to_pixel), color);
+        }
+    }
+
+    ProfileImage {
+        width: width_px,
+        height: height_px,
+        pixels: raster.pixels,
+        legend: match style {
+            ProfileStyle::Silhouette => Vec::new(),
+            ProfileStyle::Armor => armor_color_legend(),
+        },
+    }
+}
+
+/// Color and depth buffers.
+struct Raster {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+}
+
+impl Raster {
+    fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; len],
+            depth: vec![f32::INFINITY; len],
+        }
+    }
+
+    /// Fill the pixels whose centers lie in the triangle, where it is nearer
+    /// than what was drawn before. Either winding is accepted.
+    fn fill_triangle(&mut self, [a, b, c]: [[f32; 3]; 3], color: [u8; 4]) {
+        let area = edge(a, b, c);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let x0 = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let y0 = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let x1 = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(self.width);
+        let y1 = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(self.height);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+                let (w0, w1, w2) = (
+                    edge(b, c, p) / area,
+                    edge(c, a, p) / area,
+                    edge(a, b, p) / area,
+                );
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                let depth = w0 * a[2] + w1 * b[2] + w2 * c[2];
+                let i = y as usize * self.width as usize + x as usize;
+                if depth < self.depth[i] {
+                    self.depth[i] = depth;
+                    self.pixels[i] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Twice the signed area of triangle (a, b, p) in the XY plane.
+fn edge(a: [f32; 3], b: [f32; 3], p: [f32; 3]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Project a right-handed (Z negated) scene point to (right, down, depth),
+/// bow to the right and smaller depth nearer the viewer.
+fn project(view: ProfileView, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    match view {
+        ProfileView::Side => [-z, -y, -x],
+        ProfileView::Top => [-z, x, -y],
+    }
+}
+
+fn to_rgba8(color: [f32; 4]) -> [u8; 4] {
+    let [r, g, b, _] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    [r, g, b, 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::mesh_formats::{MeshInstance, SceneMesh};
+
+    fn quad(name: &str, x: f32, colors: Vec<[f32; 4]>) -> SceneMesh {
+        // A square in the YZ plane at `x`, facing the side view.
+        SceneMesh {
+            name: name.to_string(),
+            positions: vec![
+                [x, 0.0, 0.0],
+                [x, 0.0, -10.0],
+                [x, 5.0, -10.0],
+                [x, 5.0, 0.0],
+            ],
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors,
+            indices: vec![0, 1, 2, 0, 2, 3],
+            material: name.to_string(),
+            texture: None,
+            base_color: [1.0; 4],
+        }
+    }
+
+    #[test]
+    fn armor_is_drawn_over_the_visual_model() {
+        let red = [1.0, 0.0, 0.0, 0.8];
+        let scene = MeshScene {
+            meshes: vec![
+                quad("hull", 2.0, Vec::new()),
+                quad("plate", 0.0, vec![red; 4]),
+            ],
+            instances: vec![
+                MeshInstance {
+                    mesh: 0,
+                    transform: None,
+                },
+                MeshInstance {
+                    mesh: 1,
+                    transform: None,
+                },
+            ],
+            textures: Vec::new(),
+        };
+
+        let silhouette = render_profile(&scene, ProfileView::Side, ProfileStyle::Silhouette, 116);
+        assert_eq!((silhouette.width, silhouette.height), (116, 66));
+        let center = silhouette.pixels[33 * 116 + 58];
+        assert_eq!(center, SILHOUETTE_COLOR);
+        assert_eq!(silhouette.pixels[0], [0; 4]);
+        assert!(silhouette.legend.is_empty());
+
+        // The plate is behind the hull skin but still drawn on top.
+        let armor = render_profile(&scene, ProfileView::Side, ProfileStyle::Armor, 116);
+        assert_eq!(armor.pixels[33 * 116 + 58], [255, 0, 0, 255]);
+        assert!(!armor.legend.is_empty());
+
+        let svg = armor.to_svg();
+        assert!(svg.contains(r##"<path fill="#ff0000""##));
+        assert!(svg.contains("mm</text>"));
+        assert!(armor.to_png().is_ok());
+    }
+}
@@ -6,7 +6,7 @@
 //! # Quick start
 //! ```no_run
 //! use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
-//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # fn main() -> Result<(), rootcause::Report> {
 //! # let vfs: vfs::VfsPath = todo!();
 //! let assets = ShipAssets::load(&vfs)?;
 //! let ctx = assets.load_ship("Yamato", &ShipExportOptions::default())?;
@@ -16,7 +16,7 @@
 //! # }
 //! ```
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::Arc;
@@ -27,15 +27,29 @@ use vfs::VfsPath;
 use crate::data::ResourceLoader;
 use crate::game_params::keys;
 use crate::game_params::provider::GameMetadataProvider;
-use crate::game_params::types::{ArmorMap, GameParamProvider, MountPoint, Vehicle};
+use crate::game_params::types::{
+    ArmorMap, GameParamProvider, HullUpgradeConfig, Meters, MountPoint, Vehicle,
+};
+use crate::models::animation::Animation;
 use crate::models::assets_bin::{self, PrototypeDatabase};
 use crate::models::geometry;
-use crate::models::model;
+use crate::models::model::{self, ResolvedDye};
 use crate::models::visual::{self, VisualPrototype};
 
+use super::aircraft::{self, AircraftInfo, AircraftNode, PayloadInfo, PayloadKind};
+use super::armor_raycast::ArmorScene;
 use super::camouflage::{self, CamouflageDb};
-use super::gltf_export::{self, InteractiveArmorMesh, SubModel, TextureSet};
+use super::firing_arcs::{self, MountArc};
+use super::gltf_export::{
+    self, DyeVariant, InteractiveArmorMesh, NodeMarker, SplashBoxModel, SubModel, TextureSet,
+};
+use super::gltf_split;
+use super::mesh_formats::{self, MeshFormat, MeshScene};
+use super::profile::{self, ProfileImage, ProfileStyle, ProfileView};
 use super::texture;
+use super::texture_cache::TextureCache;
+use super::usd_export;
+use super::zone_report::{self, ZoneReport};
 
 // ---------------------------------------------------------------------------
 // Public types
@@ -46,6 +60,9 @@ use super::texture;
 pub struct ShipExportOptions {
     /// LOD level (0 = highest detail). Default: 0.
     pub lod: usize,
+    /// Also export every lower-detail LOD after `lod`, chained to each part
+    /// through `MSFT_lod`. Default: false.
+    pub all_lods: bool,
     /// Hull upgrade selection. `None` = first/stock hull.
     /// Accepts full upgrade name (e.g. "PJUH911_Yamato_1944") or a prefix
     /// match against the hull component name (e.g. "B").
@@ -56,6 +73,19 @@ pub struct ShipExportOptions {
     /// When true, crack geometry is included and patch geometry is excluded.
     /// Default: false (intact hull).
     pub damaged: bool,
+    /// Include collision hulls (`CM_*` models) as their own untextured meshes.
+    /// Default: false.
+    pub collision: bool,
+    /// Include the hull's splash boxes as translucent boxes colored by hit
+    /// location (GLB only). Default: false.
+    pub splash: bool,
+    /// Include each mount's firing arcs as translucent flat fans at mount
+    /// height (GLB only). Default: false.
+    pub firing_arcs: bool,
+    /// Export each part's skeleton as a glTF skin with per-vertex joints and
+    /// weights, so turrets, barrels, radars, etc. can be posed. Also exports
+    /// the parts' animation clips as glTF animations. Default: false.
+    pub skinned: bool,
     /// Module overrides: component type key (e.g. "artillery") to component name.
     /// Overrides the default component for specific types.
     pub module_overrides:
@@ -66,9 +96,14 @@ impl Default for ShipExportOptions {
     fn default() -> Self {
         Self {
             lod: 0,
+            all_lods: false,
             hull: None,
             textures: true,
             damaged: false,
+            collision: false,
+            splash: false,
+            firing_arcs: false,
+            skinned: false,
             module_overrides: std::collections::HashMap::new(),
         }
     }
@@ -85,6 +120,33 @@ pub struct ShipInfo {
     pub param_index: String,
 }
 
+/// A ship with a model, as listed by [`ShipAssets::ships`].
+#[derive(Debug, Clone)]
+pub struct ShipCatalogEntry {
+    pub info: ShipInfo,
+    /// GameParam name, e.g. "PJSB018_Yamato_1944".
+    pub param_name: String,
+    /// Nation, e.g. "Japan".
+    pub nation: String,
+    /// Ship class, e.g. "Battleship".
+    pub species: String,
+    pub tier: u32,
+    /// GameParams group, e.g. "start", "upgradeable", "event".
+    pub group: String,
+}
+
+impl ShipCatalogEntry {
+    /// Output path for this ship relative to an export directory, without
+    /// extension: `nation/class/TT_ParamName`, e.g.
+    /// `Japan/Battleship/10_PJSB018_Yamato_1944`.
+    pub fn output_stem(&self) -> String {
+        format!(
+            "{}/{}/{:02}_{}",
+            self.nation, self.species, self.tier, self.param_name
+        )
+    }
+}
+
 /// Summary of a hull upgrade for listing purposes.
 #[derive(Debug, Clone)]
 pub struct HullUpgradeInfo {
@@ -101,12 +163,15 @@ pub struct HullUpgradeInfo {
 /// Shared game assets for ship export operations.
 ///
 /// Creating this is the expensive step (~18 seconds for GameParams parsing).
-/// Reuse a single instance across multiple ship exports.
+/// Reuse a single instance across multiple ship exports, so decoded textures
+/// and mount geometry are shared between them.
 pub struct ShipAssets {
     assets_bin_bytes: Vec<u8>,
     vfs: VfsPath,
     metadata: Arc<GameMetadataProvider>,
     camo_db: Option<CamouflageDb>,
+    texture_cache: Arc<TextureCache>,
+    geometry_cache: GeometryCache,
 }
 
 impl ShipAssets {
@@ -132,6 +197,8 @@ impl ShipAssets {
             vfs: vfs.clone(),
             metadata,
             camo_db,
+            texture_cache: Arc::new(TextureCache::new()),
+            geometry_cache: GeometryCache::default(),
         })
     }
 
@@ -157,6 +224,8 @@ impl ShipAssets {
             vfs: vfs.clone(),
             metadata,
             camo_db,
+            texture_cache: Arc::new(TextureCache::new()),
+            geometry_cache: GeometryCache::default(),
         })
     }
 
@@ -193,6 +262,17 @@ impl ShipAssets {
         &self.vfs
     }
 
+    /// Replace the texture cache used by ships loaded from now on, e.g. with
+    /// one backed by a disk directory. Defaults to an in-memory cache.
+    pub fn set_texture_cache(&mut self, cache: TextureCache) {
+        self.texture_cache = Arc::new(cache);
+    }
+
+    /// Access the texture cache shared by all ships loaded from these assets.
+    pub fn texture_cache(&self) -> &TextureCache {
+        &self.texture_cache
+    }
+
     /// Find a ship by name (fuzzy display-name match or exact model dir).
     pub fn find_ship(&self, name: &str) -> Result<ShipInfo, Report> {
         let db = self.db()?;
@@ -324,6 +404,263 @@ impl ShipAssets {
         }
     }
 
+    /// Every ship param with a model, in GameParams order.
+    pub fn ships(&self) -> impl Iterator<Item = ShipCatalogEntry> + '_ {
+        self.metadata.params().iter().filter_map(|param| {
+            let vehicle = param.vehicle()?;
+            let model_path = vehicle.model_path()?;
+            let dir = model_path
+                .rsplit_once('/')
+                .map(|(d, _)| d)
+                .unwrap_or(model_path);
+            let model_dir = dir.rsplit('/').next().unwrap_or(dir);
+            Some(ShipCatalogEntry {
+                info: ShipInfo {
+                    model_dir: model_dir.to_string(),
+                    display_name: self
+                        .metadata
+                        .localized_name_from_param(param)
+                        .map(|s: &str| s.to_string()),
+                    param_index: param.index().to_string(),
+                },
+                param_name: param.name().to_string(),
+                nation: param.nation().to_string(),
+                species: param
+                    .species()
+                    .map(|s| s.as_ref().map(|s| s.name()).to_string())
+                    .unwrap_or_default(),
+                tier: vehicle.level(),
+                group: vehicle.group().to_string(),
+            })
+        })
+    }
+
+    /// Load a ship listed by [`Self::ships`].
+    pub fn load_catalog_ship(
+        &self,
+        entry: &ShipCatalogEntry,
+        options: &ShipExportOptions,
+    ) -> Result<ShipModelContext, Report> {
+        let param = self
+            .metadata
+            .game_param_by_index(&entry.info.param_index)
+            .ok_or_else(|| rootcause::report!("No GameParam '{}'", entry.info.param_index))?;
+        self.load_ship_inner(entry.info.clone(), param.vehicle(), options)
+    }
+
+    /// CRC32 of everything an export of `entry` with `options` reads: the
+    /// hull and mount visual and model prototypes, their geometry, splash,
+    /// texture, camouflage, dye and animation files, mount placements, armor
+    /// thicknesses, hit locations, the options and the tool version.
+    ///
+    /// Only the raw inputs are read and nothing is decoded, so this is much
+    /// cheaper than [`Self::load_catalog_ship`] and can decide whether a
+    /// ship needs exporting again.
+    pub fn source_crc(
+        &self,
+        entry: &ShipCatalogEntry,
+        options: &ShipExportOptions,
+    ) -> Result<u32, Report> {
+        let param = self
+            .metadata
+            .game_param_by_index(&entry.info.param_index)
+            .ok_or_else(|| rootcause::report!("No GameParam '{}'", entry.info.param_index))?;
+        let vehicle = param.vehicle();
+        let db = self.db()?;
+        let self_id_index = db.build_self_id_index();
+        let sources =
+            self.resolve_sources(&db, &self_id_index, &entry.info.model_dir, vehicle, options)?;
+
+        let mut crc = flate2::Crc::new();
+        crc.update(SOURCE_CRC_VERSION.as_bytes());
+        crc.update(options_key(options).as_bytes());
+
+        for mi in &sources.mount_points {
+            crc.update(mi.hp_name().as_bytes());
+            crc.update(mi.model_path().as_bytes());
+            crc.update(
+                format!(
+                    "{:?}|{:?}|{:?}|{:?}",
+                    mi.species(),
+                    mi.pitch_dead_zones(),
+                    mi.horiz_sector(),
+                    mi.dead_zones()
+                )
+                .as_bytes(),
+            );
+            if let Some(armor) = mi.mount_armor() {
+                hash_armor_map(&mut crc, armor);
+            }
+        }
+
+        // Every file the export reads, hashed once each at the end.
+        let mut files = BTreeSet::new();
+        let mut mfm_infos = Vec::new();
+        for part in sources.parts() {
+            crc.update(part.name.as_bytes());
+            // Records point into out-of-line data shared with other records,
+            // so the parsed record is hashed rather than the blob bytes.
+            crc.update(format!("{:?}", part.visual).as_bytes());
+            crc.update(format!("{:?}", part.dyes).as_bytes());
+            files.insert(part.geometry_path.clone());
+            files.extend(part.splash_path.clone());
+            if options.skinned {
+                files.extend(part.animation_paths.iter().cloned());
+            }
+            if options.textures {
+                files.extend(
+                    part.dyes
+                        .iter()
+                        .flat_map(|dye| &dye.tints)
+                        .filter_map(|tint| tint.material_path.as_deref())
+                        .filter_map(|mfm| texture::find_base_albedo_path(&self.vfs, mfm)),
+                );
+                mfm_infos.extend(collect_mfm_info(&part.visual, &db));
+            }
+        }
+
+        if options.textures {
+            let mut stems: Vec<String> = Vec::new();
+            for info in &mfm_infos {
+                files.insert(info.full_path.clone());
+                files.extend(texture::find_base_albedo_path(&self.vfs, &info.full_path));
+                if !stems.contains(&info.stem) {
+                    stems.push(info.stem.clone());
+                }
+            }
+            for scheme in texture::discover_texture_schemes(&self.vfs, &stems) {
+                for stem in &stems {
+                    if let Some((_, path)) = texture::find_texture_path(&self.vfs, stem, &scheme) {
+                        files.insert(path);
+                    }
+                }
+            }
+
+            for scheme in &sources.mat_camo_schemes {
+                let uv_transforms: BTreeMap<_, _> = scheme.uv_transforms.iter().collect();
+                crc.update(
+                    format!(
+                        "{}|{}|{:?}|{uv_transforms:?}",
+                        scheme.display_name, scheme.tiled, scheme.color_scheme_colors
+                    )
+                    .as_bytes(),
+                );
+                files.extend(scheme.texture_paths.iter().cloned());
+            }
+        }
+
+        if let Some(vehicle) = vehicle {
+            if let Some(armor) = vehicle.armor() {
+                hash_armor_map(&mut crc, armor);
+            }
+            if let Some(hit_locations) = vehicle.hit_locations() {
+                let sorted: BTreeMap<_, _> = hit_locations.iter().collect();
+                crc.update(format!("{sorted:?}").as_bytes());
+            }
+            let draft = select_hull_upgrade(vehicle, options.hull.as_deref())
+                .and_then(|config| config.draft());
+            crc.update(format!("{draft:?}").as_bytes());
+        }
+
+        let mut buf = Vec::new();
+        for path in &files {
+            crc.update(path.as_bytes());
+            buf.clear();
+            if let Ok(mut file) = self.vfs.join(path).and_then(|p| p.open_file())
+                && file.read_to_end(&mut buf).is_ok()
+            {
+                crc.update(&buf);
+            }
+        }
+        Ok(crc.sum())
+    }
+
+    /// Every aircraft param with a model, in GameParams order.
+    pub fn aircraft(&self) -> impl Iterator<Item = AircraftInfo> + '_ {
+        self.metadata.params().iter().filter_map(|param| {
+            let aircraft = param.aircraft()?;
+            let payload = aircraft.bomb_name().and_then(|name| {
+                let projectile = self.metadata.game_param_by_name(name)?;
+                let kind = PayloadKind::from_species(*projectile.species()?.known()?)?;
+                Some(PayloadInfo {
+                    projectile: name.to_string(),
+                    kind,
+                    model_path: projectile
+                        .projectile()
+                        .and_then(|p| p.model_path())
+                        .map(|s| s.to_string()),
+                })
+            });
+            Some(AircraftInfo {
+                param_name: param.name().to_string(),
+                param_index: param.index().to_string(),
+                display_name: self
+                    .metadata
+                    .localized_name_from_param(param)
+                    .map(|s: &str| s.to_string()),
+                category: aircraft.category().clone(),
+                model_path: aircraft.model_path()?.to_string(),
+                payload,
+            })
+        })
+    }
+
+    /// Find an aircraft by param name or index (exact, then
+    /// [`AircraftInfo::matches`]).
+    pub fn find_aircraft(&self, name: &str) -> Result<AircraftInfo, Report> {
+        let all: Vec<AircraftInfo> = self.aircraft().collect();
+        all.iter()
+            .find(|a| a.param_name == name || a.param_index == name)
+            .or_else(|| all.iter().find(|a| a.matches(name)))
+            .cloned()
+            .ok_or_else(|| rootcause::report!("No aircraft matching '{name}'"))
+    }
+
+    /// Load a plane model and its payload for export.
+    ///
+    /// Uses `lod`, `all_lods`, `textures`, `damaged` and `skinned` from
+    /// `options`. Propeller nodes are tagged `{"role": "propeller"}` in node
+    /// extras; with `skinned` they are also joints that can be spun.
+    pub fn load_aircraft(
+        &self,
+        name: &str,
+        options: &ShipExportOptions,
+    ) -> Result<AircraftModelContext, Report> {
+        let info = self.find_aircraft(name)?;
+        let db = self.db()?;
+        let self_id_index = db.build_self_id_index();
+
+        let plane = self
+            .load_single_turret(&db, &self_id_index, &info.model_path)
+            .context_with(|| format!("Failed to load plane model {}", info.model_path))?;
+        let (propellers, hardpoints) = aircraft::classify_nodes(&plane.visual, &db.strings);
+
+        let payload_model = match info.payload.as_ref().and_then(|p| p.model_path.as_ref()) {
+            Some(path) if !hardpoints.is_empty() => {
+                match self.load_single_turret(&db, &self_id_index, path) {
+                    Ok(model) => Some(model),
+                    Err(e) => {
+                        eprintln!("Warning: could not load payload model {path}: {e}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        Ok(AircraftModelContext {
+            vfs: self.vfs.clone(),
+            assets_bin_bytes: self.assets_bin_bytes.clone(),
+            info,
+            plane,
+            payload_model,
+            propellers,
+            hardpoints,
+            options: options.clone(),
+            texture_cache: self.texture_cache.clone(),
+        })
+    }
+
     /// List hull upgrades for a ship.
     pub fn list_hull_upgrades(&self, name: &str) -> Result<Vec<HullUpgradeInfo>, Report> {
         let info = self.find_ship(name)?;
@@ -359,25 +696,19 @@ impl ShipAssets {
         let db = self.db()?;
         let self_id_index = db.build_self_id_index();
 
-        // Collect visuals for the ship model dir.
-        let visual_paths = self.find_visual_paths(&db, &self_id_index, &info.model_dir);
-        let sub_models = self.load_sub_models(&db, &self_id_index, &visual_paths)?;
-
-        // Also load turret models to include their stems.
+        // Collect MFM stems of the hull and default mount visuals.
         let vehicle = self.find_vehicle(&info.model_dir).ok();
-        let mount_points = vehicle
-            .and_then(|v| self.select_hull_mount_points(v, None, &std::collections::HashMap::new()))
-            .unwrap_or_default();
-        let turret_data = self.load_turret_models(&db, &self_id_index, &mount_points)?;
+        let sources = self.resolve_sources(
+            &db,
+            &self_id_index,
+            &info.model_dir,
+            vehicle,
+            &ShipExportOptions::default(),
+        )?;
 
         let mut all_stems = Vec::new();
-        for smd in &sub_models {
-            for mfm in collect_mfm_info(&smd.visual, &db) {
-                all_stems.push(mfm.stem);
-            }
-        }
-        for tmd in &turret_data {
-            for mfm in collect_mfm_info(&tmd.visual, &db) {
+        for part in sources.parts() {
+            for mfm in collect_mfm_info(&part.visual, &db) {
                 all_stems.push(mfm.stem);
             }
         }
@@ -461,36 +792,32 @@ impl ShipAssets {
     ) -> Result<ShipModelContext, Report> {
         let db = self.db()?;
         let self_id_index = db.build_self_id_index();
-
-        // Find all .visual files in the model directory.
-        let visual_paths = self.find_visual_paths(&db, &self_id_index, &info.model_dir);
-        if visual_paths.is_empty() {
-            bail!("No .visual files found for '{}'.", info.model_dir);
+        let sources =
+            self.resolve_sources(&db, &self_id_index, &info.model_dir, vehicle, options)?;
+        for warning in &sources.warnings {
+            eprintln!("Warning: {warning}");
         }
 
         // Load hull sub-models.
-        let hull_parts = self.load_sub_models(&db, &self_id_index, &visual_paths)?;
+        let hull_parts = self.load_sub_models(sources.hull_parts)?;
 
         // Load turret/mount models from GameParams.
-        let mount_points: Vec<MountPoint> = vehicle
-            .and_then(|v| {
-                self.select_hull_mount_points(v, options.hull.as_deref(), &options.module_overrides)
-            })
-            .unwrap_or_default();
-
-        let loaded = self.load_mounts(&db, &self_id_index, &mount_points, &hull_parts)?;
+        let loaded = self.load_mounts(
+            &db,
+            &sources.mount_points,
+            sources.mount_models,
+            &hull_parts,
+        )?;
         let turret_models = loaded.turret_models;
         let mounts = loaded.mounts;
-
-        // Resolve material-based camouflage schemes (ship-specific + universal).
-        let ship_index = self.find_ship_index(&info.model_dir);
-        let ship_idx = ship_index.as_deref();
-        let mut mat_camo_schemes = self.discover_mat_camo_schemes(&info.model_dir, ship_idx);
-        mat_camo_schemes.extend(self.discover_universal_camo_schemes(ship_idx));
+        let mat_camo_schemes = sources.mat_camo_schemes;
 
         // Extract armor thickness map and hit locations from GameParams.
         let armor_map = vehicle.and_then(|v| v.armor().cloned());
         let hit_locations = vehicle.and_then(|v| v.hit_locations().cloned());
+        let draft = vehicle
+            .and_then(|v| select_hull_upgrade(v, options.hull.as_deref()))
+            .and_then(|config| config.draft());
 
         Ok(ShipModelContext {
             vfs: self.vfs.clone(),
@@ -503,6 +830,8 @@ impl ShipAssets {
             mat_camo_schemes,
             armor_map,
             hit_locations,
+            draft,
+            texture_cache: self.texture_cache.clone(),
         })
     }
 
@@ -741,21 +1070,33 @@ impl ShipAssets {
         result
     }
 
-    /// Load and parse all sub-models from (name, full_path) pairs.
-    fn load_sub_models(
+    /// Resolve the hull parts, mount models and camouflage schemes an export
+    /// of `model_dir` with `options` reads, without reading any geometry.
+    ///
+    /// [`Self::source_crc`] hashes exactly what this returns, and
+    /// [`Self::load_ship_inner`] loads it.
+    fn resolve_sources(
         &self,
         db: &PrototypeDatabase<'_>,
         self_id_index: &HashMap<u64, usize>,
-        visual_paths: &[(String, String)],
-    ) -> Result<Vec<OwnedSubModel>, Report> {
-        let mut result = Vec::new();
+        model_dir: &str,
+        vehicle: Option<&Vehicle>,
+        options: &ShipExportOptions,
+    ) -> Result<ShipSources, Report> {
+        // Find all .visual files in the model directory.
+        let visual_paths = self.find_visual_paths(db, self_id_index, model_dir);
+        if visual_paths.is_empty() {
+            bail!("No .visual files found for '{}'.", model_dir);
+        }
 
-        for (sub_name, _) in visual_paths {
+        let mut warnings = Vec::new();
+        let mut hull_parts = Vec::new();
+        for (sub_name, _) in &visual_paths {
             let visual_suffix = format!("{sub_name}.visual");
             let vis_data = match resolve_visual_data(db, &visual_suffix, self_id_index) {
                 Ok(data) => data,
                 Err(e) => {
-                    eprintln!("Warning: skipping '{visual_suffix}': {e}");
+                    warnings.push(format!("skipping '{visual_suffix}': {e}"));
                     continue;
                 }
             };
@@ -771,39 +1112,91 @@ impl ShipAssets {
                             sub_name
                         )
                     })?;
-            let geom_full_path = db.reconstruct_path(*geom_path_idx, self_id_index);
+            let geometry_path = db.reconstruct_path(*geom_path_idx, self_id_index);
+
+            // The .splash file sits next to the geometry, with the same base name.
+            let splash_path = geometry_path
+                .strip_suffix(".geometry")
+                .map(|stem| format!("{stem}.splash"));
+
+            let model_suffix = format!("{sub_name}.model");
+            hull_parts.push(PartSource {
+                name: sub_name.clone(),
+                visual: vp,
+                geometry_path,
+                splash_path,
+                dyes: resolve_model_dyes(db, self_id_index, &model_suffix),
+                animation_paths: resolve_model_animation_paths(db, self_id_index, &model_suffix),
+            });
+        }
+
+        let mount_points: Vec<MountPoint> = vehicle
+            .and_then(|v| {
+                self.select_hull_mount_points(v, options.hull.as_deref(), &options.module_overrides)
+            })
+            .unwrap_or_default();
+
+        // Unique mount models, deduplicated by model path.
+        let mut mount_models: Vec<(String, PartSource)> = Vec::new();
+        let mut seen_models = HashSet::new();
+        for mi in &mount_points {
+            if mi.model_path().is_empty() || !seen_models.insert(mi.model_path()) {
+                continue;
+            }
+            match self.resolve_single_turret(db, self_id_index, mi.model_path()) {
+                Ok(part) => mount_models.push((mi.model_path().to_string(), part)),
+                Err(e) => {
+                    warnings.push(format!("could not load turret '{}': {e}", mi.model_path()));
+                }
+            }
+        }
+
+        // Material-based camouflage schemes (ship-specific + universal).
+        let ship_index = self.find_ship_index(model_dir);
+        let ship_idx = ship_index.as_deref();
+        let mut mat_camo_schemes = self.discover_mat_camo_schemes(model_dir, ship_idx);
+        mat_camo_schemes.extend(self.discover_universal_camo_schemes(ship_idx));
+
+        Ok(ShipSources {
+            hull_parts,
+            mount_points,
+            mount_models,
+            mat_camo_schemes,
+            warnings,
+        })
+    }
 
+    /// Read the geometry and splash files of resolved hull parts.
+    fn load_sub_models(&self, parts: Vec<PartSource>) -> Result<Vec<OwnedSubModel>, Report> {
+        let mut result = Vec::new();
+
+        for part in parts {
             let mut geom_bytes = Vec::new();
             self.vfs
-                .join(&geom_full_path)
+                .join(&part.geometry_path)
                 .context("VFS path error")?
                 .open_file()
-                .context_with(|| format!("Could not open geometry: {geom_full_path}"))?
+                .context_with(|| format!("Could not open geometry: {}", part.geometry_path))?
                 .read_to_end(&mut geom_bytes)?;
 
-            // Try loading the .splash file (same directory, same base name).
-            let splash_bytes = if geom_full_path.ends_with(".geometry") {
-                let splash_path = format!(
-                    "{}.splash",
-                    &geom_full_path[..geom_full_path.len() - ".geometry".len()]
-                );
+            let splash_bytes = part.splash_path.as_ref().and_then(|splash_path| {
                 let mut buf = Vec::new();
-                match self.vfs.join(&splash_path).and_then(|p| p.open_file()) {
+                match self.vfs.join(splash_path).and_then(|p| p.open_file()) {
                     Ok(mut f) => {
                         let _ = f.read_to_end(&mut buf);
                         Some(buf)
                     }
                     Err(_) => None,
                 }
-            } else {
-                None
-            };
+            });
 
             result.push(OwnedSubModel {
-                name: sub_name.clone(),
-                visual: vp,
-                geom_bytes,
+                name: part.name,
+                visual: part.visual,
+                geom_bytes: Arc::new(geom_bytes),
                 splash_bytes,
+                dyes: part.dyes,
+                animation_paths: part.animation_paths,
             });
         }
 
@@ -820,29 +1213,7 @@ impl ShipAssets {
             String,
         >,
     ) -> Option<Vec<MountPoint>> {
-        let upgrades = vehicle.hull_upgrades()?;
-        let mut sorted: Vec<_> = upgrades.iter().collect();
-        sorted.sort_by_key(|(k, _)| (*k).clone());
-
-        let selected = if let Some(sel) = hull_selection {
-            sorted
-                .iter()
-                .find(|(name, _)| *name == sel || name.to_lowercase().contains(&sel.to_lowercase()))
-                .or_else(|| {
-                    let prefix = format!("{sel}_");
-                    sorted.iter().find(|(_, config)| {
-                        config
-                            .component_name(keys::ComponentType::Hull)
-                            .map(|n| n.starts_with(&prefix))
-                            .unwrap_or(false)
-                    })
-                })
-                .copied()
-        } else {
-            sorted.first().copied()
-        };
-
-        selected.map(|(_, config)| {
+        select_hull_upgrade(vehicle, hull_selection).map(|config| {
             if module_overrides.is_empty() {
                 config.all_mount_points().cloned().collect()
             } else {
@@ -858,8 +1229,8 @@ impl ShipAssets {
     fn load_mounts(
         &self,
         db: &PrototypeDatabase<'_>,
-        self_id_index: &HashMap<u64, usize>,
         mount_points: &[MountPoint],
+        mount_models: Vec<(String, PartSource)>,
         hull_parts: &[OwnedSubModel],
     ) -> Result<LoadedMounts, Report> {
         // Collect hardpoint transforms from hull visuals.
@@ -876,8 +1247,7 @@ impl ShipAssets {
         }
 
         // Load unique turret models.
-        let (turret_models, turret_model_index) =
-            self.load_turret_models_deduped(db, self_id_index, mount_points)?;
+        let (turret_models, turret_model_index) = self.load_turret_models(mount_models);
 
         // Map hull HP names to turret model paths so we can find the parent
         // turret visual for compound hardpoints.
@@ -955,6 +1325,8 @@ impl ShipAssets {
                 mount_armor: mi.mount_armor().cloned(),
                 species: mi.species(),
                 barrel_pitch,
+                horiz_sector: mi.horiz_sector(),
+                dead_zones: mi.dead_zones().to_vec(),
             });
         }
 
@@ -965,54 +1337,46 @@ impl ShipAssets {
         })
     }
 
-    /// Load unique turret models, deduplicating by model path.
-    fn load_turret_models_deduped(
+    /// Load resolved turret models, indexed by model path.
+    fn load_turret_models(
         &self,
-        db: &PrototypeDatabase<'_>,
-        self_id_index: &HashMap<u64, usize>,
-        mount_points: &[MountPoint],
-    ) -> Result<(Vec<OwnedSubModel>, HashMap<String, usize>), Report> {
+        mount_models: Vec<(String, PartSource)>,
+    ) -> (Vec<OwnedSubModel>, HashMap<String, usize>) {
         let mut index_map: HashMap<String, usize> = HashMap::new();
         let mut models = Vec::new();
 
-        for mi in mount_points {
-            if mi.model_path().is_empty() || index_map.contains_key(mi.model_path()) {
-                continue;
-            }
-
-            match self.load_single_turret(db, self_id_index, mi.model_path()) {
+        for (model_path, part) in mount_models {
+            match self.load_turret(part) {
                 Ok(smd) => {
-                    let idx = models.len();
-                    index_map.insert(mi.model_path().to_string(), idx);
+                    index_map.insert(model_path, models.len());
                     models.push(smd);
                 }
                 Err(e) => {
-                    eprintln!("Warning: could not load turret '{}': {e}", mi.model_path());
+                    eprintln!("Warning: could not load turret '{model_path}': {e}");
                 }
             }
         }
 
-        Ok((models, index_map))
+        (models, index_map)
     }
 
-    /// Load turret models (non-deduplicating variant for texture listing).
-    fn load_turret_models(
+    /// Load a single turret model from its .model path.
+    fn load_single_turret(
         &self,
         db: &PrototypeDatabase<'_>,
         self_id_index: &HashMap<u64, usize>,
-        mount_points: &[MountPoint],
-    ) -> Result<Vec<OwnedSubModel>, Report> {
-        let (models, _) = self.load_turret_models_deduped(db, self_id_index, mount_points)?;
-        Ok(models)
+        model_path: &str,
+    ) -> Result<OwnedSubModel, Report> {
+        self.load_turret(self.resolve_single_turret(db, self_id_index, model_path)?)
     }
 
-    /// Load a single turret model from its .model path.
-    fn load_single_turret(
+    /// Resolve a single turret model's visual and files from its .model path.
+    fn resolve_single_turret(
         &self,
         db: &PrototypeDatabase<'_>,
         self_id_index: &HashMap<u64, usize>,
         model_path: &str,
-    ) -> Result<OwnedSubModel, Report> {
+    ) -> Result<PartSource, Report> {
         let visual_path = model_path.replace(".model", ".visual");
         let visual_suffix = visual_path
             .rsplit('/')
@@ -1028,15 +1392,7 @@ impl ShipAssets {
             .ok_or_else(|| {
                 rootcause::report!("Could not resolve geometry for turret '{}'", visual_suffix)
             })?;
-        let geom_full_path = db.reconstruct_path(*geom_path_idx, self_id_index);
-
-        let mut geom_bytes = Vec::new();
-        self.vfs
-            .join(&geom_full_path)
-            .context("VFS path error")?
-            .open_file()
-            .context_with(|| format!("Could not open turret geometry: {geom_full_path}"))?
-            .read_to_end(&mut geom_bytes)?;
+        let geometry_path = db.reconstruct_path(*geom_path_idx, self_id_index);
 
         let model_short_name = model_path
             .rsplit('/')
@@ -1045,11 +1401,33 @@ impl ShipAssets {
             .strip_suffix(".model")
             .unwrap_or(model_path);
 
-        Ok(OwnedSubModel {
+        let model_suffix = model_path.rsplit('/').next().unwrap_or(model_path);
+
+        Ok(PartSource {
             name: model_short_name.to_string(),
             visual: vp,
+            geometry_path,
+            splash_path: None,
+            dyes: resolve_model_dyes(db, self_id_index, model_suffix),
+            animation_paths: resolve_model_animation_paths(db, self_id_index, model_suffix),
+        })
+    }
+
+    /// Load a resolved turret model, reading its geometry through the shared
+    /// [`GeometryCache`].
+    fn load_turret(&self, part: PartSource) -> Result<OwnedSubModel, Report> {
+        let geom_bytes = self
+            .geometry_cache
+            .read(&self.vfs, &part.geometry_path)
+            .context_with(|| format!("Could not open turret geometry: {}", part.geometry_path))?;
+
+        Ok(OwnedSubModel {
+            name: part.name,
+            visual: part.visual,
             geom_bytes,
             splash_bytes: None,
+            dyes: part.dyes,
+            animation_paths: part.animation_paths,
         })
     }
 }
@@ -1075,6 +1453,84 @@ pub struct ShipModelContext {
     armor_map: Option<ArmorMap>,
     /// Hit location zones from GameParams, keyed by zone name (e.g. "Citadel").
     hit_locations: Option<HashMap<String, crate::game_params::types::HitLocation>>,
+    /// Draft of the selected hull upgrade.
+    draft: Option<Meters>,
+    texture_cache: Arc<TextureCache>,
+}
+
+/// Length of drawn firing arcs: a quarter of the hull length, so arcs stay
+/// readable next to the hull rather than reaching actual gun range.
+fn firing_arc_radius(hull: &visual::BoundingBox) -> f32 {
+    (hull.max[2] - hull.min[2]) / 4.0
+}
+
+/// Pick a hull upgrade by name, name substring or hull component prefix
+/// (e.g. "A"), falling back to the first upgrade by name.
+fn select_hull_upgrade<'v>(
+    vehicle: &'v Vehicle,
+    hull_selection: Option<&str>,
+) -> Option<&'v HullUpgradeConfig> {
+    let upgrades = vehicle.hull_upgrades()?;
+    let mut sorted: Vec<_> = upgrades.iter().collect();
+    sorted.sort_by_key(|(k, _)| (*k).clone());
+
+    let selected = if let Some(sel) = hull_selection {
+        sorted
+            .iter()
+            .find(|(name, _)| *name == sel || name.to_lowercase().contains(&sel.to_lowercase()))
+            .or_else(|| {
+                let prefix = format!("{sel}_");
+                sorted.iter().find(|(_, config)| {
+                    config
+                        .component_name(keys::ComponentType::Hull)
+                        .map(|n| n.starts_with(&prefix))
+                        .unwrap_or(false)
+                })
+            })
+            .copied()
+    } else {
+        sorted.first().copied()
+    };
+    selected.map(|(_, config)| config)
+}
+
+/// Tool version mixed into [`ShipAssets::source_crc`], so ships are exported
+/// again after the exporter changes.
+const SOURCE_CRC_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The export options as a stable string, for [`ShipAssets::source_crc`].
+fn options_key(options: &ShipExportOptions) -> String {
+    let mut overrides: Vec<String> = options
+        .module_overrides
+        .iter()
+        .map(|(ty, name)| format!("{ty}={name}"))
+        .collect();
+    overrides.sort();
+    format!(
+        "{}|{}|{:?}|{}|{}|{}|{}|{}|{}|{}",
+        options.lod,
+        options.all_lods,
+        options.hull,
+        options.textures,
+        options.damaged,
+        options.collision,
+        options.splash,
+        options.firing_arcs,
+        options.skinned,
+        overrides.join(",")
+    )
+}
+
+/// Feed an armor map to `crc` in a stable order.
+fn hash_armor_map(crc: &mut flate2::Crc, armor_map: &ArmorMap) {
+    let sorted: BTreeMap<_, _> = armor_map.iter().collect();
+    for (material, layers) in sorted {
+        crc.update(&material.to_le_bytes());
+        for (layer, thickness) in layers {
+            crc.update(&layer.to_le_bytes());
+            crc.update(&thickness.to_le_bytes());
+        }
+    }
 }
 
 /// Resolve a visual suffix to VisualPrototype record data.
@@ -1095,13 +1551,13 @@ fn resolve_visual_data<'a>(
         1 => {
             // Direct VisualPrototype
             Ok(db
-                .get_prototype_data(vis_location, visual::VISUAL_ITEM_SIZE)
+                .get_record(vis_location)
                 .context("Failed to get visual prototype data")?)
         }
         3 => {
             // ModelPrototype -- follow visualResourceId to the actual VisualPrototype
             let model_data = db
-                .get_prototype_data(vis_location, model::MODEL_ITEM_SIZE)
+                .get_record(vis_location)
                 .context("Failed to get model prototype data")?;
             let mp = model::parse_model(model_data)
                 .context_with(|| format!("Failed to parse ModelPrototype for {visual_suffix}"))?;
@@ -1134,7 +1590,7 @@ fn resolve_visual_data<'a>(
             }
 
             Ok(db
-                .get_prototype_data(vis_loc, visual::VISUAL_ITEM_SIZE)
+                .get_record(vis_loc)
                 .context("Failed to get visual prototype data via ModelPrototype")?)
         }
         other => {
@@ -1147,12 +1603,79 @@ fn resolve_visual_data<'a>(
     }
 }
 
+/// Parse the ModelPrototype whose path ends with `model_suffix`, if any.
+fn load_model_prototype(
+    db: &PrototypeDatabase<'_>,
+    self_id_index: &HashMap<u64, usize>,
+    model_suffix: &str,
+) -> Option<model::ModelPrototype> {
+    let (location, _) = db.resolve_path(model_suffix, self_id_index).ok()?;
+    if location.blob_index != 3 {
+        return None;
+    }
+    db.get_record(location)
+        .ok()
+        .and_then(|data| model::parse_model(data).ok())
+}
+
+/// Decode a collision model, warning and skipping it if its BVH is malformed
+/// rather than failing the whole export.
+fn decode_collision(cm: &geometry::ModelPrototype<'_>) -> Option<geometry::CollisionModel> {
+    cm.decode()
+        .inspect_err(|e| eprintln!("Warning: skipping collision model '{}': {e}", cm.name))
+        .ok()
+}
+
+/// Resolve the dye entries of a `.model` prototype. Returns nothing if the
+/// suffix doesn't resolve to a ModelPrototype.
+fn resolve_model_dyes(
+    db: &PrototypeDatabase<'_>,
+    self_id_index: &HashMap<u64, usize>,
+    model_suffix: &str,
+) -> Vec<ResolvedDye> {
+    load_model_prototype(db, self_id_index, model_suffix)
+        .map(|mp| {
+            mp.dyes
+                .iter()
+                .map(|dye| dye.resolve(db, self_id_index))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Full paths of the animation resources referenced by a ModelPrototype.
+fn resolve_model_animation_paths(
+    db: &PrototypeDatabase<'_>,
+    self_id_index: &HashMap<u64, usize>,
+    model_suffix: &str,
+) -> Vec<String> {
+    load_model_prototype(db, self_id_index, model_suffix)
+        .map(|mp| {
+            mp.animations
+                .iter()
+                .filter_map(|anim| self_id_index.get(&anim.visual_resource_id))
+                .map(|&idx| db.reconstruct_path(idx, self_id_index))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 impl ShipModelContext {
     /// Ship identity information.
     pub fn info(&self) -> &ShipInfo {
         &self.info
     }
 
+    /// Dye entries of the hull parts and turret models, as
+    /// `(sub-model name, dye)` pairs.
+    pub fn dyes(&self) -> Vec<(&str, &ResolvedDye)> {
+        self.hull_parts
+            .iter()
+            .chain(&self.turret_models)
+            .flat_map(|part| part.dyes.iter().map(move |dye| (part.name.as_str(), dye)))
+            .collect()
+    }
+
     /// Hull part names (sub-model names).
     pub fn hull_part_names(&self) -> Vec<&str> {
         self.hull_parts.iter().map(|p| p.name.as_str()).collect()
@@ -1266,6 +1789,80 @@ impl ShipModelContext {
 
         Ok(result)
     }
+
+    /// Per-zone splash box volumes, armor area by thickness, citadel height
+    /// and HP pools. See [`ZoneReport`].
+    pub fn zone_report(&self) -> Result<ZoneReport, Report> {
+        let splash_boxes = match self.hull_splash_bytes() {
+            Some(bytes) => geometry::parse_splash_file(bytes)?,
+            None => Vec::new(),
+        };
+        let no_hit_locations = HashMap::new();
+        Ok(zone_report::build_zone_report(
+            &self.info.model_dir,
+            self.hit_locations.as_ref().unwrap_or(&no_hit_locations),
+            &splash_boxes,
+            &self.interactive_armor_meshes()?,
+            self.draft,
+        ))
+    }
+
+    /// Combined hull and mount armor as an [`ArmorScene`] for ray casts.
+    pub fn armor_scene(&self) -> Result<ArmorScene, Report> {
+        Ok(ArmorScene::new(&self.interactive_armor_meshes()?))
+    }
+
+    /// Firing arcs of every placed mount. See [`MountArc`].
+    pub fn mount_arcs(&self) -> Vec<MountArc> {
+        self.mounts
+            .iter()
+            .filter_map(|mount| {
+                Some(MountArc::new(
+                    mount.hp_name.clone(),
+                    mount.species,
+                    mount.armor_transform.as_ref()?,
+                    mount.horiz_sector,
+                    mount.dead_zones.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Model-space bounding box of all hull parts.
+    pub fn hull_bounds(&self) -> Option<visual::BoundingBox> {
+        self.hull_parts
+            .iter()
+            .map(|part| &part.visual.bounding_box)
+            .fold(None, |acc, bb| {
+                Some(match acc {
+                    None => bb.clone(),
+                    Some(acc) => visual::BoundingBox {
+                        min: std::array::from_fn(|i| acc.min[i].min(bb.min[i])),
+                        max: std::array::from_fn(|i| acc.max[i].max(bb.max[i])),
+                    },
+                })
+            })
+    }
+
+    /// Top-down SVG diagram of the mounts' firing arcs over the hull, bow
+    /// up. `None` if the ship has no hull parts.
+    pub fn firing_arcs_svg(&self) -> Option<String> {
+        let bounds = self.hull_bounds()?;
+        Some(firing_arcs::firing_arcs_svg(
+            &self.mount_arcs(),
+            &bounds,
+            firing_arc_radius(&bounds),
+        ))
+    }
+
+    /// Firing arc fans for the GLB.
+    fn firing_arc_models(&self) -> Vec<gltf_export::ArmorSubModel> {
+        let Some(bounds) = self.hull_bounds() else {
+            return Vec::new();
+        };
+        firing_arcs::arc_fan_meshes(&self.mount_arcs(), firing_arc_radius(&bounds))
+    }
+
     /// Collect hull visual meshes for interactive display.
     ///
     /// Returns one [`InteractiveHullMesh`](gltf_export::InteractiveHullMesh) per
@@ -1359,8 +1956,135 @@ impl ShipModelContext {
         Ok(result)
     }
 
+    /// Splash boxes of the hull paired with their hit locations.
+    pub fn splash_box_models(&self) -> Result<Vec<SplashBoxModel>, Report> {
+        let Some(bytes) = self.hull_splash_bytes() else {
+            return Ok(Vec::new());
+        };
+        let boxes = geometry::parse_splash_file(bytes)?;
+        Ok(SplashBoxModel::from_splash_boxes(
+            &boxes,
+            self.hit_locations.as_ref(),
+        ))
+    }
+
     /// Export the loaded ship model to GLB format.
     pub fn export_glb(&self, writer: &mut impl Write) -> Result<(), Report> {
+        let splash_boxes = if self.options.splash {
+            self.splash_box_models()?
+        } else {
+            Vec::new()
+        };
+        let firing_arc_models = if self.options.firing_arcs {
+            self.firing_arc_models()
+        } else {
+            Vec::new()
+        };
+        self.with_export_parts(|parts| {
+            gltf_export::export_ship_glb(
+                parts.sub_models,
+                parts.armor_models,
+                parts.collision_models,
+                &firing_arc_models,
+                &splash_boxes,
+                parts.db,
+                self.options.lod,
+                parts.texture_set,
+                self.options.damaged,
+                self.options.all_lods,
+                writer,
+            )
+            .context("Failed to export ship GLB")?;
+            Ok(())
+        })
+    }
+
+    /// Export the loaded ship model as a `.gltf` at `path` with a `.bin`
+    /// next to it and textures as PNG files in `textures_dir`.
+    ///
+    /// Textures are named by content, so exports sharing `textures_dir`
+    /// store each texture once. See [`gltf_split::write_gltf_split`].
+    pub fn export_gltf(&self, path: &Path, textures_dir: &Path) -> Result<(), Report> {
+        let mut glb = Vec::new();
+        self.export_glb(&mut glb)?;
+        gltf_split::write_gltf_split(&glb, path, textures_dir)
+            .context("Failed to write ship glTF")?;
+        Ok(())
+    }
+
+    /// Export the loaded ship model to OBJ, STL or PLY at `path`.
+    ///
+    /// Only the `lod` level is exported and skins are ignored; OBJ writes its
+    /// material library and textures next to `path`.
+    pub fn export_mesh(&self, format: MeshFormat, path: &Path) -> Result<(), Report> {
+        self.with_export_parts(|parts| {
+            let scene = MeshScene::from_ship(
+                parts.sub_models,
+                parts.armor_models,
+                parts.collision_models,
+                parts.db,
+                self.options.lod,
+                parts.texture_set,
+                self.options.damaged,
+            )
+            .context("Failed to collect ship meshes")?;
+            mesh_formats::write_mesh_file(&scene, format, path)
+                .context("Failed to write ship mesh file")?;
+            Ok(())
+        })
+    }
+
+    /// Render a side profile or top-down plan of the hull and mounts,
+    /// `width_px` pixels wide. See [`profile::render_profile`].
+    ///
+    /// Textures are not used; load the ship with `textures: false` to skip
+    /// decoding them.
+    pub fn render_profile(
+        &self,
+        view: ProfileView,
+        style: ProfileStyle,
+        width_px: u32,
+    ) -> Result<ProfileImage, Report> {
+        self.with_export_parts(|parts| {
+            let scene = MeshScene::from_ship(
+                parts.sub_models,
+                parts.armor_models,
+                &[],
+                parts.db,
+                self.options.lod,
+                parts.texture_set,
+                self.options.damaged,
+            )
+            .context("Failed to collect ship meshes")?;
+            Ok(profile::render_profile(&scene, view, style, width_px))
+        })
+    }
+
+    /// Export the loaded ship model as a USD layer at `path`, with camouflage
+    /// schemes as a variant set. Textures are written next to `path`.
+    pub fn export_usda(&self, path: &Path) -> Result<(), Report> {
+        self.with_export_parts(|parts| {
+            usd_export::export_ship_usda(
+                parts.sub_models,
+                parts.armor_models,
+                parts.collision_models,
+                parts.db,
+                self.options.lod,
+                parts.texture_set,
+                self.options.damaged,
+                path,
+            )
+            .context("Failed to export ship USD")?;
+            Ok(())
+        })
+    }
+
+    /// Decode geometries, textures, armor and collision meshes, then hand
+    /// them to `f` and return its result.
+    fn with_export_parts<T>(
+        &self,
+        f: impl FnOnce(ExportParts<'_>) -> Result<T, Report>,
+    ) -> Result<T, Report> {
         let db = assets_bin::parse_assets_bin(&self.assets_bin_bytes)
             .context("Failed to re-parse assets.bin")?;
 
@@ -1379,11 +2103,32 @@ impl ShipModelContext {
             })
             .collect();
 
+        // Animation clips are only exported alongside skeletons.
+        let load_part_animations = |parts: &[OwnedSubModel]| -> Vec<Vec<Animation>> {
+            parts
+                .iter()
+                .map(|part| {
+                    if self.options.skinned {
+                        gltf_export::load_animations(&self.vfs, &part.animation_paths)
+                    } else {
+                        Vec::new()
+                    }
+                })
+                .collect()
+        };
+        let hull_animations = load_part_animations(&self.hull_parts);
+        let turret_animations = load_part_animations(&self.turret_models);
+
         // Build SubModel list.
         let mut sub_models: Vec<SubModel<'_>> = Vec::new();
 
         // Hull sub-models.
-        for (data, geom) in self.hull_parts.iter().zip(hull_geoms.iter()) {
+        for ((data, geom), animations) in self
+            .hull_parts
+            .iter()
+            .zip(hull_geoms.iter())
+            .zip(hull_animations.iter())
+        {
             sub_models.push(SubModel {
                 name: data.name.clone(),
                 visual: &data.visual,
@@ -1391,6 +2136,9 @@ impl ShipModelContext {
                 transform: None,
                 group: "Hull",
                 barrel_pitch: None,
+                skinned: self.options.skinned,
+                animations,
+                markers: &[],
             });
         }
 
@@ -1406,6 +2154,9 @@ impl ShipModelContext {
                 transform: mount.transform,
                 group: mount_group(mount.species),
                 barrel_pitch: mount.barrel_pitch.clone(),
+                skinned: self.options.skinned,
+                animations: &turret_animations[mount.turret_model_index],
+                markers: &[],
             });
         }
 
@@ -1415,7 +2166,7 @@ impl ShipModelContext {
             for sub in &sub_models {
                 all_mfm_infos.extend(collect_mfm_info(sub.visual, &db));
             }
-            let mut tex_set = build_texture_set(&all_mfm_infos, &self.vfs);
+            let mut tex_set = build_texture_set(&all_mfm_infos, &self.vfs, &self.texture_cache);
             let per_ship_count = tex_set.camo_schemes.len();
 
             // Merge material-based camo textures (mat_Steel, mat_Yamato_KoF, etc.).
@@ -1438,7 +2189,7 @@ impl ShipModelContext {
                                 if let Some(dds) = texture::load_dds_from_vfs(&self.vfs, path) {
                                     match texture::bake_tiled_camo_png(&dds, colors) {
                                         Ok(png) => {
-                                            png_bytes = Some(png);
+                                            png_bytes = Some(Arc::new(png));
                                             break;
                                         }
                                         Err(e) => {
@@ -1454,18 +2205,17 @@ impl ShipModelContext {
                     } else {
                         // Non-tiled mat_camo: load DDS and convert to PNG.
                         for path in &scheme.texture_paths {
-                            if let Some(dds) = texture::load_dds_from_vfs(&self.vfs, path) {
-                                match texture::dds_to_png(&dds) {
-                                    Ok(png) => {
-                                        png_bytes = Some(png);
-                                        break;
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "  Warning: failed to decode mat_camo texture {}: {e}",
-                                            path
-                                        );
-                                    }
+                            match self.texture_cache.png(&self.vfs, path, None) {
+                                Ok(Some(png)) => {
+                                    png_bytes = Some(png);
+                                    break;
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    eprintln!(
+                                        "  Warning: failed to decode mat_camo texture {}: {e}",
+                                        path
+                                    );
                                 }
                             }
                         }
@@ -1507,6 +2257,9 @@ impl ShipModelContext {
                 );
             }
 
+            let dyes: Vec<&ResolvedDye> = self.dyes().into_iter().map(|(_, dye)| dye).collect();
+            tex_set.dye_variants = build_dye_variants(&dyes, &self.vfs, &self.texture_cache);
+
             tex_set
         } else {
             TextureSet::empty()
@@ -1539,17 +2292,172 @@ impl ShipModelContext {
             }
         }
 
+        // Collision hulls: decoded from each geometry's CM_* models when requested.
+        let mut collision_meshes: Vec<gltf_export::ArmorSubModel> = Vec::new();
+        if self.options.collision {
+            for geom in &hull_geoms {
+                for model in geom.collision_models.iter().filter_map(decode_collision) {
+                    collision_meshes.push(gltf_export::ArmorSubModel::from_collision_model(&model));
+                }
+            }
+            for mount in &self.mounts {
+                let turret_geom = &turret_geoms[mount.turret_model_index];
+                for model in turret_geom
+                    .collision_models
+                    .iter()
+                    .filter_map(decode_collision)
+                {
+                    let mut mesh = gltf_export::ArmorSubModel::from_collision_model(&model);
+                    mesh.transform = mount.armor_transform;
+                    mesh.name = format!("{} [{}]", mesh.name, mount.hp_name);
+                    collision_meshes.push(mesh);
+                }
+            }
+        }
+
+        f(ExportParts {
+            sub_models: &sub_models,
+            armor_models: &armor_meshes,
+            collision_models: &collision_meshes,
+            db: &db,
+            texture_set: &texture_set,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AircraftModelContext — fully-loaded plane, ready for export
+// ---------------------------------------------------------------------------
+
+/// A fully-loaded plane model with its payload.
+///
+/// Created via [`ShipAssets::load_aircraft()`].
+pub struct AircraftModelContext {
+    vfs: VfsPath,
+    assets_bin_bytes: Vec<u8>,
+    info: AircraftInfo,
+    plane: OwnedSubModel,
+    /// Bomb, torpedo or rocket model, if the payload has one.
+    payload_model: Option<OwnedSubModel>,
+    propellers: Vec<AircraftNode>,
+    hardpoints: Vec<AircraftNode>,
+    options: ShipExportOptions,
+    texture_cache: Arc<TextureCache>,
+}
+
+impl AircraftModelContext {
+    pub fn info(&self) -> &AircraftInfo {
+        &self.info
+    }
+
+    /// Propeller nodes of the plane visual.
+    pub fn propeller_nodes(&self) -> &[AircraftNode] {
+        &self.propellers
+    }
+
+    /// Payload attachment points of the plane visual.
+    pub fn payload_hardpoints(&self) -> &[AircraftNode] {
+        &self.hardpoints
+    }
+
+    /// Whether the payload model was loaded and is placed at the hardpoints.
+    pub fn has_payload_model(&self) -> bool {
+        self.payload_model.is_some()
+    }
+
+    /// Export the plane as GLB, with one payload model at every payload
+    /// hardpoint grouped by payload kind.
+    pub fn export_glb(&self, writer: &mut impl Write) -> Result<(), Report> {
+        let db = assets_bin::parse_assets_bin(&self.assets_bin_bytes)
+            .context("Failed to re-parse assets.bin")?;
+        let plane_geom = geometry::parse_geometry(&self.plane.geom_bytes)
+            .context("Failed to parse plane geometry")?;
+        let payload_geom = match &self.payload_model {
+            Some(model) => Some(
+                geometry::parse_geometry(&model.geom_bytes)
+                    .context("Failed to parse payload geometry")?,
+            ),
+            None => None,
+        };
+        let plane_animations = if self.options.skinned {
+            gltf_export::load_animations(&self.vfs, &self.plane.animation_paths)
+        } else {
+            Vec::new()
+        };
+
+        let propeller_markers: Vec<NodeMarker> = self
+            .propellers
+            .iter()
+            .map(|node| NodeMarker {
+                name: node.name.clone(),
+                transform: node.transform,
+                role: "propeller",
+            })
+            .collect();
+
+        let mut sub_models = vec![SubModel {
+            name: self.plane.name.clone(),
+            visual: &self.plane.visual,
+            geometry: &plane_geom,
+            transform: None,
+            group: "Aircraft",
+            barrel_pitch: None,
+            skinned: self.options.skinned,
+            animations: &plane_animations,
+            markers: &propeller_markers,
+        }];
+        if let (Some(model), Some(geom), Some(payload)) =
+            (&self.payload_model, &payload_geom, &self.info.payload)
+        {
+            for hp in &self.hardpoints {
+                sub_models.push(SubModel {
+                    name: format!("{} ({})", hp.name, model.name),
+                    visual: &model.visual,
+                    geometry: geom,
+                    transform: Some(hp.transform),
+                    group: payload.kind.group(),
+                    barrel_pitch: None,
+                    skinned: false,
+                    animations: &[],
+                    markers: &[],
+                });
+            }
+        }
+
+        let texture_set = if self.options.textures {
+            let mut mfm_infos = Vec::new();
+            for sub in &sub_models {
+                mfm_infos.extend(collect_mfm_info(sub.visual, &db));
+            }
+            build_texture_set(&mfm_infos, &self.vfs, &self.texture_cache)
+        } else {
+            TextureSet::empty()
+        };
+
         gltf_export::export_ship_glb(
             &sub_models,
-            &armor_meshes,
+            &[],
+            &[],
+            &[],
+            &[],
             &db,
             self.options.lod,
             &texture_set,
             self.options.damaged,
+            self.options.all_lods,
             writer,
         )
-        .context("Failed to export ship GLB")?;
+        .context("Failed to export aircraft GLB")?;
+        Ok(())
+    }
 
+    /// Export the plane as `.gltf` with textures in `textures_dir`. See
+    /// [`ShipModelContext::export_gltf`].
+    pub fn export_gltf(&self, path: &Path, textures_dir: &Path) -> Result<(), Report> {
+        let mut glb = Vec::new();
+        self.export_glb(&mut glb)?;
+        gltf_split::write_gltf_split(&glb, path, textures_dir)
+            .context("Failed to write aircraft glTF")?;
         Ok(())
     }
 }
@@ -1558,13 +2466,97 @@ impl ShipModelContext {
 // Internal types
 // ---------------------------------------------------------------------------
 
+/// Decoded inputs shared by the GLB and mesh-format ship exporters.
+struct ExportParts<'a> {
+    sub_models: &'a [SubModel<'a>],
+    armor_models: &'a [gltf_export::ArmorSubModel],
+    collision_models: &'a [gltf_export::ArmorSubModel],
+    db: &'a PrototypeDatabase<'a>,
+    texture_set: &'a TextureSet,
+}
+
 /// Owns visual + geometry bytes for one sub-model (no lifetime parameters).
 struct OwnedSubModel {
     name: String,
     visual: VisualPrototype,
-    geom_bytes: Vec<u8>,
+    /// Shared with other ships for mount models, see [`GeometryCache`].
+    geom_bytes: Arc<Vec<u8>>,
     /// Raw `.splash` file bytes (only present for base hull models).
     splash_bytes: Option<Vec<u8>>,
+    /// Dye entries from the sub-model's ModelPrototype.
+    dyes: Vec<ResolvedDye>,
+    /// Animation resource paths from the sub-model's ModelPrototype.
+    animation_paths: Vec<String>,
+}
+
+/// A sub-model's parsed visual and the files loading it reads.
+struct PartSource {
+    name: String,
+    visual: VisualPrototype,
+    geometry_path: String,
+    /// `.splash` file next to the geometry (only for base hull models).
+    splash_path: Option<String>,
+    dyes: Vec<ResolvedDye>,
+    animation_paths: Vec<String>,
+}
+
+/// Result of [`ShipAssets::resolve_sources`].
+struct ShipSources {
+    hull_parts: Vec<PartSource>,
+    mount_points: Vec<MountPoint>,
+    /// Unique mount models with their model path, in mount order.
+    mount_models: Vec<(String, PartSource)>,
+    mat_camo_schemes: Vec<MatCamoScheme>,
+    /// Parts that could not be resolved and are left out.
+    warnings: Vec<String>,
+}
+
+impl ShipSources {
+    /// Hull parts, then mount models.
+    fn parts(&self) -> impl Iterator<Item = &PartSource> {
+        self.hull_parts
+            .iter()
+            .chain(self.mount_models.iter().map(|(_, part)| part))
+    }
+}
+
+/// Bytes of [`GeometryCache`] held in memory: 512 MiB.
+const GEOMETRY_CACHE_LIMIT: usize = 512 << 20;
+
+/// Mount geometry files shared across ship exports, keyed by VFS path.
+///
+/// Most mount models (AA guns, directors, secondaries) are shared between
+/// ships, so a batch export reads each file once. Files are kept until the
+/// cache holds [`GEOMETRY_CACHE_LIMIT`] bytes; later files are read but not
+/// kept.
+#[derive(Debug, Default)]
+struct GeometryCache {
+    files: std::sync::Mutex<HashMap<String, Arc<Vec<u8>>>>,
+}
+
+impl GeometryCache {
+    /// Bytes of the file at `path`, read from the VFS on first use.
+    fn read(&self, vfs: &VfsPath, path: &str) -> Result<Arc<Vec<u8>>, Report> {
+        if let Some(bytes) = self.files.lock().unwrap().get(path) {
+            return Ok(bytes.clone());
+        }
+
+        // Read without holding the lock; concurrent misses on the same file
+        // may both read it, which is harmless.
+        let mut bytes = Vec::new();
+        vfs.join(path)
+            .context("VFS path error")?
+            .open_file()?
+            .read_to_end(&mut bytes)?;
+        let bytes = Arc::new(bytes);
+
+        let mut files = self.files.lock().unwrap();
+        let held: usize = files.values().map(|b| b.len()).sum();
+        if held + bytes.len() <= GEOMETRY_CACHE_LIMIT {
+            files.insert(path.to_string(), bytes.clone());
+        }
+        Ok(bytes)
+    }
 }
 
 /// Result of [`ShipAssets::load_mounts`].
@@ -1589,6 +2581,10 @@ struct ResolvedMount {
     species: Option<crate::game_params::types::MountSpecies>,
     /// Per-vertex barrel pitch configuration (if pitchDeadZones applies).
     barrel_pitch: Option<super::gltf_export::BarrelPitch>,
+    /// Traverse limits from GameParams `horizSector`.
+    horiz_sector: Option<[f32; 2]>,
+    /// Firing dead zones from GameParams `deadZone`.
+    dead_zones: Vec<[f32; 2]>,
 }
 
 /// Pre-resolved material-based camouflage scheme (owned data, no lifetimes).
@@ -1644,7 +2640,10 @@ pub fn collect_mfm_info(visual: &VisualPrototype, db: &PrototypeDatabase<'_>) ->
 }
 
 /// Build a `TextureSet` from MFM infos: base albedo + all camo schemes.
-pub fn build_texture_set(mfm_infos: &[MfmInfo], vfs: &VfsPath) -> TextureSet {
+///
+/// Textures are decoded through `cache`, so textures shared with earlier
+/// ships are not decoded again.
+pub fn build_texture_set(mfm_infos: &[MfmInfo], vfs: &VfsPath, cache: &TextureCache) -> TextureSet {
     let mut base = HashMap::new();
 
     let mut seen_stems = HashSet::new();
@@ -1657,11 +2656,12 @@ pub fn build_texture_set(mfm_infos: &[MfmInfo], vfs: &VfsPath) -> TextureSet {
 
     // Load base albedo textures.
     for info in &unique_infos {
-        if let Some(dds_bytes) = texture::load_base_albedo_bytes(vfs, &info.full_path) {
-            match texture::dds_to_png(&dds_bytes) {
-                Ok(png_bytes) => {
+        if let Some(dds_path) = texture::find_base_albedo_path(vfs, &info.full_path) {
+            match cache.png(vfs, &dds_path, None) {
+                Ok(Some(png_bytes)) => {
                     base.insert(info.stem.clone(), png_bytes);
                 }
+                Ok(None) => {}
                 Err(e) => {
                     eprintln!(
                         "  Warning: failed to decode base texture for {}: {e}",
@@ -1680,13 +2680,14 @@ pub fn build_texture_set(mfm_infos: &[MfmInfo], vfs: &VfsPath) -> TextureSet {
     for scheme in &schemes {
         let mut scheme_textures = HashMap::new();
         for info in &unique_infos {
-            if let Some((_base_name, dds_bytes)) =
-                texture::load_texture_bytes(vfs, &info.stem, scheme)
+            if let Some((_base_name, dds_path)) =
+                texture::find_texture_path(vfs, &info.stem, scheme)
             {
-                match texture::dds_to_png(&dds_bytes) {
-                    Ok(png_bytes) => {
+                match cache.png(vfs, &dds_path, None) {
+                    Ok(Some(png_bytes)) => {
                         scheme_textures.insert(info.stem.clone(), png_bytes);
                     }
+                    Ok(None) => {}
                     Err(e) => {
                         eprintln!(
                             "  Warning: failed to decode camo texture {}_{}: {e}",
@@ -1705,7 +2706,55 @@ pub fn build_texture_set(mfm_infos: &[MfmInfo], vfs: &VfsPath) -> TextureSet {
         base,
         camo_schemes,
         tiled_uv_transforms: HashMap::new(),
+        dye_variants: Vec::new(),
+    }
+}
+
+/// Build one material variant per dye tint name.
+///
+/// Tints with the same name across dyes (e.g. every dye's "Gold" tint) are
+/// merged into a single variant. Each tint's albedo comes from its `.mfm`
+/// material and replaces the dye's target (`matter`) material.
+pub fn build_dye_variants(
+    dyes: &[&ResolvedDye],
+    vfs: &VfsPath,
+    cache: &TextureCache,
+) -> Vec<DyeVariant> {
+    let mut by_tint: Vec<DyeVariant> = Vec::new();
+
+    for dye in dyes {
+        for tint in &dye.tints {
+            let Some(mfm_path) = &tint.material_path else {
+                continue;
+            };
+            let Some(dds_path) = texture::find_base_albedo_path(vfs, mfm_path) else {
+                continue;
+            };
+            let png_bytes = match cache.png(vfs, &dds_path, None) {
+                Ok(Some(png)) => png,
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("  Warning: failed to decode dye texture {mfm_path}: {e}");
+                    continue;
+                }
+            };
+
+            let name = format!("{} (dye)", tint.name);
+            let variant = match by_tint.iter_mut().position(|v| v.name == name) {
+                Some(i) => &mut by_tint[i],
+                None => {
+                    by_tint.push(DyeVariant {
+                        name,
+                        materials: HashMap::new(),
+                    });
+                    by_tint.last_mut().expect("just pushed")
+                }
+            };
+            variant.materials.insert(dye.matter.clone(), png_bytes);
+        }
     }
+
+    by_tint
 }
 
 /// Resolve a compound hardpoint (e.g. `HP_AGM_3_HP_AGA_1`) by finding the
@@ -1886,3 +2935,462 @@ pub fn export_ship_glb(
     ctx.export_glb(writer)?;
     Ok(info)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_params::types::{
+        Aircraft, Param, ParamData, PlaneCategory, Projectile, Species,
+    };
+    use crate::models::assets_bin::PathEntry;
+    use crate::models::assets_bin_writer::PrototypeDatabaseWriter;
+    use crate::models::prototype_layout;
+    use crate::recognized::Recognized;
+
+    const VISUAL_BLOB: usize = 1;
+
+    /// Assets for one ship, `JSB001_Test`, whose hull visual points at
+    /// `content/JSB001_Test/Hull.geometry` containing `geometry`.
+    fn test_assets(geometry: &[u8]) -> ShipAssets {
+        let prototypes: Vec<_> = prototype_layout::LAYOUTS
+            .iter()
+            .map(|l| (l.magic, 0))
+            .collect();
+        let mut writer = PrototypeDatabaseWriter::new(&prototypes);
+        for (self_id, parent_id, name) in [
+            (1, 0, "content"),
+            (2, 1, "JSB001_Test"),
+            (3, 2, "Hull.visual"),
+            (4, 2, "Hull.geometry"),
+        ] {
+            writer.set_path(PathEntry {
+                self_id,
+                parent_id,
+                name: name.to_string(),
+            });
+        }
+        // A VisualPrototype with no nodes, render sets or LODs, only its
+        // merged geometry path.
+        let mut visual = vec![0u8; visual::VISUAL_ITEM_SIZE];
+        visual[0x30..0x38].copy_from_slice(&4u64.to_le_bytes());
+        writer.insert_prototype(3, VISUAL_BLOB, &visual).unwrap();
+
+        let vfs = VfsPath::new(vfs::MemoryFS::new());
+        vfs.join("content/JSB001_Test")
+            .unwrap()
+            .create_dir_all()
+            .unwrap();
+        vfs.join("content/JSB001_Test/Hull.geometry")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(geometry)
+            .unwrap();
+
+        let vehicle = Vehicle::builder()
+            .level(10)
+            .group("upgradeable".to_string())
+            .upgrades(Vec::new())
+            .permoflages(Vec::new())
+            .model_path("content/JSB001_Test/JSB001_Test.model".to_string())
+            .build();
+        let param = Param::builder()
+            .id(1u64.into())
+            .index("PJSB001".to_string())
+            .name("PJSB001_Test".to_string())
+            .species(Recognized::Known(Species::Battleship))
+            .nation("Japan".to_string())
+            .data(ParamData::Vehicle(vehicle))
+            .build();
+        let metadata = GameMetadataProvider::from_params_no_specs(vec![param]).unwrap();
+
+        ShipAssets {
+            assets_bin_bytes: writer.to_bytes(),
+            vfs,
+            metadata: Arc::new(metadata),
+            camo_db: None,
+            texture_cache: Arc::new(TextureCache::new()),
+            geometry_cache: GeometryCache::default(),
+        }
+    }
+
+    #[test]
+    fn lists_ships_and_hashes_their_sources() {
+        let assets = test_assets(b"hull v1");
+        let ships: Vec<_> = assets.ships().collect();
+        assert_eq!(ships.len(), 1);
+        let ship = &ships[0];
+        assert_eq!(ship.info.model_dir, "JSB001_Test");
+        assert_eq!(ship.info.param_index, "PJSB001");
+        assert_eq!(ship.output_stem(), "Japan/Battleship/10_PJSB001_Test");
+
+        let options = ShipExportOptions::default();
+        let crc = assets.source_crc(ship, &options).unwrap();
+        assert_eq!(assets.source_crc(ship, &options).unwrap(), crc);
+        assert_ne!(
+            test_assets(b"hull v2").source_crc(ship, &options).unwrap(),
+            crc
+        );
+        let damaged = ShipExportOptions {
+            damaged: true,
+            ..Default::default()
+        };
+        assert_ne!(assets.source_crc(ship, &damaged).unwrap(), crc);
+    }
+
+    #[test]
+    fn dye_tints_become_material_variants() {
+        use crate::models::geometry_writer::GeometryWriter;
+        use crate::models::model::{DyeEntry, DyeTint};
+        use crate::models::visual::{BoundingBox, Lod, RenderSet, VisualNodes};
+
+        let prototypes: Vec<_> = prototype_layout::LAYOUTS
+            .iter()
+            .map(|l| (l.magic, 0))
+            .collect();
+        let mut writer = PrototypeDatabaseWriter::new(&prototypes);
+        for (id, name) in [(1, "Hull"), (2, "Deck"), (3, "Deck_old"), (4, "Red")] {
+            writer.add_string(id, name);
+        }
+        for (self_id, parent_id, name) in [
+            (1, 0, "content"),
+            (2, 1, "JSB001_Test"),
+            (3, 2, "Deck_Red.mfm"),
+        ] {
+            writer.set_path(PathEntry {
+                self_id,
+                parent_id,
+                name: name.to_string(),
+            });
+        }
+        let db_bytes = writer.to_bytes();
+        let db = assets_bin::parse_assets_bin(&db_bytes).unwrap();
+
+        // A missing tint material is kept unresolved rather than dropped.
+        let dye = DyeEntry {
+            matter_id: 2,
+            replaces_id: 3,
+            tint_name_ids: vec![4, 5],
+            tint_material_ids: vec![3, 99],
+        }
+        .resolve(&db, &db.build_self_id_index());
+        assert_eq!(
+            dye,
+            ResolvedDye {
+                matter: "Deck".to_string(),
+                replaces: "Deck_old".to_string(),
+                tints: vec![
+                    DyeTint {
+                        name: "Red".to_string(),
+                        material_path: Some("content/JSB001_Test/Deck_Red.mfm".to_string()),
+                    },
+                    DyeTint {
+                        name: "0x00000005".to_string(),
+                        material_path: None,
+                    },
+                ],
+            }
+        );
+
+        let dds = image_dds::Surface {
+            width: 4,
+            height: 4,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: image_dds::ImageFormat::Rgba8Unorm,
+            data: [200u8, 40, 40, 255].repeat(16),
+        }
+        .to_dds()
+        .unwrap();
+        let mut dds_bytes = Vec::new();
+        dds.write(&mut dds_bytes).unwrap();
+        let vfs = VfsPath::new(vfs::MemoryFS::new());
+        vfs.join("content/JSB001_Test")
+            .unwrap()
+            .create_dir_all()
+            .unwrap();
+        vfs.join("content/JSB001_Test/Deck_Red_a.dds")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&dds_bytes)
+            .unwrap();
+
+        let variants = build_dye_variants(&[&dye], &vfs, &TextureCache::new());
+        assert_eq!(variants.len(), 1);
+        assert_eq!(variants[0].name, "Red (dye)");
+        assert_eq!(variants[0].materials.keys().collect::<Vec<_>>(), ["Deck"]);
+
+        // One triangle whose render set uses the dyed material.
+        let mut vertices = Vec::new();
+        for pos in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            for c in pos {
+                vertices.extend_from_slice(&c.to_le_bytes());
+            }
+            vertices.extend_from_slice(&[127, 127, 255, 0, 0, 0, 0, 0]);
+        }
+        let mut geometry_writer = GeometryWriter::new();
+        geometry_writer
+            .add_mesh(0x10, 0x20, "set3/xyznuv", &vertices, &[0, 1, 2])
+            .unwrap();
+        let geometry_bytes = geometry_writer.to_bytes().unwrap();
+        let geometry = geometry::parse_geometry(&geometry_bytes).unwrap();
+        let visual = VisualPrototype {
+            nodes: VisualNodes {
+                name_map_name_ids: Vec::new(),
+                name_map_node_ids: Vec::new(),
+                name_ids: Vec::new(),
+                matrices: Vec::new(),
+                parent_ids: Vec::new(),
+            },
+            merged_geometry_path_id: 0,
+            underwater_model: false,
+            abovewater_model: true,
+            bounding_box: BoundingBox {
+                min: [0.0; 3],
+                max: [1.0, 1.0, 0.0],
+            },
+            render_sets: vec![RenderSet {
+                name_id: 1,
+                material_name_id: 2,
+                vertices_mapping_id: 0x10,
+                indices_mapping_id: 0x20,
+                material_mfm_path_id: 0,
+                skinned: false,
+                node_name_ids: Vec::new(),
+            }],
+            lods: vec![Lod {
+                extent: 10.0,
+                casts_shadow: false,
+                render_set_names: vec![1],
+            }],
+        };
+
+        let mut texture_set = TextureSet::empty();
+        texture_set.dye_variants = variants;
+        let mut glb = Vec::new();
+        gltf_export::export_glb(
+            &visual,
+            &geometry,
+            &db,
+            0,
+            &texture_set,
+            false,
+            false,
+            false,
+            &mut glb,
+        )
+        .unwrap();
+        let glb = gltf::binary::Glb::from_slice(&glb).unwrap();
+        let root = gltf_json::Root::from_slice(&glb.json).unwrap();
+
+        let variants = &root
+            .extensions
+            .as_ref()
+            .unwrap()
+            .khr_materials_variants
+            .as_ref()
+            .unwrap()
+            .variants;
+        assert_eq!(variants.len(), 1);
+        assert_eq!(variants[0].name, "Red (dye)");
+        let primitive = &root.meshes[0].primitives[0];
+        let mappings = &primitive
+            .extensions
+            .as_ref()
+            .unwrap()
+            .khr_materials_variants
+            .as_ref()
+            .unwrap()
+            .mappings;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].variants, [0]);
+        let default_material = primitive.material.unwrap().value();
+        let dyed_material = mappings[0].material as usize;
+        assert_ne!(dyed_material, default_material);
+        assert_eq!(
+            root.materials[dyed_material].name.as_deref(),
+            Some("Deck [Red (dye)]")
+        );
+        assert!(
+            root.materials[dyed_material]
+                .pbr_metallic_roughness
+                .base_color_texture
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn geometry_cache_reads_each_file_once() {
+        let vfs = VfsPath::new(vfs::MemoryFS::new());
+        let file = vfs.join("gun.geometry").unwrap();
+        file.create_file().unwrap().write_all(b"v1").unwrap();
+
+        let cache = GeometryCache::default();
+        let first = cache.read(&vfs, "gun.geometry").unwrap();
+        file.create_file().unwrap().write_all(b"v2").unwrap();
+        let second = cache.read(&vfs, "gun.geometry").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.as_slice(), b"v1");
+        assert!(cache.read(&vfs, "missing.geometry").is_err());
+    }
+
+    /// A VisualPrototype record pointing at geometry `geometry_id`, with no
+    /// render sets or LODs and the given nodes as (name id, parent index,
+    /// column-major local matrix).
+    fn visual_record(geometry_id: u64, nodes: &[(u32, u16, [f32; 16])]) -> Vec<u8> {
+        fn push_array(data: &mut Vec<u8>, relptr_at: usize, bytes: Vec<u8>) {
+            data.resize(data.len().next_multiple_of(8), 0);
+            let start = data.len() as i64;
+            data[relptr_at..relptr_at + 8].copy_from_slice(&start.to_le_bytes());
+            data.extend(bytes);
+        }
+
+        let mut data = vec![0u8; visual::VISUAL_ITEM_SIZE];
+        data[..4].copy_from_slice(&(nodes.len() as u32).to_le_bytes());
+        data[0x30..0x38].copy_from_slice(&geometry_id.to_le_bytes());
+        if !nodes.is_empty() {
+            let name_ids: Vec<u8> = nodes.iter().flat_map(|n| n.0.to_le_bytes()).collect();
+            push_array(&mut data, 0x08, name_ids.clone());
+            push_array(
+                &mut data,
+                0x10,
+                (0..nodes.len() as u16).flat_map(u16::to_le_bytes).collect(),
+            );
+            push_array(&mut data, 0x18, name_ids);
+            push_array(
+                &mut data,
+                0x20,
+                nodes
+                    .iter()
+                    .flat_map(|n| n.2)
+                    .flat_map(f32::to_le_bytes)
+                    .collect(),
+            );
+            push_array(
+                &mut data,
+                0x28,
+                nodes.iter().flat_map(|n| n.1.to_le_bytes()).collect(),
+            );
+        }
+        data
+    }
+
+    #[test]
+    fn places_payload_at_payload_hardpoints_only() {
+        fn translation(x: f32, y: f32, z: f32) -> [f32; 16] {
+            [
+                1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, x, y, z, 1.0,
+            ]
+        }
+
+        let prototypes: Vec<_> = prototype_layout::LAYOUTS
+            .iter()
+            .map(|l| (l.magic, 0))
+            .collect();
+        let mut writer = PrototypeDatabaseWriter::new(&prototypes);
+        for (id, name) in [
+            (1, "Root"),
+            (2, "Propeller_1"),
+            (3, "HP_Bomb_1"),
+            (4, "HP_Gun_1"),
+        ] {
+            writer.add_string(id, name);
+        }
+        for (self_id, parent_id, name) in [
+            (1, 0, "content"),
+            (2, 1, "Plane.visual"),
+            (3, 1, "Plane.geometry"),
+            (4, 1, "Bomb.visual"),
+            (5, 1, "Bomb.geometry"),
+        ] {
+            writer.set_path(PathEntry {
+                self_id,
+                parent_id,
+                name: name.to_string(),
+            });
+        }
+        // Nodes hang under a root raised above the origin, so hardpoints are
+        // placed by their model-space rather than local transform.
+        let plane = visual_record(
+            3,
+            &[
+                (1, 0xFFFF, translation(0.0, 1.0, 0.0)),
+                (2, 0, translation(0.0, 0.0, 3.0)),
+                (3, 0, translation(1.5, -0.5, 0.0)),
+                (4, 0, translation(-1.0, 0.0, 2.0)),
+            ],
+        );
+        writer.insert_prototype(2, VISUAL_BLOB, &plane).unwrap();
+        writer
+            .insert_prototype(4, VISUAL_BLOB, &visual_record(5, &[]))
+            .unwrap();
+
+        let vfs = VfsPath::new(vfs::MemoryFS::new());
+        vfs.join("content").unwrap().create_dir_all().unwrap();
+        for (path, bytes) in [
+            ("content/Plane.geometry", b"plane".as_slice()),
+            ("content/Bomb.geometry", b"bomb".as_slice()),
+        ] {
+            vfs.join(path)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all(bytes)
+                .unwrap();
+        }
+
+        let plane = Aircraft::builder()
+            .category(PlaneCategory::Controllable)
+            .ammo_type("bomb".to_string())
+            .model_path("content/Plane.model".to_string())
+            .bomb_name("PAPB001_Bomb".to_string())
+            .build();
+        let bomb = Projectile::builder()
+            .ammo_type("bomb".to_string())
+            .model_path("content/Bomb.model".to_string())
+            .build();
+        let params = vec![
+            Param::builder()
+                .id(1u64.into())
+                .index("PAUA001".to_string())
+                .name("PAUA001_Plane".to_string())
+                .nation("USA".to_string())
+                .data(ParamData::Aircraft(plane))
+                .build(),
+            Param::builder()
+                .id(2u64.into())
+                .index("PAPB001".to_string())
+                .name("PAPB001_Bomb".to_string())
+                .species(Recognized::Known(Species::Bomb))
+                .nation("USA".to_string())
+                .data(ParamData::Projectile(bomb))
+                .build(),
+        ];
+        let metadata = GameMetadataProvider::from_params_no_specs(params).unwrap();
+        let assets = ShipAssets {
+            assets_bin_bytes: writer.to_bytes(),
+            vfs,
+            metadata: Arc::new(metadata),
+            camo_db: None,
+            texture_cache: Arc::new(TextureCache::new()),
+            geometry_cache: GeometryCache::default(),
+        };
+
+        let ctx = assets
+            .load_aircraft("PAUA001", &ShipExportOptions::default())
+            .unwrap();
+        assert_eq!(ctx.info().payload.as_ref().unwrap().kind, PayloadKind::Bomb);
+        assert!(ctx.has_payload_model());
+        let names = |nodes: &[AircraftNode]| -> Vec<String> {
+            nodes.iter().map(|n| n.name.clone()).collect()
+        };
+        assert_eq!(names(ctx.propeller_nodes()), ["Propeller_1"]);
+        assert_eq!(names(ctx.payload_hardpoints()), ["HP_Bomb_1"]);
+        assert_eq!(
+            ctx.payload_hardpoints()[0].transform,
+            translation(1.5, 0.5, 0.0)
+        );
+    }
+}
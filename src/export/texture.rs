@@ -66,12 +66,11 @@ pub fn dds_to_png_resized(
                         count += 1;
                     }
                 }
-                if count > 0 {
-                    let di = (dy * nw + dx) as usize * 4;
-                    dst[di] = (r / count) as u8;
-                    dst[di + 1] = (g / count) as u8;
-                    dst[di + 2] = (b / count) as u8;
-                    dst[di + 3] = (a / count) as u8;
+                let di = (dy * nw + dx) as usize * 4;
+                for (c, sum) in [r, g, b, a].into_iter().enumerate() {
+                    if let Some(avg) = sum.checked_div(count) {
+                        dst[di + c] = avg as u8;
+                    }
                 }
             }
         }
@@ -225,6 +224,19 @@ pub fn load_texture_bytes(
     mfm_stem: &str,
     scheme: &str,
 ) -> Option<(String, Vec<u8>)> {
+    let (base, path) = find_texture_path(vfs, mfm_stem, scheme)?;
+    Some((base, load_dds_from_vfs(vfs, &path)?))
+}
+
+/// Find the camo albedo texture that [`load_texture_bytes`] would load,
+/// without reading it.
+///
+/// Returns `(base_name, vfs_path)` if found, or `None`.
+pub fn find_texture_path(
+    vfs: &vfs::VfsPath,
+    mfm_stem: &str,
+    scheme: &str,
+) -> Option<(String, String)> {
     for base in texture_base_names(mfm_stem) {
         // Try explicit albedo channel first ({base}_{scheme}_a), then direct ({base}_{scheme}).
         let candidates = [
@@ -234,21 +246,24 @@ pub fn load_texture_bytes(
             format!("{TEXTURE_BASE}/{base}_{scheme}.dds"),
         ];
 
-        for path in &candidates {
-            if let Ok(vfs_path) = vfs.join(path)
-                && let Ok(mut file) = vfs_path.open_file()
-            {
-                let mut data = Vec::new();
-                if std::io::Read::read_to_end(&mut file, &mut data).is_ok() && !data.is_empty() {
-                    return Some((base, data));
-                }
-            }
+        if let Some(path) = candidates
+            .into_iter()
+            .find(|path| is_nonempty_file(vfs, path))
+        {
+            return Some((base, path));
         }
     }
 
     None
 }
 
+/// Whether `path` is a file in the VFS with any content.
+fn is_nonempty_file(vfs: &vfs::VfsPath, path: &str) -> bool {
+    vfs.join(path)
+        .and_then(|p| p.metadata())
+        .is_ok_and(|meta| meta.file_type == vfs::VfsFileType::File && meta.len > 0)
+}
+
 /// Load the base albedo texture for a hull mesh from the VFS.
 ///
 /// The base albedo is the "default" ship appearance — gray/weathered paint without
@@ -262,6 +277,12 @@ pub fn load_texture_bytes(
 /// `mfm_full_path` is the full VFS path to the MFM file (e.g. ending in `.mfm`).
 /// Returns DDS bytes if found.
 pub fn load_base_albedo_bytes(vfs: &vfs::VfsPath, mfm_full_path: &str) -> Option<Vec<u8>> {
+    load_dds_from_vfs(vfs, &find_base_albedo_path(vfs, mfm_full_path)?)
+}
+
+/// Find the base albedo texture that [`load_base_albedo_bytes`] would load,
+/// without reading it. Returns its VFS path.
+pub fn find_base_albedo_path(vfs: &vfs::VfsPath, mfm_full_path: &str) -> Option<String> {
     let dir = mfm_full_path.rsplit_once('/')?.0;
     let mfm_filename = mfm_full_path.rsplit_once('/')?.1;
     let stem = mfm_filename.strip_suffix(".mfm")?;
@@ -293,21 +314,58 @@ pub fn load_base_albedo_bytes(vfs: &vfs::VfsPath, mfm_full_path: &str) -> Option
             candidates.push(format!("{tiled_subdir}/{base}{suffix}.dds"));
         }
 
-        for path in &candidates {
-            if let Ok(vfs_path) = vfs.join(path)
-                && let Ok(mut file) = vfs_path.open_file()
-            {
-                let mut data = Vec::new();
-                if std::io::Read::read_to_end(&mut file, &mut data).is_ok() && !data.is_empty() {
-                    return Some(data);
-                }
-            }
+        if let Some(path) = candidates
+            .into_iter()
+            .find(|path| is_nonempty_file(vfs, path))
+        {
+            return Some(path);
         }
     }
 
     None
 }
 
+/// Texture channel suffixes a material may reference, in lookup order.
+const MATERIAL_TEXTURE_SUFFIXES: &[&str] = &["_a", "_od", "_n", "_mg", "_mgn", "_ao"];
+
+/// Find the texture files that belong to a material by naming convention.
+///
+/// Searches the same directories as [`load_base_albedo_bytes`] (the `textures/`
+/// sibling directory, the MFM's own directory, and its `TILED/` subdirectory)
+/// for every known channel suffix, returning the VFS paths that exist.
+pub fn material_texture_paths(vfs: &vfs::VfsPath, mfm_full_path: &str) -> Vec<String> {
+    let Some((dir, mfm_filename)) = mfm_full_path.rsplit_once('/') else {
+        return Vec::new();
+    };
+    let Some(stem) = mfm_filename.strip_suffix(".mfm") else {
+        return Vec::new();
+    };
+
+    let mut dirs = Vec::new();
+    if let Some((parent, _)) = dir.rsplit_once('/') {
+        dirs.push(format!("{parent}/textures"));
+    }
+    dirs.push(dir.to_string());
+    dirs.push(format!("{dir}/TILED"));
+
+    let mut found = Vec::new();
+    for base in texture_base_names(stem) {
+        for suffix in MATERIAL_TEXTURE_SUFFIXES {
+            for dir in &dirs {
+                for ext in ["dd0", "dds"] {
+                    let path = format!("{dir}/{base}{suffix}.{ext}");
+                    if vfs.join(&path).and_then(|p| p.exists()).unwrap_or(false)
+                        && !found.contains(&path)
+                    {
+                        found.push(path);
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
 /// Strip texture channel suffixes (`_a`, `_mg`, `_mgn`) from a raw scheme name.
 ///
 /// E.g. `GW_a` → `GW`, `camo_01` → `camo_01` (no channel suffix).
@@ -0,0 +1,323 @@
+//! Decoded texture cache shared across exports.
+//!
+//! Decoding a DDS texture to PNG is the slowest part of a textured export,
+//! and ships share many textures (common AA guns, directors, camouflage
+//! tiles). [`TextureCache`] keeps decoded PNGs in memory keyed by DDS path
+//! and maximum size, up to a byte budget, and can also persist them to a
+//! directory. On disk,
+//! files are named by the CRC32 and length of the source DDS bytes, so a
+//! texture that changes between game versions is decoded again while
+//! unchanged ones are reused.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rootcause::Report;
+use vfs::VfsPath;
+
+use super::texture::{self, TextureError};
+
+/// Memory key: DDS VFS path and maximum texture size.
+type CacheKey = (String, Option<u32>);
+
+/// Suffix counter for temporary files, see [`write_file_atomic`].
+static NEXT_TMP: AtomicUsize = AtomicUsize::new(0);
+
+/// Default [`TextureCache::with_memory_limit`]: 1 GiB of PNG bytes.
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 30;
+
+/// Counters of where textures came from, see [`TextureCache::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCacheStats {
+    /// Served from memory.
+    pub memory_hits: usize,
+    /// Read from the disk cache.
+    pub disk_hits: usize,
+    /// Decoded from DDS.
+    pub decoded: usize,
+}
+
+/// Decoded textures held in memory, evicted oldest first.
+#[derive(Debug, Default)]
+struct Memory {
+    /// `None` entries record textures missing from the VFS.
+    entries: HashMap<CacheKey, Option<Arc<Vec<u8>>>>,
+    /// Keys in insertion order.
+    order: VecDeque<CacheKey>,
+    /// Bytes of all keys and PNGs in `entries`.
+    bytes: usize,
+}
+
+/// Bytes an entry counts against the memory limit.
+fn entry_size(key: &CacheKey, png: &Option<Arc<Vec<u8>>>) -> usize {
+    key.0.len() + png.as_ref().map_or(0, |png| png.len())
+}
+
+/// Thread-safe cache of DDS textures decoded to PNG.
+#[derive(Debug)]
+pub struct TextureCache {
+    memory: Mutex<Memory>,
+    memory_limit: usize,
+    disk_dir: Option<PathBuf>,
+    memory_hits: AtomicUsize,
+    disk_hits: AtomicUsize,
+    decoded: AtomicUsize,
+}
+
+impl Default for TextureCache {
+    fn default() -> Self {
+        Self {
+            memory: Mutex::default(),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            disk_dir: None,
+            memory_hits: AtomicUsize::new(0),
+            disk_hits: AtomicUsize::new(0),
+            decoded: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl TextureCache {
+    /// An in-memory cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `bytes` of decoded PNGs in memory, dropping the oldest
+    /// first. Defaults to [`DEFAULT_MEMORY_LIMIT`]. PNGs already handed out
+    /// stay valid after they are dropped from the cache.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// A cache that also stores PNGs in `dir`, reusing them across runs.
+    /// The directory is created on first write.
+    pub fn with_disk_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            disk_dir: Some(dir.into()),
+            ..Self::default()
+        }
+    }
+
+    /// PNG bytes of the DDS texture at `dds_path`, downsampled to at most
+    /// `max_size` pixels per side. See [`texture::dds_to_png_resized`].
+    ///
+    /// Returns `Ok(None)` if the texture is not in the VFS.
+    pub fn png(
+        &self,
+        vfs: &VfsPath,
+        dds_path: &str,
+        max_size: Option<u32>,
+    ) -> Result<Option<Arc<Vec<u8>>>, Report<TextureError>> {
+        let key = (dds_path.to_string(), max_size);
+        if let Some(cached) = self.memory.lock().unwrap().entries.get(&key) {
+            self.memory_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached.clone());
+        }
+
+        // Decode without holding the lock; concurrent misses on the same
+        // texture may both decode it, which is harmless.
+        let png = match texture::load_dds_from_vfs(vfs, dds_path) {
+            Some(dds_bytes) => Some(Arc::new(self.decode(&dds_bytes, max_size)?)),
+            None => None,
+        };
+        self.remember(key, png.clone());
+        Ok(png)
+    }
+
+    /// Add an entry to the memory cache, then evict the oldest entries until
+    /// it fits the memory limit. Entries larger than the limit are not kept.
+    fn remember(&self, key: CacheKey, png: Option<Arc<Vec<u8>>>) {
+        let mut memory = self.memory.lock().unwrap();
+        let size = entry_size(&key, &png);
+        if size > self.memory_limit {
+            return;
+        }
+        if let Some(old) = memory.entries.insert(key.clone(), png) {
+            // A concurrent miss already stored this texture.
+            memory.bytes -= entry_size(&key, &old);
+        } else {
+            memory.order.push_back(key);
+        }
+        memory.bytes += size;
+        while memory.bytes > self.memory_limit {
+            let Some(oldest) = memory.order.pop_front() else {
+                break;
+            };
+            if let Some(old) = memory.entries.remove(&oldest) {
+                memory.bytes -= entry_size(&oldest, &old);
+            }
+        }
+    }
+
+    /// Counters since the cache was created.
+    pub fn stats(&self) -> TextureCacheStats {
+        TextureCacheStats {
+            memory_hits: self.memory_hits.load(Ordering::Relaxed),
+            disk_hits: self.disk_hits.load(Ordering::Relaxed),
+            decoded: self.decoded.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Decode `dds_bytes`, going through the disk cache if there is one.
+    fn decode(
+        &self,
+        dds_bytes: &[u8],
+        max_size: Option<u32>,
+    ) -> Result<Vec<u8>, Report<TextureError>> {
+        let disk_path = self.disk_dir.as_ref().map(|dir| {
+            let mut crc = flate2::Crc::new();
+            crc.update(dds_bytes);
+            let size = max_size.map_or_else(|| "full".to_string(), |s| s.to_string());
+            dir.join(format!(
+                "{:08x}-{:x}-{size}.png",
+                crc.sum(),
+                dds_bytes.len()
+            ))
+        });
+        if let Some(path) = &disk_path
+            && let Ok(png) = std::fs::read(path)
+        {
+            self.disk_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(png);
+        }
+
+        let png = texture::dds_to_png_resized(dds_bytes, max_size)?;
+        self.decoded.fetch_add(1, Ordering::Relaxed);
+        // Failing to cache is not an error.
+        if let Some(path) = &disk_path
+            && let Err(e) = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| write_file_atomic(path, &png))
+        {
+            eprintln!(
+                "  Warning: failed to write texture cache {}: {e}",
+                path.display()
+            );
+        }
+        Ok(png)
+    }
+}
+
+/// Write `bytes` to `path` through a temporary file in the same directory,
+/// so concurrent readers never see a partial file.
+pub(crate) fn write_file_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = std::fs::write(&tmp, bytes).and_then(|()| std::fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A VFS with the same small DDS texture at each of `paths`.
+    fn vfs_with_textures(paths: &[&str]) -> VfsPath {
+        let dds = image_dds::Surface {
+            width: 8,
+            height: 8,
+            depth: 1,
+            layers: 1,
+            mipmaps: 1,
+            image_format: image_dds::ImageFormat::Rgba8Unorm,
+            data: [200u8, 40, 40, 255].repeat(64),
+        }
+        .to_dds()
+        .unwrap();
+        let mut dds_bytes = Vec::new();
+        dds.write(&mut dds_bytes).unwrap();
+
+        let vfs = VfsPath::new(vfs::MemoryFS::new());
+        vfs.join("textures").unwrap().create_dir().unwrap();
+        for path in paths {
+            vfs.join(path)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all(&dds_bytes)
+                .unwrap();
+        }
+        vfs
+    }
+
+    #[test]
+    fn decodes_once_and_reuses_disk_cache() {
+        let vfs = vfs_with_textures(&["textures/gun_a.dds"]);
+
+        let dir = std::env::temp_dir().join(format!("texture_cache_test_{}", std::process::id()));
+        let cache = TextureCache::with_disk_dir(&dir);
+        let png = cache
+            .png(&vfs, "textures/gun_a.dds", None)
+            .unwrap()
+            .unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let again = cache
+            .png(&vfs, "textures/gun_a.dds", None)
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&png, &again));
+        assert!(
+            cache
+                .png(&vfs, "textures/missing.dds", None)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            cache.stats(),
+            TextureCacheStats {
+                memory_hits: 1,
+                disk_hits: 0,
+                decoded: 1,
+            }
+        );
+
+        // A new cache over the same directory skips decoding.
+        let cache = TextureCache::with_disk_dir(&dir);
+        let from_disk = cache
+            .png(&vfs, "textures/gun_a.dds", None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(from_disk, png);
+        assert_eq!(cache.stats().disk_hits, 1);
+        assert_eq!(cache.stats().decoded, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn evicts_oldest_textures_over_the_memory_limit() {
+        let vfs = vfs_with_textures(&["textures/a.dds", "textures/b.dds"]);
+        let png = TextureCache::new()
+            .png(&vfs, "textures/a.dds", None)
+            .unwrap()
+            .unwrap();
+
+        // Room for exactly one of the two identical textures.
+        let cache = TextureCache::new().with_memory_limit("textures/a.dds".len() + png.len());
+        let a = cache.png(&vfs, "textures/a.dds", None).unwrap().unwrap();
+        cache.png(&vfs, "textures/b.dds", None).unwrap().unwrap();
+        cache.png(&vfs, "textures/b.dds", None).unwrap().unwrap();
+        let a_again = cache.png(&vfs, "textures/a.dds", None).unwrap().unwrap();
+        assert_eq!(a_again, a);
+        assert!(!Arc::ptr_eq(&a_again, &a));
+        assert_eq!(
+            cache.stats(),
+            TextureCacheStats {
+                memory_hits: 1,
+                disk_hits: 0,
+                decoded: 3,
+            }
+        );
+    }
+}
@@ -0,0 +1,1055 @@
+//! Export ships and map scenes to ASCII USD (`.usda`).
+//!
+//! The layer is plain text, so no USD runtime is needed. Materials are
+//! `UsdPreviewSurface` networks reading PNGs written next to the layer, and
+//! camouflage schemes and dye tints become a `camouflage` variant set that
+//! swaps those textures.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rootcause::Report;
+
+use crate::models::assets_bin::PrototypeDatabase;
+
+use super::gltf_export::{
+    ArmorSubModel, ExportError, MapMesh, MapScene, SubModel, TextureSet, collect_primitives,
+    negate_z_transform,
+};
+
+/// Export multiple sub-models as a USD layer at `path`.
+///
+/// Mirrors [`export_ship_glb`](super::gltf_export::export_ship_glb): one Xform
+/// per sub-model under its group, carrying the mount transform, with armor
+/// and collision meshes colored through `primvars:displayColor`. Textures are
+/// written to a `<stem>_textures` directory next to `path`.
+#[allow(clippy::too_many_arguments)]
+pub fn export_ship_usda(
+    sub_models: &[SubModel<'_>],
+    armor_models: &[ArmorSubModel],
+    collision_models: &[ArmorSubModel],
+    db: &PrototypeDatabase<'_>,
+    lod: usize,
+    texture_set: &TextureSet,
+    damaged: bool,
+    path: &Path,
+) -> Result<(), Report<ExportError>> {
+    let self_id_index = db.build_self_id_index();
+
+    // Decode everything up front so materials can be written before meshes.
+    let mut parts = Vec::new();
+    for sub in sub_models {
+        if sub.visual.lods.is_empty() || lod >= sub.visual.lods.len() {
+            eprintln!(
+                "Warning: sub-model '{}' has {} LODs, skipping (requested LOD {})",
+                sub.name,
+                sub.visual.lods.len(),
+                lod
+            );
+            continue;
+        }
+        let primitives = collect_primitives(
+            sub.visual,
+            sub.geometry,
+            Some(db),
+            Some(&self_id_index),
+            &sub.visual.lods[lod],
+            damaged,
+            sub.barrel_pitch.as_ref(),
+            false,
+        )?;
+        parts.push((sub, primitives));
+    }
+
+    let write = || -> io::Result<()> {
+        let mut textures = TextureFiles::new(path);
+        let mut usda = Usda::create(path, "Ship")?;
+        let variant_names = variant_names(texture_set);
+
+        // Materials are keyed like the GLB material cache: MFM stem, else material name.
+        let mut materials: BTreeMap<String, (String, Option<String>)> = BTreeMap::new();
+        for (_, primitives) in &parts {
+            for prim in primitives {
+                let key = prim
+                    .mfm_stem
+                    .clone()
+                    .unwrap_or_else(|| prim.material_name.clone());
+                materials
+                    .entry(key)
+                    .or_insert_with(|| (prim.material_name.clone(), prim.mfm_stem.clone()));
+            }
+        }
+
+        let mut root_meta = Vec::new();
+        if !variant_names.is_empty() {
+            root_meta.push("variants = {\n        string camouflage = \"Default\"\n    }");
+            root_meta.push("prepend variantSets = \"camouflage\"");
+        }
+        usda.open_prim("def Xform", "Ship", &root_meta)?;
+
+        // Default materials.
+        let mut material_paths = BTreeMap::new();
+        let mut material_names = UniqueNames::default();
+        usda.open_prim("def Scope", "Materials", &[])?;
+        for (key, (material_name, stem)) in &materials {
+            let id = material_names.get(key);
+            let png = stem.as_ref().and_then(|stem| texture_set.base.get(stem));
+            let file = png.map(|png| textures.file_for(png)).transpose()?;
+            let path = format!("/Ship/Materials/{id}");
+            write_material(
+                &mut usda,
+                &path,
+                &id,
+                file.as_deref(),
+                [1.0; 4],
+                false,
+                None,
+            )?;
+            material_paths.insert(key.clone(), (path, id, material_name, stem));
+        }
+        usda.close()?;
+
+        // Sub-models, grouped like the GLB scene.
+        let mut groups: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for (sub, primitives) in &parts {
+            groups.entry(sub.group).or_default().push((sub, primitives));
+        }
+        let mut group_names = UniqueNames::default();
+        for (group, subs) in &groups {
+            usda.open_prim("def Xform", &group_names.get(group), &[])?;
+            let mut sub_names = UniqueNames::default();
+            for (sub, primitives) in subs {
+                usda.open_prim("def Xform", &sub_names.get(&sub.name), &[])?;
+                if let Some(m) = sub.transform {
+                    usda.transform(&negate_z_transform(m))?;
+                }
+                let mut mesh_names = UniqueNames::default();
+                for prim in primitives.iter() {
+                    let key = prim.mfm_stem.as_ref().unwrap_or(&prim.material_name);
+                    let mesh = MeshData {
+                        positions: &prim.positions,
+                        normals: &prim.normals,
+                        uvs: &prim.uvs,
+                        colors: &[],
+                        indices: &prim.indices,
+                    };
+                    let material = material_paths.get(key).map(|(path, ..)| path.as_str());
+                    usda.mesh(&mesh_names.get(&prim.material_name), &mesh, material, None)?;
+                }
+                usda.close()?;
+            }
+            usda.close()?;
+        }
+
+        for (group, meshes) in [("Armor", armor_models), ("Collision", collision_models)] {
+            if meshes.iter().all(|m| m.positions.is_empty()) {
+                continue;
+            }
+            usda.open_prim("def Xform", group, &[])?;
+            let mut mesh_names = UniqueNames::default();
+            for armor in meshes.iter().filter(|m| !m.positions.is_empty()) {
+                let mesh = MeshData {
+                    positions: &armor.positions,
+                    normals: &armor.normals,
+                    uvs: &[],
+                    colors: &armor.colors,
+                    indices: &armor.indices,
+                };
+                let transform = armor.transform.map(negate_z_transform);
+                usda.mesh(
+                    &mesh_names.get(&armor.name),
+                    &mesh,
+                    None,
+                    transform.as_ref(),
+                )?;
+            }
+            usda.close()?;
+        }
+
+        // One variant per camo scheme, then dye tint, overriding the textures
+        // of every material the variant covers.
+        if !variant_names.is_empty() {
+            usda.line("variantSet \"camouflage\" = {")?;
+            usda.depth += 1;
+            usda.line("\"Default\" {")?;
+            usda.line("}")?;
+            for (vi, variant) in variant_names.iter().enumerate() {
+                usda.line(format_args!("\"{variant}\" {{"))?;
+                usda.depth += 1;
+                usda.open_prim("over", "Materials", &[])?;
+                for (path, id, material_name, stem) in material_paths.values() {
+                    let (png, uv_transform) = if vi < texture_set.camo_schemes.len() {
+                        let scheme = &texture_set.camo_schemes[vi].1;
+                        let png = stem.as_ref().and_then(|stem| scheme.get(stem));
+                        let xform = stem.as_ref().and_then(|stem| {
+                            texture_set.tiled_uv_transforms.get(&(vi, stem.clone()))
+                        });
+                        (png, xform.copied())
+                    } else {
+                        let dye = &texture_set.dye_variants[vi - texture_set.camo_schemes.len()];
+                        let png = dye.materials.get(*material_name).or_else(|| {
+                            stem.as_ref()
+                                .and_then(|stem| dye.materials.get(stem.as_str()))
+                        });
+                        (png, None)
+                    };
+                    let Some(png) = png else { continue };
+                    let file = textures.file_for(png)?;
+                    write_texture_override(&mut usda, path, id, &file, uv_transform)?;
+                }
+                usda.close()?;
+                usda.depth -= 1;
+                usda.line("}")?;
+            }
+            usda.depth -= 1;
+            usda.line("}")?;
+        }
+
+        usda.close()?;
+        usda.finish()
+    };
+
+    write().map_err(|e| Report::new(ExportError::Io(e.to_string())))
+}
+
+/// Export a decoded map scene as a USD layer at `path`.
+///
+/// Model instances and vegetation become `PointInstancer`s whose prototypes are
+/// the scene's unique models, so each model's geometry is written once.
+/// Terrain and water are plain meshes. Textures are written to a
+/// `<stem>_textures` directory next to `path`.
+pub fn export_map_scene_usda(scene: &MapScene, path: &Path) -> Result<(), Report<ExportError>> {
+    let write = || -> io::Result<()> {
+        let mut textures = TextureFiles::new(path);
+        let mut usda = Usda::create(path, "Map")?;
+        usda.open_prim("def Xform", "Map", &[])?;
+
+        // Materials, deduplicated by their visual parameters.
+        let all_meshes = scene
+            .model_meshes
+            .iter()
+            .chain(scene.terrain.as_ref())
+            .chain(scene.water.as_ref());
+        let mut material_paths: HashMap<String, String> = HashMap::new();
+        usda.open_prim("def Scope", "Materials", &[])?;
+        for mesh in all_meshes {
+            let id = map_material_name(mesh);
+            if material_paths.contains_key(&id) {
+                continue;
+            }
+            let file = mesh
+                .albedo_texture
+                .map(|tex| textures.file_for(&scene.textures[tex]))
+                .transpose()?;
+            let path = format!("/Map/Materials/{id}");
+            write_material(
+                &mut usda,
+                &path,
+                &id,
+                file.as_deref(),
+                mesh.base_color,
+                mesh.alpha_blend,
+                mesh.alpha_cutoff,
+            )?;
+            material_paths.insert(id, path);
+        }
+        usda.close()?;
+
+        let map_mesh = |usda: &mut Usda, name: &str, mesh: &MapMesh| -> io::Result<()> {
+            let data = MeshData {
+                positions: &mesh.positions,
+                normals: &mesh.normals,
+                uvs: &mesh.uvs,
+                colors: &[],
+                indices: &mesh.indices,
+            };
+            let material = material_paths.get(&map_material_name(mesh));
+            usda.mesh(name, &data, material.map(String::as_str), None)
+        };
+
+        // Model instances: one prototype per distinct model (mesh range).
+        if !scene.model_instances.is_empty() {
+            let mut prototypes: Vec<std::ops::Range<usize>> = Vec::new();
+            let mut proto_indices = Vec::with_capacity(scene.model_instances.len());
+            for inst in &scene.model_instances {
+                let idx = match prototypes.iter().position(|r| *r == inst.mesh_range) {
+                    Some(idx) => idx,
+                    None => {
+                        prototypes.push(inst.mesh_range.clone());
+                        prototypes.len() - 1
+                    }
+                };
+                proto_indices.push(idx);
+            }
+            let decomposed: Vec<_> = scene
+                .model_instances
+                .iter()
+                .map(|inst| decompose_transform(&inst.transform))
+                .collect();
+
+            usda.open_prim("def PointInstancer", "Models", &[])?;
+            usda.array(
+                "rel prototypes",
+                (0..prototypes.len()).map(|i| format!("</Map/Models/Prototypes/Model_{i}>")),
+            )?;
+            usda.array("int[] protoIndices", proto_indices.iter())?;
+            usda.array(
+                "point3f[] positions",
+                decomposed.iter().map(|&(t, _, _)| Tuple(t)),
+            )?;
+            usda.array(
+                "quath[] orientations",
+                decomposed.iter().map(|&(_, q, _)| Tuple(q)),
+            )?;
+            usda.array(
+                "float3[] scales",
+                decomposed.iter().map(|&(_, _, s)| Tuple(s)),
+            )?;
+            usda.open_prim("def Scope", "Prototypes", &[])?;
+            for (i, range) in prototypes.iter().enumerate() {
+                usda.open_prim("def Xform", &format!("Model_{i}"), &[])?;
+                let mut mesh_names = UniqueNames::default();
+                for mesh in &scene.model_meshes[range.clone()] {
+                    map_mesh(&mut usda, &mesh_names.get(&mesh.name), mesh)?;
+                }
+                usda.close()?;
+            }
+            usda.close()?;
+            usda.close()?;
+        }
+
+        // Vegetation: one prototype per species mesh, positions only.
+        if !scene.vegetation_instances.is_empty() {
+            usda.open_prim("def PointInstancer", "Vegetation", &[])?;
+            usda.array(
+                "rel prototypes",
+                (0..scene.vegetation_instances.len())
+                    .map(|i| format!("</Map/Vegetation/Prototypes/Species_{i}>")),
+            )?;
+            usda.array(
+                "int[] protoIndices",
+                scene
+                    .vegetation_instances
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, (_, positions))| std::iter::repeat_n(i, positions.len())),
+            )?;
+            usda.array(
+                "point3f[] positions",
+                scene
+                    .vegetation_instances
+                    .iter()
+                    .flat_map(|(_, positions)| positions.iter().map(|&p| Tuple(p))),
+            )?;
+            usda.open_prim("def Scope", "Prototypes", &[])?;
+            for (i, (mesh_idx, _)) in scene.vegetation_instances.iter().enumerate() {
+                map_mesh(
+                    &mut usda,
+                    &format!("Species_{i}"),
+                    &scene.model_meshes[*mesh_idx],
+                )?;
+            }
+            usda.close()?;
+            usda.close()?;
+        }
+
+        if let Some(terrain) = &scene.terrain {
+            map_mesh(&mut usda, "Terrain", terrain)?;
+        }
+        if let Some(water) = &scene.water {
+            map_mesh(&mut usda, "Water", water)?;
+        }
+
+        usda.close()?;
+        usda.finish()
+    };
+
+    write().map_err(|e| Report::new(ExportError::Io(e.to_string())))
+}
+
+/// Variant names for the `camouflage` variant set: camo schemes, then dye
+/// tints (the same order as `KHR_materials_variants` in the GLB export).
+fn variant_names(texture_set: &TextureSet) -> Vec<String> {
+    let mut names = UniqueNames::default();
+    // "Default" is the no-variant selection.
+    names.get("Default");
+    texture_set
+        .camo_schemes
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain(texture_set.dye_variants.iter().map(|dye| dye.name.as_str()))
+        .map(|name| names.get(name))
+        .collect()
+}
+
+/// Material prim name for a map mesh, unique per set of visual parameters.
+fn map_material_name(mesh: &MapMesh) -> String {
+    let mut name = match mesh.albedo_texture {
+        Some(tex) => format!("Texture_{tex}"),
+        None => {
+            let [r, g, b, a] = mesh
+                .base_color
+                .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            format!("Color_{r:02x}{g:02x}{b:02x}{a:02x}")
+        }
+    };
+    if mesh.alpha_blend {
+        name.push_str("_Blend");
+    }
+    if let Some(cutoff) = mesh.alpha_cutoff {
+        name.push_str(&format!("_Cutout{}", (cutoff * 100.0).round() as u32));
+    }
+    name
+}
+
+/// Write a `UsdPreviewSurface` material at `path`, textured with `texture`
+/// (a layer-relative asset path) or flat `base_color`.
+///
+/// Every material carries the texture reader chain, so camouflage variants
+/// can texture materials that are untextured by default.
+fn write_material(
+    usda: &mut Usda,
+    path: &str,
+    id: &str,
+    texture: Option<&str>,
+    base_color: [f32; 4],
+    alpha_blend: bool,
+    alpha_cutoff: Option<f32>,
+) -> io::Result<()> {
+    usda.open_prim("def Material", id, &[])?;
+    usda.line(format_args!(
+        "token outputs:surface.connect = <{path}/Surface.outputs:surface>"
+    ))?;
+
+    usda.open_prim("def Shader", "Surface", &[])?;
+    usda.line("uniform token info:id = \"UsdPreviewSurface\"")?;
+    if texture.is_some() {
+        usda.line(format_args!(
+            "color3f inputs:diffuseColor.connect = <{path}/Texture.outputs:rgb>"
+        ))?;
+    } else {
+        let [r, g, b, _] = base_color;
+        usda.line(format_args!(
+            "color3f inputs:diffuseColor = ({r}, {g}, {b})"
+        ))?;
+    }
+    if let Some(cutoff) = alpha_cutoff {
+        usda.line(format_args!(
+            "float inputs:opacity.connect = <{path}/Texture.outputs:a>"
+        ))?;
+        usda.line(format_args!("float inputs:opacityThreshold = {cutoff}"))?;
+    } else if alpha_blend {
+        usda.line(format_args!("float inputs:opacity = {}", base_color[3]))?;
+    }
+    usda.line("float inputs:metallic = 0")?;
+    usda.line("float inputs:roughness = 0.8")?;
+    usda.line("token outputs:surface")?;
+    usda.close()?;
+
+    usda.open_prim("def Shader", "Texture", &[])?;
+    usda.line("uniform token info:id = \"UsdUVTexture\"")?;
+    if let Some(texture) = texture {
+        usda.line(format_args!("asset inputs:file = @{texture}@"))?;
+    }
+    usda.line(format_args!(
+        "float2 inputs:st.connect = <{path}/StTransform.outputs:result>"
+    ))?;
+    usda.line("token inputs:wrapS = \"repeat\"")?;
+    usda.line("token inputs:wrapT = \"repeat\"")?;
+    usda.line("float3 outputs:rgb")?;
+    usda.line("float outputs:a")?;
+    usda.close()?;
+
+    usda.open_prim("def Shader", "StTransform", &[])?;
+    usda.line("uniform token info:id = \"UsdTransform2d\"")?;
+    usda.line(format_args!(
+        "float2 inputs:in.connect = <{path}/StReader.outputs:result>"
+    ))?;
+    usda.line("float2 outputs:result")?;
+    usda.close()?;
+
+    usda.open_prim("def Shader", "StReader", &[])?;
+    usda.line("uniform token info:id = \"UsdPrimvarReader_float2\"")?;
+    usda.line("string inputs:varname = \"st\"")?;
+    usda.line("float2 outputs:result")?;
+    usda.close()?;
+
+    usda.close()
+}
+
+/// Override a material's texture inside a variant.
+///
+/// `uv_transform` is the `[scale_x, scale_y, offset_x, offset_y]` of
+/// `KHR_texture_transform`, converted here to USD's bottom-left UV origin.
+fn write_texture_override(
+    usda: &mut Usda,
+    path: &str,
+    id: &str,
+    texture: &str,
+    uv_transform: Option<[f32; 4]>,
+) -> io::Result<()> {
+    usda.open_prim("over", id, &[])?;
+    usda.open_prim("over", "Surface", &[])?;
+    usda.line(format_args!(
+        "color3f inputs:diffuseColor.connect = <{path}/Texture.outputs:rgb>"
+    ))?;
+    usda.close()?;
+    usda.open_prim("over", "Texture", &[])?;
+    usda.line(format_args!("asset inputs:file = @{texture}@"))?;
+    usda.close()?;
+    if let Some([sx, sy, ox, oy]) = uv_transform {
+        // glTF v' = v * sy + oy with v = 1 - v_usd gives
+        // v_usd' = v_usd * sy + (1 - sy - oy).
+        usda.open_prim("over", "StTransform", &[])?;
+        usda.line(format_args!("float2 inputs:scale = ({sx}, {sy})"))?;
+        usda.line(format_args!(
+            "float2 inputs:translation = ({ox}, {})",
+            1.0 - sy - oy
+        ))?;
+        usda.close()?;
+    }
+    usda.close()
+}
+
+/// Split a column-major affine transform into translation, orientation
+/// quaternion `(w, x, y, z)` and per-axis scale, as `PointInstancer` wants.
+fn decompose_transform(m: &[f32; 16]) -> ([f32; 3], [f32; 4], [f32; 3]) {
+    let translation = [m[12], m[13], m[14]];
+    let mut cols = [[m[0], m[1], m[2]], [m[4], m[5], m[6]], [m[8], m[9], m[10]]];
+    let mut scale = cols.map(|[x, y, z]| (x * x + y * y + z * z).sqrt());
+    for (col, s) in cols.iter_mut().zip(&scale) {
+        if *s > 0.0 {
+            *col = col.map(|c| c / s);
+        }
+    }
+    // A mirrored basis can't be a rotation; fold the reflection into X scale.
+    let [a, b, c] = cols;
+    let det = a[0] * (b[1] * c[2] - b[2] * c[1]) - b[0] * (a[1] * c[2] - a[2] * c[1])
+        + c[0] * (a[1] * b[2] - a[2] * b[1]);
+    if det < 0.0 {
+        scale[0] = -scale[0];
+        cols[0] = cols[0].map(|v| -v);
+    }
+
+    // Rotation matrix element r(row, col).
+    let r = |row: usize, col: usize| cols[col][row];
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            0.25 * s,
+            (r(2, 1) - r(1, 2)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+            (r(1, 0) - r(0, 1)) / s,
+        ]
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        [
+            (r(2, 1) - r(1, 2)) / s,
+            0.25 * s,
+            (r(0, 1) + r(1, 0)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+        ]
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        [
+            (r(0, 2) - r(2, 0)) / s,
+            (r(0, 1) + r(1, 0)) / s,
+            0.25 * s,
+            (r(1, 2) + r(2, 1)) / s,
+        ]
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        [
+            (r(1, 0) - r(0, 1)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+            (r(1, 2) + r(2, 1)) / s,
+            0.25 * s,
+        ]
+    };
+    (translation, q, scale)
+}
+
+/// Turn an arbitrary name into a valid USD prim identifier.
+fn usd_identifier(name: &str) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        id.insert(0, '_');
+    }
+    id
+}
+
+/// Hands out identifiers that are unique among siblings.
+#[derive(Default)]
+struct UniqueNames(HashSet<String>);
+
+impl UniqueNames {
+    fn get(&mut self, name: &str) -> String {
+        let base = usd_identifier(name);
+        let mut id = base.clone();
+        let mut n = 1;
+        while !self.0.insert(id.clone()) {
+            id = format!("{base}_{n}");
+            n += 1;
+        }
+        id
+    }
+}
+
+/// PNG files written next to the layer, deduplicated by content.
+struct TextureFiles {
+    dir: PathBuf,
+    /// Directory name relative to the layer, used in asset paths.
+    rel_dir: String,
+    files: HashMap<Vec<u8>, String>,
+}
+
+impl TextureFiles {
+    fn new(layer_path: &Path) -> Self {
+        let stem = layer_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "output".to_string());
+        let rel_dir = format!("{stem}_textures");
+        let dir = layer_path.parent().unwrap_or(Path::new("")).join(&rel_dir);
+        Self {
+            dir,
+            rel_dir,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Layer-relative asset path for `png`, writing the file on first use.
+    fn file_for(&mut self, png: &[u8]) -> io::Result<String> {
+        if let Some(path) = self.files.get(png) {
+            return Ok(path.clone());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let name = format!("texture_{}.png", self.files.len());
+        std::fs::write(self.dir.join(&name), png)?;
+        let path = format!("./{}/{name}", self.rel_dir);
+        self.files.insert(png.to_vec(), path.clone());
+        Ok(path)
+    }
+}
+
+/// Vertex data for one `Mesh` prim. Positions and normals are right-handed.
+struct MeshData<'a> {
+    positions: &'a [[f32; 3]],
+    normals: &'a [[f32; 3]],
+    /// glTF convention (origin top-left); flipped on write.
+    uvs: &'a [[f32; 2]],
+    colors: &'a [[f32; 4]],
+    indices: &'a [u32],
+}
+
+/// Formats floats as a USD tuple, e.g. `(1, 2, 3)`.
+struct Tuple<const N: usize>([f32; N]);
+
+impl<const N: usize> Display for Tuple<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, v) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{v}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Indented `.usda` text writer.
+struct Usda {
+    out: BufWriter<std::fs::File>,
+    depth: usize,
+}
+
+impl Usda {
+    /// Create the layer and write its header with `default_prim`.
+    fn create(path: &Path, default_prim: &str) -> io::Result<Self> {
+        let mut usda = Self {
+            out: BufWriter::new(std::fs::File::create(path)?),
+            depth: 0,
+        };
+        usda.line("#usda 1.0")?;
+        usda.line("(")?;
+        usda.line(format_args!("    defaultPrim = \"{default_prim}\""))?;
+        usda.line("    doc = \"Generated by wowsunpack\"")?;
+        usda.line("    metersPerUnit = 1")?;
+        usda.line("    upAxis = \"Y\"")?;
+        usda.line(")")?;
+        usda.line("")?;
+        Ok(usda)
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn indent(&mut self) -> io::Result<()> {
+        for _ in 0..self.depth {
+            self.out.write_all(b"    ")?;
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, text: impl Display) -> io::Result<()> {
+        self.indent()?;
+        writeln!(self.out, "{text}")
+    }
+
+    /// Open a prim block, e.g. `def Xform "Hull"`, with optional metadata entries.
+    fn open_prim(&mut self, specifier: &str, name: &str, metadata: &[&str]) -> io::Result<()> {
+        if metadata.is_empty() {
+            self.line(format_args!("{specifier} \"{name}\""))?;
+        } else {
+            self.line(format_args!("{specifier} \"{name}\" ("))?;
+            for entry in metadata {
+                self.line(format_args!("    {entry}"))?;
+            }
+            self.line(")")?;
+        }
+        self.line("{")?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.depth -= 1;
+        self.line("}")
+    }
+
+    /// Write `decl = [a, b, ...]` on one line.
+    fn array<T: Display>(
+        &mut self,
+        decl: &str,
+        items: impl IntoIterator<Item = T>,
+    ) -> io::Result<()> {
+        self.indent()?;
+        write!(self.out, "{decl} = [")?;
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.out.write_all(b", ")?;
+            }
+            write!(self.out, "{item}")?;
+        }
+        writeln!(self.out, "]")
+    }
+
+    /// Like [`Self::array`], for a primvar with per-vertex interpolation.
+    fn vertex_array<T: Display>(
+        &mut self,
+        decl: &str,
+        items: impl IntoIterator<Item = T>,
+    ) -> io::Result<()> {
+        self.indent()?;
+        write!(self.out, "{decl} = [")?;
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.out.write_all(b", ")?;
+            }
+            write!(self.out, "{item}")?;
+        }
+        writeln!(self.out, "] (")?;
+        self.line("    interpolation = \"vertex\"")?;
+        self.line(")")
+    }
+
+    /// Column-major 4x4 as `xformOp:transform`. USD matrices use row vectors,
+    /// so the column-major array is already in USD's row order.
+    fn transform(&mut self, m: &[f32; 16]) -> io::Result<()> {
+        self.line(format_args!(
+            "matrix4d xformOp:transform = ( {}, {}, {}, {} )",
+            Tuple([m[0], m[1], m[2], m[3]]),
+            Tuple([m[4], m[5], m[6], m[7]]),
+            Tuple([m[8], m[9], m[10], m[11]]),
+            Tuple([m[12], m[13], m[14], m[15]]),
+        ))?;
+        self.line("uniform token[] xformOpOrder = [\"xformOp:transform\"]")
+    }
+
+    /// Write a triangle `Mesh` prim, bound to `material` if given.
+    fn mesh(
+        &mut self,
+        name: &str,
+        mesh: &MeshData<'_>,
+        material: Option<&str>,
+        transform: Option<&[f32; 16]>,
+    ) -> io::Result<()> {
+        let metadata: &[&str] = if material.is_some() {
+            &["prepend apiSchemas = [\"MaterialBindingAPI\"]"]
+        } else {
+            &[]
+        };
+        self.open_prim("def Mesh", name, metadata)?;
+        if let Some(m) = transform {
+            self.transform(m)?;
+        }
+
+        let vertex_count = mesh.positions.len();
+        let tri_count = mesh.indices.len() / 3;
+        if vertex_count > 0 {
+            let mut min = [f32::MAX; 3];
+            let mut max = [f32::MIN; 3];
+            for p in mesh.positions {
+                for i in 0..3 {
+                    min[i] = min[i].min(p[i]);
+                    max[i] = max[i].max(p[i]);
+                }
+            }
+            self.line(format_args!(
+                "float3[] extent = [{}, {}]",
+                Tuple(min),
+                Tuple(max)
+            ))?;
+        }
+        self.array("int[] faceVertexCounts", std::iter::repeat_n(3, tri_count))?;
+        self.array("int[] faceVertexIndices", &mesh.indices[..tri_count * 3])?;
+        self.array("point3f[] points", mesh.positions.iter().map(|&p| Tuple(p)))?;
+        if mesh.normals.len() == vertex_count {
+            self.vertex_array("normal3f[] normals", mesh.normals.iter().map(|&n| Tuple(n)))?;
+        }
+        if mesh.uvs.len() == vertex_count {
+            self.vertex_array(
+                "texCoord2f[] primvars:st",
+                mesh.uvs.iter().map(|&[u, v]| Tuple([u, 1.0 - v])),
+            )?;
+        }
+        if mesh.colors.len() == vertex_count && vertex_count > 0 {
+            self.vertex_array(
+                "color3f[] primvars:displayColor",
+                mesh.colors.iter().map(|&[r, g, b, _]| Tuple([r, g, b])),
+            )?;
+        }
+        if let Some(material) = material {
+            self.line(format_args!("rel material:binding = <{material}>"))?;
+        }
+        self.line("uniform token subdivisionScheme = \"none\"")?;
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::export::gltf_export::{MapModelInstance, SpaceBounds};
+
+    #[test]
+    fn decompose_rotation_scale_translation() {
+        // 90° about +Y, uniform scale 2, translated to (1, 2, 3). Column-major.
+        let m = [
+            0.0, 0.0, -2.0, 0.0, //
+            0.0, 2.0, 0.0, 0.0, //
+            2.0, 0.0, 0.0, 0.0, //
+            1.0, 2.0, 3.0, 1.0,
+        ];
+        let (t, q, s) = decompose_transform(&m);
+        assert_eq!(t, [1.0, 2.0, 3.0]);
+        assert_eq!(s, [2.0, 2.0, 2.0]);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        for (got, want) in q.iter().zip([half, 0.0, half, 0.0]) {
+            assert!((got - want).abs() < 1e-6, "{q:?}");
+        }
+    }
+
+    #[test]
+    fn map_scene_layer_matches_golden() {
+        let triangle = |name: &str| MapMesh {
+            name: name.to_string(),
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+            normals: vec![[0.0, 1.0, 0.0]; 3],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]],
+            indices: vec![0, 1, 2],
+            albedo_texture: None,
+            base_color: [1.0; 4],
+            alpha_blend: false,
+            alpha_cutoff: None,
+            joints: Vec::new(),
+            weights: Vec::new(),
+        };
+        let mut translated = [0.0; 16];
+        for i in [0, 5, 10, 15] {
+            translated[i] = 1.0;
+        }
+        translated[12] = 5.0;
+        let scene = MapScene {
+            model_meshes: vec![MapMesh {
+                albedo_texture: Some(0),
+                alpha_cutoff: Some(0.5),
+                ..triangle("Rock")
+            }],
+            model_instances: vec![MapModelInstance {
+                mesh_range: 0..1,
+                transform: translated,
+                animated_model: None,
+            }],
+            animated_models: Vec::new(),
+            textures: vec![Arc::new(b"png".to_vec())],
+            terrain: None,
+            water: Some(MapMesh {
+                base_color: [0.0, 0.5, 1.0, 0.5],
+                alpha_blend: true,
+                ..triangle("Water")
+            }),
+            bounds: SpaceBounds {
+                min_x: 0.0,
+                max_x: 100.0,
+                min_z: 0.0,
+                max_z: 100.0,
+            },
+            vegetation_instances: Vec::new(),
+        };
+
+        let dir = std::env::temp_dir().join(format!("usd_export_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        export_map_scene_usda(&scene, &dir.join("map.usda")).unwrap();
+        let usda = std::fs::read_to_string(dir.join("map.usda")).unwrap();
+        let texture = std::fs::read(dir.join("map_textures/texture_0.png")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(texture, b"png");
+        assert_eq!(usda, GOLDEN_MAP_USDA);
+    }
+
+    const GOLDEN_MAP_USDA: &str = r#"#usda 1.0
+(
+    defaultPrim = "Map"
+    doc = "Generated by wowsunpack"
+    metersPerUnit = 1
+    upAxis = "Y"
+)
+
+def Xform "Map"
+{
+    def Scope "Materials"
+    {
+        def Material "Texture_0_Cutout50"
+        {
+            token outputs:surface.connect = </Map/Materials/Texture_0_Cutout50/Surface.outputs:surface>
+            def Shader "Surface"
+            {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor.connect = </Map/Materials/Texture_0_Cutout50/Texture.outputs:rgb>
+                float inputs:opacity.connect = </Map/Materials/Texture_0_Cutout50/Texture.outputs:a>
+                float inputs:opacityThreshold = 0.5
+                float inputs:metallic = 0
+                float inputs:roughness = 0.8
+                token outputs:surface
+            }
+            def Shader "Texture"
+            {
+                uniform token info:id = "UsdUVTexture"
+                asset inputs:file = @./map_textures/texture_0.png@
+                float2 inputs:st.connect = </Map/Materials/Texture_0_Cutout50/StTransform.outputs:result>
+                token inputs:wrapS = "repeat"
+                token inputs:wrapT = "repeat"
+                float3 outputs:rgb
+                float outputs:a
+            }
+            def Shader "StTransform"
+            {
+                uniform token info:id = "UsdTransform2d"
+                float2 inputs:in.connect = </Map/Materials/Texture_0_Cutout50/StReader.outputs:result>
+                float2 outputs:result
+            }
+            def Shader "StReader"
+            {
+                uniform token info:id = "UsdPrimvarReader_float2"
+                string inputs:varname = "st"
+                float2 outputs:result
+            }
+        }
+        def Material "Color_0080ff80_Blend"
+        {
+            token outputs:surface.connect = </Map/Materials/Color_0080ff80_Blend/Surface.outputs:surface>
+            def Shader "Surface"
+            {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor = (0, 0.5, 1)
+                float inputs:opacity = 0.5
+                float inputs:metallic = 0
+                float inputs:roughness = 0.8
+                token outputs:surface
+            }
+            def Shader "Texture"
+            {
+                uniform token info:id = "UsdUVTexture"
+                float2 inputs:st.connect = </Map/Materials/Color_0080ff80_Blend/StTransform.outputs:result>
+                token inputs:wrapS = "repeat"
+                token inputs:wrapT = "repeat"
+                float3 outputs:rgb
+                float outputs:a
+            }
+            def Shader "StTransform"
+            {
+                uniform token info:id = "UsdTransform2d"
+                float2 inputs:in.connect = </Map/Materials/Color_0080ff80_Blend/StReader.outputs:result>
+                float2 outputs:result
+            }
+            def Shader "StReader"
+            {
+                uniform token info:id = "UsdPrimvarReader_float2"
+                string inputs:varname = "st"
+                float2 outputs:result
+            }
+        }
+    }
+    def PointInstancer "Models"
+    {
+        rel prototypes = [</Map/Models/Prototypes/Model_0>]
+        int[] protoIndices = [0]
+        point3f[] positions = [(5, 0, 0)]
+        quath[] orientations = [(1, 0, 0, 0)]
+        float3[] scales = [(1, 1, 1)]
+        def Scope "Prototypes"
+        {
+            def Xform "Model_0"
+            {
+                def Mesh "Rock" (
+                    prepend apiSchemas = ["MaterialBindingAPI"]
+                )
+                {
+                    float3[] extent = [(0, 0, -1), (1, 0, 0)]
+                    int[] faceVertexCounts = [3]
+                    int[] faceVertexIndices = [0, 1, 2]
+                    point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, -1)]
+                    normal3f[] normals = [(0, 1, 0), (0, 1, 0), (0, 1, 0)] (
+                        interpolation = "vertex"
+                    )
+                    texCoord2f[] primvars:st = [(0, 1), (1, 1), (0, 0)] (
+                        interpolation = "vertex"
+                    )
+                    rel material:binding = </Map/Materials/Texture_0_Cutout50>
+                    uniform token subdivisionScheme = "none"
+                }
+            }
+        }
+    }
+    def Mesh "Water" (
+        prepend apiSchemas = ["MaterialBindingAPI"]
+    )
+    {
+        float3[] extent = [(0, 0, -1), (1, 0, 0)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, -1)]
+        normal3f[] normals = [(0, 1, 0), (0, 1, 0), (0, 1, 0)] (
+            interpolation = "vertex"
+        )
+        texCoord2f[] primvars:st = [(0, 1), (1, 1), (0, 0)] (
+            interpolation = "vertex"
+        )
+        rel material:binding = </Map/Materials/Color_0080ff80_Blend>
+        uniform token subdivisionScheme = "none"
+    }
+}
+"#;
+}
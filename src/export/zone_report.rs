@@ -0,0 +1,342 @@
+//! Per-zone volume, armor and HP summary of a ship.
+//!
+//! Combines GameParams hit locations (HP pools and splash box names), the
+//! hull's `.splash` boxes and the armor meshes into a [`ZoneReport`] that can
+//! be serialized to JSON and compared between ships.
+//!
+//! Geometry is in ship-model units ([`ShipModelDistance`]); the report
+//! converts everything to meters.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::game_params::types::{HitLocation, Meters, ShipModelDistance};
+use crate::models::geometry::SplashBox;
+
+use super::gltf_export::InteractiveArmorMesh;
+
+/// Lower bounds of the armor thickness buckets, in mm.
+const THICKNESS_BUCKETS: [f32; 10] = [
+    0.0, 16.0, 26.0, 33.0, 51.0, 76.0, 101.0, 151.0, 251.0, 351.0,
+];
+
+/// Hit-location name of the citadel.
+const CITADEL: &str = "Cit";
+
+/// Summary of a ship's hit-location zones and armor.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneReport {
+    /// Model directory name, e.g. "JSB039_Yamato_1945".
+    pub ship: String,
+    /// Draft of the selected hull upgrade.
+    pub draft_m: Option<Meters>,
+    /// Model-space height of the waterline, estimated as the lowest hull
+    /// armor point plus the draft.
+    pub waterline_y_m: Option<Meters>,
+    /// Hit-location zones, sorted by name.
+    pub zones: Vec<ZoneStats>,
+    /// Armor surface area per armor zone (see
+    /// [`zone_from_material_name`](super::gltf_export::zone_from_material_name)),
+    /// sorted by zone. Plates the armor viewer hides are left out.
+    pub armor: Vec<ArmorZoneStats>,
+    /// Vertical extent of the citadel splash boxes.
+    pub citadel: Option<CitadelHeight>,
+}
+
+/// One GameParams hit location with its splash box volume.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneStats {
+    /// Hit-location name (e.g. "Bow", "Cit", "SS").
+    pub name: String,
+    pub hl_type: String,
+    pub max_hp: f32,
+    /// Fraction of damage to this zone that can be healed.
+    pub regenerated_hp_part: f32,
+    /// `max_hp * regenerated_hp_part`.
+    pub regenerable_hp: f32,
+    /// Default plating thickness in mm.
+    pub thickness_mm: f32,
+    /// Total volume of the zone's splash boxes in m³. Overlapping boxes are
+    /// counted twice.
+    pub volume_m3: f32,
+    /// Combined bounds of the splash boxes, in meters.
+    pub bounds_m: Option<([Meters; 3], [Meters; 3])>,
+    /// Splash boxes found in the `.splash` file.
+    pub splash_boxes: Vec<String>,
+    /// Splash boxes named by GameParams but missing from the `.splash` file.
+    pub missing_splash_boxes: Vec<String>,
+}
+
+/// Armor surface area of one armor zone.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArmorZoneStats {
+    pub zone: String,
+    /// Total plate area in m².
+    pub area_m2: f32,
+    /// Area per thickness bucket; empty buckets are omitted.
+    pub by_thickness: Vec<ThicknessBucket>,
+}
+
+/// Plate area with thickness in `min_mm..max_mm` (`max_mm` is `None` for
+/// the last bucket).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThicknessBucket {
+    pub min_mm: f32,
+    pub max_mm: Option<f32>,
+    pub area_m2: f32,
+}
+
+/// Vertical extent of the citadel, in meters.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CitadelHeight {
+    /// Model-space height of the citadel top.
+    pub top_m: Meters,
+    /// Model-space height of the citadel bottom.
+    pub bottom_m: Meters,
+    /// Citadel top above the waterline; `None` without a draft.
+    pub top_above_waterline_m: Option<Meters>,
+    /// Citadel bottom below the waterline; `None` without a draft.
+    pub bottom_below_waterline_m: Option<Meters>,
+}
+
+/// Build a [`ZoneReport`]. Hull armor meshes are those without a transform.
+///
+/// Armor area skips triangles marked
+/// [`hidden`](super::gltf_export::ArmorTriangleInfo::hidden), and counts a
+/// plate modeled with both windings (front and back face) once per layer.
+pub fn build_zone_report(
+    ship: &str,
+    hit_locations: &HashMap<String, HitLocation>,
+    splash_boxes: &[SplashBox],
+    armor: &[InteractiveArmorMesh],
+    draft: Option<Meters>,
+) -> ZoneReport {
+    let boxes: HashMap<&str, &SplashBox> =
+        splash_boxes.iter().map(|b| (b.name.as_str(), b)).collect();
+
+    let mut zones: Vec<ZoneStats> = hit_locations
+        .iter()
+        .map(|(name, hl)| {
+            let (found, missing): (Vec<&String>, Vec<&String>) = hl
+                .splash_boxes()
+                .iter()
+                .partition(|n| boxes.contains_key(n.as_str()));
+            let found: Vec<&SplashBox> = found.iter().map(|n| boxes[n.as_str()]).collect();
+            let volume_m3: f32 = found
+                .iter()
+                .map(|b| {
+                    (0..3)
+                        .map(|i| model_to_meters(b.max[i] - b.min[i]).value().max(0.0))
+                        .product::<f32>()
+                })
+                .sum();
+            ZoneStats {
+                name: name.clone(),
+                hl_type: hl.hl_type().to_string(),
+                max_hp: hl.max_hp(),
+                regenerated_hp_part: hl.regenerated_hp_part(),
+                regenerable_hp: hl.max_hp() * hl.regenerated_hp_part(),
+                thickness_mm: hl.thickness(),
+                volume_m3,
+                bounds_m: box_bounds(&found)
+                    .map(|(min, max)| (min.map(model_to_meters), max.map(model_to_meters))),
+                splash_boxes: found.iter().map(|b| b.name.clone()).collect(),
+                missing_splash_boxes: missing.into_iter().cloned().collect(),
+            }
+        })
+        .collect();
+    zones.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Armor area per zone and thickness bucket.
+    let mut areas: BTreeMap<&str, [f32; THICKNESS_BUCKETS.len()]> = BTreeMap::new();
+    for mesh in armor {
+        // Triangles already counted, by layer and unordered vertex positions.
+        let mut counted = HashSet::new();
+        for (tri, info) in mesh.indices.chunks_exact(3).zip(&mesh.triangle_info) {
+            if info.hidden {
+                continue;
+            }
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+            let mut key = [a, b, c].map(|p| p.map(f32::to_bits));
+            key.sort();
+            if !counted.insert((info.model_index, key)) {
+                continue;
+            }
+            let [a, b, c] = [a, b, c].map(|p| p.map(|v| model_to_meters(v).value()));
+            let area = triangle_area(a, b, c);
+            let bucket = THICKNESS_BUCKETS
+                .iter()
+                .rposition(|&min| info.thickness_mm >= min)
+                .unwrap_or(0);
+            areas.entry(info.zone.as_str()).or_default()[bucket] += area;
+        }
+    }
+    let armor_stats = areas
+        .into_iter()
+        .map(|(zone, buckets)| ArmorZoneStats {
+            zone: zone.to_string(),
+            area_m2: buckets.iter().sum(),
+            by_thickness: buckets
+                .iter()
+                .enumerate()
+                .filter(|(_, area)| **area > 0.0)
+                .map(|(i, &area_m2)| ThicknessBucket {
+                    min_mm: THICKNESS_BUCKETS[i],
+                    max_mm: THICKNESS_BUCKETS.get(i + 1).copied(),
+                    area_m2,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let keel_y = armor
+        .iter()
+        .filter(|m| m.transform.is_none())
+        .flat_map(|m| m.positions.iter().map(|p| p[1]))
+        .reduce(f32::min);
+    let waterline_y_m = keel_y
+        .zip(draft)
+        .map(|(keel, draft)| model_to_meters(keel) + draft);
+
+    let citadel = zones
+        .iter()
+        .find(|z| z.name == CITADEL)
+        .and_then(|z| z.bounds_m)
+        .map(|(min, max)| CitadelHeight {
+            top_m: max[1],
+            bottom_m: min[1],
+            top_above_waterline_m: waterline_y_m.map(|w| max[1] - w),
+            bottom_below_waterline_m: waterline_y_m.map(|w| w - min[1]),
+        });
+
+    ZoneReport {
+        ship: ship.to_string(),
+        draft_m: draft,
+        waterline_y_m,
+        zones,
+        armor: armor_stats,
+        citadel,
+    }
+}
+
+fn model_to_meters(v: f32) -> Meters {
+    ShipModelDistance::from(v).to_meters()
+}
+
+fn box_bounds(boxes: &[&SplashBox]) -> Option<([f32; 3], [f32; 3])> {
+    boxes.iter().fold(None, |acc, b| {
+        Some(match acc {
+            None => (b.min, b.max),
+            Some((min, max)) => (
+                [0, 1, 2].map(|i| min[i].min(b.min[i])),
+                [0, 1, 2].map(|i| max[i].max(b.max[i])),
+            ),
+        })
+    })
+}
+
+fn triangle_area(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> f32 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::gltf_export::ArmorTriangleInfo;
+
+    #[test]
+    fn reports_volumes_areas_and_citadel_height() {
+        let splash = |name: &str, min, max| SplashBox {
+            name: name.to_string(),
+            min,
+            max,
+        };
+        let boxes = [
+            splash("CM_SB_cit_1", [-1.0, -2.0, -5.0], [1.0, 1.0, 0.0]),
+            splash("CM_SB_cit_2", [-1.0, -2.0, 0.0], [1.0, 1.0, 5.0]),
+        ];
+        let citadel = HitLocation::builder()
+            .max_hp(10_000.0)
+            .hl_type("CITADEL".to_string())
+            .regenerated_hp_part(0.1)
+            .thickness(19.0)
+            .splash_boxes(vec![
+                "CM_SB_cit_1".to_string(),
+                "CM_SB_cit_2".to_string(),
+                "CM_SB_cit_3".to_string(),
+            ])
+            .build();
+        let hit_locations = HashMap::from([("Cit".to_string(), citadel)]);
+
+        // One 1x1 (model units) right triangle of 100 mm citadel armor, keel at y = -3.
+        let info = ArmorTriangleInfo {
+            model_index: 1,
+            triangle_index: 0,
+            material_id: 0,
+            material_name: "Cit_Belt".to_string(),
+            zone: "Citadel".to_string(),
+            thickness_mm: 100.0,
+            layers: vec![100.0],
+            color: [1.0; 4],
+            hidden: false,
+        };
+        // Its back face (reversed winding) counts once; a hidden plate not at all.
+        let hidden = ArmorTriangleInfo {
+            zone: "Hull".to_string(),
+            hidden: true,
+            ..info.clone()
+        };
+        let armor = InteractiveArmorMesh {
+            name: "CM_PA_united".to_string(),
+            positions: vec![
+                [0.0, -3.0, 0.0],
+                [1.0, -3.0, 0.0],
+                [0.0, -2.0, 0.0],
+                [0.0, -3.0, 1.0],
+                [1.0, -3.0, 1.0],
+                [0.0, -2.0, 1.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 6],
+            indices: vec![0, 1, 2, 0, 2, 1, 3, 4, 5],
+            colors: vec![[1.0; 4]; 6],
+            triangle_info: vec![info.clone(), info, hidden],
+            transform: None,
+        };
+
+        let report = build_zone_report(
+            "TEST",
+            &hit_locations,
+            &boxes,
+            &[armor],
+            Some(Meters::new(8.0)),
+        );
+
+        let cit = &report.zones[0];
+        // Two 2x3x5 boxes, 8 m³ per cubic model unit.
+        assert_eq!(cit.volume_m3, 2.0 * 30.0 * 8.0);
+        assert_eq!(cit.regenerable_hp, 1000.0);
+        assert_eq!(cit.missing_splash_boxes, ["CM_SB_cit_3"]);
+
+        assert_eq!(report.armor.len(), 1);
+        assert_eq!(report.armor[0].zone, "Citadel");
+        assert_eq!(report.armor[0].area_m2, 2.0);
+        assert_eq!(report.armor[0].by_thickness[0].min_mm, 76.0);
+
+        // Keel at -6 m, waterline 8 m above; citadel spans -4 m..2 m.
+        assert_eq!(report.waterline_y_m, Some(Meters::new(2.0)));
+        let height = report.citadel.unwrap();
+        assert_eq!(height.top_above_waterline_m, Some(Meters::new(0.0)));
+        assert_eq!(height.bottom_below_waterline_m, Some(Meters::new(6.0)));
+    }
+}
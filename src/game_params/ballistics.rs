@@ -0,0 +1,268 @@
+//! External ballistics for artillery shells.
+//!
+//! Integrates a shell's flight with quadratic air drag in a standard
+//! atmosphere (air density falling with altitude), using the shell's
+//! `bulletAirDrag`, mass, caliber and muzzle velocity. AP penetration uses the
+//! community-derived Krupp formula:
+//!
+//! ```text
+//! pen_mm = 0.5561613 * (krupp / 2400) * v^1.1 * mass^0.55 / caliber_mm^0.65
+//! ```
+//!
+//! These constants come from community reverse engineering and are not read
+//! from game files; results should match in-game values closely but not
+//! exactly.
+
+use super::types::{AmmoType, Meters, ShellInfo};
+
+/// Gravitational acceleration in m/s².
+const GRAVITY: f32 = 9.8;
+/// Sea-level temperature in K.
+const SEA_LEVEL_TEMPERATURE: f32 = 288.15;
+/// Temperature lapse rate in K/m.
+const LAPSE_RATE: f32 = 0.0065;
+/// Sea-level pressure in Pa.
+const SEA_LEVEL_PRESSURE: f32 = 101_325.0;
+/// Universal gas constant in J/(mol·K).
+const GAS_CONSTANT: f32 = 8.31447;
+/// Molar mass of dry air in kg/mol.
+const AIR_MOLAR_MASS: f32 = 0.0289644;
+/// Krupp penetration formula scale.
+const PENETRATION_SCALE: f32 = 0.5561613;
+
+/// Integration time step in seconds.
+const TIME_STEP: f32 = 0.01;
+/// Longest flight simulated, in steps (five minutes), so malformed shell
+/// parameters can't loop forever.
+const MAX_STEPS: u32 = 30_000;
+/// Elevation sweep step in degrees when building range tables.
+const ELEVATION_STEP: f32 = 0.05;
+/// Highest elevation considered when building range tables.
+const MAX_ELEVATION: f32 = 45.0;
+
+/// Where and how a shell lands when fired at a given gun elevation over a
+/// flat sea.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShellImpact {
+    /// Horizontal distance travelled.
+    pub range_m: Meters,
+    /// Gun elevation in degrees above the horizon.
+    pub elevation_deg: f32,
+    /// Simulated flight time in seconds.
+    pub flight_time_s: f32,
+    /// Shell speed at impact in m/s.
+    pub impact_velocity: f32,
+    /// Angle of descent below the horizon at impact, in degrees.
+    pub impact_angle_deg: f32,
+    /// Penetration against a plate perpendicular to the shell's path, in mm.
+    /// Krupp-derived for AP; the flat `he_pen_mm` / `sap_pen_mm` otherwise.
+    pub penetration_mm: f32,
+}
+
+/// Air density in kg/m³ at `altitude` meters.
+fn air_density(altitude: f32) -> f32 {
+    let altitude = altitude.max(0.0);
+    let temperature = SEA_LEVEL_TEMPERATURE - LAPSE_RATE * altitude;
+    let pressure = SEA_LEVEL_PRESSURE
+        * (1.0 - LAPSE_RATE * altitude / SEA_LEVEL_TEMPERATURE)
+            .powf(GRAVITY * AIR_MOLAR_MASS / (GAS_CONSTANT * LAPSE_RATE));
+    pressure * AIR_MOLAR_MASS / (GAS_CONSTANT * temperature)
+}
+
+/// AP penetration in mm of a shell at `velocity` m/s hitting a perpendicular
+/// plate.
+pub fn krupp_penetration(shell: &ShellInfo, velocity: f32) -> f32 {
+    let caliber_mm = shell.caliber.value();
+    if caliber_mm <= 0.0 {
+        return 0.0;
+    }
+    PENETRATION_SCALE * (shell.krupp / 2400.0) * velocity.powf(1.1) * shell.mass_kg.powf(0.55)
+        / caliber_mm.powf(0.65)
+}
+
+/// Penetration in mm at impact velocity: Krupp-derived for AP, the flat
+/// HE/SAP value otherwise.
+pub fn penetration_at(shell: &ShellInfo, velocity: f32) -> f32 {
+    match shell.ammo_type {
+        AmmoType::HE => shell.he_pen_mm.unwrap_or(0.0),
+        AmmoType::SAP => shell.sap_pen_mm.unwrap_or(0.0),
+        AmmoType::AP | AmmoType::Unknown(_) => krupp_penetration(shell, velocity),
+    }
+}
+
+/// Fly a shell fired from sea level at `elevation_deg` until it returns to
+/// sea level.
+///
+/// Returns `None` if the shell has no mass, caliber or muzzle velocity, has
+/// negative drag, any of these is NaN or infinite, the elevation is not
+/// positive, or the shell is still airborne after [`MAX_STEPS`] steps.
+pub fn simulate(shell: &ShellInfo, elevation_deg: f32) -> Option<ShellImpact> {
+    let caliber_m = shell.caliber.to_meters().value();
+    let inputs = [
+        shell.mass_kg,
+        caliber_m,
+        shell.muzzle_velocity,
+        shell.air_drag,
+        elevation_deg,
+    ];
+    if !inputs.iter().all(|v| v.is_finite())
+        || shell.mass_kg <= 0.0
+        || caliber_m <= 0.0
+        || shell.muzzle_velocity <= 0.0
+        || shell.air_drag < 0.0
+        || elevation_deg <= 0.0
+    {
+        return None;
+    }
+    // Drag deceleration is k * rho * v² along each axis.
+    let k = 0.5 * shell.air_drag * (caliber_m / 2.0).powi(2) * std::f32::consts::PI / shell.mass_kg;
+
+    let (sin, cos) = elevation_deg.to_radians().sin_cos();
+    let (mut x, mut y) = (0.0f32, 0.0f32);
+    let (mut vx, mut vy) = (shell.muzzle_velocity * cos, shell.muzzle_velocity * sin);
+    let mut t = 0.0f32;
+    for _ in 0..MAX_STEPS {
+        let (px, py, pvx, pvy) = (x, y, vx, vy);
+        x += vx * TIME_STEP;
+        y += vy * TIME_STEP;
+        let drag = k * air_density(y);
+        vx -= TIME_STEP * drag * vx * vx;
+        vy -= TIME_STEP * (GRAVITY + drag * vy * vy.abs());
+        t += TIME_STEP;
+
+        if y <= 0.0 {
+            // Interpolate the sea-level crossing within the last step.
+            let f = py / (py - y);
+            let lerp = |a: f32, b: f32| a + (b - a) * f;
+            let (vx, vy) = (lerp(pvx, vx), lerp(pvy, vy));
+            let velocity = vx.hypot(vy);
+            return Some(ShellImpact {
+                range_m: Meters::new(lerp(px, x)),
+                elevation_deg,
+                flight_time_s: t - TIME_STEP * (1.0 - f),
+                impact_velocity: velocity,
+                impact_angle_deg: (-vy).atan2(vx).to_degrees(),
+                penetration_mm: penetration_at(shell, velocity),
+            });
+        }
+    }
+    None
+}
+
+/// Range table for `shell` every `step` meters up to `max_range` (typically
+/// [`ShipRanges::main_battery_m`](super::types::ShipRanges::main_battery_m)).
+///
+/// Elevations are swept upward and each row is interpolated between the two
+/// elevations bracketing its range. Rows stop early if the shell cannot reach
+/// `max_range`.
+pub fn range_table(shell: &ShellInfo, max_range: Meters, step: Meters) -> Vec<ShellImpact> {
+    let mut rows = Vec::new();
+    let step = step.value();
+    if step <= 0.0 {
+        return rows;
+    }
+
+    // At zero range the shell is still at the muzzle.
+    let mut prev = ShellImpact {
+        range_m: Meters::new(0.0),
+        elevation_deg: 0.0,
+        flight_time_s: 0.0,
+        impact_velocity: shell.muzzle_velocity,
+        impact_angle_deg: 0.0,
+        penetration_mm: penetration_at(shell, shell.muzzle_velocity),
+    };
+    let mut next_range = step;
+    let mut elevation = ELEVATION_STEP;
+    while elevation <= MAX_ELEVATION && next_range <= max_range.value() {
+        let Some(impact) = simulate(shell, elevation) else {
+            break;
+        };
+        if impact.range_m <= prev.range_m {
+            // Past the maximum-range elevation.
+            break;
+        }
+        while next_range <= impact.range_m.value() && next_range <= max_range.value() {
+            rows.push(interpolate(&prev, &impact, next_range));
+            next_range += step;
+        }
+        prev = impact;
+        elevation += ELEVATION_STEP;
+    }
+    rows
+}
+
+fn interpolate(a: &ShellImpact, b: &ShellImpact, range: f32) -> ShellImpact {
+    let f = (range - a.range_m.value()) / (b.range_m.value() - a.range_m.value());
+    let lerp = |a: f32, b: f32| a + (b - a) * f;
+    ShellImpact {
+        range_m: Meters::new(range),
+        elevation_deg: lerp(a.elevation_deg, b.elevation_deg),
+        flight_time_s: lerp(a.flight_time_s, b.flight_time_s),
+        impact_velocity: lerp(a.impact_velocity, b.impact_velocity),
+        impact_angle_deg: lerp(a.impact_angle_deg, b.impact_angle_deg),
+        penetration_mm: lerp(a.penetration_mm, b.penetration_mm),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::game_params::types::Millimeters;
+
+    /// Roughly a 460 mm AP shell.
+    pub(crate) fn ap_shell() -> ShellInfo {
+        ShellInfo {
+            name: "test".to_string(),
+            ammo_type: AmmoType::AP,
+            caliber: Millimeters::new(460.0),
+            he_pen_mm: None,
+            sap_pen_mm: None,
+            alpha_damage: 14_800.0,
+            muzzle_velocity: 780.0,
+            mass_kg: 1460.0,
+            krupp: 2574.0,
+            ricochet_angle: 45.0,
+            always_ricochet_angle: 60.0,
+            fuse_time: 0.033,
+            fuse_threshold: 76.0,
+            burn_prob: -0.5,
+            air_drag: 0.292,
+            normalization: 6.0,
+        }
+    }
+
+    #[test]
+    fn range_table_slows_and_steepens_with_range() {
+        let shell = ap_shell();
+        let rows = range_table(&shell, Meters::new(20_000.0), Meters::new(5_000.0));
+        let ranges: Vec<f32> = rows.iter().map(|r| r.range_m.value()).collect();
+        assert_eq!(ranges, [5_000.0, 10_000.0, 15_000.0, 20_000.0]);
+        for pair in rows.windows(2) {
+            assert!(pair[1].flight_time_s > pair[0].flight_time_s);
+            assert!(pair[1].impact_velocity < pair[0].impact_velocity);
+            assert!(pair[1].impact_angle_deg > pair[0].impact_angle_deg);
+            assert!(pair[1].penetration_mm < pair[0].penetration_mm);
+        }
+        assert!(rows[0].impact_velocity < 780.0);
+        assert!(rows[0].penetration_mm < krupp_penetration(&shell, 780.0));
+    }
+
+    #[test]
+    fn simulate_rejects_non_finite_and_endless_flights() {
+        assert!(simulate(&ap_shell(), 10.0).is_some());
+        assert!(simulate(&ap_shell(), f32::NAN).is_none());
+        let nan_drag = ShellInfo {
+            air_drag: f32::NAN,
+            ..ap_shell()
+        };
+        assert!(simulate(&nan_drag, 10.0).is_none());
+        // Straight up without drag stays airborne for well over five minutes.
+        let endless = ShellInfo {
+            air_drag: 0.0,
+            muzzle_velocity: 5_000.0,
+            ..ap_shell()
+        };
+        assert!(simulate(&endless, 90.0).is_none());
+    }
+}
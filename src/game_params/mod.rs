@@ -1,5 +1,7 @@
+pub mod ballistics;
 pub mod convert;
 pub mod keys;
+pub mod penetration;
 pub mod provider;
 pub mod translations;
 pub mod types;
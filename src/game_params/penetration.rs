@@ -0,0 +1,174 @@
+//! Outcome of a single shell impact against an armor plate.
+//!
+//! Applies the usual rules in order:
+//!
+//! 1. **Overmatch**: a caliber more than 14.3× the plate thickness cannot
+//!    ricochet off it.
+//! 2. **Ricochet**: at or past `always_ricochet_angle` from the plate normal
+//!    the shell always ricochets; between `ricochet_angle` and that, it
+//!    ricochets with a chance rising linearly to 1.
+//! 3. **Normalization**: the impact angle is reduced by `normalization`
+//!    degrees before computing effective thickness.
+//! 4. **Shatter**: penetration below the effective thickness.
+//! 5. **Fuse**: the fuse arms if the effective thickness reaches
+//!    `fuse_threshold`; the shell then detonates `fuse_time` seconds later at
+//!    its post-penetration speed. Otherwise it overpenetrates.
+//!
+//! Post-penetration speed uses the community-derived
+//! `v * (1 - exp(1 - penetration / effective_thickness))`.
+
+use super::ballistics::penetration_at;
+use super::types::{AmmoType, ShellInfo};
+
+/// A shell overmatches plates thinner than caliber / this factor.
+pub const OVERMATCH_FACTOR: f32 = 14.3;
+
+/// What a shell does after hitting a plate.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImpactOutcome {
+    /// The shell bounced off.
+    Ricochet,
+    /// The shell failed to penetrate.
+    Shatter,
+    /// The shell penetrated without arming its fuse and keeps flying.
+    Overpenetration,
+    /// The shell penetrated with an armed fuse and detonates this far behind
+    /// the plate.
+    FuseArmed { detonation_distance_m: f32 },
+}
+
+/// Classification of one impact, with the intermediate values behind it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImpactClassification {
+    pub outcome: ImpactOutcome,
+    /// Whether the caliber overmatches the plate (no ricochet possible).
+    pub overmatch: bool,
+    /// Chance of ricochet at this angle, 0..=1. Impacts with a chance below 1
+    /// are classified as if the shell did not ricochet.
+    pub ricochet_chance: f32,
+    /// Impact angle from the plate normal after normalization, in degrees.
+    pub normalized_angle_deg: f32,
+    /// Plate thickness along the normalized path, in mm.
+    pub effective_thickness_mm: f32,
+    /// Shell penetration at the impact velocity, in mm.
+    pub penetration_mm: f32,
+    /// Shell speed after the plate in m/s (0 unless it penetrated).
+    pub residual_velocity: f32,
+}
+
+/// Whether a shell of `caliber_mm` overmatches a plate of `thickness_mm`.
+pub fn overmatches(caliber_mm: f32, thickness_mm: f32) -> bool {
+    caliber_mm > thickness_mm * OVERMATCH_FACTOR
+}
+
+/// Classify a hit of `shell` at `velocity` m/s on a plate `thickness_mm`
+/// thick, `impact_angle_deg` away from the plate normal (0 = perpendicular).
+///
+/// Models AP shells. HE shells never ricochet or normalize; HE and SAP
+/// compare their flat penetration against the plate and detonate on impact.
+pub fn classify_impact(
+    shell: &ShellInfo,
+    thickness_mm: f32,
+    impact_angle_deg: f32,
+    velocity: f32,
+) -> ImpactClassification {
+    let angle = impact_angle_deg.clamp(0.0, 90.0);
+    let overmatch = overmatches(shell.caliber.value(), thickness_mm);
+    let ricochet_chance = if overmatch || shell.ammo_type == AmmoType::HE {
+        0.0
+    } else if angle >= shell.always_ricochet_angle {
+        1.0
+    } else if angle > shell.ricochet_angle {
+        (angle - shell.ricochet_angle) / (shell.always_ricochet_angle - shell.ricochet_angle)
+    } else {
+        0.0
+    };
+
+    let is_ap = matches!(shell.ammo_type, AmmoType::AP | AmmoType::Unknown(_));
+    let normalized_angle_deg = if is_ap {
+        (angle - shell.normalization).max(0.0)
+    } else {
+        angle
+    };
+    let cos = normalized_angle_deg.to_radians().cos();
+    let effective_thickness_mm = if is_ap {
+        thickness_mm / cos
+    } else {
+        thickness_mm
+    };
+    let penetration_mm = penetration_at(shell, velocity);
+
+    let mut result = ImpactClassification {
+        outcome: ImpactOutcome::Ricochet,
+        overmatch,
+        ricochet_chance,
+        normalized_angle_deg,
+        effective_thickness_mm,
+        penetration_mm,
+        residual_velocity: 0.0,
+    };
+    if ricochet_chance >= 1.0 {
+        return result;
+    }
+    if penetration_mm < effective_thickness_mm {
+        result.outcome = ImpactOutcome::Shatter;
+        return result;
+    }
+
+    if !is_ap {
+        result.outcome = ImpactOutcome::FuseArmed {
+            detonation_distance_m: 0.0,
+        };
+        return result;
+    }
+    result.residual_velocity = if effective_thickness_mm > 0.0 {
+        velocity * (1.0 - (1.0 - penetration_mm / effective_thickness_mm).exp())
+    } else {
+        velocity
+    };
+    result.outcome = if effective_thickness_mm >= shell.fuse_threshold {
+        ImpactOutcome::FuseArmed {
+            detonation_distance_m: result.residual_velocity * shell.fuse_time,
+        }
+    } else {
+        ImpactOutcome::Overpenetration
+    };
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_params::ballistics::tests::ap_shell;
+
+    #[test]
+    fn classifies_ap_outcomes() {
+        let shell = ap_shell();
+        let outcome = |thickness, angle| classify_impact(&shell, thickness, angle, 600.0);
+
+        // 32 mm is overmatched (460 / 14.3 ≈ 32.2) even at a grazing angle.
+        let hit = outcome(32.0, 80.0);
+        assert!(hit.overmatch);
+        assert!(matches!(hit.outcome, ImpactOutcome::FuseArmed { .. }));
+
+        assert_eq!(outcome(50.0, 65.0).outcome, ImpactOutcome::Ricochet);
+        let partial = outcome(60.0, 52.5);
+        assert_eq!(partial.ricochet_chance, 0.5);
+        assert!(matches!(partial.outcome, ImpactOutcome::FuseArmed { .. }));
+
+        // Thin plate below the fuse threshold.
+        assert_eq!(outcome(25.0, 0.0).outcome, ImpactOutcome::Overpenetration);
+        // Far more armor than the shell can penetrate.
+        assert_eq!(outcome(1000.0, 0.0).outcome, ImpactOutcome::Shatter);
+
+        let ImpactOutcome::FuseArmed {
+            detonation_distance_m,
+        } = outcome(300.0, 20.0).outcome
+        else {
+            panic!("expected armed fuse");
+        };
+        assert!(detonation_distance_m > 0.0 && detonation_distance_m < 600.0 * 0.033);
+    }
+}
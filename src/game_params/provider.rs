@@ -628,45 +628,49 @@ fn read_string(dict: &BTreeMap<HashableValue, Value>, key: &str) -> Option<Strin
         .map(|s| s.inner().to_string())
 }
 
-/// Extract `pitchDeadZones` from a mount dict.
-/// Each entry is `[yaw_min, yaw_max, pitch_min, pitch_max]` in degrees.
-fn parse_pitch_dead_zones(mount_dict: &BTreeMap<HashableValue, Value>) -> Vec<[f32; 4]> {
-    fn as_f32(v: &Value) -> Option<f32> {
-        v.f64_ref()
-            .map(|f| *f as f32)
-            .or_else(|| v.i64_ref().map(|i| *i as f32))
-    }
-    fn extract_entries(items: &[Value]) -> Vec<[f32; 4]> {
-        items
-            .iter()
-            .filter_map(|entry| {
-                let inner: Vec<f32> = if let Some(l) = entry.list_ref() {
-                    l.inner().iter().filter_map(as_f32).collect()
-                } else if let Some(t) = entry.tuple_ref() {
-                    t.inner().iter().filter_map(as_f32).collect()
-                } else {
-                    return None;
-                };
-                if inner.len() == 4 {
-                    Some([inner[0], inner[1], inner[2], inner[3]])
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-    let Some(val) = mount_dict.get(&pk("pitchDeadZones")) else {
+/// Helper: read a number from a pickled value, accepting both f64 and i64.
+fn value_as_f32(v: &Value) -> Option<f32> {
+    v.f64_ref()
+        .map(|f| *f as f32)
+        .or_else(|| v.i64_ref().map(|i| *i as f32))
+}
+
+/// Helper: read a list or tuple of exactly `N` numbers.
+fn value_as_floats<const N: usize>(v: &Value) -> Option<[f32; N]> {
+    let inner: Vec<f32> = if let Some(l) = v.list_ref() {
+        l.inner().iter().filter_map(value_as_f32).collect()
+    } else if let Some(t) = v.tuple_ref() {
+        t.inner().iter().filter_map(value_as_f32).collect()
+    } else {
+        return None;
+    };
+    inner.try_into().ok()
+}
+
+/// Helper: read a list or tuple of `N`-number entries from a pickled dict,
+/// skipping malformed entries.
+fn read_float_tuples<const N: usize>(
+    dict: &BTreeMap<HashableValue, Value>,
+    key: &str,
+) -> Vec<[f32; N]> {
+    let Some(val) = dict.get(&pk(key)) else {
         return Vec::new();
     };
     if let Some(l) = val.list_ref() {
-        extract_entries(&l.inner())
+        l.inner().iter().filter_map(value_as_floats).collect()
     } else if let Some(t) = val.tuple_ref() {
-        extract_entries(t.inner())
+        t.inner().iter().filter_map(value_as_floats).collect()
     } else {
         Vec::new()
     }
 }
 
+/// Extract `pitchDeadZones` from a mount dict.
+/// Each entry is `[yaw_min, yaw_max, pitch_min, pitch_max]` in degrees.
+fn parse_pitch_dead_zones(mount_dict: &BTreeMap<HashableValue, Value>) -> Vec<[f32; 4]> {
+    read_float_tuples(mount_dict, "pitchDeadZones")
+}
+
 /// Parse a raw GameParams armor dict into an [`ArmorMap`].
 ///
 /// Raw keys are `(model_index << 16) | material_id`.  We group by `material_id`
@@ -744,13 +748,23 @@ fn extract_mounts(
             // Extract pitchDeadZones: list of [yaw_min, yaw_max, pitch_min, pitch_max].
             let pitch_dead_zones: Vec<[f32; 4]> = parse_pitch_dead_zones(&mount_inner);
 
-            Some(MountPoint::with_armor(
-                key_str.clone(),
-                model_path,
-                mount_armor,
-                species,
-                pitch_dead_zones,
-            ))
+            // Traverse limits `horizSector: [yaw_min, yaw_max]` and firing
+            // dead zones `deadZone: [[yaw_min, yaw_max], ...]`.
+            let horiz_sector = mount_inner
+                .get(&pk("horizSector"))
+                .and_then(value_as_floats::<2>);
+            let dead_zones: Vec<[f32; 2]> = read_float_tuples(&mount_inner, "deadZone");
+
+            Some(
+                MountPoint::with_armor(
+                    key_str.clone(),
+                    model_path,
+                    mount_armor,
+                    species,
+                    pitch_dead_zones,
+                )
+                .with_firing_sectors(horiz_sector, dead_zones),
+            )
         })
         .collect()
 }
@@ -1340,11 +1354,13 @@ impl GameMetadataProvider {
                                     } else {
                                         PlaneCategory::Controllable
                                     };
-                                    // Resolve ammo type: bombName -> projectile dict -> ammoType
-                                    let ammo_type = param_data
+                                    let model_path = read_string(&param_data, keys::MODEL);
+                                    let bomb_name = param_data
                                         .get(&pk("bombName"))
                                         .and_then(|v| v.string_ref())
-                                        .filter(|s| !s.inner().is_empty())
+                                        .filter(|s| !s.inner().is_empty());
+                                    // Resolve ammo type: bombName -> projectile dict -> ammoType
+                                    let ammo_type = bomb_name
                                         .and_then(|bomb_name| {
                                             params_dict.inner()
                                                 .get(&HashableValue::String(bomb_name.clone()))
@@ -1360,6 +1376,8 @@ impl GameMetadataProvider {
                                     Some(ParamData::Aircraft(Aircraft::builder()
                                         .category(category)
                                         .ammo_type(ammo_type)
+                                        .maybe_model_path(model_path)
+                                        .maybe_bomb_name(bomb_name.map(|s| s.inner().to_string()))
                                         .build()))
                                 },
                                 ParamType::Projectile => {
@@ -1400,6 +1418,7 @@ impl GameMetadataProvider {
                                     let alpha_damage = read_opt_f32("alphaDamage");
                                     let burn_prob = read_opt_f32("burnProb");
                                     let bullet_air_drag = read_opt_f32("bulletAirDrag");
+                                    let model_path = read_string(&param_data, keys::MODEL);
                                     Some(ParamData::Projectile(Projectile::builder()
                                         .ammo_type(ammo_type)
                                         .maybe_max_dist(max_dist)
@@ -1418,6 +1437,7 @@ impl GameMetadataProvider {
                                         .maybe_alpha_damage(alpha_damage)
                                         .maybe_burn_prob(burn_prob)
                                         .maybe_bullet_air_drag(bullet_air_drag)
+                                        .maybe_model_path(model_path)
                                         .build()))
                                 },
                                 _ => {
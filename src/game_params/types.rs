@@ -98,6 +98,17 @@ impl From<i32> for BigWorldDistance {
     }
 }
 
+impl From<f32> for ShipModelDistance {
+    fn from(v: f32) -> Self {
+        Self(v)
+    }
+}
+impl From<i32> for ShipModelDistance {
+    fn from(v: i32) -> Self {
+        Self(v as f32)
+    }
+}
+
 impl From<f32> for Km {
     fn from(v: f32) -> Self {
         Self(v)
@@ -989,6 +1000,20 @@ pub struct MountPoint {
         serde(default, skip_serializing_if = "Vec::is_empty")
     )]
     pitch_dead_zones: Vec<[f32; 4]>,
+    /// Traverse limits `[yaw_min_deg, yaw_max_deg]` relative to the mount's
+    /// rest direction (`horizSector`). `None` if the mount has no limits.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    horiz_sector: Option<[f32; 2]>,
+    /// Yaw ranges `[yaw_min_deg, yaw_max_deg]` the mount can traverse through
+    /// but not fire in (`deadZone`), in the same frame as `horiz_sector`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    dead_zones: Vec<[f32; 2]>,
 }
 
 impl MountPoint {
@@ -999,6 +1024,8 @@ impl MountPoint {
             mount_armor: None,
             species: None,
             pitch_dead_zones: Vec::new(),
+            horiz_sector: None,
+            dead_zones: Vec::new(),
         }
     }
 
@@ -1015,9 +1042,22 @@ impl MountPoint {
             mount_armor,
             species,
             pitch_dead_zones,
+            horiz_sector: None,
+            dead_zones: Vec::new(),
         }
     }
 
+    /// Set the traverse limits and firing dead zones.
+    pub fn with_firing_sectors(
+        mut self,
+        horiz_sector: Option<[f32; 2]>,
+        dead_zones: Vec<[f32; 2]>,
+    ) -> Self {
+        self.horiz_sector = horiz_sector;
+        self.dead_zones = dead_zones;
+        self
+    }
+
     pub fn hp_name(&self) -> &str {
         &self.hp_name
     }
@@ -1038,6 +1078,17 @@ impl MountPoint {
         &self.pitch_dead_zones
     }
 
+    /// Traverse limits `[yaw_min_deg, yaw_max_deg]` relative to the mount's
+    /// rest direction.
+    pub fn horiz_sector(&self) -> Option<[f32; 2]> {
+        self.horiz_sector
+    }
+
+    /// Yaw ranges the mount can traverse through but not fire in.
+    pub fn dead_zones(&self) -> &[[f32; 2]] {
+        &self.dead_zones
+    }
+
     /// Get the minimum barrel elevation (degrees) at a given yaw angle (degrees)
     /// by checking pitch dead zones. Returns 0.0 if no dead zone applies.
     pub fn min_pitch_at_yaw(&self, yaw_deg: f32) -> f32 {
@@ -1930,6 +1981,12 @@ pub struct Aircraft {
     category: PlaneCategory,
     #[cfg_attr(feature = "serde", serde(default))]
     ammo_type: String,
+    /// Plane `.model` path.
+    #[cfg_attr(feature = "serde", serde(default))]
+    model_path: Option<String>,
+    /// Name of the Projectile param the plane drops or fires (bombName).
+    #[cfg_attr(feature = "serde", serde(default))]
+    bomb_name: Option<String>,
 }
 
 impl Aircraft {
@@ -1940,6 +1997,14 @@ impl Aircraft {
     pub fn ammo_type(&self) -> &str {
         &self.ammo_type
     }
+
+    pub fn model_path(&self) -> Option<&str> {
+        self.model_path.as_deref()
+    }
+
+    pub fn bomb_name(&self) -> Option<&str> {
+        self.bomb_name.as_deref()
+    }
 }
 
 // ─── Shell / Ammo Types ─────────────────────────────────────────────────────────────
@@ -2075,6 +2140,9 @@ pub struct Projectile {
     /// Air drag coefficient.
     #[cfg_attr(feature = "serde", serde(default))]
     bullet_air_drag: Option<f32>,
+    /// Projectile `.model` path. Present for bombs, torpedoes and rockets.
+    #[cfg_attr(feature = "serde", serde(default))]
+    model_path: Option<String>,
 }
 
 impl Projectile {
@@ -2146,6 +2214,10 @@ impl Projectile {
         self.bullet_air_drag
     }
 
+    pub fn model_path(&self) -> Option<&str> {
+        self.model_path.as_deref()
+    }
+
     /// Convert this projectile to a [`ShellInfo`] with the given name.
     ///
     /// This is the canonical way to build a `ShellInfo` from game data,
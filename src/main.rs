@@ -9,7 +9,7 @@ use std::{
     io::{BufWriter, Read, Write, stdout},
     path::{Path, PathBuf},
     sync::{
-        Mutex,
+        Arc, Mutex,
         atomic::{AtomicUsize, Ordering},
     },
     time::Instant,
@@ -25,6 +25,9 @@ use wowsunpack::data::{
     wrappers::mmap::MmapPkgSource,
 };
 use wowsunpack::export::gltf_export;
+use wowsunpack::export::gltf_split;
+use wowsunpack::export::mesh_formats::{self, MeshFormat, MeshScene};
+use wowsunpack::export::usd_export;
 use wowsunpack::game_params::convert::game_params_to_pickle;
 
 use clap::{Parser, Subcommand, ValueEnum};
@@ -60,6 +63,12 @@ struct Args {
     #[clap(short, long)]
     idx_files: Vec<PathBuf>,
 
+    /// Expose every assets.bin prototype as an additional `<path>.json` file
+    /// containing the decoded record, so it can be listed, extracted, or grepped
+    /// as readable data.
+    #[clap(long, global = true)]
+    assets_json: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -164,10 +173,19 @@ enum Commands {
         #[clap(long)]
         decode: bool,
 
+        /// Validate every mesh (indices, triangles, positions, normals, UVs,
+        /// mapping ranges) and print mesh statistics. Fails if any issue is found.
+        #[clap(long)]
+        check: bool,
+
         /// Read file from disk instead of VFS
         #[clap(long)]
         no_vfs: bool,
     },
+    /// Scan every .geometry file and check each vertex format's computed
+    /// stride against the `stride_in_bytes` stored with its buffers. Fails if
+    /// any format is unknown or mismatched.
+    AuditVertexFormats,
     /// Export a ship sub-model to GLB format
     ExportModel {
         /// Path to a .geometry file (VFS path by default, disk path with --no-vfs).
@@ -175,14 +193,25 @@ enum Commands {
         /// names, textures, and LOD filtering. Otherwise raw geometry is exported.
         file: PathBuf,
 
-        /// Output file path
-        #[arg(short, long, default_value = "output.glb")]
-        output: PathBuf,
+        /// Output file path [default: output.<format>]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format. OBJ and USD also write their PNG textures next to
+        /// the output, glTF into a `textures` directory next to it; STL and
+        /// PLY are untextured.
+        #[clap(long, default_value_t = ModelFormat::Glb, value_enum)]
+        format: ModelFormat,
 
         /// LOD level (0 = highest detail)
         #[arg(long, default_value = "0")]
         lod: usize,
 
+        /// Also export every lower-detail LOD after --lod, chained through
+        /// MSFT_lod with screen-coverage switch points (GLB and glTF only)
+        #[arg(long)]
+        all_lods: bool,
+
         /// Skip loading camouflage textures
         #[arg(long)]
         no_textures: bool,
@@ -195,10 +224,24 @@ enum Commands {
         #[arg(long)]
         list_textures: bool,
 
+        /// Export the skeleton as a glTF skin so parts can be posed
+        #[arg(long)]
+        skinned: bool,
+
         /// Read file from disk instead of VFS
         #[clap(long)]
         no_vfs: bool,
     },
+    /// Import a GLB file into a .geometry file and print the matching
+    /// render-set layout for its VisualPrototype.
+    ImportGlb {
+        /// Path to the .glb file on disk
+        file: PathBuf,
+
+        /// Output .geometry file path
+        #[arg(short, long, default_value = "output.geometry")]
+        output: PathBuf,
+    },
     /// Export all sub-models of a ship to a single GLB file.
     /// Each sub-model becomes a separate named object in Blender.
     ExportShip {
@@ -206,14 +249,25 @@ enum Commands {
         /// or a translated display name (e.g. "Yamato") for fuzzy lookup
         name: String,
 
-        /// Output file path
-        #[arg(short, long, default_value = "output.glb")]
-        output: PathBuf,
+        /// Output file path [default: output.<format>]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format. OBJ and USD also write their PNG textures next to
+        /// the output, glTF into a `textures` directory next to it; STL and
+        /// PLY are untextured.
+        #[clap(long, default_value_t = ModelFormat::Glb, value_enum)]
+        format: ModelFormat,
 
         /// LOD level (0 = highest detail)
         #[arg(long, default_value = "0")]
         lod: usize,
 
+        /// Also export every lower-detail LOD after --lod, chained through
+        /// MSFT_lod with screen-coverage switch points (GLB and glTF only)
+        #[arg(long)]
+        all_lods: bool,
+
         /// List available hull upgrades and their components, then exit
         #[arg(long)]
         list_upgrades: bool,
@@ -235,6 +289,25 @@ enum Commands {
         #[arg(long)]
         list_textures: bool,
 
+        /// Include collision hulls (CM_* models) as separate meshes
+        #[arg(long)]
+        collision: bool,
+
+        /// Include splash boxes as translucent boxes colored by hit location,
+        /// with zone name, HP and regeneration in node extras (glb and gltf only)
+        #[arg(long)]
+        splash: bool,
+
+        /// Include each mount's firing arcs as translucent flat fans at mount
+        /// height (GLB only)
+        #[arg(long)]
+        firing_arcs: bool,
+
+        /// Export skeletons as glTF skins so turrets, barrels, etc. can be posed,
+        /// along with the models' animation clips
+        #[arg(long)]
+        skinned: bool,
+
         /// Enable verbose debug output (UV decoding details, etc.)
         #[arg(long)]
         debug: bool,
@@ -246,9 +319,15 @@ enum Commands {
         /// Path to a space directory (e.g. "spaces/16_OC_bees_to_honey")
         space_dir: PathBuf,
 
-        /// Output file path
-        #[arg(short, long, default_value = "output.glb")]
-        output: PathBuf,
+        /// Output file path [default: output.<format>]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format. OBJ and USD also write their PNG textures next to
+        /// the output, glTF into a `textures` directory next to it; STL and
+        /// PLY are untextured.
+        #[clap(long, default_value_t = ModelFormat::Glb, value_enum)]
+        format: ModelFormat,
 
         /// LOD level (0 = highest detail)
         #[arg(long, default_value = "0")]
@@ -287,6 +366,11 @@ enum Commands {
         /// are downsampled with box filtering. Reduces GLB file size significantly.
         #[arg(long)]
         max_texture_size: Option<u32>,
+
+        /// Export animated models (radars, flags, cranes) with their skeletons
+        /// as glTF skins, along with their animation clips (glTF only)
+        #[arg(long)]
+        skinned: bool,
     },
     /// Inspect armor model geometry and GameParams thickness data for a ship
     Armor {
@@ -298,6 +382,159 @@ enum Commands {
         #[arg(long)]
         hull: Option<String>,
     },
+    /// Export every ship with a model to GLB, named by nation/class/tier, with
+    /// an index.json listing them. Ships whose source CRC matches the
+    /// previous index.json are skipped.
+    ExportAllShips {
+        /// Output directory
+        output_dir: PathBuf,
+
+        /// Only export ships whose param name, model directory or display
+        /// name contains this (case-insensitive)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// LOD level to export (0 = highest detail)
+        #[arg(long, default_value = "0")]
+        lod: usize,
+
+        /// Skip embedding textures
+        #[arg(long)]
+        no_textures: bool,
+
+        /// Re-export every ship, even if its CRC matches the previous index.json
+        #[arg(long)]
+        force: bool,
+
+        /// Write `.gltf` files with textures as PNGs in `<OUTPUT_DIR>/textures`,
+        /// shared by all ships, instead of self-contained `.glb` files
+        #[arg(long)]
+        gltf: bool,
+
+        /// Keep decoded textures in this directory and reuse them on later runs
+        #[arg(long)]
+        texture_cache: Option<PathBuf>,
+    },
+    /// Export a carrier or airsupport plane model with its bombs, torpedoes or
+    /// rockets placed at the payload hardpoints
+    ExportAircraft {
+        /// Aircraft GameParam name or index (e.g. "PAUA102_F4U_Corsair"), or
+        /// part of its name or translated display name
+        name: Option<String>,
+
+        /// Output file path [default: output.<format>]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Output format: glb, or gltf with textures in a `textures`
+        /// directory next to the output
+        #[clap(long, default_value_t = ModelFormat::Glb, value_enum)]
+        format: ModelFormat,
+
+        /// LOD level (0 = highest detail)
+        #[arg(long, default_value = "0")]
+        lod: usize,
+
+        /// Also export every lower-detail LOD after --lod, chained through
+        /// MSFT_lod with screen-coverage switch points (GLB and glTF only)
+        #[arg(long)]
+        all_lods: bool,
+
+        /// Skip embedding textures
+        #[arg(long)]
+        no_textures: bool,
+
+        /// Export the skeleton as a glTF skin so propellers can be spun,
+        /// along with the model's animation clips
+        #[arg(long)]
+        skinned: bool,
+
+        /// List aircraft matching NAME (or all aircraft) with their payload,
+        /// then exit
+        #[arg(long)]
+        list: bool,
+    },
+    /// Write a JSON report of a ship's hit-location zones: splash box volumes,
+    /// armor area by thickness, citadel height and HP pools
+    ZoneReport {
+        /// Ship name — either a model directory name or a translated display name
+        name: String,
+
+        /// Hull upgrade to use (e.g. "A" for stock, "B" for upgraded)
+        #[arg(long)]
+        hull: Option<String>,
+
+        /// A value of "-" will print to stdout
+        #[clap(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Write a JSON report of each mount's firing arcs and how many guns bear
+    /// at each angle around the ship
+    FiringArcs {
+        /// Ship name — either a model directory name or a translated display name
+        name: String,
+
+        /// Hull upgrade to use (e.g. "A" for stock, "B" for upgraded)
+        #[arg(long)]
+        hull: Option<String>,
+
+        /// Bearing step of the guns-bearing table in degrees
+        #[clap(long, default_value_t = 5.0)]
+        step: f32,
+
+        /// Also write a top-down SVG diagram of the arcs to this path
+        #[clap(long)]
+        svg: Option<PathBuf>,
+
+        /// A value of "-" will print to stdout
+        #[clap(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Render a ship's side profile or top-down plan as SVG or PNG, optionally
+    /// with armor colored by thickness
+    ShipProfile {
+        /// Ship name — either a model directory name or a translated display name
+        name: String,
+
+        /// Hull upgrade to use (e.g. "A" for stock, "B" for upgraded)
+        #[arg(long)]
+        hull: Option<String>,
+
+        #[clap(long, default_value_t = ProfileViewArg::Side, value_enum)]
+        view: ProfileViewArg,
+
+        /// Draw armor plates colored by thickness instead of a silhouette
+        #[arg(long)]
+        armor: bool,
+
+        /// Image width in pixels, at most 16384
+        #[clap(long, default_value_t = 2000)]
+        width: u32,
+
+        #[clap(short, long, default_value_t = ProfileFormat::Svg, value_enum)]
+        format: ProfileFormat,
+
+        /// Output file path
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Main battery range tables (flight time, impact velocity, impact angle,
+    /// penetration) for each of a ship's shells
+    Ballistics {
+        /// Ship name — either a model directory name or a translated display name
+        name: String,
+
+        /// Distance between table rows in meters
+        #[clap(long, default_value_t = 1000.0)]
+        step: f32,
+
+        #[clap(short, long, default_value_t = BallisticsFormat::Text, value_enum)]
+        format: BallisticsFormat,
+
+        /// A value of "-" will print to stdout
+        #[clap(short, long, default_value = "-")]
+        output: PathBuf,
+    },
     /// Parse and inspect an assets.bin (PrototypeDatabase) file
     AssetsBin {
         /// Path to the assets.bin file (VFS path by default, disk path with --no-vfs)
@@ -319,10 +556,40 @@ enum Commands {
         #[clap(long)]
         parse_visual: Option<String>,
 
+        /// Verify the header checksum and prototype layouts, explaining any
+        /// mismatches
+        #[clap(long)]
+        validate: bool,
+
         /// Read file from disk instead of VFS
         #[clap(long)]
         no_vfs: bool,
     },
+    /// Show asset dependencies between assets.bin prototypes (visuals, models,
+    /// geometry, materials, textures). Without a path, exports the full graph.
+    Deps {
+        /// Asset path or path suffix (e.g. "JSB039_Yamato_1945_Hull.mfm")
+        path: Option<String>,
+
+        /// Show what uses the asset instead of what it depends on
+        #[clap(long)]
+        reverse: bool,
+
+        /// Follow dependencies transitively instead of listing direct edges only
+        #[clap(long)]
+        transitive: bool,
+
+        /// Skip resolving material → texture edges
+        #[clap(long)]
+        no_textures: bool,
+
+        #[clap(short, long, default_value_t = DepsFormat::Text, value_enum)]
+        format: DepsFormat,
+
+        /// A value of "-" will print to stdout
+        #[clap(short, long, default_value = "-")]
+        output: PathBuf,
+    },
     /// Dump UV coordinate statistics for hull meshes of a ship
     DumpUvs {
         /// Ship name
@@ -341,6 +608,101 @@ enum MetadataFormat {
     Csv,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, ValueEnum)]
+enum DepsFormat {
+    Text,
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, ValueEnum)]
+enum BallisticsFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+enum ProfileViewArg {
+    Side,
+    Top,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+enum ProfileFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+enum ModelFormat {
+    Glb,
+    Gltf,
+    Usd,
+    Obj,
+    Stl,
+    Ply,
+}
+
+impl ModelFormat {
+    /// The flattened mesh format, or `None` for glTF and USD.
+    fn mesh_format(self) -> Option<MeshFormat> {
+        match self {
+            ModelFormat::Glb | ModelFormat::Gltf | ModelFormat::Usd => None,
+            ModelFormat::Obj => Some(MeshFormat::Obj),
+            ModelFormat::Stl => Some(MeshFormat::Stl),
+            ModelFormat::Ply => Some(MeshFormat::Ply),
+        }
+    }
+
+    /// Reject `--all-lods` for formats that can only hold a single LOD.
+    fn check_all_lods(self, all_lods: bool) -> Result<(), Report> {
+        if all_lods && !matches!(self, ModelFormat::Glb | ModelFormat::Gltf) {
+            bail!("--all-lods is only supported for glb and gltf, not --format {self:?}");
+        }
+        Ok(())
+    }
+
+    fn check_splash(self, splash: bool) -> Result<(), Report> {
+        if splash && !matches!(self, ModelFormat::Glb | ModelFormat::Gltf) {
+            bail!("--splash is only supported for glb and gltf, not --format {self:?}");
+        }
+        Ok(())
+    }
+
+    fn default_output(self) -> PathBuf {
+        let ext = match self {
+            ModelFormat::Glb => "glb",
+            ModelFormat::Gltf => "gltf",
+            ModelFormat::Usd => "usda",
+            ModelFormat::Obj => MeshFormat::Obj.extension(),
+            ModelFormat::Stl => MeshFormat::Stl.extension(),
+            ModelFormat::Ply => MeshFormat::Ply.extension(),
+        };
+        PathBuf::from(format!("output.{ext}"))
+    }
+}
+
+/// Write the GLB produced by `export` to `output`. For
+/// [`ModelFormat::Gltf`], write it as `.gltf` + `.bin` instead, with
+/// textures in a `textures` directory next to `output`.
+fn write_glb_output(
+    format: ModelFormat,
+    output: &Path,
+    export: impl FnOnce(&mut Vec<u8>) -> Result<(), Report>,
+) -> Result<(), Report> {
+    let mut glb = Vec::new();
+    export(&mut glb)?;
+    if format == ModelFormat::Gltf {
+        let textures_dir = output.parent().unwrap_or(Path::new(".")).join("textures");
+        gltf_split::write_gltf_split(&glb, output, &textures_dir)
+            .context("Failed to write glTF")?;
+    } else {
+        fs::write(output, glb).context("Failed to write output file")?;
+    }
+    Ok(())
+}
+
 fn load_idx_file(path: PathBuf) -> Result<idx::IdxFile, Report> {
     let file_data = std::fs::read(&path).context("Failed to read idx file")?;
     Ok(idx::parse(&file_data)?)
@@ -390,7 +752,9 @@ fn add_vfs_entries_to_file_tree(
         volume_id: 0,
         filename: String::new(),
     };
-    for (file_path, size) in assets_vfs.files() {
+    // JSON views are unsized until rendered, so they are listed with size 0.
+    let json_files = assets_vfs.json_files().map(|path| (path, 0));
+    for (file_path, size) in assets_vfs.files().chain(json_files) {
         let path = file_path.to_string();
         assets_bin_paths.insert(path.clone());
         file_tree.entry(path).or_insert(VfsEntry::File {
@@ -525,7 +889,12 @@ fn run() -> Result<(), Report> {
                 .is_ok();
 
             if assets_loaded {
-                match AssetsBinVfs::new(assets_bin_data) {
+                let assets_vfs = if args.assets_json {
+                    AssetsBinVfs::with_json_view(assets_bin_data)
+                } else {
+                    AssetsBinVfs::new(assets_bin_data)
+                };
+                match assets_vfs {
                     Ok(assets_vfs) => {
                         // Add assets.bin entries to the file_tree so list/extract can find them.
                         assets_bin_paths =
@@ -819,7 +1188,13 @@ fn run() -> Result<(), Report> {
                 match entry {
                     VfsEntry::File { file_info, .. } => {
                         let tag = if from_assets_bin { "A" } else { "F" };
-                        println!("({tag}) {} {} bytes", path, file_info.unpacked_size);
+                        // assets.bin JSON views are only sized once rendered,
+                        // so list them without rendering every one.
+                        if from_assets_bin && file_info.unpacked_size == 0 {
+                            println!("({tag}) {path}");
+                        } else {
+                            println!("({tag}) {path} {} bytes", file_info.unpacked_size);
+                        }
                     }
                     VfsEntry::Directory => {
                         let tag = if from_assets_bin { "A" } else { "D" };
@@ -1031,27 +1406,40 @@ fn run() -> Result<(), Report> {
         Commands::Geometry {
             file,
             decode,
+            check,
             no_vfs,
         } => {
             let file_data = read_file_data(&file, no_vfs, vfs.as_ref())?;
-            run_geometry(&file_data, &file.to_string_lossy(), decode)?;
+            run_geometry(&file_data, &file.to_string_lossy(), decode, check)?;
+        }
+        Commands::AuditVertexFormats => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required for the audit. Use --game-dir to specify a game install.");
+            };
+            run_audit_vertex_formats(vfs, &file_tree)?;
         }
         Commands::ExportModel {
             file,
             output,
+            format,
             lod,
+            all_lods,
             no_textures,
             damaged,
             list_textures,
+            skinned,
             no_vfs,
         } => {
             run_export_model(&ExportModelParams {
                 file: &file,
-                output: &output,
+                output: &output.unwrap_or_else(|| format.default_output()),
+                format,
                 lod,
+                all_lods,
                 no_textures,
                 damaged,
                 list_textures,
+                skinned,
                 no_vfs,
                 vfs: vfs.as_ref(),
             })?;
@@ -1059,12 +1447,18 @@ fn run() -> Result<(), Report> {
         Commands::ExportShip {
             name,
             output,
+            format,
             lod,
+            all_lods,
             list_upgrades,
             hull,
             no_textures,
             damaged,
             list_textures,
+            collision,
+            splash,
+            firing_arcs,
+            skinned,
             debug,
         } => {
             let Some(vfs) = &vfs else {
@@ -1074,8 +1468,10 @@ fn run() -> Result<(), Report> {
             run_export_ship(
                 vfs,
                 &name,
-                &output,
+                &output.unwrap_or_else(|| format.default_output()),
+                format,
                 lod,
+                all_lods,
                 &game_dir,
                 game_version,
                 list_upgrades,
@@ -1083,12 +1479,17 @@ fn run() -> Result<(), Report> {
                 no_textures,
                 damaged,
                 list_textures,
+                collision,
+                splash,
+                firing_arcs,
+                skinned,
                 debug,
             )?;
         }
         Commands::ExportMap {
             space_dir,
             output,
+            format,
             lod,
             no_vfs,
             terrain_step,
@@ -1098,10 +1499,12 @@ fn run() -> Result<(), Report> {
             vegetation_density,
             no_textures,
             max_texture_size,
+            skinned,
         } => {
             run_export_map(
                 &space_dir,
-                &output,
+                &output.unwrap_or_else(|| format.default_output()),
+                format,
                 lod,
                 no_vfs,
                 vfs.as_ref(),
@@ -1112,8 +1515,12 @@ fn run() -> Result<(), Report> {
                 vegetation_density,
                 no_textures,
                 max_texture_size,
+                skinned,
             )?;
         }
+        Commands::ImportGlb { file, output } => {
+            run_import_glb(&file, &output)?;
+        }
         Commands::Armor { name, hull } => {
             let Some(vfs) = &vfs else {
                 bail!(
@@ -1123,6 +1530,131 @@ fn run() -> Result<(), Report> {
 
             run_armor(vfs, &name, &game_dir, game_version, hull.as_deref())?;
         }
+        Commands::Deps {
+            path,
+            reverse,
+            transitive,
+            no_textures,
+            format,
+            output,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required for deps. Use --game-dir to specify a game install.");
+            };
+            run_deps(
+                vfs,
+                path.as_deref(),
+                reverse,
+                transitive,
+                no_textures,
+                format,
+                &output,
+            )?;
+        }
+        Commands::ExportAllShips {
+            output_dir,
+            filter,
+            lod,
+            no_textures,
+            force,
+            gltf,
+            texture_cache,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_export_all_ships(
+                vfs,
+                &game_dir,
+                game_version,
+                &output_dir,
+                filter.as_deref(),
+                lod,
+                no_textures,
+                force,
+                gltf,
+                texture_cache.as_deref(),
+            )?;
+        }
+        Commands::ExportAircraft {
+            name,
+            output,
+            format,
+            lod,
+            all_lods,
+            no_textures,
+            skinned,
+            list,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_export_aircraft(
+                vfs,
+                &game_dir,
+                game_version,
+                name.as_deref(),
+                &output.unwrap_or_else(|| format.default_output()),
+                format,
+                lod,
+                all_lods,
+                no_textures,
+                skinned,
+                list,
+            )?;
+        }
+        Commands::ZoneReport { name, hull, output } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_zone_report(vfs, &name, hull.as_deref(), &output)?;
+        }
+        Commands::FiringArcs {
+            name,
+            hull,
+            step,
+            svg,
+            output,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_firing_arcs(vfs, &name, hull.as_deref(), step, svg.as_deref(), &output)?;
+        }
+        Commands::ShipProfile {
+            name,
+            hull,
+            view,
+            armor,
+            width,
+            format,
+            output,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_ship_profile(
+                vfs,
+                &name,
+                hull.as_deref(),
+                view,
+                armor,
+                width,
+                format,
+                &output,
+            )?;
+        }
+        Commands::Ballistics {
+            name,
+            step,
+            format,
+            output,
+        } => {
+            let Some(vfs) = &vfs else {
+                bail!("VFS required. Use --game-dir to specify a game install.");
+            };
+            run_ballistics(vfs, &name, &game_dir, game_version, step, format, &output)?;
+        }
         Commands::DumpUvs { name, hull } => {
             let Some(vfs) = &vfs else {
                 bail!("VFS required. Use --game-dir to specify a game install.");
@@ -1135,17 +1667,26 @@ fn run() -> Result<(), Report> {
             max_paths,
             resolve,
             parse_visual,
+            validate,
             no_vfs,
         } => {
             let file_data = read_file_data(&file, no_vfs, vfs.as_ref())?;
-            run_assets_bin(
-                &file_data,
-                &file.to_string_lossy(),
-                filter.as_deref(),
-                max_paths,
-                resolve.as_deref(),
-                parse_visual.as_deref(),
-            )?;
+            if validate {
+                let report = wowsunpack::models::assets_bin::validate_assets_bin(&file_data)?;
+                print!("{report}");
+                if !report.is_ok() {
+                    bail!("{} failed validation", file.display());
+                }
+            } else {
+                run_assets_bin(
+                    &file_data,
+                    &file.to_string_lossy(),
+                    filter.as_deref(),
+                    max_paths,
+                    resolve.as_deref(),
+                    parse_visual.as_deref(),
+                )?;
+            }
         }
     }
 
@@ -1210,8 +1751,8 @@ fn load_game_params(vfs: &VfsPath) -> Result<pickled::Value, Report> {
     Ok(pickle)
 }
 
-fn run_geometry(file_data: &[u8], name: &str, decode: bool) -> Result<(), Report> {
-    use wowsunpack::models::geometry;
+fn run_geometry(file_data: &[u8], name: &str, decode: bool, check: bool) -> Result<(), Report> {
+    use wowsunpack::models::{geometry, geometry_check};
 
     let geom = geometry::parse_geometry(file_data)?;
 
@@ -1222,12 +1763,13 @@ fn run_geometry(file_data: &[u8], name: &str, decode: bool) -> Result<(), Report
     println!("Vertices mappings: {}", geom.vertices_mapping.len());
     for (i, m) in geom.vertices_mapping.iter().enumerate() {
         println!(
-            "  [{i}] id=0x{:08X} buf={} offset={} count={} texelDensity=0x{:04X}",
+            "  [{i}] id=0x{:08X} buf={} offset={} count={} texelDensity=0x{:04X} ({}, unverified f16)",
             m.mapping_id,
             m.merged_buffer_index,
             m.items_offset,
             m.items_count,
-            m.packed_texel_density
+            m.packed_texel_density,
+            geometry_check::texel_density(m)
         );
     }
     println!();
@@ -1292,6 +1834,16 @@ fn run_geometry(file_data: &[u8], name: &str, decode: bool) -> Result<(), Report
     println!("Collision models: {}", geom.collision_models.len());
     for (i, cm) in geom.collision_models.iter().enumerate() {
         println!("  [{i}] name=\"{}\" size={}", cm.name, cm.size_in_bytes);
+        if decode {
+            match cm.decode() {
+                Ok(model) => println!(
+                    "    -> decoded {} BVH nodes, {} triangles",
+                    model.nodes.len(),
+                    model.triangles.len()
+                ),
+                Err(e) => println!("    -> decode error: {e:?}"),
+            }
+        }
     }
     println!();
 
@@ -1304,14 +1856,154 @@ fn run_geometry(file_data: &[u8], name: &str, decode: bool) -> Result<(), Report
         );
     }
 
-    Ok(())
-}
-
-fn run_assets_bin(
-    file_data: &[u8],
-    name: &str,
-    filter: Option<&str>,
-    max_paths: usize,
+    if check {
+        let report = geometry_check::check_geometry(&geom);
+        println!();
+        println!("Meshes: {}", report.meshes.len());
+        for m in &report.meshes {
+            let bbox = m
+                .bounding_box
+                .map(|(min, max)| format!("{min:?}..{max:?}"))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "  [{}] vm=0x{:08X} im=0x{:08X} format=\"{}\" vertices={} triangles={} bbox={} texelDensity={}",
+                m.mesh,
+                m.vertices_mapping_id,
+                m.indices_mapping_id,
+                m.vertex_format,
+                m.vertex_count,
+                m.triangle_count,
+                bbox,
+                m.texel_density
+            );
+        }
+        println!(
+            "Total: {} vertices, {} triangles",
+            report.vertex_count(),
+            report.triangle_count()
+        );
+        if let Some((min, max)) = report.bounding_box() {
+            println!("Bounding box: {min:?}..{max:?}");
+        }
+        if let Some(td) = report.texel_density() {
+            println!(
+                "Texel density (unverified f16 decoding): min={} max={} mean={} ({} meshes)",
+                td.min, td.max, td.mean, td.count
+            );
+        }
+        println!();
+
+        if report.is_ok() {
+            println!("No issues found.");
+        } else {
+            println!("Issues: {}", report.issues.len());
+            for issue in &report.issues {
+                println!("  {issue}");
+            }
+            bail!("{} mesh issues found in {name}", report.issues.len());
+        }
+    }
+
+    Ok(())
+}
+
+fn run_audit_vertex_formats(
+    vfs: &VfsPath,
+    file_tree: &HashMap<String, VfsEntry>,
+) -> Result<(), Report> {
+    use wowsunpack::models::{geometry, vertex_format};
+
+    let mut files: Vec<&String> = file_tree
+        .iter()
+        .filter(|(path, entry)| {
+            path.ends_with(".geometry") && matches!(entry, VfsEntry::File { .. })
+        })
+        .map(|(path, _)| path)
+        .collect();
+    files.sort();
+
+    // format name -> stored stride -> buffer count
+    let observed: Mutex<BTreeMap<String, BTreeMap<u16, usize>>> = Mutex::new(BTreeMap::new());
+    let parse_failures = Mutex::new(Vec::new());
+
+    let bar = ProgressBar::new(files.len() as u64);
+    files
+        .par_iter()
+        .progress_with(bar.clone())
+        .for_each(|path| {
+            let mut data = Vec::new();
+            let read = vfs
+                .join(path.as_str())
+                .and_then(|p| p.open_file())
+                .map(|mut f| f.read_to_end(&mut data));
+            if !matches!(read, Ok(Ok(_))) {
+                parse_failures
+                    .lock()
+                    .unwrap()
+                    .push(format!("{path}: read failed"));
+                return;
+            }
+            match geometry::parse_geometry(&data) {
+                Ok(geom) => {
+                    let mut observed = observed.lock().unwrap();
+                    for proto in &geom.merged_vertices {
+                        *observed
+                            .entry(proto.format_name.clone())
+                            .or_default()
+                            .entry(proto.stride_in_bytes)
+                            .or_default() += 1;
+                    }
+                }
+                Err(e) => parse_failures.lock().unwrap().push(format!("{path}: {e}")),
+            }
+        });
+    bar.finish_and_clear();
+
+    let observed = observed.into_inner().unwrap();
+    let mut problems = 0;
+    println!(
+        "{:<32} {:>8} {:>8} {:>8}  status",
+        "format", "computed", "stored", "buffers"
+    );
+    for (format_name, strides) in &observed {
+        let computed = vertex_format::parse_vertex_format(format_name);
+        for (&stored, &count) in strides {
+            let (computed_str, status) = match &computed {
+                Ok(format) => match format.check_stride(format_name, stored as usize) {
+                    Ok(()) => (format.stride.to_string(), "ok".to_string()),
+                    Err(e) => (format.stride.to_string(), format!("MISMATCH: {e}")),
+                },
+                Err(e) => ("-".to_string(), format!("UNKNOWN: {e}")),
+            };
+            if status != "ok" {
+                problems += 1;
+            }
+            println!("{format_name:<32} {computed_str:>8} {stored:>8} {count:>8}  {status}");
+        }
+    }
+
+    let parse_failures = parse_failures.into_inner().unwrap();
+    for failure in &parse_failures {
+        eprintln!("Warning: {failure}");
+    }
+    println!(
+        "\n{} geometry files, {} vertex formats, {} parse failures",
+        files.len(),
+        observed.len(),
+        parse_failures.len()
+    );
+
+    if problems > 0 {
+        bail!("{problems} vertex format/stride combinations failed the audit");
+    }
+    Ok(())
+}
+
+fn run_assets_bin(
+    file_data: &[u8],
+    name: &str,
+    filter: Option<&str>,
+    max_paths: usize,
     resolve: Option<&str>,
     parse_visual: Option<&str>,
 ) -> Result<(), Report> {
@@ -1337,9 +2029,8 @@ fn run_assets_bin(
         );
 
         // Print the first 64 bytes of the record as hex
-        let item_sizes: &[usize] = &[0x78, 0x70, 0x20, 0x28, 0x70, 0x10, 0x18, 0x10, 0x10, 0x10];
-        if location.blob_index < item_sizes.len() {
-            let item_size = item_sizes[location.blob_index];
+        if let Ok(layout) = db.layout(location.blob_index) {
+            let item_size = layout.item_size;
             match db.get_prototype_data(location, item_size) {
                 Ok(data) => {
                     let show_len = item_size.min(data.len()).min(128);
@@ -1377,7 +2068,7 @@ fn run_assets_bin(
         }
 
         let record_data = db
-            .get_prototype_data(location, visual::VISUAL_ITEM_SIZE)
+            .get_record(location)
             .context("Failed to get visual prototype data")?;
 
         let vp = visual::parse_visual(record_data).context("Failed to parse VisualPrototype")?;
@@ -1482,13 +2173,645 @@ fn run_assets_bin(
     Ok(())
 }
 
+/// Open `output` for writing, or stdout if it is `-`.
+fn open_output(output: &Path) -> Result<Box<dyn Write>, Report> {
+    Ok(if output.to_str() == Some("-") {
+        Box::new(stdout().lock())
+    } else {
+        Box::new(BufWriter::new(File::create(output)?))
+    })
+}
+
+fn run_deps(
+    vfs: &VfsPath,
+    path: Option<&str>,
+    reverse: bool,
+    transitive: bool,
+    no_textures: bool,
+    format: DepsFormat,
+    output: &Path,
+) -> Result<(), Report> {
+    use std::collections::BTreeSet;
+    use wowsunpack::models::assets_bin;
+    use wowsunpack::models::dependency_graph::DependencyGraph;
+
+    let assets_bin_data = read_file_data(Path::new("content/assets.bin"), false, Some(vfs))?;
+    let db = assets_bin::parse_assets_bin(&assets_bin_data)?;
+
+    let mut graph = DependencyGraph::build(&db);
+    if !no_textures {
+        graph.add_texture_edges(vfs);
+    }
+
+    let mut writer = open_output(output)?;
+    let graph = match path {
+        Some(suffix) => {
+            let Some(node) = graph.find_node(suffix) else {
+                bail!("No asset matching '{suffix}' found in the dependency graph");
+            };
+            let mut keep: BTreeSet<&str> = match (reverse, transitive) {
+                (false, false) => graph.dependencies(node).map(|d| d.path.as_str()).collect(),
+                (true, false) => graph.dependents(node).map(|d| d.path.as_str()).collect(),
+                (false, true) => graph.transitive_dependencies(node),
+                (true, true) => graph.transitive_dependents(node),
+            };
+
+            if format == DepsFormat::Text {
+                let direction = if reverse { "Used by" } else { "Depends on" };
+                writeln!(writer, "{direction} ({} assets): {node}", keep.len())?;
+                for p in &keep {
+                    writeln!(writer, "  {p}")?;
+                }
+                writer.flush()?;
+                return Ok(());
+            }
+
+            keep.insert(node);
+            graph.subgraph(&keep)
+        }
+        None => graph,
+    };
+
+    match format {
+        DepsFormat::Text => {
+            for node in graph.nodes() {
+                for dep in graph.dependencies(node) {
+                    writeln!(writer, "{node} -> {} ({})", dep.path, dep.kind.label())?;
+                }
+            }
+        }
+        DepsFormat::Dot => writer.write_all(graph.to_dot().as_bytes())?,
+        DepsFormat::Json => serde_json::to_writer_pretty(&mut writer, &graph)?,
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// One exported ship in `index.json`, written by [`run_export_all_ships`].
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ShipIndexEntry {
+    param_index: String,
+    param_name: String,
+    model_dir: String,
+    display_name: Option<String>,
+    nation: String,
+    species: String,
+    tier: u32,
+    group: String,
+    /// GLB path relative to the output directory.
+    path: String,
+    /// [`ShipAssets::source_crc`](wowsunpack::export::ship::ShipAssets::source_crc)
+    /// of the export.
+    crc32: u32,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct ShipIndex {
+    ships: Vec<ShipIndexEntry>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_export_all_ships(
+    vfs: &VfsPath,
+    game_dir: &Path,
+    game_version: Option<u64>,
+    output_dir: &Path,
+    filter: Option<&str>,
+    lod: usize,
+    no_textures: bool,
+    force: bool,
+    gltf: bool,
+    texture_cache: Option<&Path>,
+) -> Result<(), Report> {
+    use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
+    use wowsunpack::export::texture_cache::TextureCache;
+
+    let mut assets = ShipAssets::load(vfs)?;
+    if let Some(dir) = texture_cache {
+        assets.set_texture_cache(TextureCache::with_disk_dir(dir));
+    }
+    if let Some(version) = game_version {
+        let mo_path = wowsunpack::game_data::translations_path(game_dir, version as u32);
+        if let Ok(data) = std::fs::read(&mo_path)
+            && let Ok(catalog) = gettext::Catalog::parse(&*data)
+        {
+            assets.set_translations(catalog);
+        }
+    }
+
+    let filter = filter.map(|f| f.to_lowercase());
+    let ships: Vec<_> = assets
+        .ships()
+        .filter(|ship| {
+            let Some(filter) = &filter else {
+                return true;
+            };
+            [
+                Some(&ship.param_name),
+                Some(&ship.info.model_dir),
+                ship.info.display_name.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|name| name.to_lowercase().contains(filter))
+        })
+        .collect();
+
+    let index_path = output_dir.join("index.json");
+    let mut previous: HashMap<String, ShipIndexEntry> = fs::read(&index_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<ShipIndex>(&data).ok())
+        .unwrap_or_default()
+        .ships
+        .into_iter()
+        .map(|entry| (entry.param_index.clone(), entry))
+        .collect();
+
+    fs::create_dir_all(output_dir)?;
+    let textures_dir = output_dir.join("textures");
+    let extension = if gltf { "gltf" } else { "glb" };
+    let options = ShipExportOptions {
+        lod,
+        textures: !no_textures,
+        ..Default::default()
+    };
+
+    let unchanged = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+    let start = Instant::now();
+    let bar = ProgressBar::new(ships.len() as u64);
+    let mut entries: Vec<ShipIndexEntry> = ships
+        .par_iter()
+        .progress_with(bar.clone())
+        .filter_map(|ship| {
+            let export = || -> Result<ShipIndexEntry, Report> {
+                let crc32 = assets.source_crc(ship, &options)?;
+                let path = format!("{}.{extension}", ship.output_stem());
+                let output = output_dir.join(&path);
+
+                let is_unchanged = !force
+                    && previous
+                        .get(&ship.info.param_index)
+                        .is_some_and(|prev| prev.crc32 == crc32 && prev.path == path)
+                    && output.exists();
+                if is_unchanged {
+                    unchanged.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    if let Some(parent) = output.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    let ctx = assets.load_catalog_ship(ship, &options)?;
+                    if gltf {
+                        ctx.export_gltf(&output, &textures_dir)?;
+                    } else {
+                        let mut writer = BufWriter::new(File::create(&output)?);
+                        ctx.export_glb(&mut writer)?;
+                        writer.flush()?;
+                    }
+                }
+
+                Ok(ShipIndexEntry {
+                    param_index: ship.info.param_index.clone(),
+                    param_name: ship.param_name.clone(),
+                    model_dir: ship.info.model_dir.clone(),
+                    display_name: ship.info.display_name.clone(),
+                    nation: ship.nation.clone(),
+                    species: ship.species.clone(),
+                    tier: ship.tier,
+                    group: ship.group.clone(),
+                    path,
+                    crc32,
+                })
+            };
+            match export() {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    failures
+                        .lock()
+                        .unwrap()
+                        .push(format!("{}: {e}", ship.param_name));
+                    None
+                }
+            }
+        })
+        .collect();
+    bar.finish_and_clear();
+
+    // Keep ships left out by the filter in the index.
+    if filter.is_some() {
+        for ship in &ships {
+            previous.remove(&ship.info.param_index);
+        }
+        entries.extend(previous.into_values());
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut writer = BufWriter::new(File::create(&index_path)?);
+    serde_json::to_writer_pretty(&mut writer, &ShipIndex { ships: entries })?;
+    writer.flush()?;
+
+    let failures = failures.into_inner().unwrap();
+    let unchanged = unchanged.into_inner();
+    println!(
+        "Exported {} ships, {} unchanged, {} failed in {:.1}s. Index: {}",
+        ships.len() - unchanged - failures.len(),
+        unchanged,
+        failures.len(),
+        start.elapsed().as_secs_f64(),
+        index_path.display()
+    );
+    if !no_textures {
+        let stats = assets.texture_cache().stats();
+        println!(
+            "Textures: {} decoded, {} from disk cache, {} reused in memory",
+            stats.decoded, stats.disk_hits, stats.memory_hits
+        );
+    }
+    for failure in &failures {
+        eprintln!("  {failure}");
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_export_aircraft(
+    vfs: &VfsPath,
+    game_dir: &Path,
+    game_version: Option<u64>,
+    name: Option<&str>,
+    output: &Path,
+    format: ModelFormat,
+    lod: usize,
+    all_lods: bool,
+    no_textures: bool,
+    skinned: bool,
+    list: bool,
+) -> Result<(), Report> {
+    use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
+
+    let mut assets = ShipAssets::load(vfs)?;
+    if let Some(version) = game_version {
+        let mo_path = wowsunpack::game_data::translations_path(game_dir, version as u32);
+        if let Ok(data) = std::fs::read(&mo_path)
+            && let Ok(catalog) = gettext::Catalog::parse(&*data)
+        {
+            assets.set_translations(catalog);
+        }
+    }
+
+    if list {
+        for aircraft in assets.aircraft() {
+            if let Some(name) = name
+                && !aircraft.matches(name)
+            {
+                continue;
+            }
+            let payload = aircraft
+                .payload
+                .as_ref()
+                .map(|p| format!("{:?} {}", p.kind, p.projectile))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "{:<40} {:<24} {:?}, payload: {payload}",
+                aircraft.param_name,
+                aircraft.display_name.as_deref().unwrap_or("-"),
+                aircraft.category,
+            );
+        }
+        return Ok(());
+    }
+
+    let Some(name) = name else {
+        bail!("Aircraft name required (use --list to see available aircraft)");
+    };
+    if !matches!(format, ModelFormat::Glb | ModelFormat::Gltf) {
+        bail!("--format {format:?} is not supported for aircraft; use glb or gltf");
+    }
+
+    let options = ShipExportOptions {
+        lod,
+        all_lods,
+        textures: !no_textures,
+        skinned,
+        ..Default::default()
+    };
+    let ctx = assets.load_aircraft(name, &options)?;
+    let info = ctx.info();
+    println!(
+        "Found {} ({}), {} propeller nodes, {} payload hardpoints",
+        info.param_name,
+        info.model_path,
+        ctx.propeller_nodes().len(),
+        ctx.payload_hardpoints().len()
+    );
+    if let Some(payload) = &info.payload
+        && !ctx.has_payload_model()
+    {
+        println!(
+            "No model placed for payload {} ({:?})",
+            payload.projectile, payload.kind
+        );
+    }
+
+    write_glb_output(format, output, |glb| ctx.export_glb(glb))?;
+
+    let file_size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+    println!("Exported to {} ({} bytes)", output.display(), file_size);
+
+    Ok(())
+}
+
+fn run_zone_report(
+    vfs: &VfsPath,
+    name: &str,
+    hull_selection: Option<&str>,
+    output: &Path,
+) -> Result<(), Report> {
+    use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
+
+    let assets = ShipAssets::load(vfs)?;
+    let options = ShipExportOptions {
+        hull: hull_selection.map(|s| s.to_string()),
+        textures: false,
+        ..Default::default()
+    };
+    let ctx = assets.load_ship(name, &options)?;
+    let report = ctx.zone_report()?;
+
+    let mut writer = open_output(output)?;
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn run_firing_arcs(
+    vfs: &VfsPath,
+    name: &str,
+    hull_selection: Option<&str>,
+    step: f32,
+    svg: Option<&Path>,
+    output: &Path,
+) -> Result<(), Report> {
+    use wowsunpack::export::firing_arcs::{self, BearingCount, MountArc};
+    use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
+
+    #[derive(serde::Serialize)]
+    struct MountReport {
+        #[serde(flatten)]
+        mount: MountArc,
+        /// Firing arcs as ship-bearing intervals.
+        arcs: Vec<[f32; 2]>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct FiringArcsReport {
+        ship: String,
+        mounts: Vec<MountReport>,
+        bearings: Vec<BearingCount>,
+    }
+
+    let assets = ShipAssets::load(vfs)?;
+    let options = ShipExportOptions {
+        hull: hull_selection.map(|s| s.to_string()),
+        textures: false,
+        ..Default::default()
+    };
+    let ctx = assets.load_ship(name, &options)?;
+    let mounts = ctx.mount_arcs();
+
+    if let Some(svg_path) = svg {
+        let Some(diagram) = ctx.firing_arcs_svg() else {
+            bail!("Ship '{name}' has no hull geometry to draw arcs over.");
+        };
+        std::fs::write(svg_path, diagram)?;
+        eprintln!("Wrote firing arc diagram to {}", svg_path.display());
+    }
+
+    let report = FiringArcsReport {
+        ship: ctx.info().model_dir.clone(),
+        bearings: firing_arcs::bearing_table(&mounts, step),
+        mounts: mounts
+            .into_iter()
+            .map(|mount| MountReport {
+                arcs: mount.arcs(),
+                mount,
+            })
+            .collect(),
+    };
+
+    let mut writer = open_output(output)?;
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_ship_profile(
+    vfs: &VfsPath,
+    name: &str,
+    hull_selection: Option<&str>,
+    view: ProfileViewArg,
+    armor: bool,
+    width: u32,
+    format: ProfileFormat,
+    output: &Path,
+) -> Result<(), Report> {
+    use wowsunpack::export::profile::{ProfileStyle, ProfileView};
+    use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
+
+    let assets = ShipAssets::load(vfs)?;
+    let options = ShipExportOptions {
+        hull: hull_selection.map(|s| s.to_string()),
+        textures: false,
+        ..Default::default()
+    };
+    let ctx = assets.load_ship(name, &options)?;
+
+    let view = match view {
+        ProfileViewArg::Side => ProfileView::Side,
+        ProfileViewArg::Top => ProfileView::Top,
+    };
+    let style = if armor {
+        ProfileStyle::Armor
+    } else {
+        ProfileStyle::Silhouette
+    };
+    let image = ctx.render_profile(view, style, width)?;
+
+    let bytes = match format {
+        ProfileFormat::Svg => image.to_svg().into_bytes(),
+        ProfileFormat::Png => image.to_png()?,
+    };
+    std::fs::write(output, bytes)?;
+    println!(
+        "Wrote {}x{} profile to {}",
+        image.width,
+        image.height,
+        output.display()
+    );
+
+    Ok(())
+}
+
+fn run_ballistics(
+    vfs: &VfsPath,
+    name: &str,
+    game_dir: &Path,
+    game_version: Option<u64>,
+    step: f32,
+    format: BallisticsFormat,
+    output: &Path,
+) -> Result<(), Report> {
+    use wowsunpack::export::ship::ShipAssets;
+    use wowsunpack::game_params::ballistics::{self, ShellImpact};
+    use wowsunpack::game_params::types::{GameParamProvider, Meters, ShellInfo};
+
+    let mut assets = ShipAssets::load(vfs)?;
+    if let Some(version) = game_version {
+        let mo_path = wowsunpack::game_data::translations_path(game_dir, version as u32);
+        if let Ok(data) = std::fs::read(&mo_path)
+            && let Ok(catalog) = gettext::Catalog::parse(&*data)
+        {
+            assets.set_translations(catalog);
+        }
+    }
+
+    let info = assets.find_ship(name)?;
+    let metadata = assets.metadata();
+    let Some(param) = metadata.game_param_by_index(&info.param_index) else {
+        bail!("No GameParams entry for {}", info.model_dir);
+    };
+    let Some(config) = param.vehicle().and_then(|v| v.config_data()) else {
+        bail!("{} has no ship config data", param.name());
+    };
+    let Some(max_range) = config.main_battery_m else {
+        bail!("{} has no main battery", param.name());
+    };
+
+    let mut shells: Vec<ShellInfo> = config
+        .main_battery_ammo
+        .iter()
+        .filter_map(|ammo| metadata.game_param_by_name(ammo))
+        .filter_map(|p| {
+            p.projectile()
+                .map(|proj| proj.to_shell_info(p.name().to_string()))
+        })
+        .collect();
+    shells.sort_by(|a, b| {
+        (a.ammo_type.sort_order(), &a.name).cmp(&(b.ammo_type.sort_order(), &b.name))
+    });
+
+    #[derive(Serialize)]
+    struct ShellTable {
+        shell: ShellInfo,
+        max_range_m: Meters,
+        rows: Vec<ShellImpact>,
+    }
+
+    #[derive(Serialize)]
+    struct CsvRow<'a> {
+        shell: &'a str,
+        ammo_type: &'a str,
+        range_m: f32,
+        elevation_deg: f32,
+        flight_time_s: f32,
+        impact_velocity: f32,
+        impact_angle_deg: f32,
+        penetration_mm: f32,
+    }
+
+    let tables: Vec<ShellTable> = shells
+        .into_iter()
+        .map(|shell| {
+            let rows = ballistics::range_table(&shell, max_range, Meters::new(step));
+            ShellTable {
+                shell,
+                max_range_m: max_range,
+                rows,
+            }
+        })
+        .collect();
+
+    let mut writer = open_output(output)?;
+    match format {
+        BallisticsFormat::Text => {
+            writeln!(
+                writer,
+                "Ship: {} ({}), main battery range {:.0} m",
+                info.display_name.as_deref().unwrap_or("?"),
+                info.model_dir,
+                max_range.value()
+            )?;
+            for table in &tables {
+                let shell = &table.shell;
+                writeln!(
+                    writer,
+                    "\n{} ({}, {:.0} mm, {:.0} m/s, {:.1} kg)",
+                    shell.name,
+                    shell.ammo_type,
+                    shell.caliber.value(),
+                    shell.muzzle_velocity,
+                    shell.mass_kg
+                )?;
+                writeln!(
+                    writer,
+                    "  {:>8}  {:>7}  {:>8}  {:>9}  {:>8}  {:>8}",
+                    "range_m", "elev", "time_s", "impact_v", "angle", "pen_mm"
+                )?;
+                for row in &table.rows {
+                    writeln!(
+                        writer,
+                        "  {:>8.0}  {:>7.2}  {:>8.2}  {:>9.1}  {:>8.2}  {:>8.1}",
+                        row.range_m.value(),
+                        row.elevation_deg,
+                        row.flight_time_s,
+                        row.impact_velocity,
+                        row.impact_angle_deg,
+                        row.penetration_mm
+                    )?;
+                }
+            }
+        }
+        BallisticsFormat::Json => serde_json::to_writer_pretty(&mut writer, &tables)?,
+        BallisticsFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut writer);
+            for table in &tables {
+                for row in &table.rows {
+                    csv.serialize(CsvRow {
+                        shell: &table.shell.name,
+                        ammo_type: table.shell.ammo_type.display_name(),
+                        range_m: row.range_m.value(),
+                        elevation_deg: row.elevation_deg,
+                        flight_time_s: row.flight_time_s,
+                        impact_velocity: row.impact_velocity,
+                        impact_angle_deg: row.impact_angle_deg,
+                        penetration_mm: row.penetration_mm,
+                    })?;
+                }
+            }
+            csv.flush()?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
 struct ExportModelParams<'a> {
     file: &'a Path,
     output: &'a Path,
+    format: ModelFormat,
     lod: usize,
+    all_lods: bool,
     no_textures: bool,
     damaged: bool,
     list_textures: bool,
+    skinned: bool,
     no_vfs: bool,
     vfs: Option<&'a VfsPath>,
 }
@@ -1497,16 +2820,21 @@ fn run_export_model(params: &ExportModelParams<'_>) -> Result<(), Report> {
     let ExportModelParams {
         file,
         output,
+        format,
         lod,
+        all_lods,
         no_textures,
         damaged,
         list_textures,
+        skinned,
         no_vfs,
         vfs,
     } = *params;
+    format.check_all_lods(all_lods)?;
     use wowsunpack::export::gltf_export;
     use wowsunpack::export::ship::{build_texture_set, collect_mfm_info};
     use wowsunpack::export::texture;
+    use wowsunpack::export::texture_cache::TextureCache;
     use wowsunpack::models::assets_bin;
     use wowsunpack::models::geometry;
     use wowsunpack::models::visual;
@@ -1558,7 +2886,7 @@ fn run_export_model(params: &ExportModelParams<'_>) -> Result<(), Report> {
         match db.resolve_path(vis_suffix, &self_id_index) {
             Ok((vis_location, vis_full_path)) if vis_location.blob_index == 1 => {
                 let vis_data = db
-                    .get_prototype_data(vis_location, visual::VISUAL_ITEM_SIZE)
+                    .get_record(vis_location)
                     .context("Failed to get visual prototype data")?;
                 let vp =
                     visual::parse_visual(vis_data).context("Failed to parse VisualPrototype")?;
@@ -1598,18 +2926,69 @@ fn run_export_model(params: &ExportModelParams<'_>) -> Result<(), Report> {
             gltf_export::TextureSet::empty()
         } else {
             let mfm_infos = collect_mfm_info(vp, db);
-            build_texture_set(&mfm_infos, vfs)
+            build_texture_set(&mfm_infos, vfs, &TextureCache::new())
         };
 
-        let mut out_file = std::fs::File::create(output).context("Failed to create output file")?;
-        gltf_export::export_glb(vp, &geom, db, lod, &texture_set, damaged, &mut out_file)
-            .context("Failed to export GLB")?;
+        if let Some(mesh_format) = format.mesh_format() {
+            let scene = MeshScene::from_model(vp, &geom, db, lod, &texture_set, damaged)
+                .context("Failed to collect model meshes")?;
+            mesh_formats::write_mesh_file(&scene, mesh_format, output)
+                .context("Failed to write mesh file")?;
+        } else if format == ModelFormat::Usd {
+            let name = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let sub_model = gltf_export::SubModel {
+                name,
+                visual: vp,
+                geometry: &geom,
+                transform: None,
+                group: "Model",
+                barrel_pitch: None,
+                skinned: false,
+                animations: &[],
+                markers: &[],
+            };
+            usd_export::export_ship_usda(
+                &[sub_model],
+                &[],
+                &[],
+                db,
+                lod,
+                &texture_set,
+                damaged,
+                output,
+            )
+            .context("Failed to export USD")?;
+        } else {
+            write_glb_output(format, output, |glb| {
+                gltf_export::export_glb(
+                    vp,
+                    &geom,
+                    db,
+                    lod,
+                    &texture_set,
+                    damaged,
+                    skinned,
+                    all_lods,
+                    glb,
+                )
+                .context("Failed to export GLB")?;
+                Ok(())
+            })?;
+        }
     } else {
         // 4. Fallback: raw geometry export (no visual available).
         if list_textures {
             println!("No visual found; texture listing not available for raw geometry export.");
             return Ok(());
         }
+        if format != ModelFormat::Glb {
+            bail!(
+                "--format {format:?} requires a matching .visual; raw geometry exports as GLB only"
+            );
+        }
         println!("No matching .visual found; exporting raw geometry.");
 
         let mut out_file = std::fs::File::create(output).context("Failed to create output file")?;
@@ -1661,10 +3040,48 @@ fn parse_lightmap_path(xml: &str) -> Option<String> {
     }
 }
 
+fn run_import_glb(file: &Path, output: &Path) -> Result<(), Report> {
+    let data = fs::read(file)?;
+    let imported = wowsunpack::export::gltf_import::import_glb(&data)?;
+    let names: HashMap<u32, &str> = imported
+        .strings
+        .iter()
+        .map(|(id, text)| (*id, text.as_str()))
+        .collect();
+    let name = |id: u32| names.get(&id).copied().unwrap_or("<unknown>");
+
+    println!("RenderSets: {}", imported.render_sets.len());
+    for (i, rs) in imported.render_sets.iter().enumerate() {
+        let nodes: Vec<&str> = rs.node_name_ids.iter().map(|&id| name(id)).collect();
+        println!(
+            "  [{i}] name=\"{}\" (0x{:08X}) material=\"{}\" (0x{:08X}) skinned={} nodes=[{}]\n      vertices_mapping=0x{:08X} indices_mapping=0x{:08X}",
+            name(rs.name_id),
+            rs.name_id,
+            name(rs.material_name_id),
+            rs.material_name_id,
+            rs.skinned,
+            nodes.join(", "),
+            rs.vertices_mapping_id,
+            rs.indices_mapping_id,
+        );
+    }
+    let bb = &imported.bounding_box;
+    println!(
+        "BoundingBox: min=({:.3}, {:.3}, {:.3}) max=({:.3}, {:.3}, {:.3})",
+        bb.min[0], bb.min[1], bb.min[2], bb.max[0], bb.max[1], bb.max[2],
+    );
+
+    let bytes = imported.geometry.to_bytes()?;
+    fs::write(output, &bytes)?;
+    println!("Wrote {} ({} bytes)", output.display(), bytes.len());
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn run_export_map(
     space_dir: &Path,
     output: &Path,
+    format: ModelFormat,
     lod: usize,
     no_vfs: bool,
     vfs: Option<&VfsPath>,
@@ -1675,9 +3092,11 @@ fn run_export_map(
     vegetation_density: f32,
     no_textures: bool,
     max_texture_size: Option<u32>,
+    skinned: bool,
 ) -> Result<(), Report> {
     use wowsunpack::export::gltf_export;
     use wowsunpack::export::texture;
+    use wowsunpack::export::texture_cache::TextureCache;
     use wowsunpack::models::assets_bin;
     use wowsunpack::models::forest;
     use wowsunpack::models::geometry;
@@ -1906,7 +3325,9 @@ fn run_export_map(
                                             read_file_data(&PathBuf::from(&dd0_path), no_vfs, vfs)
                                         })
                                         .ok()?;
-                                texture::dds_to_png_resized(&tex_data, max_texture_size).ok()
+                                texture::dds_to_png_resized(&tex_data, max_texture_size)
+                                    .ok()
+                                    .map(Arc::new)
                             } else {
                                 None
                             };
@@ -1961,26 +3382,39 @@ fn run_export_map(
     };
 
     // 9. Build the format-agnostic MapScene.
-    let vfs_for_textures = if no_textures { None } else { vfs };
     let scene = gltf_export::build_map_scene(&gltf_export::BuildMapSceneParams {
         merged: &merged,
         geometry: &geom,
         space: space.as_ref(),
         db: db.as_ref(),
         lod,
-        vfs: vfs_for_textures,
+        vfs,
         env: &env,
         bounds: bounds.clone(),
+        textures: !no_textures,
         max_texture_size,
+        texture_cache: &TextureCache::new(),
+        skinned,
         vegetation: vegetation_data.as_ref(),
         vegetation_density,
     })
     .context("Failed to build map scene")?;
 
-    // 9. Export to GLB.
-    let mut out_file = std::fs::File::create(output).context("Failed to create output file")?;
-    gltf_export::export_map_scene_glb(&scene, &mut out_file)
-        .context("Failed to export map scene GLB")?;
+    // 10. Export to GLB or USD, or flatten for the other mesh formats.
+    if let Some(mesh_format) = format.mesh_format() {
+        let mesh_scene = MeshScene::from_map(&scene);
+        mesh_formats::write_mesh_file(&mesh_scene, mesh_format, output)
+            .context("Failed to write map mesh file")?;
+    } else if format == ModelFormat::Usd {
+        usd_export::export_map_scene_usda(&scene, output)
+            .context("Failed to export map scene USD")?;
+    } else {
+        write_glb_output(format, output, |glb| {
+            gltf_export::export_map_scene_glb(&scene, glb)
+                .context("Failed to export map scene GLB")?;
+            Ok(())
+        })?;
+    }
 
     let file_size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
     println!("Exported to {} ({} bytes)", output.display(), file_size);
@@ -1993,7 +3427,9 @@ fn run_export_ship(
     vfs: &VfsPath,
     name: &str,
     output: &Path,
+    format: ModelFormat,
     lod: usize,
+    all_lods: bool,
     game_dir: &Path,
     game_version: Option<u64>,
     list_upgrades: bool,
@@ -2001,10 +3437,16 @@ fn run_export_ship(
     no_textures: bool,
     damaged: bool,
     list_textures: bool,
+    collision: bool,
+    splash: bool,
+    firing_arcs: bool,
+    skinned: bool,
     debug: bool,
 ) -> Result<(), Report> {
     use wowsunpack::export::ship::{ShipAssets, ShipExportOptions};
 
+    format.check_all_lods(all_lods)?;
+    format.check_splash(splash)?;
     let mut assets = ShipAssets::load(vfs)?;
 
     // Load translations if available.
@@ -2052,9 +3494,14 @@ fn run_export_ship(
 
     let options = ShipExportOptions {
         lod,
+        all_lods,
         hull: hull_selection.map(|s| s.to_string()),
         textures: !no_textures,
         damaged,
+        collision,
+        splash,
+        firing_arcs,
+        skinned,
         ..Default::default()
     };
     let ctx = assets.load_ship(name, &options)?;
@@ -2069,8 +3516,13 @@ fn run_export_ship(
     let has_armor = ctx.armor_map().is_some() || ctx.hull_splash_bytes().is_some();
 
     wowsunpack::export::set_debug(debug);
-    let mut file = std::fs::File::create(output).context("Failed to create output file")?;
-    ctx.export_glb(&mut file)?;
+    if let Some(mesh_format) = format.mesh_format() {
+        ctx.export_mesh(mesh_format, output)?;
+    } else if format == ModelFormat::Usd {
+        ctx.export_usda(output)?;
+    } else {
+        write_glb_output(format, output, |glb| ctx.export_glb(glb))?;
+    }
 
     let file_size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
     println!("Exported to {} ({} bytes)", output.display(), file_size);
@@ -2616,7 +4068,7 @@ fn run_dump_uvs(
         }
 
         for (buf_idx, vert_proto) in geom.merged_vertices.iter().enumerate() {
-            let format = vertex_format::parse_vertex_format(&vert_proto.format_name);
+            let format = vertex_format::parse_vertex_format(&vert_proto.format_name)?;
             let stride = vert_proto.stride_in_bytes as usize;
 
             let uv_attr = format
@@ -6,9 +6,10 @@ use winnow::Parser;
 use winnow::binary::{le_i64, le_u16, le_u32};
 
 use crate::data::parser_utils::{WResult, parse_packed_string_fields, resolve_relptr};
+use crate::models::prototype_layout::{self, LayoutMatch, PrototypeLayout};
 
-const BWDB_MAGIC: u32 = 0x42574442;
-const BWDB_VERSION: u32 = 0x01010000;
+pub(crate) const BWDB_MAGIC: u32 = 0x42574442;
+pub(crate) const BWDB_VERSION: u32 = 0x01010000;
 
 /// Errors that can occur during assets.bin (PrototypeDatabase) parsing.
 #[derive(Debug, Error)]
@@ -29,6 +30,18 @@ pub enum AssetsBinError {
         blob: usize,
         count: u64,
     },
+    #[error("records in blob {blob} can't be edited: record layout unknown")]
+    UnsupportedBlobEdit { blob: usize },
+    #[error("invalid prototype record: {0}")]
+    InvalidRecord(String),
+    #[error("unknown prototype type in blob {blob}: magic 0x{magic:08X}")]
+    UnknownPrototype { blob: usize, magic: u32 },
+    #[error("{name} in blob {blob} has checksum 0x{checksum:08X}, which matches no pinned layout")]
+    ChecksumMismatch {
+        blob: usize,
+        name: &'static str,
+        checksum: u32,
+    },
 }
 
 /// The top-level parsed assets.bin (PrototypeDatabase) file.
@@ -70,7 +83,7 @@ pub struct HashmapSection<'a> {
 }
 
 /// A path entry from the pathsStorage array.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PathEntry {
     pub self_id: u64,
     pub parent_id: u64,
@@ -108,6 +121,43 @@ impl<'a> PrototypeDatabase<'a> {
             .collect()
     }
 
+    /// Build a lookup table from `selfId` to the full reconstructed path.
+    pub fn build_path_table(&self) -> HashMap<u64, String> {
+        let self_id_index = self.build_self_id_index();
+        self.paths_storage
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.self_id, self.reconstruct_path(i, &self_id_index)))
+            .collect()
+    }
+
+    /// Look up the record layout of a database blob from its prototype magic
+    /// and checksum.
+    ///
+    /// Use [`Self::layout_match`] to tell whether the layout was verified
+    /// against the blob's `prototype_checksum` or assumed from the baseline.
+    /// Fails if the checksum matches none of the type's pinned layouts.
+    pub fn layout(
+        &self,
+        blob_index: usize,
+    ) -> Result<&'static PrototypeLayout, Report<AssetsBinError>> {
+        let db = self.databases.get(blob_index).ok_or_else(|| {
+            Report::new(AssetsBinError::ParseError(format!(
+                "blob_index {blob_index} >= database count {}",
+                self.databases.len()
+            )))
+        })?;
+        decodable_layout(blob_index, db.prototype_magic, db.prototype_checksum)
+    }
+
+    /// Match a database blob against the known prototype layouts.
+    pub fn layout_match(&self, blob_index: usize) -> LayoutMatch {
+        match self.databases.get(blob_index) {
+            Some(db) => prototype_layout::resolve_layout(db.prototype_magic, db.prototype_checksum),
+            None => LayoutMatch::UnknownType,
+        }
+    }
+
     /// Look up a `selfId` in the resourceToPrototypeMap hashmap.
     ///
     /// The hashmap uses open addressing with linear probing. Each bucket is 16 bytes:
@@ -229,6 +279,16 @@ impl<'a> PrototypeDatabase<'a> {
         Ok(&db.data[record_offset..])
     }
 
+    /// [`Self::get_prototype_data`] with the record size taken from the
+    /// blob's layout (see [`Self::layout`]).
+    pub fn get_record(
+        &self,
+        location: PrototypeLocation,
+    ) -> Result<&'a [u8], Report<AssetsBinError>> {
+        let item_size = self.layout(location.blob_index)?.item_size;
+        self.get_prototype_data(location, item_size)
+    }
+
     /// Reconstruct the full path for a path entry by walking parent links.
     pub fn reconstruct_path(
         &self,
@@ -604,13 +664,20 @@ pub fn parse_assets_bin(file_data: &[u8]) -> Result<PrototypeDatabase<'_>, Repor
             expected: BWDB_MAGIC,
         }));
     }
-    if header.version != BWDB_VERSION {
-        return Err(Report::new(AssetsBinError::UnsupportedVersion {
-            actual: header.version,
+    match header.version {
+        BWDB_VERSION => parse_body_v1_1(file_data, header),
+        actual => Err(Report::new(AssetsBinError::UnsupportedVersion {
+            actual,
             expected: BWDB_VERSION,
-        }));
+        })),
     }
+}
 
+/// Parse the body of a version 0x01010000 database.
+fn parse_body_v1_1(
+    file_data: &[u8],
+    header: Header,
+) -> Result<PrototypeDatabase<'_>, Report<AssetsBinError>> {
     let body_base = 0x10; // header is 16 bytes
 
     let body = parse_body_header(&mut &file_data[body_base..])
@@ -673,3 +740,179 @@ pub fn parse_assets_bin(file_data: &[u8]) -> Result<PrototypeDatabase<'_>, Repor
         databases,
     })
 }
+
+/// Look up the layout for a blob's magic and checksum, failing if its records
+/// can't be decoded.
+pub(crate) fn decodable_layout(
+    blob_index: usize,
+    magic: u32,
+    checksum: u32,
+) -> Result<&'static PrototypeLayout, Report<AssetsBinError>> {
+    match prototype_layout::resolve_layout(magic, checksum) {
+        LayoutMatch::Exact(layout) | LayoutMatch::Assumed(layout) => Ok(layout),
+        LayoutMatch::ChecksumMismatch(layout) => {
+            Err(Report::new(AssetsBinError::ChecksumMismatch {
+                blob: blob_index,
+                name: layout.name,
+                checksum,
+            }))
+        }
+        LayoutMatch::UnknownType => Err(Report::new(AssetsBinError::UnknownPrototype {
+            blob: blob_index,
+            magic,
+        })),
+    }
+}
+
+/// Compute the header checksum of an assets.bin file: CRC32 of everything
+/// after the 16-byte header.
+pub fn compute_checksum(file_data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(file_data.get(0x10..).unwrap_or_default());
+    crc.sum()
+}
+
+/// Result of checking an assets.bin file's checksum and prototype layouts.
+#[derive(Debug)]
+pub struct ValidationReport {
+    pub version: u32,
+    pub stored_checksum: u32,
+    pub computed_checksum: u32,
+    pub databases: Vec<DatabaseValidation>,
+}
+
+/// Validation result for a single database blob.
+#[derive(Debug)]
+pub struct DatabaseValidation {
+    pub blob_index: usize,
+    pub prototype_magic: u32,
+    pub prototype_checksum: u32,
+    pub layout: LayoutMatch,
+    pub record_count: u64,
+    pub size: u32,
+    /// Problems that make records in this blob unsafe to decode.
+    pub issues: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Whether the stored checksum matches and no blob has issues.
+    ///
+    /// Blobs decoded with an [`LayoutMatch::Assumed`] layout do not fail
+    /// validation, but are called out in the report. A
+    /// [`LayoutMatch::ChecksumMismatch`] is an issue.
+    pub fn is_ok(&self) -> bool {
+        self.stored_checksum == self.computed_checksum
+            && self.databases.iter().all(|db| db.issues.is_empty())
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "version: 0x{:08X}", self.version)?;
+        if self.stored_checksum == self.computed_checksum {
+            writeln!(f, "checksum: 0x{:08X} (ok)", self.stored_checksum)?;
+        } else {
+            writeln!(
+                f,
+                "checksum: MISMATCH, header has 0x{:08X} but the body hashes to 0x{:08X}; \
+                 the file was modified or truncated after it was written",
+                self.stored_checksum, self.computed_checksum
+            )?;
+        }
+
+        for db in &self.databases {
+            let name = db.layout.name().unwrap_or("unknown");
+            let status = match db.layout {
+                LayoutMatch::Exact(_) => "layout verified",
+                LayoutMatch::Assumed(_) => "layout assumed, checksum not verified",
+                LayoutMatch::ChecksumMismatch(_) => "checksum matches no pinned layout",
+                LayoutMatch::UnknownType => "unknown prototype type",
+            };
+            writeln!(
+                f,
+                "[{}] {name}: magic=0x{:08X} checksum=0x{:08X} records={} size={} ({status})",
+                db.blob_index, db.prototype_magic, db.prototype_checksum, db.record_count, db.size
+            )?;
+            for issue in &db.issues {
+                writeln!(f, "    {issue}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse an assets.bin file and check its header checksum, prototype magics
+/// and blob sizes against the known layouts.
+pub fn validate_assets_bin(file_data: &[u8]) -> Result<ValidationReport, Report<AssetsBinError>> {
+    let db = parse_assets_bin(file_data)?;
+
+    let databases = db
+        .databases
+        .iter()
+        .enumerate()
+        .map(|(blob_index, entry)| {
+            let layout = db.layout_match(blob_index);
+            let mut issues = Vec::new();
+
+            match layout {
+                LayoutMatch::Exact(l) | LayoutMatch::Assumed(l) => {
+                    if l.blob_index != blob_index {
+                        issues.push(format!(
+                            "{} is expected at blob index {}; records will be misinterpreted",
+                            l.name, l.blob_index
+                        ));
+                    }
+                    let need = 16 + entry.record_count as usize * l.item_size;
+                    if entry.size > 0 && need > entry.data.len() {
+                        issues.push(format!(
+                            "{} records of 0x{:X} bytes need {need} bytes but the blob has {}; \
+                             the record size likely changed for this checksum",
+                            entry.record_count,
+                            l.item_size,
+                            entry.data.len()
+                        ));
+                    }
+                }
+                LayoutMatch::ChecksumMismatch(l) => issues.push(format!(
+                    "{} layouts are pinned for other checksums; add one for 0x{:08X} \
+                     before decoding its records",
+                    l.name, entry.prototype_checksum
+                )),
+                LayoutMatch::UnknownType => issues.push(format!(
+                    "magic 0x{:08X} does not match any known prototype type",
+                    entry.prototype_magic
+                )),
+            }
+
+            if entry.data.len() >= 16 {
+                let header_size =
+                    u64::from_le_bytes(entry.data[8..16].try_into().expect("8 bytes"));
+                if header_size != 16 {
+                    issues.push(format!("blob header_size is {header_size}, expected 16"));
+                }
+            } else if entry.size > 0 {
+                issues.push(format!(
+                    "blob is {} bytes, too short for its header",
+                    entry.size
+                ));
+            }
+
+            DatabaseValidation {
+                blob_index,
+                prototype_magic: entry.prototype_magic,
+                prototype_checksum: entry.prototype_checksum,
+                layout,
+                record_count: entry.record_count,
+                size: entry.size,
+                issues,
+            }
+        })
+        .collect();
+
+    Ok(ValidationReport {
+        version: db.header.version,
+        stored_checksum: db.header.checksum,
+        computed_checksum: compute_checksum(file_data),
+        databases,
+    })
+}
@@ -0,0 +1,993 @@
+//! Writer for assets.bin (PrototypeDatabase) files.
+//!
+//! [`PrototypeDatabaseWriter`] takes a parsed [`PrototypeDatabase`], allows
+//! strings, path entries and prototype records to be added, replaced or
+//! removed, and serializes the result back into the BWDB container with all
+//! relative pointers rebuilt. Sections are written in a fixed order, so a
+//! file laid out differently keeps its contents but not its byte layout; a
+//! file written by [`PrototypeDatabaseWriter`] round-trips byte-for-byte.
+//! The header checksum is always recomputed.
+//!
+//! Database blobs are kept as borrowed bytes until a record in them is
+//! edited. At that point the blob is split into its fixed-size records and
+//! the out-of-line (OOL) data that follows them, and the relptr fields of each
+//! record are rebased onto the OOL region so records can move freely. This
+//! requires knowing where the relptr fields sit in a record (see
+//! [`PrototypeLayout::relptr_offsets`](crate::models::prototype_layout::PrototypeLayout::relptr_offsets)), which is only known for
+//! VisualPrototype and ModelPrototype; other blobs can't be edited.
+//!
+//! OOL data belonging to replaced or removed records is left in place, so
+//! edited blobs are valid but not compacted.
+
+use std::borrow::Cow;
+
+use rootcause::Report;
+
+use crate::models::assets_bin::{
+    self, AssetsBinError, BWDB_MAGIC, BWDB_VERSION, DatabaseEntry, HashmapSection, PathEntry,
+    PrototypeDatabase, PrototypeLocation,
+};
+
+/// Size of the file header plus the body header.
+const HEADERS_SIZE: usize = 0x10 + 0x60;
+/// Size of a blob header (`u64 count`, `u64 header_size`).
+const BLOB_HEADER_SIZE: usize = 16;
+const PATH_ENTRY_SIZE: usize = 32;
+const DATABASE_ENTRY_SIZE: usize = 0x18;
+
+/// Bucket metadata written for newly inserted offsetsMap entries.
+const STRING_SENTINEL: u64 = 0x8000_0000;
+/// Bucket metadata written for newly inserted resourceToPrototypeMap entries.
+const R2P_SENTINEL: u64 = 1;
+
+/// An open-addressing hashmap with linear probing, as stored in assets.bin.
+///
+/// Slots keep their original bucket metadata so untouched tables are written
+/// back exactly.
+#[derive(Debug, Clone)]
+struct SlotTable {
+    slots: Vec<Option<Slot>>,
+    /// Size in bytes of the key and of the metadata word in each bucket.
+    key_size: usize,
+    /// Metadata written for newly inserted keys.
+    sentinel: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    key: u64,
+    meta: u64,
+    value: u32,
+}
+
+impl SlotTable {
+    fn new(key_size: usize, sentinel: u64) -> Self {
+        Self {
+            slots: Vec::new(),
+            key_size,
+            sentinel,
+        }
+    }
+
+    fn from_section(section: &HashmapSection<'_>, key_size: usize, sentinel: u64) -> Self {
+        let read = |bytes: &[u8]| -> u64 {
+            let mut buf = [0u8; 8];
+            buf[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(buf)
+        };
+
+        let slots = (0..section.capacity as usize)
+            .map(|slot| {
+                let bucket = &section.buckets[slot * section.bucket_stride..];
+                let key = read(&bucket[..key_size]);
+                let meta = read(&bucket[key_size..key_size * 2]);
+                if key == 0 && meta == 0 {
+                    return None;
+                }
+                let value_offset = slot * section.value_stride;
+                let value = u32::from_le_bytes(
+                    section.values[value_offset..value_offset + 4]
+                        .try_into()
+                        .expect("4-byte slice"),
+                );
+                Some(Slot { key, meta, value })
+            })
+            .collect();
+
+        Self {
+            slots,
+            key_size,
+            sentinel,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn home(&self, key: u64) -> usize {
+        (key % self.capacity() as u64) as usize
+    }
+
+    fn find(&self, key: u64) -> Option<usize> {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return None;
+        }
+        let start = self.home(key);
+        for probe in 0..capacity {
+            let slot = (start + probe) % capacity;
+            match &self.slots[slot] {
+                None => return None,
+                Some(s) if s.key == key => return Some(slot),
+                Some(_) => {}
+            }
+        }
+        None
+    }
+
+    fn get(&self, key: u64) -> Option<u32> {
+        self.find(key)
+            .and_then(|slot| self.slots[slot].map(|s| s.value))
+    }
+
+    fn insert(&mut self, key: u64, value: u32) {
+        if let Some(slot) = self.find(key) {
+            if let Some(s) = &mut self.slots[slot] {
+                s.value = value;
+            }
+            return;
+        }
+
+        // Always leave at least one empty slot so lookups terminate.
+        let occupied = self.slots.iter().filter(|s| s.is_some()).count();
+        if occupied + 1 >= self.capacity() {
+            self.grow();
+        }
+
+        let capacity = self.capacity();
+        let mut slot = self.home(key);
+        while self.slots[slot].is_some() {
+            slot = (slot + 1) % capacity;
+        }
+        self.slots[slot] = Some(Slot {
+            key,
+            meta: self.sentinel,
+            value,
+        });
+    }
+
+    /// Remove a key, shifting later entries of the probe chain back so that
+    /// lookups don't stop at the hole.
+    fn remove(&mut self, key: u64) -> Option<u32> {
+        let mut hole = self.find(key)?;
+        let removed = self.slots[hole].take().map(|s| s.value);
+
+        let capacity = self.capacity();
+        let mut next = (hole + 1) % capacity;
+        while let Some(s) = self.slots[next] {
+            let home = self.home(s.key);
+            // Move the entry into the hole unless its home lies cyclically
+            // in (hole, next], in which case it's already reachable.
+            let reachable = if hole <= next {
+                home > hole && home <= next
+            } else {
+                home > hole || home <= next
+            };
+            if !reachable {
+                self.slots[hole] = self.slots[next].take();
+                hole = next;
+            }
+            next = (next + 1) % capacity;
+        }
+
+        removed
+    }
+
+    fn grow(&mut self) {
+        let mut capacity = (self.capacity() * 2 + 1).max(17);
+        while !is_prime(capacity) {
+            capacity += 1;
+        }
+
+        let old = std::mem::replace(&mut self.slots, vec![None; capacity]);
+        for s in old.into_iter().flatten() {
+            let mut slot = self.home(s.key);
+            while self.slots[slot].is_some() {
+                slot = (slot + 1) % capacity;
+            }
+            self.slots[slot] = Some(s);
+        }
+    }
+
+    fn write_buckets(&self, out: &mut Vec<u8>) {
+        for slot in &self.slots {
+            let (key, meta) = slot.map(|s| (s.key, s.meta)).unwrap_or((0, 0));
+            out.extend_from_slice(&key.to_le_bytes()[..self.key_size]);
+            out.extend_from_slice(&meta.to_le_bytes()[..self.key_size]);
+        }
+    }
+
+    fn write_values(&self, out: &mut Vec<u8>) {
+        for slot in &self.slots {
+            out.extend_from_slice(&slot.map(|s| s.value).unwrap_or(0).to_le_bytes());
+        }
+    }
+}
+
+fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut i = 2;
+    while i * i <= n {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// A database blob, either untouched or split into editable records.
+#[derive(Debug, Clone)]
+enum BlobData<'a> {
+    /// Original blob bytes (including the 16-byte header), written as-is.
+    Raw(&'a [u8]),
+    Edited(EditedBlob),
+}
+
+/// A blob split into fixed records and the OOL region that follows them.
+#[derive(Debug, Clone)]
+struct EditedBlob {
+    item_size: usize,
+    relptr_offsets: &'static [usize],
+    records: Vec<EditedRecord>,
+    /// OOL data, written directly after the last fixed record.
+    ool: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct EditedRecord {
+    fixed: Vec<u8>,
+    /// Target of each relptr field as an offset into the OOL region, in
+    /// `relptr_offsets` order. `None` for null relptrs.
+    targets: Vec<Option<usize>>,
+}
+
+impl EditedBlob {
+    fn split(
+        blob_index: usize,
+        slot: &DatabaseSlot<'_>,
+        data: &[u8],
+    ) -> Result<Self, Report<AssetsBinError>> {
+        let layout = assets_bin::decodable_layout(
+            blob_index,
+            slot.prototype_magic,
+            slot.prototype_checksum,
+        )?;
+        let relptr_offsets = layout
+            .relptr_offsets
+            .ok_or_else(|| Report::new(AssetsBinError::UnsupportedBlobEdit { blob: blob_index }))?;
+        let item_size = layout.item_size;
+        let record_count = slot.record_count;
+
+        let count = record_count as usize;
+        let ool_start = BLOB_HEADER_SIZE + count * item_size;
+        if data.is_empty() {
+            return Ok(Self {
+                item_size,
+                relptr_offsets,
+                records: Vec::new(),
+                ool: Vec::new(),
+            });
+        }
+        if ool_start > data.len() {
+            return Err(Report::new(AssetsBinError::OutOfBounds {
+                offset: ool_start,
+            }));
+        }
+
+        let mut records = Vec::with_capacity(count);
+        for i in 0..count {
+            let base = BLOB_HEADER_SIZE + i * item_size;
+            let fixed = data[base..base + item_size].to_vec();
+            let targets = relptr_offsets
+                .iter()
+                .map(|&off| {
+                    let relptr = read_i64(&fixed, off);
+                    if relptr == 0 {
+                        return Ok(None);
+                    }
+                    let abs = base as i64 + relptr;
+                    if abs < ool_start as i64 || abs > data.len() as i64 {
+                        return Err(Report::new(AssetsBinError::InvalidRecord(format!(
+                            "blob {blob_index} record {i}: relptr at +0x{off:X} points outside OOL data"
+                        ))));
+                    }
+                    Ok(Some(abs as usize - ool_start))
+                })
+                .collect::<Result<_, _>>()?;
+            records.push(EditedRecord { fixed, targets });
+        }
+
+        Ok(Self {
+            item_size,
+            relptr_offsets,
+            records,
+            ool: data[ool_start..].to_vec(),
+        })
+    }
+
+    /// Copy `record_data` (fixed record followed by its OOL data, relptrs
+    /// relative to the record start) into this blob, appending its OOL bytes.
+    fn import_record(
+        &mut self,
+        record_data: &[u8],
+    ) -> Result<EditedRecord, Report<AssetsBinError>> {
+        let item_size = self.item_size;
+        if record_data.len() < item_size {
+            return Err(Report::new(AssetsBinError::InvalidRecord(format!(
+                "record is {} bytes, expected at least {item_size}",
+                record_data.len()
+            ))));
+        }
+
+        // Keep appended arrays 8-byte aligned relative to the OOL start.
+        self.ool.resize(self.ool.len().next_multiple_of(8), 0);
+        let chunk_start = self.ool.len();
+
+        let fixed = record_data[..item_size].to_vec();
+        let targets = self
+            .relptr_offsets
+            .iter()
+            .map(|&off| {
+                let relptr = read_i64(&fixed, off);
+                if relptr == 0 {
+                    return Ok(None);
+                }
+                if relptr < item_size as i64 || relptr > record_data.len() as i64 {
+                    return Err(Report::new(AssetsBinError::InvalidRecord(format!(
+                        "relptr at +0x{off:X} points outside the record's OOL data"
+                    ))));
+                }
+                Ok(Some(chunk_start + relptr as usize - item_size))
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.ool.extend_from_slice(&record_data[item_size..]);
+        Ok(EditedRecord { fixed, targets })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        let count = self.records.len();
+        out.extend_from_slice(&(count as u64).to_le_bytes());
+        out.extend_from_slice(&(BLOB_HEADER_SIZE as u64).to_le_bytes());
+
+        for (i, record) in self.records.iter().enumerate() {
+            let mut fixed = record.fixed.clone();
+            for (&off, target) in self.relptr_offsets.iter().zip(&record.targets) {
+                let relptr = match target {
+                    Some(t) => ((count - i) * self.item_size + t) as i64,
+                    None => 0,
+                };
+                fixed[off..off + 8].copy_from_slice(&relptr.to_le_bytes());
+            }
+            out.extend_from_slice(&fixed);
+        }
+        out.extend_from_slice(&self.ool);
+    }
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().expect("8-byte slice"))
+}
+
+#[derive(Debug, Clone)]
+struct DatabaseSlot<'a> {
+    prototype_magic: u32,
+    prototype_checksum: u32,
+    record_count: u64,
+    data: BlobData<'a>,
+}
+
+impl DatabaseSlot<'_> {
+    fn len(&self) -> usize {
+        match &self.data {
+            BlobData::Raw(data) => data.len(),
+            BlobData::Edited(blob) => {
+                BLOB_HEADER_SIZE + blob.records.len() * blob.item_size + blob.ool.len()
+            }
+        }
+    }
+}
+
+/// An editable PrototypeDatabase that can be serialized back to assets.bin.
+///
+/// ```no_run
+/// # use wowsunpack::models::assets_bin::parse_assets_bin;
+/// # use wowsunpack::models::assets_bin_writer::PrototypeDatabaseWriter;
+/// # fn main() -> Result<(), rootcause::Report> {
+/// # let data = std::fs::read("assets.bin")?;
+/// # let (self_id, record) = (0u64, vec![0u8; 0x28]);
+/// let db = parse_assets_bin(&data)?;
+/// let mut writer = PrototypeDatabaseWriter::from_database(&db);
+/// writer.insert_prototype(self_id, 3, &record)?;
+/// std::fs::write("assets.bin", writer.to_bytes())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrototypeDatabaseWriter<'a> {
+    architecture: u16,
+    endianness: u16,
+    strings: SlotTable,
+    string_data: Cow<'a, [u8]>,
+    resource_to_prototype: SlotTable,
+    paths: Vec<PathEntry>,
+    databases: Vec<DatabaseSlot<'a>>,
+}
+
+impl<'a> PrototypeDatabaseWriter<'a> {
+    /// Create an empty 64-bit little-endian database with one empty blob per
+    /// `(prototype_magic, prototype_checksum)` pair.
+    pub fn new(prototypes: &[(u32, u32)]) -> Self {
+        Self {
+            architecture: 0x40,
+            endianness: 0,
+            strings: SlotTable::new(4, STRING_SENTINEL),
+            string_data: Cow::Owned(Vec::new()),
+            resource_to_prototype: SlotTable::new(8, R2P_SENTINEL),
+            paths: Vec::new(),
+            databases: prototypes
+                .iter()
+                .map(|&(prototype_magic, prototype_checksum)| DatabaseSlot {
+                    prototype_magic,
+                    prototype_checksum,
+                    record_count: 0,
+                    data: BlobData::Raw(&[]),
+                })
+                .collect(),
+        }
+    }
+
+    /// Start from an existing database. Blob and string data stay borrowed
+    /// until modified.
+    pub fn from_database(db: &PrototypeDatabase<'a>) -> Self {
+        Self {
+            architecture: db.header.architecture,
+            endianness: db.header.endianness,
+            strings: SlotTable::from_section(&db.strings.offsets_map, 4, STRING_SENTINEL),
+            string_data: Cow::Borrowed(db.strings.string_data),
+            resource_to_prototype: SlotTable::from_section(
+                &db.resource_to_prototype_map,
+                8,
+                R2P_SENTINEL,
+            ),
+            paths: db.paths_storage.clone(),
+            databases: db
+                .databases
+                .iter()
+                .map(|entry: &DatabaseEntry<'a>| DatabaseSlot {
+                    prototype_magic: entry.prototype_magic,
+                    prototype_checksum: entry.prototype_checksum,
+                    record_count: entry.record_count,
+                    data: BlobData::Raw(entry.data),
+                })
+                .collect(),
+        }
+    }
+
+    /// Add a string to the strings section under `name_id`, returning its
+    /// offset in the string data pool. An existing entry with the same text
+    /// is reused; otherwise the ID is pointed at the new text.
+    pub fn add_string(&mut self, name_id: u32, text: &str) -> u32 {
+        if let Some(offset) = self.strings.get(name_id as u64) {
+            let existing = &self.string_data[offset as usize..];
+            if existing.starts_with(text.as_bytes()) && existing.get(text.len()) == Some(&0) {
+                return offset;
+            }
+        }
+
+        let data = self.string_data.to_mut();
+        let offset = data.len() as u32;
+        data.extend_from_slice(text.as_bytes());
+        data.push(0);
+        self.strings.insert(name_id as u64, offset);
+        offset
+    }
+
+    /// Add a path entry, replacing any entry with the same `self_id`.
+    pub fn set_path(&mut self, entry: PathEntry) {
+        match self.paths.iter_mut().find(|p| p.self_id == entry.self_id) {
+            Some(existing) => *existing = entry,
+            None => self.paths.push(entry),
+        }
+    }
+
+    /// Remove the path entry for `self_id`. Returns whether it existed.
+    pub fn remove_path(&mut self, self_id: u64) -> bool {
+        let before = self.paths.len();
+        self.paths.retain(|p| p.self_id != self_id);
+        self.paths.len() != before
+    }
+
+    /// Look up where `self_id` currently maps to.
+    pub fn prototype_location(&self, self_id: u64) -> Option<PrototypeLocation> {
+        let value = self.resource_to_prototype.get(self_id)?;
+        Some(PrototypeLocation {
+            blob_index: (value & 0xFF) as usize / 4,
+            record_index: (value >> 8) as usize,
+        })
+    }
+
+    fn edited_blob(
+        &mut self,
+        blob_index: usize,
+    ) -> Result<&mut EditedBlob, Report<AssetsBinError>> {
+        let count = self.databases.len();
+        let slot = self.databases.get_mut(blob_index).ok_or_else(|| {
+            Report::new(AssetsBinError::ParseError(format!(
+                "blob_index {blob_index} >= database count {count}"
+            )))
+        })?;
+
+        if let BlobData::Raw(data) = slot.data {
+            slot.data = BlobData::Edited(EditedBlob::split(blob_index, slot, data)?);
+        }
+        match &mut slot.data {
+            BlobData::Edited(blob) => Ok(blob),
+            BlobData::Raw(_) => unreachable!("blob was just split"),
+        }
+    }
+
+    /// Insert or replace the prototype record for `self_id` in `blob_index`.
+    ///
+    /// `record_data` is the fixed record followed by its OOL data, with
+    /// relptrs relative to the record start, i.e. the layout returned by
+    /// [`PrototypeDatabase::get_prototype_data`]. If `self_id` already maps
+    /// to a record in the same blob that record is replaced in place;
+    /// otherwise a new record is appended.
+    pub fn insert_prototype(
+        &mut self,
+        self_id: u64,
+        blob_index: usize,
+        record_data: &[u8],
+    ) -> Result<PrototypeLocation, Report<AssetsBinError>> {
+        let existing = self.prototype_location(self_id);
+        if let Some(location) = existing
+            && location.blob_index != blob_index
+        {
+            self.remove_prototype(self_id)?;
+        }
+
+        let blob = self.edited_blob(blob_index)?;
+        let record = blob.import_record(record_data)?;
+        let record_index = match existing {
+            Some(location) if location.blob_index == blob_index => {
+                blob.records[location.record_index] = record;
+                location.record_index
+            }
+            _ => {
+                if blob.records.len() >= 1 << 24 {
+                    return Err(Report::new(AssetsBinError::InvalidRecord(format!(
+                        "blob {blob_index} is full"
+                    ))));
+                }
+                blob.records.push(record);
+                blob.records.len() - 1
+            }
+        };
+
+        let count = blob.records.len() as u64;
+        self.databases[blob_index].record_count = count;
+        self.resource_to_prototype.insert(
+            self_id,
+            ((record_index as u32) << 8) | (blob_index as u32 * 4),
+        );
+
+        Ok(PrototypeLocation {
+            blob_index,
+            record_index,
+        })
+    }
+
+    /// Remove the prototype record `self_id` maps to, along with every
+    /// resourceToPrototypeMap entry pointing at it. Returns `false` if
+    /// `self_id` has no prototype. Path entries are left untouched.
+    pub fn remove_prototype(&mut self, self_id: u64) -> Result<bool, Report<AssetsBinError>> {
+        let Some(location) = self.prototype_location(self_id) else {
+            return Ok(false);
+        };
+
+        let blob = self.edited_blob(location.blob_index)?;
+        if location.record_index >= blob.records.len() {
+            return Err(Report::new(AssetsBinError::PrototypeOutOfRange {
+                index: location.record_index,
+                blob: location.blob_index,
+                count: blob.records.len() as u64,
+            }));
+        }
+        blob.records.remove(location.record_index);
+        let count = blob.records.len() as u64;
+        self.databases[location.blob_index].record_count = count;
+
+        // Drop every key referencing the removed record and shift the indices
+        // of later records in the same blob down by one.
+        let tag = location.blob_index as u32 * 4;
+        let removed = location.record_index as u32;
+        let mut stale = Vec::new();
+        for slot in self.resource_to_prototype.slots.iter_mut().flatten() {
+            if slot.value & 0xFF != tag {
+                continue;
+            }
+            let index = slot.value >> 8;
+            if index == removed {
+                stale.push(slot.key);
+            } else if index > removed {
+                slot.value = ((index - 1) << 8) | tag;
+            }
+        }
+        for key in stale {
+            self.resource_to_prototype.remove(key);
+        }
+
+        Ok(true)
+    }
+
+    /// Serialize the database into an assets.bin file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body_base = 0x10;
+        let strings_base = body_base;
+        let r2p_base = body_base + 0x28;
+        let paths_base = body_base + 0x40;
+
+        // Section offsets, laid out back to back after the headers.
+        let offsets_buckets = HEADERS_SIZE;
+        let offsets_values = offsets_buckets + self.strings.capacity() * 8;
+        let string_data = offsets_values + self.strings.capacity() * 4;
+        let r2p_buckets = string_data + self.string_data.len();
+        let r2p_values = r2p_buckets + self.resource_to_prototype.capacity() * 16;
+        let paths_data = r2p_values + self.resource_to_prototype.capacity() * 4;
+        let path_names = paths_data + self.paths.len() * PATH_ENTRY_SIZE;
+        let names_size: usize = self
+            .paths
+            .iter()
+            .filter(|p| !p.name.is_empty())
+            .map(|p| p.name.len() + 1)
+            .sum();
+        let databases = path_names + names_size;
+        let blobs = databases + self.databases.len() * DATABASE_ENTRY_SIZE;
+        let total = blobs + self.databases.iter().map(DatabaseSlot::len).sum::<usize>();
+
+        let rel = |base: usize, target: usize| (target as i64 - base as i64).to_le_bytes();
+
+        let mut out = Vec::with_capacity(total);
+
+        // File header; the checksum is patched in once the body is written.
+        out.extend_from_slice(&BWDB_MAGIC.to_le_bytes());
+        out.extend_from_slice(&BWDB_VERSION.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&self.architecture.to_le_bytes());
+        out.extend_from_slice(&self.endianness.to_le_bytes());
+
+        // Body header.
+        out.extend_from_slice(&(self.strings.capacity() as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&rel(strings_base, offsets_buckets));
+        out.extend_from_slice(&rel(strings_base, offsets_values));
+        out.extend_from_slice(&(self.string_data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&rel(strings_base, string_data));
+        out.extend_from_slice(&(self.resource_to_prototype.capacity() as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&rel(r2p_base, r2p_buckets));
+        out.extend_from_slice(&rel(r2p_base, r2p_values));
+        out.extend_from_slice(&(self.paths.len() as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&rel(paths_base, paths_data));
+        out.extend_from_slice(&(self.databases.len() as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&rel(body_base, databases));
+        debug_assert_eq!(out.len(), HEADERS_SIZE);
+
+        // Strings and resourceToPrototypeMap.
+        self.strings.write_buckets(&mut out);
+        self.strings.write_values(&mut out);
+        out.extend_from_slice(&self.string_data);
+        self.resource_to_prototype.write_buckets(&mut out);
+        self.resource_to_prototype.write_values(&mut out);
+
+        // pathsStorage entries, then their name pool.
+        let mut name_offset = path_names;
+        for (i, entry) in self.paths.iter().enumerate() {
+            let name_base = paths_data + i * PATH_ENTRY_SIZE + 0x10;
+            out.extend_from_slice(&entry.self_id.to_le_bytes());
+            out.extend_from_slice(&entry.parent_id.to_le_bytes());
+            if entry.name.is_empty() {
+                out.extend_from_slice(&[0; 16]);
+            } else {
+                out.extend_from_slice(&(entry.name.len() as u32 + 1).to_le_bytes());
+                out.extend_from_slice(&0u32.to_le_bytes());
+                out.extend_from_slice(&rel(name_base, name_offset));
+                name_offset += entry.name.len() + 1;
+            }
+        }
+        for entry in self.paths.iter().filter(|p| !p.name.is_empty()) {
+            out.extend_from_slice(entry.name.as_bytes());
+            out.push(0);
+        }
+
+        // Database entries, then their blobs.
+        let mut blob_offset = blobs;
+        for (i, db) in self.databases.iter().enumerate() {
+            let entry_base = databases + i * DATABASE_ENTRY_SIZE;
+            let size = db.len();
+            out.extend_from_slice(&db.prototype_magic.to_le_bytes());
+            out.extend_from_slice(&db.prototype_checksum.to_le_bytes());
+            out.extend_from_slice(&(size as u32).to_le_bytes());
+            out.extend_from_slice(&0u32.to_le_bytes());
+            if size == 0 {
+                out.extend_from_slice(&0i64.to_le_bytes());
+            } else {
+                out.extend_from_slice(&rel(entry_base, blob_offset));
+            }
+            blob_offset += size;
+        }
+        for db in &self.databases {
+            match &db.data {
+                BlobData::Raw(data) => out.extend_from_slice(data),
+                BlobData::Edited(blob) => blob.write(&mut out),
+            }
+        }
+        debug_assert_eq!(out.len(), total);
+
+        let checksum = assets_bin::compute_checksum(&out);
+        out[0x08..0x0C].copy_from_slice(&checksum.to_le_bytes());
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::assets_bin::parse_assets_bin;
+    use crate::models::prototype_layout;
+
+    const MODEL_BLOB: usize = 3;
+
+    /// A ModelPrototype record with `ids` as its skeleton extender array.
+    fn model_record(visual_id: u64, ids: &[u64]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x28];
+        data[..8].copy_from_slice(&visual_id.to_le_bytes());
+        data[8] = ids.len() as u8;
+        let ool_start = data.len() as i64;
+        data[0x10..0x18].copy_from_slice(&ool_start.to_le_bytes());
+        for id in ids {
+            data.extend_from_slice(&id.to_le_bytes());
+        }
+        data
+    }
+
+    fn skel_ext_ids(db: &PrototypeDatabase<'_>, self_id: u64) -> (u64, Vec<u64>) {
+        let value = db.lookup_r2p(self_id).unwrap();
+        let location = db.decode_r2p_value(value).unwrap();
+        let data = db.get_prototype_data(location, 0x28).unwrap();
+        let visual = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let abs = read_i64(data, 0x10) as usize;
+        let ids = (0..data[8] as usize)
+            .map(|i| u64::from_le_bytes(data[abs + i * 8..abs + i * 8 + 8].try_into().unwrap()))
+            .collect();
+        (visual, ids)
+    }
+
+    fn sample() -> Vec<u8> {
+        let prototypes: Vec<_> = prototype_layout::LAYOUTS
+            .iter()
+            .map(|l| (l.magic, 0))
+            .collect();
+        let mut writer = PrototypeDatabaseWriter::new(&prototypes);
+        writer.add_string(0x1234, "Hull");
+        writer.set_path(PathEntry {
+            self_id: 1,
+            parent_id: 0,
+            name: "content".to_string(),
+        });
+        for id in 10..40u64 {
+            writer.set_path(PathEntry {
+                self_id: id,
+                parent_id: 1,
+                name: format!("ship_{id}.model"),
+            });
+            writer
+                .insert_prototype(id, MODEL_BLOB, &model_record(id * 100, &[id, id + 1]))
+                .unwrap();
+        }
+        writer.to_bytes()
+    }
+
+    #[test]
+    fn unmodified_round_trip_is_exact() {
+        let data = sample();
+        let db = parse_assets_bin(&data).unwrap();
+        assert_eq!(db.strings.get_string_by_id(0x1234), Some("Hull"));
+        assert_eq!(db.databases[MODEL_BLOB].record_count, 30);
+        assert_eq!(skel_ext_ids(&db, 25), (2500, vec![25, 26]));
+
+        let written = PrototypeDatabaseWriter::from_database(&db).to_bytes();
+        assert_eq!(written, data);
+    }
+
+    #[test]
+    fn edits_keep_other_records_intact() {
+        let data = sample();
+        let db = parse_assets_bin(&data).unwrap();
+        let mut writer = PrototypeDatabaseWriter::from_database(&db);
+
+        assert!(writer.remove_prototype(12).unwrap());
+        writer
+            .insert_prototype(20, MODEL_BLOB, &model_record(7, &[1, 2, 3]))
+            .unwrap();
+        writer
+            .insert_prototype(99, MODEL_BLOB, &model_record(9, &[]))
+            .unwrap();
+        let edited = writer.to_bytes();
+
+        let db = parse_assets_bin(&edited).unwrap();
+        assert_eq!(db.lookup_r2p(12), None);
+        assert_eq!(db.databases[MODEL_BLOB].record_count, 30);
+        assert_eq!(skel_ext_ids(&db, 11), (1100, vec![11, 12]));
+        assert_eq!(skel_ext_ids(&db, 39), (3900, vec![39, 40]));
+        assert_eq!(skel_ext_ids(&db, 20), (7, vec![1, 2, 3]));
+        assert_eq!(skel_ext_ids(&db, 99), (9, vec![]));
+
+        // The checksum is recomputed for modified files, and a second pass
+        // over the edited file is still exact.
+        assert_ne!(edited[0x08..0x0C], data[0x08..0x0C]);
+        assert_eq!(
+            PrototypeDatabaseWriter::from_database(&db).to_bytes(),
+            edited
+        );
+    }
+
+    #[test]
+    fn written_file_validates() {
+        let mut data = sample();
+        let report = assets_bin::validate_assets_bin(&data).unwrap();
+        assert!(report.is_ok(), "{report}");
+
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        let report = assets_bin::validate_assets_bin(&data).unwrap();
+        assert!(!report.is_ok());
+        assert!(report.to_string().contains("MISMATCH"));
+    }
+
+    /// A small assets.bin built byte by byte from the format description,
+    /// with its sections in a different order than the writer uses: database
+    /// entries and blobs first, then paths, resourceToPrototypeMap and
+    /// strings last.
+    fn game_layout_file() -> Vec<u8> {
+        fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+            buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+        fn rel(base: usize, target: usize) -> [u8; 8] {
+            (target as i64 - base as i64).to_le_bytes()
+        }
+        let mut buf = vec![0u8; 0x20D];
+
+        put(&mut buf, 0x00, &0x42574442u32.to_le_bytes());
+        put(&mut buf, 0x04, &0x01010000u32.to_le_bytes());
+        put(&mut buf, 0x0C, &0x40u16.to_le_bytes());
+
+        // Body header: strings, resourceToPrototypeMap, pathsStorage,
+        // databases.
+        put(&mut buf, 0x10, &2u32.to_le_bytes());
+        put(&mut buf, 0x18, &rel(0x10, 0x1F0));
+        put(&mut buf, 0x20, &rel(0x10, 0x200));
+        put(&mut buf, 0x28, &5u32.to_le_bytes());
+        put(&mut buf, 0x30, &rel(0x10, 0x208));
+        put(&mut buf, 0x38, &4u32.to_le_bytes());
+        put(&mut buf, 0x40, &rel(0x38, 0x1A0));
+        put(&mut buf, 0x48, &rel(0x38, 0x1E0));
+        put(&mut buf, 0x50, &2u32.to_le_bytes());
+        put(&mut buf, 0x58, &rel(0x50, 0x148));
+        put(&mut buf, 0x60, &4u32.to_le_bytes());
+        put(&mut buf, 0x68, &rel(0x10, 0x70));
+
+        // Database entries; only ModelPrototype (blob 3) has records.
+        for (i, magic) in [0x5069C471u32, 0x480DC57B, 0x1AE023FF, 0xA9576F28]
+            .into_iter()
+            .enumerate()
+        {
+            put(&mut buf, 0x70 + i * 0x18, &magic.to_le_bytes());
+        }
+        put(&mut buf, 0xB8 + 0x04, &0x1C2D3E4Fu32.to_le_bytes());
+        put(&mut buf, 0xB8 + 0x08, &0x78u32.to_le_bytes());
+        put(&mut buf, 0xB8 + 0x10, &rel(0xB8, 0xD0));
+
+        // Blob: header, two 0x28-byte records, then their skelExtResIds.
+        put(&mut buf, 0xD0, &2u64.to_le_bytes());
+        put(&mut buf, 0xD8, &16u64.to_le_bytes());
+        put(&mut buf, 0xE0, &100u64.to_le_bytes());
+        buf[0xE8] = 2;
+        put(&mut buf, 0xF0, &rel(0xE0, 0x130));
+        put(&mut buf, 0x108, &200u64.to_le_bytes());
+        buf[0x110] = 1;
+        put(&mut buf, 0x118, &rel(0x108, 0x140));
+        for (i, id) in [5u64, 6, 7].into_iter().enumerate() {
+            put(&mut buf, 0x130 + i * 8, &id.to_le_bytes());
+        }
+
+        // Path entries and their name pool.
+        for (i, (self_id, parent_id, name, name_offset)) in [
+            (1u64, 0u64, "content\0", 0x188),
+            (16, 1, "hull.model\0", 0x190),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let entry = 0x148 + i * 32;
+            put(&mut buf, entry, &self_id.to_le_bytes());
+            put(&mut buf, entry + 0x08, &parent_id.to_le_bytes());
+            put(&mut buf, entry + 0x10, &(name.len() as u32).to_le_bytes());
+            put(&mut buf, entry + 0x18, &rel(entry + 0x10, name_offset));
+            put(&mut buf, name_offset, name.as_bytes());
+        }
+
+        // resourceToPrototypeMap: 16 -> record 0, 33 -> record 1.
+        for (slot, key, value) in [(0usize, 16u64, 0x0Cu32), (1, 33, 0x10C)] {
+            put(&mut buf, 0x1A0 + slot * 16, &key.to_le_bytes());
+            put(&mut buf, 0x1A8 + slot * 16, &1u64.to_le_bytes());
+            put(&mut buf, 0x1E0 + slot * 4, &value.to_le_bytes());
+        }
+
+        // offsetsMap and string data.
+        put(&mut buf, 0x1F0, &0x1234u32.to_le_bytes());
+        put(&mut buf, 0x1F4, &0x8000_0000u32.to_le_bytes());
+        put(&mut buf, 0x208, b"Hull\0");
+
+        let mut crc = flate2::Crc::new();
+        crc.update(&buf[0x10..]);
+        put(&mut buf, 0x08, &crc.sum().to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn game_layout_file_is_rewritten_intact() {
+        let data = game_layout_file();
+        assert!(assets_bin::validate_assets_bin(&data).unwrap().is_ok());
+
+        let check = |db: &PrototypeDatabase<'_>| {
+            assert_eq!(db.strings.get_string_by_id(0x1234), Some("Hull"));
+            assert_eq!(db.paths_storage[1].name, "hull.model");
+            assert_eq!(db.paths_storage[1].parent_id, 1);
+            assert_eq!(db.databases[MODEL_BLOB].prototype_checksum, 0x1C2D3E4F);
+            assert_eq!(skel_ext_ids(db, 16), (100, vec![5, 6]));
+            assert_eq!(skel_ext_ids(db, 33), (200, vec![7]));
+        };
+        let db = parse_assets_bin(&data).unwrap();
+        check(&db);
+
+        // Unmodified: same contents and blob bytes, valid checksum.
+        let written = PrototypeDatabaseWriter::from_database(&db).to_bytes();
+        assert!(assets_bin::validate_assets_bin(&written).unwrap().is_ok());
+        let rewritten = parse_assets_bin(&written).unwrap();
+        check(&rewritten);
+        assert_eq!(
+            rewritten.databases[MODEL_BLOB].data,
+            db.databases[MODEL_BLOB].data
+        );
+
+        // Edited: the other record survives the blob split.
+        let mut writer = PrototypeDatabaseWriter::from_database(&db);
+        writer
+            .insert_prototype(16, MODEL_BLOB, &model_record(300, &[8]))
+            .unwrap();
+        let edited = writer.to_bytes();
+        let db = parse_assets_bin(&edited).unwrap();
+        assert_eq!(skel_ext_ids(&db, 16), (300, vec![8]));
+        assert_eq!(skel_ext_ids(&db, 33), (200, vec![7]));
+    }
+}
@@ -0,0 +1,333 @@
+//! Asset dependency graph across assets.bin prototypes.
+//!
+//! Edges come from decoded prototype records:
+//! - VisualPrototype → `.geometry` (`merged_geometry_path_id`) and `.mfm`
+//!   materials (`RenderSet.material_mfm_path_id`)
+//! - ModelPrototype → `.visual`, skeleton extenders, animations, and dye tint
+//!   materials
+//!
+//! Material → texture edges are not stored in assets.bin; they can be added
+//! from a VFS with [`DependencyGraph::add_texture_edges`], which uses the same
+//! naming conventions as texture loading.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write;
+
+use crate::models::assets_bin::PrototypeDatabase;
+use crate::models::model::{self, ModelPrototype};
+use crate::models::visual;
+
+/// How one asset depends on another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DependencyKind {
+    /// Visual → merged `.geometry` file.
+    Geometry,
+    /// Visual → render set `.mfm` material.
+    Material,
+    /// Model → `.visual`.
+    Visual,
+    /// Model → skeleton extender prototype.
+    SkeletonExtender,
+    /// Model → animation resource.
+    Animation,
+    /// Model → dye tint `.mfm` material.
+    DyeMaterial,
+    /// Material → texture file (resolved by naming convention).
+    Texture,
+}
+
+impl DependencyKind {
+    /// Short lowercase label used in text and DOT output.
+    pub fn label(self) -> &'static str {
+        match self {
+            DependencyKind::Geometry => "geometry",
+            DependencyKind::Material => "material",
+            DependencyKind::Visual => "visual",
+            DependencyKind::SkeletonExtender => "skeleton_extender",
+            DependencyKind::Animation => "animation",
+            DependencyKind::DyeMaterial => "dye_material",
+            DependencyKind::Texture => "texture",
+        }
+    }
+}
+
+/// A directed edge to another asset.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dependency {
+    pub path: String,
+    pub kind: DependencyKind,
+}
+
+/// Directed graph of asset paths and what they reference.
+///
+/// Both directions are indexed, so "what does X depend on" and "what uses Y"
+/// are equally cheap.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DependencyGraph {
+    /// Outgoing edges: asset → assets it references.
+    dependencies: BTreeMap<String, BTreeSet<Dependency>>,
+    /// Incoming edges: asset → assets that reference it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dependents: BTreeMap<String, BTreeSet<Dependency>>,
+}
+
+impl DependencyGraph {
+    /// Build the graph from every visual and model prototype in the database.
+    ///
+    /// Records that fail to parse are skipped.
+    pub fn build(db: &PrototypeDatabase<'_>) -> Self {
+        let paths = db.build_path_table();
+        let mut graph = Self::default();
+
+        let resolve = |self_id: u64| -> Option<String> {
+            if self_id == 0 {
+                return None;
+            }
+            Some(
+                paths
+                    .get(&self_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("0x{self_id:016X}")),
+            )
+        };
+
+        for entry in &db.paths_storage {
+            let Some(r2p_value) = db.lookup_r2p(entry.self_id) else {
+                continue;
+            };
+            let Ok(location) = db.decode_r2p_value(r2p_value) else {
+                continue;
+            };
+            let Some(from) = paths.get(&entry.self_id) else {
+                continue;
+            };
+
+            match location.blob_index {
+                1 => {
+                    let Ok(data) = db.get_record(location) else {
+                        continue;
+                    };
+                    let Ok(vp) = visual::parse_visual(data) else {
+                        continue;
+                    };
+                    if let Some(to) = resolve(vp.merged_geometry_path_id) {
+                        graph.add_edge(from, &to, DependencyKind::Geometry);
+                    }
+                    for rs in &vp.render_sets {
+                        if let Some(to) = resolve(rs.material_mfm_path_id) {
+                            graph.add_edge(from, &to, DependencyKind::Material);
+                        }
+                    }
+                }
+                3 => {
+                    let Ok(data) = db.get_record(location) else {
+                        continue;
+                    };
+                    let Ok(mp) = model::parse_model(data) else {
+                        continue;
+                    };
+                    graph.add_model_edges(from, &mp, &resolve);
+                }
+                _ => {}
+            }
+        }
+
+        graph
+    }
+
+    fn add_model_edges(
+        &mut self,
+        from: &str,
+        mp: &ModelPrototype,
+        resolve: &impl Fn(u64) -> Option<String>,
+    ) {
+        if let Some(to) = resolve(mp.visual_resource_id) {
+            self.add_edge(from, &to, DependencyKind::Visual);
+        }
+        for &id in &mp.skel_ext_res_ids {
+            if let Some(to) = resolve(id) {
+                self.add_edge(from, &to, DependencyKind::SkeletonExtender);
+            }
+        }
+        for anim in &mp.animations {
+            if let Some(to) = resolve(anim.visual_resource_id) {
+                self.add_edge(from, &to, DependencyKind::Animation);
+            }
+        }
+        for dye in &mp.dyes {
+            for &id in &dye.tint_material_ids {
+                if let Some(to) = resolve(id) {
+                    self.add_edge(from, &to, DependencyKind::DyeMaterial);
+                }
+            }
+        }
+    }
+
+    /// Add material → texture edges for every `.mfm` node in the graph.
+    ///
+    /// Texture references live inside the material files rather than in
+    /// assets.bin, so they are resolved by naming convention against the VFS.
+    pub fn add_texture_edges(&mut self, vfs: &vfs::VfsPath) {
+        let materials: Vec<String> = self
+            .nodes()
+            .filter(|p| p.ends_with(".mfm"))
+            .map(str::to_string)
+            .collect();
+        for mfm in materials {
+            for tex in crate::export::texture::material_texture_paths(vfs, &mfm) {
+                self.add_edge(&mfm, &tex, DependencyKind::Texture);
+            }
+        }
+    }
+
+    /// Record that `from` depends on `to`.
+    pub fn add_edge(&mut self, from: &str, to: &str, kind: DependencyKind) {
+        self.dependencies
+            .entry(from.to_string())
+            .or_default()
+            .insert(Dependency {
+                path: to.to_string(),
+                kind,
+            });
+        self.dependents
+            .entry(to.to_string())
+            .or_default()
+            .insert(Dependency {
+                path: from.to_string(),
+                kind,
+            });
+    }
+
+    /// Every asset path that appears in the graph, sorted.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.dependencies
+            .keys()
+            .chain(self.dependents.keys())
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    /// Number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.dependencies.values().map(BTreeSet::len).sum()
+    }
+
+    /// Find a node by exact path, falling back to the first path ending with
+    /// `suffix`.
+    pub fn find_node(&self, suffix: &str) -> Option<&str> {
+        self.nodes()
+            .find(|p| *p == suffix)
+            .or_else(|| self.nodes().find(|p| p.ends_with(suffix)))
+    }
+
+    /// Direct dependencies of `path` (what it references).
+    pub fn dependencies(&self, path: &str) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.get(path).into_iter().flatten()
+    }
+
+    /// Direct dependents of `path` (what references it).
+    pub fn dependents(&self, path: &str) -> impl Iterator<Item = &Dependency> {
+        self.dependents.get(path).into_iter().flatten()
+    }
+
+    /// Every asset reachable from `path` by following dependencies.
+    pub fn transitive_dependencies(&self, path: &str) -> BTreeSet<&str> {
+        Self::reachable(&self.dependencies, path)
+    }
+
+    /// Every asset that reaches `path`, e.g. every ship visual and model
+    /// affected by a change to a shared texture.
+    pub fn transitive_dependents(&self, path: &str) -> BTreeSet<&str> {
+        Self::reachable(&self.dependents, path)
+    }
+
+    fn reachable<'a>(
+        edges: &'a BTreeMap<String, BTreeSet<Dependency>>,
+        start: &str,
+    ) -> BTreeSet<&'a str> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            for dep in edges.get(node).into_iter().flatten() {
+                if seen.insert(dep.path.as_str()) {
+                    queue.push_back(dep.path.as_str());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Restrict the graph to edges whose endpoints are both in `paths`.
+    pub fn subgraph(&self, paths: &BTreeSet<&str>) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (from, deps) in &self.dependencies {
+            if !paths.contains(from.as_str()) {
+                continue;
+            }
+            for dep in deps {
+                if paths.contains(dep.path.as_str()) {
+                    graph.add_edge(from, &dep.path, dep.kind);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Render the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph assets {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (from, deps) in &self.dependencies {
+            for dep in deps {
+                let _ = writeln!(
+                    out,
+                    "    {:?} -> {:?} [label={:?}];",
+                    from,
+                    dep.path,
+                    dep.kind.label()
+                );
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DependencyGraph {
+        let mut g = DependencyGraph::default();
+        g.add_edge("ship.model", "ship.visual", DependencyKind::Visual);
+        g.add_edge("ship.visual", "ship.geometry", DependencyKind::Geometry);
+        g.add_edge("ship.visual", "hull.mfm", DependencyKind::Material);
+        g.add_edge("aa.visual", "hull.mfm", DependencyKind::Material);
+        g.add_edge("hull.mfm", "hull_a.dds", DependencyKind::Texture);
+        g
+    }
+
+    #[test]
+    fn transitive_dependents_of_texture() {
+        let g = sample();
+        let users = g.transitive_dependents("hull_a.dds");
+        assert_eq!(
+            users.into_iter().collect::<Vec<_>>(),
+            vec!["aa.visual", "hull.mfm", "ship.model", "ship.visual"]
+        );
+    }
+
+    #[test]
+    fn subgraph_keeps_internal_edges() {
+        let g = sample();
+        let mut keep = g.transitive_dependencies("ship.visual");
+        keep.insert("ship.visual");
+        let sub = g.subgraph(&keep);
+        assert_eq!(sub.edge_count(), 3);
+        assert!(sub.to_dot().contains("\"hull.mfm\" -> \"hull_a.dds\""));
+    }
+}
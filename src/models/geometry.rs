@@ -7,7 +7,7 @@ use winnow::token::take;
 
 use crate::data::parser_utils::{WResult, parse_packed_string, resolve_relptr};
 
-const ENCD_MAGIC: u32 = 0x44434E45;
+pub(crate) const ENCD_MAGIC: u32 = 0x44434E45;
 
 /// Errors that can occur during `.geometry` file parsing.
 #[derive(Debug, Error)]
@@ -22,6 +22,10 @@ pub enum GeometryError {
     DecodeError(String),
     #[error("parse error: {0}")]
     ParseError(String),
+    #[error("meshopt encode error: {0}")]
+    EncodeError(String),
+    #[error("invalid mesh: {0}")]
+    InvalidMesh(String),
 }
 
 #[derive(Debug)]
@@ -35,7 +39,7 @@ pub struct MergedGeometry<'a> {
 }
 
 /// A single triangle in an armor model.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ArmorTriangle {
     pub vertices: [[f32; 3]; 3],
     pub normals: [[f32; 3]; 3],
@@ -55,6 +59,48 @@ pub struct ArmorTriangle {
 pub struct ArmorModel {
     pub name: String,
     pub triangles: Vec<ArmorTriangle>,
+    /// The BVH bytes this model was parsed from, if any. The geometry writer
+    /// keeps them while `triangles` is unchanged.
+    pub stored: Option<StoredArmor>,
+}
+
+/// Armor BVH data as laid out in a `.geometry` file.
+#[derive(Debug, Clone)]
+pub struct StoredArmor {
+    /// Entry stream from right after the prototype to the end of the data.
+    pub data: Vec<u8>,
+    /// The prototype's `size_in_bytes`: `data_relptr` points this many bytes
+    /// before the end of `data`.
+    pub size_in_bytes: u32,
+}
+
+/// A single triangle in a collision model.
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionTriangle {
+    pub vertices: [[f32; 3]; 3],
+    pub normals: [[f32; 3]; 3],
+    /// Collision material ID from the owning BVH node header.
+    /// Maps to a collision material name via the same table as armor triangles.
+    pub material_id: u8,
+}
+
+/// A BVH leaf node of a collision model.
+#[derive(Debug, Clone)]
+pub struct CollisionNode {
+    /// Collision material ID shared by every triangle in this node.
+    pub material_id: u8,
+    pub bbox_min: [f32; 3],
+    pub bbox_max: [f32; 3],
+    /// Range of this node's triangles in [`CollisionModel::triangles`].
+    pub triangles: std::ops::Range<usize>,
+}
+
+/// A decoded collision model (`CM_*`), as returned by [`ModelPrototype::decode`].
+#[derive(Debug)]
+pub struct CollisionModel {
+    pub name: String,
+    pub nodes: Vec<CollisionNode>,
+    pub triangles: Vec<CollisionTriangle>,
 }
 
 /// A named axis-aligned bounding box from a `.splash` file.
@@ -99,6 +145,16 @@ pub struct ModelPrototype<'a> {
     pub size_in_bytes: u32,
 }
 
+impl ModelPrototype<'_> {
+    /// Decode this collision model's BVH into typed nodes and triangles.
+    ///
+    /// Collision data uses the same 16-byte entry stream as armor models
+    /// (see [`ArmorModel`]), but `data` already starts at the global header.
+    pub fn decode(&self) -> Result<CollisionModel, Report<GeometryError>> {
+        parse_collision_data(&self.name, self.data)
+    }
+}
+
 #[derive(Debug)]
 pub enum VertexData<'a> {
     Encoded {
@@ -547,7 +603,16 @@ fn parse_model_fields(input: &mut &[u8]) -> WResult<(i64, u32)> {
     Ok((data_relptr, size_in_bytes))
 }
 
-/// Parse the BVH + triangle data from an armor model's raw 16-byte entry stream.
+/// A BVH leaf node header as stored in armor and collision model data.
+#[derive(Debug, Clone, Copy)]
+struct BvhNodeHeader {
+    material_id: u8,
+    layer_index: u8,
+    bbox_min: [f32; 3],
+    bbox_max: [f32; 3],
+}
+
+/// Walk the BVH node groups of an armor or collision model's raw 16-byte entry stream.
 ///
 /// Format (all entries are 16 bytes):
 ///   - 2 global header entries (bounding box + BVH node count)
@@ -555,26 +620,34 @@ fn parse_model_fields(input: &mut &[u8]) -> WResult<(i64, u32)> {
 ///       - 2 entries: node header (flags, bbox min) + bbox max with vertex_count
 ///       - vertex_count triangle vertices (groups of 3 = triangles)
 ///   - Each vertex: f32 x, f32 y, f32 z, u8[3] packed_normal, u8 zero
-fn parse_armor_data(data: &[u8]) -> Result<Vec<ArmorTriangle>, Report<GeometryError>> {
+///
+/// `visit` is called once per node group with the node header and the node's
+/// triangles as `(vertices, normals)` pairs. `kind` is only used in error messages.
+fn walk_bvh_data(
+    data: &[u8],
+    kind: &str,
+    mut visit: impl FnMut(BvhNodeHeader, Vec<([[f32; 3]; 3], [[f32; 3]; 3])>),
+) -> Result<(), Report<GeometryError>> {
     const ENTRY_SIZE: usize = 16;
 
     if data.len() < ENTRY_SIZE * 2 {
-        return Ok(Vec::new());
+        return Ok(());
     }
 
     let entry_count = data.len() / ENTRY_SIZE;
     if entry_count < 2 {
-        return Ok(Vec::new());
+        return Ok(());
     }
 
     let read_f32 =
         |off: usize| -> f32 { f32::from_le_bytes(data[off..off + 4].try_into().unwrap()) };
     let read_u32 =
         |off: usize| -> u32 { u32::from_le_bytes(data[off..off + 4].try_into().unwrap()) };
+    let read_vec3 =
+        |off: usize| -> [f32; 3] { [read_f32(off), read_f32(off + 4), read_f32(off + 8)] };
 
     // Skip 2-entry global header, then walk BVH node groups
     let mut pos = 2; // entry index
-    let mut triangles = Vec::new();
 
     while pos < entry_count {
         // Each BVH node group: 2 header entries + vertex_count vertex entries
@@ -586,11 +659,14 @@ fn parse_armor_data(data: &[u8]) -> Result<Vec<ArmorTriangle>, Report<GeometryEr
         // byte 0 = collision material ID, byte 2 = 1-based layer index
         // This matches the GameParams key encoding: (model_index << 16) | material_id
         let node_entry0_off = pos * ENTRY_SIZE;
-        let material_id = data[node_entry0_off];
-        let layer_index = data[node_entry0_off + 2];
-
         // Second entry of the node header has vertex_count at bytes 12..16
         let node_entry1_off = (pos + 1) * ENTRY_SIZE;
+        let header = BvhNodeHeader {
+            material_id: data[node_entry0_off],
+            layer_index: data[node_entry0_off + 2],
+            bbox_min: read_vec3(node_entry0_off + 4),
+            bbox_max: read_vec3(node_entry1_off),
+        };
         let vertex_count = read_u32(node_entry1_off + 12) as usize;
         pos += 2; // skip 2 node header entries
 
@@ -599,7 +675,7 @@ fn parse_armor_data(data: &[u8]) -> Result<Vec<ArmorTriangle>, Report<GeometryEr
         }
         if pos + vertex_count > entry_count {
             return Err(Report::new(GeometryError::ParseError(format!(
-                "armor BVH node claims {} vertices but only {} entries remain",
+                "{kind} BVH node claims {} vertices but only {} entries remain",
                 vertex_count,
                 entry_count - pos
             ))));
@@ -607,35 +683,71 @@ fn parse_armor_data(data: &[u8]) -> Result<Vec<ArmorTriangle>, Report<GeometryEr
 
         // vertex_count should be divisible by 3 (triangle soup)
         let tri_count = vertex_count / 3;
+        let mut triangles = Vec::with_capacity(tri_count);
         for t in 0..tri_count {
-            let mut tri = ArmorTriangle {
-                vertices: [[0.0; 3]; 3],
-                normals: [[0.0; 3]; 3],
-                material_id,
-                layer_index,
-            };
+            let mut vertices = [[0.0; 3]; 3];
+            let mut normals = [[0.0; 3]; 3];
             for v in 0..3 {
                 let entry_off = (pos + t * 3 + v) * ENTRY_SIZE;
-                tri.vertices[v] = [
-                    read_f32(entry_off),
-                    read_f32(entry_off + 4),
-                    read_f32(entry_off + 8),
-                ];
+                vertices[v] = read_vec3(entry_off);
                 // Packed normal: 3 bytes at offset 12, each maps [-1, 1]
                 let nx = data[entry_off + 12] as f32 / 127.5 - 1.0;
                 let ny = data[entry_off + 13] as f32 / 127.5 - 1.0;
                 let nz = data[entry_off + 14] as f32 / 127.5 - 1.0;
-                tri.normals[v] = [nx, ny, nz];
+                normals[v] = [nx, ny, nz];
             }
-            triangles.push(tri);
+            triangles.push((vertices, normals));
         }
+        visit(header, triangles);
 
         pos += vertex_count;
     }
 
+    Ok(())
+}
+
+/// Parse the BVH + triangle data from an armor model's raw 16-byte entry stream.
+pub(crate) fn parse_armor_data(data: &[u8]) -> Result<Vec<ArmorTriangle>, Report<GeometryError>> {
+    let mut triangles = Vec::new();
+    walk_bvh_data(data, "armor", |header, tris| {
+        triangles.extend(tris.into_iter().map(|(vertices, normals)| ArmorTriangle {
+            vertices,
+            normals,
+            material_id: header.material_id,
+            layer_index: header.layer_index,
+        }));
+    })?;
     Ok(triangles)
 }
 
+/// Parse the BVH + triangle data from a collision model's raw 16-byte entry stream.
+fn parse_collision_data(name: &str, data: &[u8]) -> Result<CollisionModel, Report<GeometryError>> {
+    let mut nodes = Vec::new();
+    let mut triangles = Vec::new();
+    walk_bvh_data(data, "collision", |header, tris| {
+        let first_triangle = triangles.len();
+        triangles.extend(
+            tris.into_iter()
+                .map(|(vertices, normals)| CollisionTriangle {
+                    vertices,
+                    normals,
+                    material_id: header.material_id,
+                }),
+        );
+        nodes.push(CollisionNode {
+            material_id: header.material_id,
+            bbox_min: header.bbox_min,
+            bbox_max: header.bbox_max,
+            triangles: first_triangle..triangles.len(),
+        });
+    })?;
+    Ok(CollisionModel {
+        name: name.to_string(),
+        nodes,
+        triangles,
+    })
+}
+
 /// Parse armor model array. Unlike collision models where data_relptr points to
 /// the start of the data, for armor models the actual data extends from right
 /// after the struct (struct_base + 0x20) to data_offset + size_in_bytes.
@@ -671,8 +783,16 @@ fn parse_armor_model_array(
 
         let armor_data = &file_data[data_start..data_end];
         let triangles = parse_armor_data(armor_data)?;
+        let stored = (size_in_bytes as usize <= armor_data.len()).then(|| StoredArmor {
+            data: armor_data.to_vec(),
+            size_in_bytes,
+        });
 
-        result.push(ArmorModel { name, triangles });
+        result.push(ArmorModel {
+            name,
+            triangles,
+            stored,
+        });
     }
 
     Ok(result)
@@ -716,3 +836,51 @@ fn parse_model_array<'a>(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One 16-byte BVH entry: three f32s and a trailing u32.
+    fn entry(xyz: [f32; 3], tail: [u8; 4]) -> Vec<u8> {
+        let mut e: Vec<u8> = xyz.iter().flat_map(|v| v.to_le_bytes()).collect();
+        e.extend_from_slice(&tail);
+        e
+    }
+
+    #[test]
+    fn decodes_collision_bvh_fixture() {
+        // Global header (bbox + node count), one node with material 7 and a
+        // single triangle whose normals all point up (+Y).
+        let mut data = entry([-1.0, 0.0, -2.0], [0; 4]);
+        data.extend(entry([1.0, 1.0, 2.0], 1u32.to_le_bytes()));
+        data.extend([7, 0, 1, 0]);
+        data.extend(entry([-1.0, 0.0, -2.0], [0; 4])[..12].iter());
+        data.extend(entry([1.0, 1.0, 2.0], 3u32.to_le_bytes()));
+        for v in [[-1.0, 0.0, -2.0], [1.0, 0.0, -2.0], [0.0, 1.0, 2.0]] {
+            data.extend(entry(v, [128, 255, 128, 0]));
+        }
+
+        let cm = ModelPrototype {
+            data: &data,
+            name: "CM_Hull".to_string(),
+            size_in_bytes: data.len() as u32,
+        };
+        let model = cm.decode().unwrap();
+        assert_eq!(model.nodes.len(), 1);
+        assert_eq!(model.nodes[0].material_id, 7);
+        assert_eq!(model.nodes[0].bbox_max, [1.0, 1.0, 2.0]);
+        assert_eq!(model.nodes[0].triangles, 0..1);
+        let tri = &model.triangles[0];
+        assert_eq!(tri.vertices[2], [0.0, 1.0, 2.0]);
+        assert!((tri.normals[0][1] - 1.0).abs() < 0.01);
+
+        // A node claiming more vertices than remain is an error.
+        let truncated = &data[..data.len() - 16];
+        let cm = ModelPrototype {
+            data: truncated,
+            ..cm
+        };
+        assert!(cm.decode().is_err());
+    }
+}
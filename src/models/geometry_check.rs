@@ -0,0 +1,703 @@
+//! Validation and statistics for decoded `.geometry` meshes.
+//!
+//! [`check_geometry`] decodes every vertex/index mapping pair the same way the
+//! raw GLB export does (mapping `i` of each list forms mesh `i`) and reports
+//! anything that would otherwise surface as a broken export.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use thiserror::Error;
+
+use super::geometry::{MappingEntry, MergedGeometry};
+use super::vertex_format::{self, AttributeSemantic, VertexFormat};
+
+/// Normals are unpacked from signed bytes, so unit normals land within about
+/// 1% of length 1. Anything further off was not a unit normal when packed.
+const NORMAL_LENGTH_TOLERANCE: f32 = 0.05;
+
+/// Tiled UVs legitimately leave `[0, 1]`, but not by this much.
+const MAX_UV_MAGNITUDE: f32 = 16.0;
+
+/// Axis-aligned `(min, max)` bounds.
+pub type Bounds = ([f32; 3], [f32; 3]);
+
+/// Which mapping list or merged buffer list an issue refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingKind {
+    Vertices,
+    Indices,
+}
+
+impl fmt::Display for MappingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MappingKind::Vertices => write!(f, "vertices"),
+            MappingKind::Indices => write!(f, "indices"),
+        }
+    }
+}
+
+/// A problem found by [`check_geometry`]. `mesh` is the mapping pair index.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum GeometryIssue {
+    #[error("{vertices} vertices mappings vs {indices} indices mappings")]
+    MappingCountMismatch { vertices: usize, indices: usize },
+    #[error("{kind} mapping {mapping}: buffer index {index} out of range (count: {count})")]
+    BufferIndexOutOfRange {
+        kind: MappingKind,
+        mapping: usize,
+        index: usize,
+        count: usize,
+    },
+    #[error("{kind} mapping {mapping}: items {start}..{end} exceed buffer {buffer} ({len} items)")]
+    MappingOutOfBounds {
+        kind: MappingKind,
+        mapping: usize,
+        buffer: usize,
+        start: u64,
+        end: u64,
+        len: usize,
+    },
+    #[error("{kind} mappings {first} and {second} partially overlap in buffer {buffer}")]
+    MappingOverlap {
+        kind: MappingKind,
+        buffer: usize,
+        first: usize,
+        second: usize,
+    },
+    #[error("{kind} mapping {inner} lies inside mapping {outer} in buffer {buffer}")]
+    MappingContained {
+        kind: MappingKind,
+        buffer: usize,
+        outer: usize,
+        inner: usize,
+    },
+    #[error("{kind} buffer {buffer}: {message}")]
+    BufferDecode {
+        kind: MappingKind,
+        buffer: usize,
+        message: String,
+    },
+    #[error("mesh {mesh}: {count} indices out of range (max {max}, vertex count {vertex_count})")]
+    IndexOutOfRange {
+        mesh: usize,
+        count: usize,
+        max: u32,
+        vertex_count: usize,
+    },
+    #[error("mesh {mesh}: index count {count} is not a multiple of 3")]
+    PartialTriangle { mesh: usize, count: usize },
+    #[error("mesh {mesh}: {count} degenerate triangles")]
+    DegenerateTriangles { mesh: usize, count: usize },
+    #[error("mesh {mesh}: {count} duplicate triangles")]
+    DuplicateTriangles { mesh: usize, count: usize },
+    #[error("mesh {mesh}: {count} NaN/Inf positions")]
+    NonFinitePositions { mesh: usize, count: usize },
+    #[error("mesh {mesh}: {count} normals off unit length (worst length {worst})")]
+    UnnormalizedNormals {
+        mesh: usize,
+        count: usize,
+        worst: f32,
+    },
+    #[error("mesh {mesh}: {count} UVs outside ±{MAX_UV_MAGNITUDE} or NaN/Inf")]
+    UvOutOfRange { mesh: usize, count: usize },
+}
+
+/// Statistics for one vertex/index mapping pair.
+#[derive(Debug, Clone)]
+pub struct MeshStats {
+    /// Mapping pair index.
+    pub mesh: usize,
+    pub vertices_mapping_id: u32,
+    pub indices_mapping_id: u32,
+    pub vertex_format: String,
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    /// Bounds of the finite positions, or `None` if there are none.
+    pub bounding_box: Option<Bounds>,
+    /// `packed_texel_density` of the vertices mapping, decoded by [`texel_density`].
+    pub texel_density: f32,
+}
+
+/// Min / max / mean of the meshes' texel densities, ignoring zero entries.
+#[derive(Debug, Clone, Copy)]
+pub struct TexelDensityStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Number of meshes with a non-zero density.
+    pub count: usize,
+}
+
+/// Result of [`check_geometry`].
+#[derive(Debug, Clone, Default)]
+pub struct GeometryReport {
+    /// Stats for every mapping pair that could be decoded.
+    pub meshes: Vec<MeshStats>,
+    pub issues: Vec<GeometryIssue>,
+}
+
+impl GeometryReport {
+    /// True when no issues were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.meshes.iter().map(|m| m.vertex_count).sum()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.meshes.iter().map(|m| m.triangle_count).sum()
+    }
+
+    /// Bounds over all meshes.
+    pub fn bounding_box(&self) -> Option<Bounds> {
+        self.meshes
+            .iter()
+            .filter_map(|m| m.bounding_box)
+            .reduce(|(amin, amax), (bmin, bmax)| {
+                (
+                    std::array::from_fn(|i| amin[i].min(bmin[i])),
+                    std::array::from_fn(|i| amax[i].max(bmax[i])),
+                )
+            })
+    }
+
+    /// Texel density over all meshes, as decoded by [`texel_density`].
+    pub fn texel_density(&self) -> Option<TexelDensityStats> {
+        let values: Vec<f32> = self
+            .meshes
+            .iter()
+            .map(|m| m.texel_density)
+            .filter(|d| *d != 0.0 && d.is_finite())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(TexelDensityStats {
+            min: values.iter().copied().fold(f32::INFINITY, f32::min),
+            max: values.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            count: values.len(),
+        })
+    }
+}
+
+/// Decode a mapping's `packed_texel_density`.
+///
+/// Unverified: the packing isn't documented and is read here as an IEEE
+/// half float. `geometry` prints the raw value next to the decoded one and
+/// labels the decoding as unverified until it is checked against real files.
+pub fn texel_density(mapping: &MappingEntry) -> f32 {
+    half::f16::from_bits(mapping.packed_texel_density).to_f32()
+}
+
+/// Decoded merged vertex buffer.
+struct VertexBuffer {
+    data: Vec<u8>,
+    format: VertexFormat,
+    stride: usize,
+}
+
+/// Validate every mesh in `geometry` and gather statistics.
+///
+/// Buffers that fail to decode and mappings that point outside their buffer
+/// are reported as issues and their meshes skipped, so one bad entry doesn't
+/// hide the rest.
+pub fn check_geometry(geometry: &MergedGeometry<'_>) -> GeometryReport {
+    let mut report = GeometryReport::default();
+    let issues = &mut report.issues;
+
+    if geometry.vertices_mapping.len() != geometry.indices_mapping.len() {
+        issues.push(GeometryIssue::MappingCountMismatch {
+            vertices: geometry.vertices_mapping.len(),
+            indices: geometry.indices_mapping.len(),
+        });
+    }
+
+    let vertex_buffers: Vec<Option<VertexBuffer>> = geometry
+        .merged_vertices
+        .iter()
+        .enumerate()
+        .map(|(buffer, proto)| {
+            let decode_issue = |message: String| GeometryIssue::BufferDecode {
+                kind: MappingKind::Vertices,
+                buffer,
+                message,
+            };
+            let stride = proto.stride_in_bytes as usize;
+            let format = vertex_format::parse_vertex_format(&proto.format_name)
+                .and_then(|format| {
+                    format.check_stride(&proto.format_name, stride)?;
+                    Ok(format)
+                })
+                .map_err(|e| issues.push(decode_issue(e.current_context().to_string())))
+                .ok()?;
+            let data = proto
+                .data
+                .decode()
+                .map_err(|e| issues.push(decode_issue(e.current_context().to_string())))
+                .ok()?;
+            Some(VertexBuffer {
+                data,
+                format,
+                stride,
+            })
+        })
+        .collect();
+
+    let index_buffers: Vec<Option<Vec<u32>>> = geometry
+        .merged_indices
+        .iter()
+        .enumerate()
+        .map(|(buffer, proto)| {
+            let decode_issue = |message: String| GeometryIssue::BufferDecode {
+                kind: MappingKind::Indices,
+                buffer,
+                message,
+            };
+            let data = proto
+                .data
+                .decode()
+                .map_err(|e| issues.push(decode_issue(e.current_context().to_string())))
+                .ok()?;
+            match proto.index_size {
+                2 => Some(
+                    data.chunks_exact(2)
+                        .map(|c| u16::from_le_bytes([c[0], c[1]]) as u32)
+                        .collect(),
+                ),
+                4 => Some(
+                    data.chunks_exact(4)
+                        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .collect(),
+                ),
+                size => {
+                    issues.push(decode_issue(format!("unsupported index size: {size}")));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let vertex_lens: Vec<Option<usize>> = vertex_buffers
+        .iter()
+        .map(|b| b.as_ref().map(|b| b.data.len() / b.stride))
+        .collect();
+    let index_lens: Vec<Option<usize>> = index_buffers
+        .iter()
+        .map(|b| b.as_ref().map(Vec::len))
+        .collect();
+    let vertices_ok = check_mappings(
+        MappingKind::Vertices,
+        &geometry.vertices_mapping,
+        &vertex_lens,
+        issues,
+    );
+    let indices_ok = check_mappings(
+        MappingKind::Indices,
+        &geometry.indices_mapping,
+        &index_lens,
+        issues,
+    );
+
+    let pairs = geometry
+        .vertices_mapping
+        .iter()
+        .zip(&geometry.indices_mapping)
+        .enumerate();
+    for (mesh, (vm, im)) in pairs {
+        if !vertices_ok[mesh] || !indices_ok[mesh] {
+            continue;
+        }
+        let (Some(vb), Some(ib)) = (
+            &vertex_buffers[vm.merged_buffer_index as usize],
+            &index_buffers[im.merged_buffer_index as usize],
+        ) else {
+            continue;
+        };
+
+        let start = vm.items_offset as usize * vb.stride;
+        let vertices = &vb.data[start..start + vm.items_count as usize * vb.stride];
+        let start = im.items_offset as usize;
+        let indices = &ib[start..start + im.items_count as usize];
+
+        let (vertex_count, bounding_box) = check_mesh(mesh, vertices, vb, indices, issues);
+        report.meshes.push(MeshStats {
+            mesh,
+            vertices_mapping_id: vm.mapping_id,
+            indices_mapping_id: im.mapping_id,
+            vertex_format: geometry.merged_vertices[vm.merged_buffer_index as usize]
+                .format_name
+                .clone(),
+            vertex_count,
+            triangle_count: indices.len() / 3,
+            bounding_box,
+            texel_density: texel_density(vm),
+        });
+    }
+
+    report
+}
+
+/// Check buffer indices, bounds and overlaps of one mapping list. Returns
+/// whether each mapping is safe to slice its buffer with.
+///
+/// Mappings sharing an identical range (e.g. LODs reusing vertices) are fine.
+/// Every other pair of intersecting ranges is reported, as a containment when
+/// one range lies entirely inside the other and as a partial overlap
+/// otherwise. Empty ranges intersect nothing.
+fn check_mappings(
+    kind: MappingKind,
+    mappings: &[MappingEntry],
+    buffer_lens: &[Option<usize>],
+    issues: &mut Vec<GeometryIssue>,
+) -> Vec<bool> {
+    let mut ok = vec![false; mappings.len()];
+    let mut ranges: HashMap<usize, Vec<(u64, u64, usize)>> = HashMap::new();
+
+    for (mapping, m) in mappings.iter().enumerate() {
+        let buffer = m.merged_buffer_index as usize;
+        let Some(len) = buffer_lens.get(buffer) else {
+            issues.push(GeometryIssue::BufferIndexOutOfRange {
+                kind,
+                mapping,
+                index: buffer,
+                count: buffer_lens.len(),
+            });
+            continue;
+        };
+        let start = m.items_offset as u64;
+        let end = start + m.items_count as u64;
+        ranges
+            .entry(buffer)
+            .or_default()
+            .push((start, end, mapping));
+
+        // Undecodable buffers were already reported.
+        let Some(len) = *len else { continue };
+        if end > len as u64 {
+            issues.push(GeometryIssue::MappingOutOfBounds {
+                kind,
+                mapping,
+                buffer,
+                start,
+                end,
+                len,
+            });
+            continue;
+        }
+        ok[mapping] = true;
+    }
+
+    let mut buffers: Vec<_> = ranges.into_iter().collect();
+    buffers.sort_by_key(|(buffer, _)| *buffer);
+    for (buffer, mut ranges) in buffers {
+        // By start, longest first, so a containing range precedes its contents.
+        ranges.retain(|(start, end, _)| start < end);
+        ranges.sort_by_key(|&(start, end, mapping)| (start, std::cmp::Reverse(end), mapping));
+        ranges.dedup_by_key(|(start, end, _)| (*start, *end));
+        for (i, &(_, first_end, first)) in ranges.iter().enumerate() {
+            // Later ranges start at or after this one; stop at the first
+            // that starts past its end.
+            for &(_, second_end, second) in ranges[i + 1..]
+                .iter()
+                .take_while(|(second_start, ..)| *second_start < first_end)
+            {
+                issues.push(if second_end <= first_end {
+                    GeometryIssue::MappingContained {
+                        kind,
+                        buffer,
+                        outer: first,
+                        inner: second,
+                    }
+                } else {
+                    GeometryIssue::MappingOverlap {
+                        kind,
+                        buffer,
+                        first,
+                        second,
+                    }
+                });
+            }
+        }
+    }
+
+    ok
+}
+
+/// Validate one mesh's vertices and triangles. Returns its vertex count and
+/// the bounds of its finite positions.
+fn check_mesh(
+    mesh: usize,
+    vertices: &[u8],
+    buffer: &VertexBuffer,
+    indices: &[u32],
+    issues: &mut Vec<GeometryIssue>,
+) -> (usize, Option<Bounds>) {
+    let attr = |semantic| {
+        buffer
+            .format
+            .attributes
+            .iter()
+            .find(|a| a.semantic == semantic)
+            .map(|a| a.offset)
+    };
+    let read_u32 = |off: usize| u32::from_le_bytes(vertices[off..off + 4].try_into().unwrap());
+    let vertex_count = vertices.len() / buffer.stride;
+
+    let mut positions = Vec::with_capacity(vertex_count);
+    let mut non_finite = 0;
+    let mut bad_normals = 0;
+    let mut worst_normal = 1.0f32;
+    let mut bad_uvs = 0;
+    let mut bbox: Option<Bounds> = None;
+    for v in 0..vertex_count {
+        let base = v * buffer.stride;
+        if let Some(off) = attr(AttributeSemantic::Position) {
+            let p: [f32; 3] = std::array::from_fn(|i| f32::from_bits(read_u32(base + off + i * 4)));
+            if p.iter().all(|c| c.is_finite()) {
+                let (min, max) = bbox.get_or_insert((p, p));
+                for i in 0..3 {
+                    min[i] = min[i].min(p[i]);
+                    max[i] = max[i].max(p[i]);
+                }
+            } else {
+                non_finite += 1;
+            }
+            positions.push(p);
+        }
+        if let Some(off) = attr(AttributeSemantic::Normal) {
+            let [x, y, z] = vertex_format::unpack_normal(read_u32(base + off));
+            let len = (x * x + y * y + z * z).sqrt();
+            if (len - 1.0).abs() > NORMAL_LENGTH_TOLERANCE {
+                bad_normals += 1;
+                if (len - 1.0).abs() > (worst_normal - 1.0).abs() {
+                    worst_normal = len;
+                }
+            }
+        }
+        if let Some(off) = attr(AttributeSemantic::TexCoord0) {
+            let uv = vertex_format::unpack_uv(read_u32(base + off));
+            if !uv
+                .iter()
+                .all(|c| c.is_finite() && c.abs() <= MAX_UV_MAGNITUDE)
+            {
+                bad_uvs += 1;
+            }
+        }
+    }
+
+    if !indices.len().is_multiple_of(3) {
+        issues.push(GeometryIssue::PartialTriangle {
+            mesh,
+            count: indices.len(),
+        });
+    }
+    let out_of_range: Vec<u32> = indices
+        .iter()
+        .copied()
+        .filter(|&i| i as usize >= vertex_count)
+        .collect();
+
+    let mut degenerate = 0;
+    let mut duplicate = 0;
+    let mut seen = std::collections::HashSet::new();
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]];
+        if [a, b, c].iter().any(|&i| i as usize >= vertex_count) {
+            continue;
+        }
+        if is_degenerate([a, b, c].map(|i| positions[i as usize])) || a == b || b == c || a == c {
+            degenerate += 1;
+        }
+        // Rotate so the smallest index leads; this keeps winding, so a
+        // back-facing copy of a triangle is not a duplicate.
+        let key = match a.min(b).min(c) {
+            m if m == a => (a, b, c),
+            m if m == b => (b, c, a),
+            _ => (c, a, b),
+        };
+        if !seen.insert(key) {
+            duplicate += 1;
+        }
+    }
+
+    if !out_of_range.is_empty() {
+        issues.push(GeometryIssue::IndexOutOfRange {
+            mesh,
+            count: out_of_range.len(),
+            max: out_of_range.iter().copied().max().unwrap_or(0),
+            vertex_count,
+        });
+    }
+    let counted = [
+        (
+            degenerate,
+            GeometryIssue::DegenerateTriangles {
+                mesh,
+                count: degenerate,
+            },
+        ),
+        (
+            duplicate,
+            GeometryIssue::DuplicateTriangles {
+                mesh,
+                count: duplicate,
+            },
+        ),
+        (
+            non_finite,
+            GeometryIssue::NonFinitePositions {
+                mesh,
+                count: non_finite,
+            },
+        ),
+        (
+            bad_uvs,
+            GeometryIssue::UvOutOfRange {
+                mesh,
+                count: bad_uvs,
+            },
+        ),
+    ];
+    issues.extend(
+        counted
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(_, issue)| issue),
+    );
+    if bad_normals > 0 {
+        issues.push(GeometryIssue::UnnormalizedNormals {
+            mesh,
+            count: bad_normals,
+            worst: worst_normal,
+        });
+    }
+
+    (vertex_count, bbox)
+}
+
+/// Zero-area (or NaN) triangle.
+fn is_degenerate([a, b, c]: [[f32; 3]; 3]) -> bool {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let area2 = n[0] * n[0] + n[1] * n[1] + n[2] * n[2];
+    area2.is_nan() || area2 <= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::geometry::parse_geometry;
+    use crate::models::geometry_writer::GeometryWriter;
+    use crate::models::vertex_format::{pack_normal, pack_uv};
+
+    fn vertex(pos: [f32; 3]) -> Vec<u8> {
+        let mut v = Vec::new();
+        for c in pos {
+            v.extend_from_slice(&c.to_le_bytes());
+        }
+        v.extend_from_slice(&pack_normal([0.0, 0.0, 1.0]).to_le_bytes());
+        v.extend_from_slice(&pack_uv([0.5, 0.5]).to_le_bytes());
+        v
+    }
+
+    #[test]
+    fn reports_bad_triangles() {
+        let mut vertices = Vec::new();
+        for p in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [f32::NAN, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ] {
+            vertices.extend(vertex(p));
+        }
+        // A good triangle, its duplicate, a degenerate one, and one using the
+        // last vertex, which falls outside the mapping once it's shrunk below.
+        let indices = [0, 1, 2, 1, 2, 0, 0, 0, 1, 0, 1, 4];
+        let mut writer = GeometryWriter::new();
+        writer
+            .add_mesh(1, 2, "set3/xyznuvpc", &vertices, &indices)
+            .unwrap();
+        let bytes = writer.to_bytes().unwrap();
+        let mut geom = parse_geometry(&bytes).unwrap();
+        geom.vertices_mapping[0].items_count = 4;
+        // 1.0 as an f16.
+        geom.vertices_mapping[0].packed_texel_density = 0x3C00;
+
+        let report = check_geometry(&geom);
+        assert_eq!(report.meshes.len(), 1);
+        assert_eq!(report.vertex_count(), 4);
+        assert_eq!(report.triangle_count(), 4);
+        assert_eq!(
+            report.bounding_box(),
+            Some(([0.0, 0.0, 0.0], [1.0, 1.0, 0.0]))
+        );
+        let td = report.texel_density().unwrap();
+        assert_eq!((td.min, td.max, td.mean, td.count), (1.0, 1.0, 1.0, 1));
+        for issue in [
+            GeometryIssue::DuplicateTriangles { mesh: 0, count: 1 },
+            GeometryIssue::DegenerateTriangles { mesh: 0, count: 1 },
+            GeometryIssue::NonFinitePositions { mesh: 0, count: 1 },
+            GeometryIssue::IndexOutOfRange {
+                mesh: 0,
+                count: 1,
+                max: 4,
+                vertex_count: 4,
+            },
+        ] {
+            assert!(report.issues.contains(&issue), "{:?}", report.issues);
+        }
+        assert_eq!(report.issues.len(), 4, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn classifies_mapping_overlaps() {
+        let mapping = |offset, count| MappingEntry {
+            mapping_id: 0,
+            merged_buffer_index: 0,
+            packed_texel_density: 0,
+            items_offset: offset,
+            items_count: count,
+        };
+        // 0: [0, 10), 1: [2, 4) inside 0, 2: [8, 12) straddling 0's end,
+        // 3: [0, 10) identical to 0, 4: [20, 20) empty.
+        let mappings = [
+            mapping(0, 10),
+            mapping(2, 2),
+            mapping(8, 4),
+            mapping(0, 10),
+            mapping(20, 0),
+        ];
+        let mut issues = Vec::new();
+        let ok = check_mappings(MappingKind::Indices, &mappings, &[Some(32)], &mut issues);
+        assert!(ok.iter().all(|&ok| ok));
+        assert_eq!(
+            issues,
+            [
+                GeometryIssue::MappingContained {
+                    kind: MappingKind::Indices,
+                    buffer: 0,
+                    outer: 0,
+                    inner: 1,
+                },
+                GeometryIssue::MappingOverlap {
+                    kind: MappingKind::Indices,
+                    buffer: 0,
+                    first: 0,
+                    second: 2,
+                },
+            ]
+        );
+    }
+}
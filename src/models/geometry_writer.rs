@@ -0,0 +1,848 @@
+//! Writer for `.geometry` (MergedGeometry) files.
+//!
+//! [`GeometryWriter`] collects merged vertex and index buffers, their mapping
+//! entries, and collision and armor models, then serializes them into the
+//! layout read by [`parse_geometry`](crate::models::geometry::parse_geometry).
+//!
+//! Buffers can be added in two ways:
+//!
+//! - [`GeometryWriter::from_geometry`] copies the buffers of a parsed file as
+//!   stored (ENCD or raw), so writing an unmodified geometry does not
+//!   re-encode anything.
+//! - [`GeometryWriter::add_mesh`] takes raw vertex bytes in a BigWorld vertex
+//!   format plus triangle-list indices. Meshes with the same vertex format are
+//!   merged into one vertex buffer; each mesh gets its own mapping entries.
+//!   New buffers are meshoptimizer-encoded into ENCD blobs by
+//!   [`GeometryWriter::to_bytes`].
+//!
+//! Armor models parsed from a file are written back from their stored BVH
+//! bytes unless their triangles were edited. Edited and new armor models are
+//! encoded into a BVH with one node per run of triangles sharing a material
+//! and layer.
+//! The engine's global BVH header is not fully understood; the writer stores
+//! the overall bounding box and node count there, which the parser ignores.
+//!
+//! The file layout follows the one observed in game files: header, mapping
+//! arrays, then each prototype array followed by its data blobs and names.
+
+use rootcause::Report;
+
+use crate::models::geometry::{
+    self, ArmorModel, CollisionModel, ENCD_MAGIC, GeometryError, IndexData, MappingEntry,
+    MergedGeometry, StoredArmor, VertexData,
+};
+use crate::models::vertex_format::{self, AttributeSemantic};
+
+const HEADER_SIZE: usize = 0x48;
+const MAPPING_ENTRY_SIZE: usize = 0x10;
+const VERTICES_PROTOTYPE_SIZE: usize = 0x20;
+const INDICES_PROTOTYPE_SIZE: usize = 0x10;
+const MODEL_PROTOTYPE_SIZE: usize = 0x20;
+const BVH_ENTRY_SIZE: usize = 16;
+
+/// Buffer contents, either as stored in a file or waiting to be encoded.
+#[derive(Debug, Clone)]
+enum BufferData<T> {
+    /// Stored blob (ENCD header + payload, or raw bytes), written verbatim.
+    Stored(Vec<u8>),
+    /// Unencoded elements, ENCD-encoded on write.
+    Pending(Vec<T>),
+}
+
+#[derive(Debug, Clone)]
+struct VertexBuffer {
+    format_name: String,
+    stride: u16,
+    is_skinned: bool,
+    is_bumped: bool,
+    /// Pending vertices are raw bytes, `stride` bytes per vertex.
+    data: BufferData<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexBuffer {
+    index_size: u16,
+    data: BufferData<u32>,
+}
+
+/// Builder for `.geometry` files. See the [module docs](self).
+#[derive(Debug, Clone, Default)]
+pub struct GeometryWriter {
+    vertices_mapping: Vec<MappingEntry>,
+    indices_mapping: Vec<MappingEntry>,
+    vertex_buffers: Vec<VertexBuffer>,
+    index_buffers: Vec<IndexBuffer>,
+    /// `(name, BVH data)` per collision model.
+    collision_models: Vec<(String, Vec<u8>)>,
+    /// `(name, BVH data)` per armor model.
+    armor_models: Vec<(String, StoredArmor)>,
+}
+
+impl GeometryWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a parsed geometry, keeping its buffers as stored.
+    pub fn from_geometry(geometry: &MergedGeometry<'_>) -> Self {
+        let vertex_buffers = geometry
+            .merged_vertices
+            .iter()
+            .map(|v| VertexBuffer {
+                format_name: v.format_name.clone(),
+                stride: v.stride_in_bytes,
+                is_skinned: v.is_skinned,
+                is_bumped: v.is_bumped,
+                data: BufferData::Stored(match &v.data {
+                    VertexData::Encoded {
+                        element_count,
+                        payload,
+                        ..
+                    } => encd_blob(*element_count, payload),
+                    VertexData::Raw(data) => data.to_vec(),
+                }),
+            })
+            .collect();
+
+        let index_buffers = geometry
+            .merged_indices
+            .iter()
+            .map(|i| IndexBuffer {
+                index_size: i.index_size,
+                data: BufferData::Stored(match &i.data {
+                    IndexData::Encoded {
+                        element_count,
+                        payload,
+                        ..
+                    } => encd_blob(*element_count, payload),
+                    IndexData::Raw(data) => data.to_vec(),
+                }),
+            })
+            .collect();
+
+        Self {
+            vertices_mapping: geometry.vertices_mapping.clone(),
+            indices_mapping: geometry.indices_mapping.clone(),
+            vertex_buffers,
+            index_buffers,
+            collision_models: geometry
+                .collision_models
+                .iter()
+                .map(|cm| (cm.name.clone(), cm.data.to_vec()))
+                .collect(),
+            armor_models: geometry
+                .armor_models
+                .iter()
+                .map(|am| (am.name.clone(), armor_data(am)))
+                .collect(),
+        }
+    }
+
+    /// Add one mesh: raw vertices in `format_name` layout and triangle-list
+    /// indices relative to the first vertex of this mesh.
+    ///
+    /// The vertices are appended to the merged vertex buffer for this format
+    /// (creating one if needed) and the indices to the merged u16 or u32 index
+    /// buffer, whichever fits. Mapping entries with the given IDs record where
+    /// the mesh landed.
+    pub fn add_mesh(
+        &mut self,
+        vertices_mapping_id: u32,
+        indices_mapping_id: u32,
+        format_name: &str,
+        vertices: &[u8],
+        indices: &[u32],
+    ) -> Result<(), Report<GeometryError>> {
+        let format = vertex_format::parse_vertex_format(format_name)
+            .map_err(|e| Report::new(GeometryError::InvalidMesh(format!("{e:?}"))))?;
+        let stride = format.stride;
+        if stride == 0 || stride > 256 || !stride.is_multiple_of(4) {
+            return Err(Report::new(GeometryError::InvalidStride(stride)));
+        }
+        if !vertices.len().is_multiple_of(stride) {
+            return Err(Report::new(GeometryError::InvalidMesh(format!(
+                "{} vertex bytes is not a multiple of stride {stride}",
+                vertices.len()
+            ))));
+        }
+        if !indices.len().is_multiple_of(3) {
+            return Err(Report::new(GeometryError::InvalidMesh(format!(
+                "{} indices do not form a triangle list",
+                indices.len()
+            ))));
+        }
+        let vertex_count = vertices.len() / stride;
+        if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertex_count) {
+            return Err(Report::new(GeometryError::InvalidMesh(format!(
+                "index {bad} out of range for {vertex_count} vertices"
+            ))));
+        }
+
+        let has = |semantic| format.attributes.iter().any(|a| a.semantic == semantic);
+        let is_skinned = has(AttributeSemantic::BoneIndices);
+        let is_bumped = has(AttributeSemantic::Tangent);
+
+        let vbuf_index =
+            match self.vertex_buffers.iter().position(|b| {
+                b.format_name == format_name && matches!(b.data, BufferData::Pending(_))
+            }) {
+                Some(i) => i,
+                None => {
+                    self.vertex_buffers.push(VertexBuffer {
+                        format_name: format_name.to_string(),
+                        stride: stride as u16,
+                        is_skinned,
+                        is_bumped,
+                        data: BufferData::Pending(Vec::new()),
+                    });
+                    self.vertex_buffers.len() - 1
+                }
+            };
+        let BufferData::Pending(vdata) = &mut self.vertex_buffers[vbuf_index].data else {
+            unreachable!("selected vertex buffer is pending");
+        };
+        let items_offset = (vdata.len() / stride) as u32;
+        vdata.extend_from_slice(vertices);
+        self.vertices_mapping.push(MappingEntry {
+            mapping_id: vertices_mapping_id,
+            merged_buffer_index: vbuf_index as u16,
+            packed_texel_density: 0,
+            items_offset,
+            items_count: vertex_count as u32,
+        });
+
+        let index_size = if vertex_count > u16::MAX as usize + 1 {
+            4
+        } else {
+            2
+        };
+        let ibuf_index =
+            match self.index_buffers.iter().position(|b| {
+                b.index_size == index_size && matches!(b.data, BufferData::Pending(_))
+            }) {
+                Some(i) => i,
+                None => {
+                    self.index_buffers.push(IndexBuffer {
+                        index_size,
+                        data: BufferData::Pending(Vec::new()),
+                    });
+                    self.index_buffers.len() - 1
+                }
+            };
+        let BufferData::Pending(idata) = &mut self.index_buffers[ibuf_index].data else {
+            unreachable!("selected index buffer is pending");
+        };
+        let items_offset = idata.len() as u32;
+        idata.extend_from_slice(indices);
+        self.indices_mapping.push(MappingEntry {
+            mapping_id: indices_mapping_id,
+            merged_buffer_index: ibuf_index as u16,
+            packed_texel_density: 0,
+            items_offset,
+            items_count: indices.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /// Add a collision model, encoding its BVH nodes and triangles.
+    pub fn add_collision_model(&mut self, model: &CollisionModel) {
+        let nodes = model.nodes.iter().map(|node| BvhNode {
+            header: node.material_id as u32,
+            bbox_min: node.bbox_min,
+            bbox_max: node.bbox_max,
+            triangles: model.triangles[node.triangles.clone()]
+                .iter()
+                .map(|t| (t.vertices, t.normals))
+                .collect(),
+        });
+        self.collision_models
+            .push((model.name.clone(), encode_bvh(nodes.collect())));
+    }
+
+    /// Add an armor model, encoding its triangles into a BVH.
+    ///
+    /// The geometry format only supports a single armor model per file: the
+    /// parser reads each armor model's data from right after its prototype.
+    pub fn add_armor_model(&mut self, model: &ArmorModel) -> Result<(), Report<GeometryError>> {
+        if !self.armor_models.is_empty() {
+            return Err(Report::new(GeometryError::InvalidMesh(
+                "only one armor model per geometry is supported".to_string(),
+            )));
+        }
+        self.armor_models
+            .push((model.name.clone(), armor_data(model)));
+        Ok(())
+    }
+
+    /// Serialize to `.geometry` file bytes, encoding pending buffers.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Report<GeometryError>> {
+        if self.armor_models.len() > 1 {
+            return Err(Report::new(GeometryError::InvalidMesh(
+                "only one armor model per geometry is supported".to_string(),
+            )));
+        }
+
+        let mut out = vec![0u8; HEADER_SIZE];
+
+        let vm_offset = out.len();
+        for entry in &self.vertices_mapping {
+            write_mapping_entry(&mut out, entry);
+        }
+        let im_offset = out.len();
+        for entry in &self.indices_mapping {
+            write_mapping_entry(&mut out, entry);
+        }
+
+        // Vertex prototypes, then each buffer's blob and format name.
+        let mv_offset = out.len();
+        out.resize(
+            mv_offset + self.vertex_buffers.len() * VERTICES_PROTOTYPE_SIZE,
+            0,
+        );
+        for (i, buffer) in self.vertex_buffers.iter().enumerate() {
+            let struct_base = mv_offset + i * VERTICES_PROTOTYPE_SIZE;
+            let blob = match &buffer.data {
+                BufferData::Stored(blob) => blob.clone(),
+                BufferData::Pending(data) => {
+                    let count = data.len() / buffer.stride as usize;
+                    let payload = encode_vertex_buffer_dynamic(buffer.stride as usize, data)?;
+                    encd_blob(count as u32, &payload)
+                }
+            };
+            let blob_offset = out.len();
+            out.extend_from_slice(&blob);
+            write_relptr(&mut out, struct_base, blob_offset);
+            write_packed_string(&mut out, struct_base + 0x08, &buffer.format_name);
+            out[struct_base + 0x18..struct_base + 0x1C]
+                .copy_from_slice(&(blob.len() as u32).to_le_bytes());
+            out[struct_base + 0x1C..struct_base + 0x1E]
+                .copy_from_slice(&buffer.stride.to_le_bytes());
+            out[struct_base + 0x1E] = buffer.is_skinned as u8;
+            out[struct_base + 0x1F] = buffer.is_bumped as u8;
+        }
+
+        // Index prototypes, then each buffer's blob.
+        let mi_offset = out.len();
+        out.resize(
+            mi_offset + self.index_buffers.len() * INDICES_PROTOTYPE_SIZE,
+            0,
+        );
+        for (i, buffer) in self.index_buffers.iter().enumerate() {
+            let struct_base = mi_offset + i * INDICES_PROTOTYPE_SIZE;
+            let blob = match &buffer.data {
+                BufferData::Stored(blob) => blob.clone(),
+                BufferData::Pending(indices) => {
+                    let payload = encode_index_buffer(indices)?;
+                    encd_blob(indices.len() as u32, &payload)
+                }
+            };
+            let blob_offset = out.len();
+            out.extend_from_slice(&blob);
+            write_relptr(&mut out, struct_base, blob_offset);
+            out[struct_base + 0x08..struct_base + 0x0C]
+                .copy_from_slice(&(blob.len() as u32).to_le_bytes());
+            out[struct_base + 0x0E..struct_base + 0x10]
+                .copy_from_slice(&buffer.index_size.to_le_bytes());
+        }
+
+        // Collision prototypes: data_relptr points at the start of the data.
+        let cm_offset = out.len();
+        out.resize(
+            cm_offset + self.collision_models.len() * MODEL_PROTOTYPE_SIZE,
+            0,
+        );
+        for (i, (name, data)) in self.collision_models.iter().enumerate() {
+            let struct_base = cm_offset + i * MODEL_PROTOTYPE_SIZE;
+            let data_offset = out.len();
+            out.extend_from_slice(data);
+            write_relptr(&mut out, struct_base, data_offset);
+            write_packed_string(&mut out, struct_base + 0x08, name);
+            out[struct_base + 0x18..struct_base + 0x1C]
+                .copy_from_slice(&(data.len() as u32).to_le_bytes());
+        }
+
+        // Armor prototype: data starts right after the struct and ends at
+        // data_relptr + size_in_bytes.
+        let am_offset = out.len();
+        out.resize(
+            am_offset + self.armor_models.len() * MODEL_PROTOTYPE_SIZE,
+            0,
+        );
+        for (i, (name, armor)) in self.armor_models.iter().enumerate() {
+            let struct_base = am_offset + i * MODEL_PROTOTYPE_SIZE;
+            let data_end = out.len() + armor.data.len();
+            out.extend_from_slice(&armor.data);
+            write_relptr(
+                &mut out,
+                struct_base,
+                data_end - armor.size_in_bytes as usize,
+            );
+            write_packed_string(&mut out, struct_base + 0x08, name);
+            out[struct_base + 0x18..struct_base + 0x1C]
+                .copy_from_slice(&armor.size_in_bytes.to_le_bytes());
+        }
+
+        // Header: counts, then pointers relative to the file start.
+        let counts = [
+            self.vertex_buffers.len(),
+            self.index_buffers.len(),
+            self.vertices_mapping.len(),
+            self.indices_mapping.len(),
+            self.collision_models.len(),
+            self.armor_models.len(),
+        ];
+        for (i, count) in counts.into_iter().enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&(count as u32).to_le_bytes());
+        }
+        let ptrs = [
+            vm_offset, im_offset, mv_offset, mi_offset, cm_offset, am_offset,
+        ];
+        for (i, ptr) in ptrs.into_iter().enumerate() {
+            let off = 0x18 + i * 8;
+            out[off..off + 8].copy_from_slice(&(ptr as i64).to_le_bytes());
+        }
+
+        Ok(out)
+    }
+}
+
+fn encd_blob(element_count: u32, payload: &[u8]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(8 + payload.len());
+    blob.extend_from_slice(&ENCD_MAGIC.to_le_bytes());
+    blob.extend_from_slice(&element_count.to_le_bytes());
+    blob.extend_from_slice(payload);
+    blob
+}
+
+fn write_mapping_entry(out: &mut Vec<u8>, entry: &MappingEntry) {
+    let start = out.len();
+    out.extend_from_slice(&entry.mapping_id.to_le_bytes());
+    out.extend_from_slice(&entry.merged_buffer_index.to_le_bytes());
+    out.extend_from_slice(&entry.packed_texel_density.to_le_bytes());
+    out.extend_from_slice(&entry.items_offset.to_le_bytes());
+    out.extend_from_slice(&entry.items_count.to_le_bytes());
+    debug_assert_eq!(out.len() - start, MAPPING_ENTRY_SIZE);
+}
+
+/// Write an i64 relptr at `field` pointing to `target` (relative to `field`).
+fn write_relptr(out: &mut [u8], field: usize, target: usize) {
+    let rel = target as i64 - field as i64;
+    out[field..field + 8].copy_from_slice(&rel.to_le_bytes());
+}
+
+/// Append `text` (null-terminated) and write a PackedString at `base` pointing to it.
+fn write_packed_string(out: &mut Vec<u8>, base: usize, text: &str) {
+    let text_offset = out.len();
+    out.extend_from_slice(text.as_bytes());
+    out.push(0);
+    let char_count = text.len() as u32 + 1;
+    out[base..base + 4].copy_from_slice(&char_count.to_le_bytes());
+    out[base + 4..base + 8].fill(0);
+    // The text pointer is relative to the PackedString base, not to the field.
+    let rel = text_offset as i64 - base as i64;
+    out[base + 8..base + 16].copy_from_slice(&rel.to_le_bytes());
+}
+
+/// Encode a vertex buffer with meshoptimizer, dispatching on the runtime stride
+/// (see `decode_vertex_buffer_dynamic` in the geometry parser).
+fn encode_vertex_buffer_dynamic(
+    stride: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, Report<GeometryError>> {
+    if stride == 0 || stride > 256 || !stride.is_multiple_of(4) {
+        return Err(Report::new(GeometryError::InvalidStride(stride)));
+    }
+    let count = data.len() / stride;
+    let mut output =
+        vec![0u8; meshopt_rs::vertex::buffer::encode_vertex_buffer_bound(count, stride)];
+
+    macro_rules! encode_with_stride {
+        ($stride:literal) => {{
+            #[repr(C, align(4))]
+            #[derive(Copy, Clone)]
+            struct Vertex([u8; $stride]);
+            let vertices: Vec<Vertex> = data
+                .chunks_exact($stride)
+                .map(|c| Vertex(c.try_into().unwrap()))
+                .collect();
+            meshopt_rs::vertex::buffer::encode_vertex_buffer(
+                &mut output,
+                &vertices,
+                Default::default(),
+            )
+        }};
+    }
+
+    let written = match stride {
+        4 => encode_with_stride!(4),
+        8 => encode_with_stride!(8),
+        12 => encode_with_stride!(12),
+        16 => encode_with_stride!(16),
+        20 => encode_with_stride!(20),
+        24 => encode_with_stride!(24),
+        28 => encode_with_stride!(28),
+        32 => encode_with_stride!(32),
+        36 => encode_with_stride!(36),
+        40 => encode_with_stride!(40),
+        44 => encode_with_stride!(44),
+        48 => encode_with_stride!(48),
+        52 => encode_with_stride!(52),
+        56 => encode_with_stride!(56),
+        60 => encode_with_stride!(60),
+        64 => encode_with_stride!(64),
+        _ => return Err(Report::new(GeometryError::InvalidStride(stride))),
+    }
+    .ok_or_else(|| {
+        Report::new(GeometryError::EncodeError(
+            "vertex buffer encoding failed".to_string(),
+        ))
+    })?;
+
+    output.truncate(written);
+    Ok(output)
+}
+
+fn encode_index_buffer(indices: &[u32]) -> Result<Vec<u8>, Report<GeometryError>> {
+    let vertex_count = indices.iter().max().map_or(0, |&m| m as usize + 1);
+    let bound = meshopt_rs::index::buffer::encode_index_buffer_bound(indices.len(), vertex_count);
+    let mut output = vec![0u8; bound];
+    let written =
+        meshopt_rs::index::buffer::encode_index_buffer(&mut output, indices, Default::default())
+            .ok_or_else(|| {
+                Report::new(GeometryError::EncodeError(
+                    "index buffer encoding failed".to_string(),
+                ))
+            })?;
+    output.truncate(written);
+    Ok(output)
+}
+
+/// Triangle vertices and per-vertex normals.
+type BvhTriangle = ([[f32; 3]; 3], [[f32; 3]; 3]);
+
+/// One BVH node group to encode.
+struct BvhNode {
+    /// First u32 of the node header: `(layer_index << 16) | material_id`.
+    header: u32,
+    bbox_min: [f32; 3],
+    bbox_max: [f32; 3],
+    triangles: Vec<BvhTriangle>,
+}
+
+/// The stored BVH bytes of `model` if its triangles still match them,
+/// otherwise a fresh encoding.
+fn armor_data(model: &ArmorModel) -> StoredArmor {
+    if let Some(stored) = &model.stored
+        && geometry::parse_armor_data(&stored.data).is_ok_and(|t| t == model.triangles)
+    {
+        return stored.clone();
+    }
+    let data = encode_armor_bvh(model);
+    StoredArmor {
+        size_in_bytes: data.len() as u32,
+        data,
+    }
+}
+
+/// Group armor triangles into BVH nodes by consecutive (material, layer) runs.
+fn encode_armor_bvh(model: &ArmorModel) -> Vec<u8> {
+    let mut nodes: Vec<BvhNode> = Vec::new();
+    for tri in &model.triangles {
+        let header = ((tri.layer_index as u32) << 16) | tri.material_id as u32;
+        if nodes.last().is_none_or(|n| n.header != header) {
+            nodes.push(BvhNode {
+                header,
+                bbox_min: [f32::MAX; 3],
+                bbox_max: [f32::MIN; 3],
+                triangles: Vec::new(),
+            });
+        }
+        let node = nodes.last_mut().expect("node was just pushed");
+        for v in &tri.vertices {
+            expand_bbox(&mut node.bbox_min, &mut node.bbox_max, v, v);
+        }
+        node.triangles.push((tri.vertices, tri.normals));
+    }
+    encode_bvh(nodes)
+}
+
+fn expand_bbox(min: &mut [f32; 3], max: &mut [f32; 3], lo: &[f32; 3], hi: &[f32; 3]) {
+    for k in 0..3 {
+        min[k] = min[k].min(lo[k]);
+        max[k] = max[k].max(hi[k]);
+    }
+}
+
+/// Encode BVH node groups into the 16-byte entry stream read by the parser.
+fn encode_bvh(nodes: Vec<BvhNode>) -> Vec<u8> {
+    let mut bbox_min = [f32::MAX; 3];
+    let mut bbox_max = [f32::MIN; 3];
+    for node in &nodes {
+        expand_bbox(&mut bbox_min, &mut bbox_max, &node.bbox_min, &node.bbox_max);
+    }
+    if nodes.is_empty() {
+        bbox_min = [0.0; 3];
+        bbox_max = [0.0; 3];
+    }
+
+    let push_vec3 = |out: &mut Vec<u8>, v: [f32; 3]| {
+        for c in v {
+            out.extend_from_slice(&c.to_le_bytes());
+        }
+    };
+
+    let vertex_total: usize = nodes.iter().map(|n| n.triangles.len() * 3).sum();
+    let mut out = Vec::with_capacity((2 + nodes.len() * 2 + vertex_total) * BVH_ENTRY_SIZE);
+
+    // Global header: bounding box + node count.
+    push_vec3(&mut out, bbox_min);
+    out.extend_from_slice(&0u32.to_le_bytes());
+    push_vec3(&mut out, bbox_max);
+    out.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+
+    for node in &nodes {
+        out.extend_from_slice(&node.header.to_le_bytes());
+        push_vec3(&mut out, node.bbox_min);
+        push_vec3(&mut out, node.bbox_max);
+        out.extend_from_slice(&((node.triangles.len() * 3) as u32).to_le_bytes());
+
+        for (vertices, normals) in &node.triangles {
+            for (v, n) in vertices.iter().zip(normals) {
+                push_vec3(&mut out, *v);
+                // Inverse of the parser's `byte / 127.5 - 1.0`.
+                for c in n {
+                    out.push(((c + 1.0) * 127.5).round().clamp(0.0, 255.0) as u8);
+                }
+                out.push(0);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::geometry::{
+        ArmorTriangle, CollisionNode, CollisionTriangle, parse_geometry,
+    };
+
+    /// A `set3/xyznuv` vertex (20 bytes).
+    fn vertex(pos: [f32; 3]) -> Vec<u8> {
+        let mut v = Vec::new();
+        for c in pos {
+            v.extend_from_slice(&c.to_le_bytes());
+        }
+        v.extend_from_slice(&[127, 0, 0, 0]);
+        v.extend_from_slice(&[0, 0, 0, 0]);
+        v
+    }
+
+    fn sample_writer() -> GeometryWriter {
+        let mut writer = GeometryWriter::new();
+        let quad: Vec<u8> = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]
+        .into_iter()
+        .flat_map(vertex)
+        .collect();
+        writer
+            .add_mesh(0x10, 0x20, "set3/xyznuv", &quad, &[0, 1, 2, 0, 2, 3])
+            .unwrap();
+        writer
+            .add_mesh(0x11, 0x21, "set3/xyznuv", &quad[..60], &[0, 1, 2])
+            .unwrap();
+        writer.add_collision_model(&CollisionModel {
+            name: "CM_test.collision".to_string(),
+            nodes: vec![CollisionNode {
+                material_id: 3,
+                bbox_min: [0.0; 3],
+                bbox_max: [1.0, 1.0, 0.0],
+                triangles: 0..1,
+            }],
+            triangles: vec![CollisionTriangle {
+                vertices: [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+                normals: [[-1.0, -1.0, 1.0]; 3],
+                material_id: 3,
+            }],
+        });
+        writer
+            .add_armor_model(&ArmorModel {
+                name: "CM_PA_test.armor".to_string(),
+                triangles: vec![ArmorTriangle {
+                    vertices: [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                    normals: [[-1.0, -1.0, 1.0]; 3],
+                    material_id: 7,
+                    layer_index: 1,
+                }],
+                stored: None,
+            })
+            .unwrap();
+        writer
+    }
+
+    #[test]
+    fn written_geometry_parses_back() {
+        let bytes = sample_writer().to_bytes().unwrap();
+        let geom = parse_geometry(&bytes).unwrap();
+
+        assert_eq!(geom.merged_vertices.len(), 1);
+        assert_eq!(geom.merged_vertices[0].format_name, "set3/xyznuv");
+        assert_eq!(geom.merged_vertices[0].stride_in_bytes, 20);
+        let vertices = geom.merged_vertices[0].data.decode().unwrap();
+        assert_eq!(vertices.len(), 7 * 20);
+        assert_eq!(vertices[20..24], 1.0f32.to_le_bytes());
+
+        let second = &geom.vertices_mapping[1];
+        assert_eq!((second.items_offset, second.items_count), (4, 3));
+        let indices = geom.merged_indices[0].data.decode().unwrap();
+        let indices: Vec<u16> = indices
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3, 0, 1, 2]);
+
+        let collision = geom.collision_models[0].decode().unwrap();
+        assert_eq!(collision.name, "CM_test.collision");
+        assert_eq!(collision.nodes[0].material_id, 3);
+        assert_eq!(collision.triangles[0].vertices[2], [1.0, 1.0, 0.0]);
+
+        let armor = &geom.armor_models[0];
+        assert_eq!(armor.name, "CM_PA_test.armor");
+        assert_eq!(armor.triangles.len(), 1);
+        assert_eq!(armor.triangles[0].material_id, 7);
+        assert_eq!(armor.triangles[0].layer_index, 1);
+        assert_eq!(armor.triangles[0].vertices[1], [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn unmodified_geometry_round_trips() {
+        let bytes = sample_writer().to_bytes().unwrap();
+        let geom = parse_geometry(&bytes).unwrap();
+        let rewritten = GeometryWriter::from_geometry(&geom).to_bytes().unwrap();
+        assert_eq!(rewritten, bytes);
+    }
+
+    /// A BVH entry stream with one node of one triangle. The global header
+    /// carries values the writer would not produce itself.
+    fn bvh(material_id: u8) -> Vec<u8> {
+        let vec3 = |v: [f32; 3]| v.into_iter().flat_map(f32::to_le_bytes);
+        let mut data = Vec::new();
+        data.extend(vec3([-1.0, -1.0, -1.0]));
+        data.extend_from_slice(&0xBEEFu32.to_le_bytes());
+        data.extend(vec3([2.0, 2.0, 2.0]));
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[material_id, 0, 1, 0]);
+        data.extend(vec3([0.0; 3]));
+        data.extend(vec3([1.0, 1.0, 0.0]));
+        data.extend_from_slice(&3u32.to_le_bytes());
+        for v in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            data.extend(vec3(v));
+            data.extend_from_slice(&[128, 128, 255, 0]);
+        }
+        data
+    }
+
+    /// A `.geometry` file built byte by byte: one raw vertex and index
+    /// buffer, a collision model, and an armor model whose `data_relptr`
+    /// points into its data rather than at its start, as in game files.
+    fn game_layout_geometry() -> Vec<u8> {
+        fn relptr(f: &mut [u8], field: usize, target: usize) {
+            f[field..field + 8].copy_from_slice(&(target as i64 - field as i64).to_le_bytes());
+        }
+        fn name(f: &mut Vec<u8>, base: usize, text: &str) {
+            let len = text.len() as u32 + 1;
+            f[base..base + 4].copy_from_slice(&len.to_le_bytes());
+            let rel = f.len() as i64 - base as i64;
+            f[base + 8..base + 16].copy_from_slice(&rel.to_le_bytes());
+            f.extend_from_slice(text.as_bytes());
+            f.push(0);
+        }
+        fn mapping(f: &mut Vec<u8>, id: u32) {
+            f.extend_from_slice(&id.to_le_bytes());
+            f.extend_from_slice(&0u16.to_le_bytes());
+            f.extend_from_slice(&0x3C00u16.to_le_bytes());
+            f.extend_from_slice(&0u32.to_le_bytes());
+            f.extend_from_slice(&3u32.to_le_bytes());
+        }
+
+        let mut f = vec![0u8; 0x48];
+        let vm = f.len();
+        mapping(&mut f, 0x10);
+        let im = f.len();
+        mapping(&mut f, 0x20);
+
+        let mv = f.len();
+        f.resize(mv + 0x20, 0);
+        let data = f.len();
+        relptr(&mut f, mv, data);
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            f.extend_from_slice(&v.to_le_bytes());
+        }
+        name(&mut f, mv + 0x08, "set3/xyz");
+        f[mv + 0x18..mv + 0x1C].copy_from_slice(&36u32.to_le_bytes());
+        f[mv + 0x1C..mv + 0x1E].copy_from_slice(&12u16.to_le_bytes());
+
+        let mi = f.len();
+        f.resize(mi + 0x10, 0);
+        let data = f.len();
+        relptr(&mut f, mi, data);
+        for i in [0u16, 1, 2] {
+            f.extend_from_slice(&i.to_le_bytes());
+        }
+        f[mi + 0x08..mi + 0x0C].copy_from_slice(&6u32.to_le_bytes());
+        f[mi + 0x0E..mi + 0x10].copy_from_slice(&2u16.to_le_bytes());
+
+        let cm = f.len();
+        f.resize(cm + 0x20, 0);
+        let data = f.len();
+        relptr(&mut f, cm, data);
+        let collision = bvh(3);
+        f.extend_from_slice(&collision);
+        name(&mut f, cm + 0x08, "CM_hull.collision");
+        f[cm + 0x18..cm + 0x1C].copy_from_slice(&(collision.len() as u32).to_le_bytes());
+
+        let am = f.len();
+        f.resize(am + 0x20, 0);
+        let data = f.len() + 0x20;
+        relptr(&mut f, am, data);
+        let armor = bvh(7);
+        f.extend_from_slice(&armor);
+        name(&mut f, am + 0x08, "CM_PA_hull.armor");
+        f[am + 0x18..am + 0x1C].copy_from_slice(&(armor.len() as u32 - 0x20).to_le_bytes());
+
+        for i in 0..6 {
+            f[i * 4..i * 4 + 4].copy_from_slice(&1u32.to_le_bytes());
+        }
+        for (i, ptr) in [vm, im, mv, mi, cm, am].into_iter().enumerate() {
+            f[0x18 + i * 8..0x20 + i * 8].copy_from_slice(&(ptr as i64).to_le_bytes());
+        }
+        f
+    }
+
+    #[test]
+    fn game_layout_geometry_round_trips_exactly() {
+        let bytes = game_layout_geometry();
+        let mut geom = parse_geometry(&bytes).unwrap();
+        assert_eq!(geom.merged_vertices[0].format_name, "set3/xyz");
+        assert_eq!(geom.armor_models[0].triangles.len(), 1);
+        assert_eq!(geom.armor_models[0].triangles[0].material_id, 7);
+        let rewritten = GeometryWriter::from_geometry(&geom).to_bytes().unwrap();
+        assert_eq!(rewritten, bytes);
+
+        // Edited armor is re-encoded.
+        geom.armor_models[0].triangles[0].material_id = 9;
+        let edited = GeometryWriter::from_geometry(&geom).to_bytes().unwrap();
+        let reparsed = parse_geometry(&edited).unwrap();
+        assert_eq!(reparsed.armor_models[0].triangles[0].material_id, 9);
+        assert_eq!(
+            reparsed.collision_models[0].data,
+            geom.collision_models[0].data
+        );
+    }
+}
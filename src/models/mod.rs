@@ -1,12 +1,24 @@
+#[cfg(feature = "models")]
+pub mod animation;
 pub mod assets_bin;
+pub mod assets_bin_writer;
+#[cfg(feature = "models")]
+pub mod dependency_graph;
 #[cfg(feature = "models")]
 pub mod forest;
 #[cfg(feature = "models")]
 pub mod geometry;
 #[cfg(feature = "models")]
+pub mod geometry_check;
+#[cfg(feature = "models")]
+pub mod geometry_writer;
+#[cfg(feature = "models")]
 pub mod merged_models;
 #[cfg(feature = "models")]
 pub mod model;
+#[cfg(all(feature = "models", feature = "json"))]
+pub mod prototype_json;
+pub mod prototype_layout;
 #[cfg(feature = "models")]
 pub mod speedtree;
 #[cfg(feature = "models")]
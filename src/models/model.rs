@@ -5,6 +5,8 @@
 //! is a selfId (path hash) pointing to the corresponding `.visual` entry in
 //! pathsStorage.
 
+use std::collections::HashMap;
+
 use rootcause::Report;
 use thiserror::Error;
 use winnow::Parser;
@@ -15,6 +17,7 @@ use winnow::token::take;
 use winnow::error::{ContextError, ErrMode};
 
 use crate::data::parser_utils::{WResult, resolve_relptr};
+use crate::models::assets_bin::PrototypeDatabase;
 
 /// Errors that can occur during ModelPrototype parsing.
 #[derive(Debug, Error)]
@@ -58,6 +61,60 @@ pub struct DyeEntry {
     pub tint_material_ids: Vec<u64>,
 }
 
+/// A [`DyeEntry`] with its string IDs and material selfIds resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDye {
+    /// Name of the material the dye applies to.
+    pub matter: String,
+    /// Name of the material the dye replaces.
+    pub replaces: String,
+    pub tints: Vec<DyeTint>,
+}
+
+/// One selectable tint of a dye.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DyeTint {
+    /// Tint variant name.
+    pub name: String,
+    /// Full path of the tint's `.mfm` material, if it resolves in pathsStorage.
+    pub material_path: Option<String>,
+}
+
+impl DyeEntry {
+    /// Resolve string IDs through the strings section and tint material
+    /// selfIds through pathsStorage. Unresolvable string IDs are kept as hex.
+    pub fn resolve(
+        &self,
+        db: &PrototypeDatabase<'_>,
+        self_id_index: &HashMap<u64, usize>,
+    ) -> ResolvedDye {
+        let string = |id: u32| {
+            db.strings
+                .get_string_by_id(id)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("0x{id:08X}"))
+        };
+
+        let tints = self
+            .tint_name_ids
+            .iter()
+            .zip(&self.tint_material_ids)
+            .map(|(&name_id, mat_id)| DyeTint {
+                name: string(name_id),
+                material_path: self_id_index
+                    .get(mat_id)
+                    .map(|&idx| db.reconstruct_path(idx, self_id_index)),
+            })
+            .collect();
+
+        ResolvedDye {
+            matter: string(self.matter_id),
+            replaces: string(self.replaces_id),
+            tints,
+        }
+    }
+}
+
 /// Fixed header fields of a ModelPrototype (0x28 bytes).
 struct ModelHeaderFields {
     visual_resource_id: u64,
@@ -0,0 +1,183 @@
+//! Decoded JSON views of assets.bin prototype records.
+//!
+//! Turns parsed [`VisualPrototype`] and [`ModelPrototype`] records into
+//! human-readable JSON, resolving string IDs through the strings section and
+//! selfIds (path hashes) through a path lookup table. Prototype types without
+//! a parser are emitted with their fixed record bytes as hex.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::models::assets_bin::StringsSection;
+use crate::models::model::{self, ModelPrototype};
+use crate::models::prototype_layout;
+use crate::models::visual::{self, VisualPrototype};
+
+/// Lookup tables needed to resolve IDs embedded in prototype records.
+pub struct JsonContext<'a> {
+    pub strings: &'a StringsSection<'a>,
+    /// Full resource path for each selfId in pathsStorage.
+    pub paths: &'a HashMap<u64, String>,
+}
+
+impl JsonContext<'_> {
+    /// Resolve a string ID, falling back to its hex representation.
+    pub fn string(&self, id: u32) -> Value {
+        match self.strings.get_string_by_id(id) {
+            Some(s) => Value::String(s.to_string()),
+            None => Value::String(format!("0x{id:08X}")),
+        }
+    }
+
+    /// Resolve a selfId to its full path. A zero ID maps to `null`.
+    pub fn path(&self, self_id: u64) -> Value {
+        if self_id == 0 {
+            return Value::Null;
+        }
+        match self.paths.get(&self_id) {
+            Some(p) => Value::String(p.clone()),
+            None => Value::String(format!("0x{self_id:016X}")),
+        }
+    }
+}
+
+/// Decode a VisualPrototype into JSON.
+pub fn visual_to_json(vp: &VisualPrototype, ctx: &JsonContext<'_>) -> Value {
+    let node_name = |idx: u16| -> Value {
+        vp.nodes
+            .name_ids
+            .get(idx as usize)
+            .map(|&id| ctx.string(id))
+            .unwrap_or(Value::Null)
+    };
+
+    let nodes: Vec<Value> = vp
+        .nodes
+        .name_ids
+        .iter()
+        .enumerate()
+        .map(|(i, &name_id)| {
+            let parent = vp.nodes.parent_ids[i];
+            json!({
+                "name": ctx.string(name_id),
+                "parent": if parent == 0xFFFF { Value::Null } else { node_name(parent) },
+                "matrix": vp.nodes.matrices[i].0.to_vec(),
+            })
+        })
+        .collect();
+
+    let render_sets: Vec<Value> = vp
+        .render_sets
+        .iter()
+        .map(|rs| {
+            json!({
+                "name": ctx.string(rs.name_id),
+                "material_name": ctx.string(rs.material_name_id),
+                "material_mfm": ctx.path(rs.material_mfm_path_id),
+                "vertices_mapping_id": rs.vertices_mapping_id,
+                "indices_mapping_id": rs.indices_mapping_id,
+                "skinned": rs.skinned,
+                "nodes": rs.node_name_ids.iter().map(|&id| ctx.string(id)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    let lods: Vec<Value> = vp
+        .lods
+        .iter()
+        .map(|lod| {
+            json!({
+                "extent": lod.extent,
+                "casts_shadow": lod.casts_shadow,
+                "render_sets": lod.render_set_names.iter().map(|&id| ctx.string(id)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "nodes": nodes,
+        "merged_geometry": ctx.path(vp.merged_geometry_path_id),
+        "underwater_model": vp.underwater_model,
+        "abovewater_model": vp.abovewater_model,
+        "bounding_box": {
+            "min": vp.bounding_box.min,
+            "max": vp.bounding_box.max,
+        },
+        "render_sets": render_sets,
+        "lods": lods,
+    })
+}
+
+/// Decode a ModelPrototype (including nested animations) into JSON.
+pub fn model_to_json(mp: &ModelPrototype, ctx: &JsonContext<'_>) -> Value {
+    let dyes: Vec<Value> = mp
+        .dyes
+        .iter()
+        .map(|dye| {
+            let tints: Vec<Value> = dye
+                .tint_name_ids
+                .iter()
+                .zip(&dye.tint_material_ids)
+                .map(|(&name_id, &mat_id)| {
+                    json!({
+                        "name": ctx.string(name_id),
+                        "material": ctx.path(mat_id),
+                    })
+                })
+                .collect();
+            json!({
+                "matter": ctx.string(dye.matter_id),
+                "replaces": ctx.string(dye.replaces_id),
+                "tints": tints,
+            })
+        })
+        .collect();
+
+    json!({
+        "visual": ctx.path(mp.visual_resource_id),
+        "misc_type": mp.misc_type,
+        "skeleton_extenders": mp.skel_ext_res_ids.iter().map(|&id| ctx.path(id)).collect::<Vec<_>>(),
+        "animations": mp.animations.iter().map(|a| model_to_json(a, ctx)).collect::<Vec<_>>(),
+        "dyes": dyes,
+    })
+}
+
+/// Decode a prototype record into JSON.
+///
+/// `record_data` starts at the record and extends to the end of its blob.
+/// `item_size` is the fixed record size for the blob. Only visual and model
+/// records have a known field layout; other prototype types are emitted with
+/// their fixed record bytes as hex. Returns an error string if a known
+/// prototype type fails to parse.
+pub fn prototype_to_json(
+    path: &str,
+    blob_index: usize,
+    record_data: &[u8],
+    item_size: usize,
+    ctx: &JsonContext<'_>,
+) -> Result<Value, String> {
+    let type_name = prototype_layout::baseline_layout(blob_index).map_or("Unknown", |l| l.name);
+
+    let record = match blob_index {
+        1 => {
+            let vp = visual::parse_visual(record_data).map_err(|e| e.to_string())?;
+            visual_to_json(&vp, ctx)
+        }
+        3 => {
+            let mp = model::parse_model(record_data).map_err(|e| e.to_string())?;
+            model_to_json(&mp, ctx)
+        }
+        _ => {
+            let fixed = &record_data[..item_size.min(record_data.len())];
+            let hex: String = fixed.iter().map(|b| format!("{b:02X}")).collect();
+            json!({ "record_hex": hex })
+        }
+    };
+
+    Ok(json!({
+        "path": path,
+        "type": type_name,
+        "record": record,
+    }))
+}
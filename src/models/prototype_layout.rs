@@ -0,0 +1,240 @@
+//! Prototype record layouts for assets.bin, keyed by prototype type and
+//! schema checksum.
+//!
+//! Each database entry in assets.bin carries a `prototypeMagic` (MurmurHash3
+//! of the prototype type name) and a `prototypeChecksum` identifying the
+//! record schema. A client patch that changes a record struct changes the
+//! checksum, so record sizes and relptr positions are looked up here rather
+//! than hardcoded at each call site.
+//!
+//! [`LAYOUTS`] holds one baseline entry per prototype type with
+//! `checksum: None`. Entries built with [`pinned`] match one exact checksum
+//! and are reported as [`LayoutMatch::Exact`]. Once a prototype type has a
+//! pinned entry, any other checksum for it is a [`LayoutMatch::ChecksumMismatch`]
+//! and its records are refused rather than decoded with a stale layout.
+//! Types without pinned entries fall back to the baseline and are reported
+//! as [`LayoutMatch::Assumed`], so callers and `assets-bin --validate` can
+//! flag data decoded with an unverified layout.
+//!
+//! No checksums are pinned yet. `assets-bin --validate` prints the checksum
+//! of every blob with an assumed layout; pin it here once the layout has been
+//! checked against that client build, and add a pinned entry with the new
+//! size when a patch changes a layout.
+
+/// Record layout of one prototype type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrototypeLayout {
+    /// Prototype type name, e.g. `"VisualPrototype"`.
+    pub name: &'static str,
+    /// Expected blob index in the databases array.
+    pub blob_index: usize,
+    /// `prototypeMagic`: MurmurHash3_x86_32 of `name`.
+    pub magic: u32,
+    /// `prototypeChecksum` this layout was verified against, or `None` for
+    /// the baseline layout.
+    pub checksum: Option<u32>,
+    /// Size of the fixed record in bytes.
+    pub item_size: usize,
+    /// Offsets of relptr fields within the fixed record, if the record
+    /// layout is known.
+    pub relptr_offsets: Option<&'static [usize]>,
+}
+
+const fn baseline(
+    name: &'static str,
+    blob_index: usize,
+    magic: u32,
+    item_size: usize,
+    relptr_offsets: Option<&'static [usize]>,
+) -> PrototypeLayout {
+    PrototypeLayout {
+        name,
+        blob_index,
+        magic,
+        checksum: None,
+        item_size,
+        relptr_offsets,
+    }
+}
+
+/// A layout verified against one `prototypeChecksum`.
+pub const fn pinned(layout: PrototypeLayout, checksum: u32) -> PrototypeLayout {
+    PrototypeLayout {
+        checksum: Some(checksum),
+        ..layout
+    }
+}
+
+/// All known prototype layouts. Entries with a checksum replace the baseline
+/// entry for the same magic.
+pub const LAYOUTS: &[PrototypeLayout] = &[
+    baseline("MaterialPrototype", 0, 0x5069C471, 0x78, None),
+    // VisualNodes arrays (+0x08..+0x28), render sets (+0x60), LODs (+0x68)
+    baseline(
+        "VisualPrototype",
+        1,
+        0x480DC57B,
+        0x70,
+        Some(&[0x08, 0x10, 0x18, 0x20, 0x28, 0x60, 0x68]),
+    ),
+    baseline("SkeletonExtenderPrototype", 2, 0x1AE023FF, 0x20, None),
+    // skelExtResIds (+0x10), animations (+0x18), dyes (+0x20)
+    baseline(
+        "ModelPrototype",
+        3,
+        0xA9576F28,
+        0x28,
+        Some(&[0x10, 0x18, 0x20]),
+    ),
+    baseline("PointLightPrototype", 4, 0x0D3665A4, 0x70, None),
+    baseline("EffectPrototype", 5, 0xEB23E0AF, 0x10, None),
+    baseline("VelocityFieldPrototype", 6, 0xAFD4A63F, 0x18, None),
+    baseline("EffectPresetPrototype", 7, 0x42E15336, 0x10, None),
+    baseline("EffectMetadataPrototype", 8, 0xDFC8F8E0, 0x10, None),
+    baseline("AtlasContourProto", 9, 0xF64359AA, 0x10, None),
+];
+
+/// Result of looking up the layout for a database entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMatch {
+    /// A layout verified against this exact `prototypeChecksum`.
+    Exact(&'static PrototypeLayout),
+    /// The baseline layout for this prototype type; the checksum has not been
+    /// verified against it.
+    Assumed(&'static PrototypeLayout),
+    /// The prototype type has pinned layouts, but none for this checksum.
+    /// Its records must not be decoded.
+    ChecksumMismatch(&'static PrototypeLayout),
+    /// No prototype type with this magic is known.
+    UnknownType,
+}
+
+impl LayoutMatch {
+    /// The matched layout, if its records can be decoded.
+    pub fn layout(self) -> Option<&'static PrototypeLayout> {
+        match self {
+            LayoutMatch::Exact(layout) | LayoutMatch::Assumed(layout) => Some(layout),
+            LayoutMatch::ChecksumMismatch(_) | LayoutMatch::UnknownType => None,
+        }
+    }
+
+    /// The prototype type name, if the magic is known.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            LayoutMatch::Exact(layout)
+            | LayoutMatch::Assumed(layout)
+            | LayoutMatch::ChecksumMismatch(layout) => Some(layout.name),
+            LayoutMatch::UnknownType => None,
+        }
+    }
+}
+
+/// Look up the layout for a database entry's magic and checksum.
+pub fn resolve_layout(magic: u32, checksum: u32) -> LayoutMatch {
+    resolve_in(LAYOUTS, magic, checksum)
+}
+
+fn resolve_in(layouts: &'static [PrototypeLayout], magic: u32, checksum: u32) -> LayoutMatch {
+    let mut pinned = None;
+    let mut fallback = None;
+    for layout in layouts.iter().filter(|l| l.magic == magic) {
+        match layout.checksum {
+            Some(c) if c == checksum => return LayoutMatch::Exact(layout),
+            Some(_) => pinned = pinned.or(Some(layout)),
+            None => fallback = fallback.or(Some(layout)),
+        }
+    }
+    match (pinned, fallback) {
+        (Some(layout), _) => LayoutMatch::ChecksumMismatch(layout),
+        (None, Some(layout)) => LayoutMatch::Assumed(layout),
+        (None, None) => LayoutMatch::UnknownType,
+    }
+}
+
+/// The baseline layout for a blob index, used when no database entry is
+/// available (e.g. when creating a new database).
+pub fn baseline_layout(blob_index: usize) -> Option<&'static PrototypeLayout> {
+    LAYOUTS
+        .iter()
+        .find(|l| l.blob_index == blob_index && l.checksum.is_none())
+}
+
+/// Compute a `prototypeMagic` value from a prototype type name.
+pub fn prototype_magic(name: &str) -> u32 {
+    murmur3_32(name.as_bytes(), 0)
+}
+
+/// MurmurHash3_x86_32.
+pub fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xCC9E_2D51;
+    const C2: u32 = 0x1B87_3593;
+
+    let mix = |mut k: u32| {
+        k = k.wrapping_mul(C1);
+        k = k.rotate_left(15);
+        k.wrapping_mul(C2)
+    };
+
+    let mut h = seed;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k = u32::from_le_bytes(chunk.try_into().expect("4-byte chunk"));
+        h ^= mix(k);
+        h = h.rotate_left(13);
+        h = h.wrapping_mul(5).wrapping_add(0xE654_6B64);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0u32;
+        for (i, &b) in tail.iter().enumerate() {
+            k |= (b as u32) << (8 * i);
+        }
+        h ^= mix(k);
+    }
+
+    h ^= data.len() as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xC2B2_AE35);
+    h ^ (h >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magics_match_type_names() {
+        for layout in LAYOUTS {
+            assert_eq!(
+                prototype_magic(layout.name),
+                layout.magic,
+                "{}",
+                layout.name
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_checksum_falls_back_to_baseline() {
+        let visual = resolve_layout(0x480DC57B, 0xDEADBEEF);
+        assert_eq!(visual.layout().map(|l| l.item_size), Some(0x70));
+        assert!(matches!(visual, LayoutMatch::Assumed(_)));
+        assert_eq!(resolve_layout(0x12345678, 0), LayoutMatch::UnknownType);
+    }
+
+    #[test]
+    fn pinned_checksum_replaces_baseline() {
+        const BASE: PrototypeLayout = baseline("VisualPrototype", 1, 0x480DC57B, 0x70, None);
+        static TABLE: [PrototypeLayout; 2] = [pinned(BASE, 0x1234), BASE];
+
+        let exact = resolve_in(&TABLE, 0x480DC57B, 0x1234);
+        assert!(matches!(exact, LayoutMatch::Exact(l) if l.checksum == Some(0x1234)));
+        let mismatch = resolve_in(&TABLE, 0x480DC57B, 0x5678);
+        assert!(matches!(mismatch, LayoutMatch::ChecksumMismatch(_)));
+        assert_eq!(mismatch.layout(), None);
+        assert_eq!(mismatch.name(), Some("VisualPrototype"));
+    }
+}
@@ -1,9 +1,12 @@
-/// Vertex format string parser and attribute unpacking for BigWorld geometry.
-///
-/// Format strings look like `set3/xyznuvtbpc` where the suffix encodes which
-/// attributes are present and in what order. Vertices are always tightly packed
-/// with a fixed stride per format.
-///
+//! Vertex format string parser and attribute unpacking for BigWorld geometry.
+//!
+//! Format strings look like `set3/xyznuvtbpc` where the suffix encodes which
+//! attributes are present and in what order. Vertices are always tightly packed
+//! with a fixed stride per format.
+
+use rootcause::Report;
+use thiserror::Error;
+
 /// Semantic meaning of a vertex attribute.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AttributeSemantic {
@@ -46,194 +49,188 @@ pub struct VertexFormat {
     pub stride: usize,
 }
 
+/// Errors from [`parse_vertex_format`] and [`VertexFormat::check_stride`].
+#[derive(Debug, Error)]
+pub enum VertexFormatError {
+    #[error("unknown attribute code {code:?} at offset {offset} in vertex format {format:?}")]
+    UnknownCode {
+        format: String,
+        offset: usize,
+        code: String,
+    },
+    #[error("vertex format {0:?} has no position")]
+    MissingPosition(String),
+    #[error("vertex format {format:?}: computed stride {computed} != stored stride {stored}")]
+    StrideMismatch {
+        format: String,
+        computed: usize,
+        stored: usize,
+    },
+}
+
+/// Attribute codes, longest first so prefixes (`uv` of `uv2`, `i` of `iiiww`)
+/// don't shadow longer codes.
+const CODES: &[&str] = &["iiiww", "xyz", "uv2", "uv", "tb", "pc", "oi", "n", "i", "r"];
+
 /// Parse a format string like `set3/xyznuvtbpc` or just `xyznuvtb` into a
 /// vertex format descriptor.
 ///
-/// Recognized attribute codes (must appear in this order):
-/// - `xyz`    → POSITION (f32 x 3, 12 bytes)
+/// Recognized attribute codes:
+/// - `xyz`    → POSITION (f32 x 3, 12 bytes), must come first
 /// - `n`      → NORMAL (packed 4 bytes)
-/// - `uv`     → TEXCOORD_0 (packed 4 bytes, 2x float16)
-/// - `uv2`    → if a second `uv` appears after the first, it's TEXCOORD_1
+/// - `uv`     → TEXCOORD_0 (packed 4 bytes, 2x float16); a second `uv` is TEXCOORD_1
+/// - `uv2`    → TEXCOORD_0 + TEXCOORD_1 (2 x packed 4 bytes)
 /// - `tb`     → TANGENT + BINORMAL (2 x packed 4 bytes)
-/// - `iiiww`  → BONE_INDICES (3) + BONE_WEIGHTS (2) — 8 bytes, skipped for now
+/// - `iiiww`  → BONE_INDICES (3) + BONE_WEIGHTS (2) — 8 bytes, see [`unpack_bone_indices`]
+///   and [`unpack_bone_weights`]
+/// - `i`      → single bone index (4 bytes)
 /// - `r`      → extra data (4 bytes)
-/// - `pc`     → per-vertex color flag (0 bytes in practice based on stride matching)
-/// - `i`, `oi`→ instance data variants (0 bytes)
-pub fn parse_vertex_format(format_name: &str) -> VertexFormat {
+/// - `pc`     → per-vertex color flag (0 bytes, matched against stored strides)
+/// - `oi`     → instance flag (0 bytes)
+///
+/// Any other code is an error rather than a guess, since a wrong stride
+/// garbles every vertex after the first. Use [`VertexFormat::check_stride`]
+/// to compare the result with a buffer's stored stride, and the
+/// `audit-vertex-formats` command to check this table against every
+/// geometry file in a game install.
+pub fn parse_vertex_format(format_name: &str) -> Result<VertexFormat, Report<VertexFormatError>> {
     // Strip the `set3/` or `setN/` prefix if present.
     let code = format_name.rsplit('/').next().unwrap_or(format_name);
+    if !code.starts_with("xyz") {
+        return Err(Report::new(VertexFormatError::MissingPosition(
+            format_name.to_string(),
+        )));
+    }
 
     let mut attrs = Vec::new();
     let mut offset = 0usize;
     let mut uv_count = 0u32;
+    let mut push = |attrs: &mut Vec<VertexAttribute>, semantic, format, size| {
+        attrs.push(VertexAttribute {
+            semantic,
+            format,
+            offset,
+        });
+        offset += size;
+    };
 
-    let mut chars = code.chars().peekable();
-
-    while let Some(&ch) = chars.peek() {
-        match ch {
-            'x' => {
-                // xyz → POSITION
-                chars.next(); // x
-                if chars.peek() == Some(&'y') {
-                    chars.next();
-                } // y
-                if chars.peek() == Some(&'z') {
-                    chars.next();
-                } // z
-                attrs.push(VertexAttribute {
-                    semantic: AttributeSemantic::Position,
-                    format: AttributeFormat::Float32x3,
-                    offset,
-                });
-                offset += 12;
-            }
-            'n' => {
-                chars.next();
-                attrs.push(VertexAttribute {
-                    semantic: AttributeSemantic::Normal,
-                    format: AttributeFormat::PackedNormal,
-                    offset,
-                });
-                offset += 4;
-            }
-            'u' => {
-                // uv or uv2
-                chars.next(); // u
-                if chars.peek() == Some(&'v') {
-                    chars.next();
-                } // v
-                // Check for '2' suffix (second UV set)
-                if chars.peek() == Some(&'2') {
-                    chars.next();
-                    // This is a second UV channel, but it comes *before* the first
-                    // uv in the code string for formats like xyznuv2tb. Actually
-                    // "uv2" means there are TWO uv channels.
-                    // Format: first uv at current offset, second uv right after.
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::TexCoord0,
-                        format: AttributeFormat::PackedUV,
-                        offset,
-                    });
-                    offset += 4;
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::TexCoord1,
-                        format: AttributeFormat::PackedUV,
-                        offset,
-                    });
-                    offset += 4;
-                    uv_count = 2;
-                } else {
-                    let semantic = if uv_count == 0 {
-                        AttributeSemantic::TexCoord0
-                    } else {
-                        AttributeSemantic::TexCoord1
-                    };
-                    attrs.push(VertexAttribute {
-                        semantic,
-                        format: AttributeFormat::PackedUV,
-                        offset,
-                    });
-                    offset += 4;
-                    uv_count += 1;
-                }
-            }
-            't' => {
-                // tb → tangent + binormal
-                chars.next(); // t
-                if chars.peek() == Some(&'b') {
-                    chars.next(); // b
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::Tangent,
-                        format: AttributeFormat::PackedNormal,
-                        offset,
-                    });
-                    offset += 4;
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::Binormal,
-                        format: AttributeFormat::PackedNormal,
-                        offset,
-                    });
-                    offset += 4;
-                } else {
-                    // lone 't' — skip
-                    chars.next();
-                }
-            }
-            'i' => {
-                // iiiww → bone indices + weights (8 bytes)
-                // lone i → single index (4 bytes)
-                chars.next();
-                if chars.peek() == Some(&'i') {
-                    chars.next(); // second i
-                    if chars.peek() == Some(&'i') {
-                        chars.next(); // third i
-                    }
-                    if chars.peek() == Some(&'w') {
-                        chars.next(); // first w
-                        if chars.peek() == Some(&'w') {
-                            chars.next(); // second w
-                        }
-                    }
-                    // Skip skinning data for now (8 bytes: 3 indices + 2 weights)
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::BoneIndices,
-                        format: AttributeFormat::Raw4,
-                        offset,
-                    });
-                    offset += 4;
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::BoneWeights,
-                        format: AttributeFormat::Raw4,
-                        offset,
-                    });
-                    offset += 4;
+    let mut pos = 0;
+    while pos < code.len() {
+        let rest = &code[pos..];
+        let Some(token) = CODES.iter().copied().find(|c| rest.starts_with(c)) else {
+            return Err(Report::new(VertexFormatError::UnknownCode {
+                format: format_name.to_string(),
+                offset: pos,
+                code: rest.to_string(),
+            }));
+        };
+        pos += token.len();
+
+        match token {
+            "xyz" => push(
+                &mut attrs,
+                AttributeSemantic::Position,
+                AttributeFormat::Float32x3,
+                12,
+            ),
+            "n" => push(
+                &mut attrs,
+                AttributeSemantic::Normal,
+                AttributeFormat::PackedNormal,
+                4,
+            ),
+            "uv" => {
+                let semantic = if uv_count == 0 {
+                    AttributeSemantic::TexCoord0
                 } else {
-                    // lone 'i' → single index (4 bytes)
-                    attrs.push(VertexAttribute {
-                        semantic: AttributeSemantic::BoneIndices,
-                        format: AttributeFormat::Raw4,
-                        offset,
-                    });
-                    offset += 4;
-                }
-            }
-            'r' => {
-                chars.next();
-                attrs.push(VertexAttribute {
-                    semantic: AttributeSemantic::Extra,
-                    format: AttributeFormat::Raw4,
-                    offset,
-                });
-                offset += 4;
+                    AttributeSemantic::TexCoord1
+                };
+                push(&mut attrs, semantic, AttributeFormat::PackedUV, 4);
+                uv_count += 1;
             }
-            'p' => {
-                // pc — flag only, no extra bytes
-                chars.next();
-                if chars.peek() == Some(&'c') {
-                    chars.next();
-                }
+            "uv2" => {
+                push(
+                    &mut attrs,
+                    AttributeSemantic::TexCoord0,
+                    AttributeFormat::PackedUV,
+                    4,
+                );
+                push(
+                    &mut attrs,
+                    AttributeSemantic::TexCoord1,
+                    AttributeFormat::PackedUV,
+                    4,
+                );
+                uv_count = 2;
             }
-            'o' => {
-                // oi — instance flag, no extra bytes
-                chars.next();
-                if chars.peek() == Some(&'i') {
-                    chars.next();
-                }
+            "tb" => {
+                push(
+                    &mut attrs,
+                    AttributeSemantic::Tangent,
+                    AttributeFormat::PackedNormal,
+                    4,
+                );
+                push(
+                    &mut attrs,
+                    AttributeSemantic::Binormal,
+                    AttributeFormat::PackedNormal,
+                    4,
+                );
             }
-            'w' => {
-                // stray w (shouldn't happen outside iiiww), skip
-                chars.next();
-            }
-            _ => {
-                // Unknown character, skip
-                chars.next();
+            "iiiww" => {
+                // 3 indices + pad, then 2 weights + pad
+                push(
+                    &mut attrs,
+                    AttributeSemantic::BoneIndices,
+                    AttributeFormat::Raw4,
+                    4,
+                );
+                push(
+                    &mut attrs,
+                    AttributeSemantic::BoneWeights,
+                    AttributeFormat::Raw4,
+                    4,
+                );
             }
+            "i" => push(
+                &mut attrs,
+                AttributeSemantic::BoneIndices,
+                AttributeFormat::Raw4,
+                4,
+            ),
+            "r" => push(
+                &mut attrs,
+                AttributeSemantic::Extra,
+                AttributeFormat::Raw4,
+                4,
+            ),
+            // Flags only, no vertex data.
+            "pc" | "oi" => {}
+            _ => unreachable!("token comes from CODES"),
         }
     }
 
-    VertexFormat {
+    Ok(VertexFormat {
         attributes: attrs,
         stride: offset,
+    })
+}
+
+impl VertexFormat {
+    /// Check the computed stride against a buffer's stored `stride_in_bytes`.
+    pub fn check_stride(
+        &self,
+        format_name: &str,
+        stored: usize,
+    ) -> Result<(), Report<VertexFormatError>> {
+        if self.stride != stored {
+            return Err(Report::new(VertexFormatError::StrideMismatch {
+                format: format_name.to_string(),
+                computed: self.stride,
+                stored,
+            }));
+        }
+        Ok(())
     }
 }
 
@@ -266,13 +263,60 @@ pub fn unpack_uv(packed: u32) -> [f32; 2] {
     ]
 }
 
+/// Unpack a 4-byte `iii` bone index attribute into 3 blend bone indices.
+///
+/// Each byte indexes the render set's blend bone list (`node_name_ids`); the
+/// fourth byte is padding. The first index is the dominant bone.
+pub fn unpack_bone_indices(packed: u32) -> [u8; 3] {
+    let bytes = packed.to_le_bytes();
+    [bytes[0], bytes[1], bytes[2]]
+}
+
+/// Unpack a 4-byte `ww` bone weight attribute into 3 weights summing to 1.
+///
+/// Only the first two weights are stored (as unorm8); the third is implied.
+pub fn unpack_bone_weights(packed: u32) -> [f32; 3] {
+    let bytes = packed.to_le_bytes();
+    let w0 = bytes[0] as f32 / 255.0;
+    let w1 = bytes[1] as f32 / 255.0;
+    [w0, w1, (1.0 - w0 - w1).max(0.0)]
+}
+
+/// Pack a normal into the 4-byte format read by [`unpack_normal`].
+///
+/// Components are clamped to `[-1.0, 1.0]`; the fourth byte is zero.
+pub fn pack_normal(normal: [f32; 3]) -> u32 {
+    let pack = |c: f32| (c.clamp(-1.0, 1.0) * 127.0).round() as i8 as u8;
+    u32::from_le_bytes([pack(normal[0]), pack(normal[1]), pack(normal[2]), 0])
+}
+
+/// Pack a UV coordinate into the biased float16 format read by [`unpack_uv`].
+pub fn pack_uv(uv: [f32; 2]) -> u32 {
+    let u = half::f16::from_f32(uv[0] - 0.5).to_bits().to_le_bytes();
+    let v = half::f16::from_f32(uv[1] - 0.5).to_bits().to_le_bytes();
+    u32::from_le_bytes([u[0], u[1], v[0], v[1]])
+}
+
+/// Pack 3 blend bone indices into the `iii` attribute read by [`unpack_bone_indices`].
+pub fn pack_bone_indices(indices: [u8; 3]) -> u32 {
+    u32::from_le_bytes([indices[0], indices[1], indices[2], 0])
+}
+
+/// Pack bone weights into the `ww` attribute read by [`unpack_bone_weights`].
+///
+/// Only the first two weights are stored; the third is implied by the sum.
+pub fn pack_bone_weights(weights: [f32; 3]) -> u32 {
+    let pack = |w: f32| (w.clamp(0.0, 1.0) * 255.0).round() as u8;
+    u32::from_le_bytes([pack(weights[0]), pack(weights[1]), 0, 0])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_xyznuv() {
-        let fmt = parse_vertex_format("set3/xyznuv");
+        let fmt = parse_vertex_format("set3/xyznuv").unwrap();
         assert_eq!(fmt.stride, 20);
         assert_eq!(fmt.attributes.len(), 3);
         assert_eq!(fmt.attributes[0].semantic, AttributeSemantic::Position);
@@ -285,7 +329,7 @@ mod tests {
 
     #[test]
     fn test_xyznuvtb() {
-        let fmt = parse_vertex_format("set3/xyznuvtb");
+        let fmt = parse_vertex_format("set3/xyznuvtb").unwrap();
         assert_eq!(fmt.stride, 28);
         assert_eq!(fmt.attributes.len(), 5);
         assert_eq!(fmt.attributes[3].semantic, AttributeSemantic::Tangent);
@@ -296,20 +340,20 @@ mod tests {
 
     #[test]
     fn test_xyznuvr() {
-        let fmt = parse_vertex_format("set3/xyznuvr");
+        let fmt = parse_vertex_format("set3/xyznuvr").unwrap();
         assert_eq!(fmt.stride, 24);
     }
 
     #[test]
     fn test_xyznuvtbpc() {
         // pc adds no bytes
-        let fmt = parse_vertex_format("set3/xyznuvtbpc");
+        let fmt = parse_vertex_format("set3/xyznuvtbpc").unwrap();
         assert_eq!(fmt.stride, 28);
     }
 
     #[test]
     fn test_xyznuv2tb() {
-        let fmt = parse_vertex_format("set3/xyznuv2tb");
+        let fmt = parse_vertex_format("set3/xyznuv2tb").unwrap();
         assert_eq!(fmt.stride, 32);
         // Should have 2 UV channels
         let uv_attrs: Vec<_> = fmt
@@ -328,17 +372,37 @@ mod tests {
     #[test]
     fn test_xyznuvtbipc() {
         // xyz(12) + n(4) + uv(4) + tb(8) + i(4) + pc(0) = 32
-        let fmt = parse_vertex_format("set3/xyznuvtbipc");
+        let fmt = parse_vertex_format("set3/xyznuvtbipc").unwrap();
         assert_eq!(fmt.stride, 32);
     }
 
+    #[test]
+    fn test_game_hull_format() {
+        // The format of BSA013_Colossus_1945.geometry (see MODELS.md), whose
+        // vertex buffer stores a 28-byte stride.
+        let fmt = parse_vertex_format("set3/xyznuvtbpc").unwrap();
+        assert!(fmt.check_stride("set3/xyznuvtbpc", 28).is_ok());
+        let offsets: Vec<_> = fmt.attributes.iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 12, 16, 20, 24]);
+    }
+
     #[test]
     fn test_xyznuviiiwwtb() {
-        let fmt = parse_vertex_format("set3/xyznuviiiwwtb");
+        let fmt = parse_vertex_format("set3/xyznuviiiwwtb").unwrap();
         // xyz(12) + n(4) + uv(4) + iiiww(8) + tb(8) = 36
         assert_eq!(fmt.stride, 36);
     }
 
+    #[test]
+    fn test_rejects_unknown_codes() {
+        assert!(parse_vertex_format("set3/xyznuvq").is_err());
+        assert!(parse_vertex_format("set3/nuv").is_err());
+        let fmt = parse_vertex_format("set3/xyznuviiiww").unwrap();
+        assert_eq!(fmt.stride, 28);
+        assert!(fmt.check_stride("set3/xyznuviiiww", 28).is_ok());
+        assert!(fmt.check_stride("set3/xyznuviiiww", 32).is_err());
+    }
+
     #[test]
     fn test_unpack_normal() {
         // All-positive unit vector approximation
@@ -348,4 +412,23 @@ mod tests {
         assert!(n[1].abs() < 0.01);
         assert!(n[2].abs() < 0.01);
     }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let n = unpack_normal(pack_normal([0.6, -0.8, 0.0]));
+        assert!((n[0] - 0.6).abs() < 0.01 && (n[1] + 0.8).abs() < 0.01);
+        assert_eq!(unpack_uv(pack_uv([0.25, 1.5])), [0.25, 1.5]);
+        assert_eq!(unpack_bone_indices(pack_bone_indices([4, 2, 9])), [4, 2, 9]);
+        let w = unpack_bone_weights(pack_bone_weights([0.6, 0.3, 0.1]));
+        assert!((w[2] - 0.1).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_unpack_bone_weights() {
+        let w = unpack_bone_weights(u32::from_le_bytes([255, 0, 0, 0]));
+        assert_eq!(w, [1.0, 0.0, 0.0]);
+        let w = unpack_bone_weights(u32::from_le_bytes([102, 51, 0, 0]));
+        assert!((w.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!((w[2] - 0.4).abs() < 0.01);
+    }
 }
@@ -42,7 +42,7 @@ pub struct VisualPrototype {
 }
 
 /// Scene graph node hierarchy.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VisualNodes {
     pub name_map_name_ids: Vec<u32>,
     pub name_map_node_ids: Vec<u16>,
@@ -355,6 +355,23 @@ fn parse_lods(
 // VisualPrototype methods (unchanged)
 // ---------------------------------------------------------------------------
 
+impl VisualNodes {
+    /// Compose a node's local matrix with all of its ancestors' (model space).
+    pub fn world_transform(&self, node_idx: u16) -> [f32; 16] {
+        let mut result = self.matrices[node_idx as usize].0;
+        let mut current = node_idx;
+        loop {
+            let parent = self.parent_ids[current as usize];
+            if parent == 0xFFFF || parent as usize >= self.matrices.len() {
+                break;
+            }
+            result = mat4_mul(&self.matrices[parent as usize].0, &result);
+            current = parent;
+        }
+        result
+    }
+}
+
 impl VisualPrototype {
     /// Resolve string IDs and path IDs using the database.
     pub fn print_summary(&self, db: &PrototypeDatabase<'_>) {
@@ -441,19 +458,12 @@ impl VisualPrototype {
         strings: &StringsSection<'_>,
     ) -> Option<[f32; 16]> {
         let node_idx = self.find_node_index_by_name(hp_name, strings)?;
+        Some(self.node_world_transform(node_idx))
+    }
 
-        let mut result = self.nodes.matrices[node_idx as usize].0;
-        let mut current = node_idx;
-        loop {
-            let parent = self.nodes.parent_ids[current as usize];
-            if parent == 0xFFFF || parent as usize >= self.nodes.matrices.len() {
-                break;
-            }
-            result = mat4_mul(&self.nodes.matrices[parent as usize].0, &result);
-            current = parent;
-        }
-
-        Some(result)
+    /// Compose a node's local matrix with all of its ancestors' (model space).
+    pub fn node_world_transform(&self, node_idx: u16) -> [f32; 16] {
+        self.nodes.world_transform(node_idx)
     }
 
     /// Get the local (non-composed) matrix of a named node.
@@ -481,6 +491,16 @@ impl VisualPrototype {
         }
     }
 
+    /// Find the node index for a node name string ID, e.g. an entry of a
+    /// render set's blend bone list.
+    pub fn find_node_index_by_name_id(&self, name_id: u32) -> Option<u16> {
+        self.nodes
+            .name_map_name_ids
+            .iter()
+            .position(|&nid| nid == name_id)
+            .map(|i| self.nodes.name_map_node_ids[i])
+    }
+
     /// Find the node index for a given node name string.
     pub fn find_node_index_by_name(&self, name: &str, strings: &StringsSection<'_>) -> Option<u16> {
         for (i, &name_id) in self.nodes.name_map_name_ids.iter().enumerate() {
@@ -741,7 +741,7 @@ mod test {
 
     #[test]
     fn test_unpacker_macro() {
-        let args = vec![
+        let args = [
             ArgValue::Uint8(5),
             ArgValue::Int32(-54),
             ArgValue::Array(vec![ArgValue::Uint16(1), ArgValue::Uint16(3)]),